@@ -0,0 +1,365 @@
+//! Exercise the USB chapter 9 standard requests against a device and print a
+//! report of which checks passed.
+//!
+//! Usage: `cargo run --example ch9 -- VID:PID [--set-configuration]`
+//!
+//! `--set-configuration` also re-selects the active configuration, which
+//! resets the device's interfaces.
+
+use std::{fmt::Display, process::ExitCode};
+
+use nusb::MaybeFuture;
+
+#[derive(Debug)]
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+#[derive(Debug)]
+struct Check {
+    name: String,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn record(&mut self, name: impl Into<String>, outcome: Outcome) {
+        self.checks.push(Check {
+            name: name.into(),
+            outcome,
+        });
+    }
+
+    fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| matches!(c.outcome, Outcome::Fail(_)))
+            .count()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut passed = 0;
+        let mut skipped = 0;
+        for check in &self.checks {
+            match &check.outcome {
+                Outcome::Pass => {
+                    passed += 1;
+                    writeln!(f, "PASS  {}", check.name)?;
+                }
+                Outcome::Fail(reason) => writeln!(f, "FAIL  {}: {reason}", check.name)?,
+                Outcome::Skip(reason) => {
+                    skipped += 1;
+                    writeln!(f, "SKIP  {}: {reason}", check.name)?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "\n{passed} passed, {} failed, {skipped} skipped",
+            self.failures()
+        )
+    }
+}
+
+fn parse_vid_pid(s: &str) -> Option<(u16, u16)> {
+    let (vid, pid) = s.split_once(':')?;
+    Some((
+        u16::from_str_radix(vid, 16).ok()?,
+        u16::from_str_radix(pid, 16).ok()?,
+    ))
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let mut args = std::env::args().skip(1);
+    let Some((vid, pid)) = args.next().as_deref().and_then(parse_vid_pid) else {
+        eprintln!("usage: ch9 VID:PID [--set-configuration]");
+        return ExitCode::FAILURE;
+    };
+    let set_configuration = args.any(|a| a == "--set-configuration");
+
+    let di = nusb::list_devices()
+        .wait()
+        .unwrap()
+        .find(|d| d.vendor_id() == vid && d.product_id() == pid)
+        .expect("device should be connected");
+
+    println!("Device info: {di:?}\n");
+
+    let device = di.open().wait().unwrap();
+    let report = run(&device, set_configuration);
+    print!("{report}");
+
+    if report.failures() == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run(device: &nusb::Device, set_configuration: bool) -> Report {
+    use std::time::Duration;
+
+    use nusb::transfer::{Control, ControlType, Recipient, TransferError};
+
+    const GET_STATUS: u8 = 0x00;
+    const CLEAR_FEATURE: u8 = 0x01;
+    const SET_FEATURE: u8 = 0x03;
+    const GET_DESCRIPTOR: u8 = 0x06;
+    const GET_CONFIGURATION: u8 = 0x08;
+    const DEVICE_REMOTE_WAKEUP: u16 = 1;
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    let control_in = |recipient, request, value, index, len| {
+        let mut buf = vec![0; len];
+        let control = Control {
+            control_type: ControlType::Standard,
+            recipient,
+            request,
+            value,
+            index,
+        };
+        device
+            .control_in_blocking(control, &mut buf, TIMEOUT)
+            .map(|n| {
+                buf.truncate(n);
+                buf
+            })
+    };
+
+    let control_out = |recipient, request, value, index| {
+        let control = Control {
+            control_type: ControlType::Standard,
+            recipient,
+            request,
+            value,
+            index,
+        };
+        device.control_out_blocking(control, &[], TIMEOUT)
+    };
+
+    let get_descriptor = |desc_type: u8, desc_index: u8, language: u16, len: usize| {
+        control_in(
+            Recipient::Device,
+            GET_DESCRIPTOR,
+            (desc_type as u16) << 8 | desc_index as u16,
+            language,
+            len,
+        )
+    };
+
+    let expect_len = |r: Result<Vec<u8>, TransferError>, len: usize| match r {
+        Ok(data) if data.len() == len => Outcome::Pass,
+        Ok(data) => Outcome::Fail(format!("returned {} bytes, expected {len}", data.len())),
+        Err(e) => Outcome::Fail(e.to_string()),
+    };
+
+    let expect_stall = |r: Result<Vec<u8>, TransferError>| match r {
+        Err(TransferError::Stall) => Outcome::Pass,
+        Err(e) => Outcome::Fail(format!("expected stall, got {e}")),
+        Ok(data) => Outcome::Fail(format!("expected stall, returned {} bytes", data.len())),
+    };
+
+    let mut report = Report::default();
+
+    // GET_DESCRIPTOR(DEVICE)
+    let device_desc = get_descriptor(0x01, 0, 0, 18);
+    let outcome = match &device_desc {
+        Ok(d) if d.len() != 18 => Outcome::Fail(format!("returned {} bytes", d.len())),
+        Ok(d) if d[0] != 18 || d[1] != 0x01 => {
+            Outcome::Fail(format!("bad header {:02x} {:02x}", d[0], d[1]))
+        }
+        Ok(d) if ![8, 9, 16, 32, 64].contains(&d[7]) => {
+            Outcome::Fail(format!("invalid bMaxPacketSize0 {}", d[7]))
+        }
+        Ok(_) => Outcome::Pass,
+        Err(e) => Outcome::Fail(e.to_string()),
+    };
+    report.record("GET_DESCRIPTOR(DEVICE)", outcome);
+
+    report.record(
+        "GET_DESCRIPTOR(DEVICE) short read",
+        expect_len(get_descriptor(0x01, 0, 0, 8), 8),
+    );
+
+    // GET_DESCRIPTOR(CONFIGURATION), header then full length
+    let num_configurations = device.device_descriptor().num_configurations();
+    for index in 0..num_configurations {
+        let name = format!("GET_DESCRIPTOR(CONFIGURATION {index})");
+        let outcome = match get_descriptor(0x02, index, 0, 9) {
+            Ok(header) if header.len() == 9 && header[1] == 0x02 => {
+                let total = u16::from_le_bytes([header[2], header[3]]) as usize;
+                match get_descriptor(0x02, index, 0, total) {
+                    Ok(full) if full.len() == total && full[..9] == header[..] => Outcome::Pass,
+                    Ok(full) => Outcome::Fail(format!(
+                        "full read returned {} bytes, wTotalLength is {total}",
+                        full.len()
+                    )),
+                    Err(e) => Outcome::Fail(e.to_string()),
+                }
+            }
+            Ok(header) => Outcome::Fail(format!("bad header {header:02x?}")),
+            Err(e) => Outcome::Fail(e.to_string()),
+        };
+        report.record(name, outcome);
+    }
+
+    // GET_DESCRIPTOR(STRING)
+    let languages = get_descriptor(0x03, 0, 0, 255);
+    let language = match &languages {
+        Ok(d) if d.len() >= 4 && d[0] as usize == d.len() && d[1] == 0x03 => {
+            report.record("GET_DESCRIPTOR(STRING 0)", Outcome::Pass);
+            Some(u16::from_le_bytes([d[2], d[3]]))
+        }
+        Ok(d) if d.is_empty() => {
+            report.record(
+                "GET_DESCRIPTOR(STRING 0)",
+                Outcome::Skip("device has no string descriptors".into()),
+            );
+            None
+        }
+        Ok(d) => {
+            report.record(
+                "GET_DESCRIPTOR(STRING 0)",
+                Outcome::Fail(format!("invalid language list {d:02x?}")),
+            );
+            None
+        }
+        Err(TransferError::Stall) => {
+            report.record(
+                "GET_DESCRIPTOR(STRING 0)",
+                Outcome::Skip("device has no string descriptors".into()),
+            );
+            None
+        }
+        Err(e) => {
+            report.record("GET_DESCRIPTOR(STRING 0)", Outcome::Fail(e.to_string()));
+            None
+        }
+    };
+
+    if let (Some(language), Ok(d)) = (language, &device_desc) {
+        for (field, index) in [
+            ("iManufacturer", d[14]),
+            ("iProduct", d[15]),
+            ("iSerialNumber", d[16]),
+        ] {
+            if index == 0 {
+                continue;
+            }
+            let outcome = match get_descriptor(0x03, index, language, 255) {
+                Ok(s) if s.len() >= 2 && s[0] as usize == s.len() && s[1] == 0x03 => Outcome::Pass,
+                Ok(s) => Outcome::Fail(format!("invalid string descriptor {s:02x?}")),
+                Err(e) => Outcome::Fail(e.to_string()),
+            };
+            report.record(format!("GET_DESCRIPTOR(STRING {field})"), outcome);
+        }
+    }
+
+    // Requests the device must reject
+    report.record(
+        "GET_DESCRIPTOR(invalid type) stalls",
+        expect_stall(get_descriptor(0x3f, 0, 0, 64)),
+    );
+    report.record(
+        "invalid standard request stalls",
+        expect_stall(control_in(Recipient::Device, 0xff, 0, 0, 64)),
+    );
+
+    // GET_CONFIGURATION / SET_CONFIGURATION
+    let configuration = control_in(Recipient::Device, GET_CONFIGURATION, 0, 0, 1);
+    let outcome = match (&configuration, device.active_configuration()) {
+        (Ok(c), Ok(active)) if c.len() == 1 && c[0] == active.configuration_value() => {
+            Outcome::Pass
+        }
+        (Ok(c), Ok(active)) => Outcome::Fail(format!(
+            "returned {c:02x?}, active configuration is {}",
+            active.configuration_value()
+        )),
+        (Ok(c), Err(_)) if c.len() == 1 => Outcome::Pass,
+        (Ok(c), Err(_)) => Outcome::Fail(format!("returned {} bytes", c.len())),
+        (Err(e), _) => Outcome::Fail(e.to_string()),
+    };
+    report.record("GET_CONFIGURATION", outcome);
+
+    match configuration {
+        Ok(c) if set_configuration && c.len() == 1 => {
+            let outcome = match device.set_configuration(c[0]).wait() {
+                Ok(()) => match control_in(Recipient::Device, GET_CONFIGURATION, 0, 0, 1) {
+                    Ok(after) if after == c => Outcome::Pass,
+                    Ok(after) => Outcome::Fail(format!(
+                        "GET_CONFIGURATION returned {after:02x?} after setting {:02x}",
+                        c[0]
+                    )),
+                    Err(e) => Outcome::Fail(e.to_string()),
+                },
+                Err(e) => Outcome::Fail(e.to_string()),
+            };
+            report.record("SET_CONFIGURATION", outcome);
+        }
+        _ => report.record(
+            "SET_CONFIGURATION",
+            Outcome::Skip("pass --set-configuration to run".into()),
+        ),
+    }
+
+    // GET_STATUS
+    let device_status = control_in(Recipient::Device, GET_STATUS, 0, 0, 2);
+    let outcome = match &device_status {
+        Ok(s) if s.len() == 2 && s[0] & !0x1f == 0 && s[1] == 0 => Outcome::Pass,
+        Ok(s) => Outcome::Fail(format!("invalid status {s:02x?}")),
+        Err(e) => Outcome::Fail(e.to_string()),
+    };
+    report.record("GET_STATUS(DEVICE)", outcome);
+
+    let outcome = match control_in(Recipient::Endpoint, GET_STATUS, 0, 0, 2) {
+        Ok(s) if s == [0, 0] => Outcome::Pass,
+        Ok(s) => Outcome::Fail(format!("invalid status {s:02x?}")),
+        Err(e) => Outcome::Fail(e.to_string()),
+    };
+    report.record("GET_STATUS(ENDPOINT 0)", outcome);
+
+    // SET_FEATURE / CLEAR_FEATURE(DEVICE_REMOTE_WAKEUP)
+    let remote_wakeup = device
+        .active_configuration()
+        .map(|c| c.attributes() & 0x20 != 0)
+        .unwrap_or(false);
+    if remote_wakeup {
+        let wakeup_enabled = || {
+            control_in(Recipient::Device, GET_STATUS, 0, 0, 2)
+                .map(|s| s.first().is_some_and(|b| b & 0x02 != 0))
+        };
+        let outcome = match control_out(Recipient::Device, SET_FEATURE, DEVICE_REMOTE_WAKEUP, 0)
+            .and_then(|_| wakeup_enabled())
+        {
+            Ok(true) => Outcome::Pass,
+            Ok(false) => Outcome::Fail("GET_STATUS does not report remote wakeup".into()),
+            Err(e) => Outcome::Fail(e.to_string()),
+        };
+        report.record("SET_FEATURE(DEVICE_REMOTE_WAKEUP)", outcome);
+
+        let outcome = match control_out(Recipient::Device, CLEAR_FEATURE, DEVICE_REMOTE_WAKEUP, 0)
+            .and_then(|_| wakeup_enabled())
+        {
+            Ok(false) => Outcome::Pass,
+            Ok(true) => Outcome::Fail("GET_STATUS still reports remote wakeup".into()),
+            Err(e) => Outcome::Fail(e.to_string()),
+        };
+        report.record("CLEAR_FEATURE(DEVICE_REMOTE_WAKEUP)", outcome);
+    } else {
+        report.record(
+            "SET_FEATURE / CLEAR_FEATURE(DEVICE_REMOTE_WAKEUP)",
+            Outcome::Skip("configuration does not support remote wakeup".into()),
+        );
+    }
+
+    report
+}
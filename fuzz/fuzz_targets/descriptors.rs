@@ -1,11 +1,21 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
+use nusb::descriptors::{bos::BosDescriptor, ConfigurationDescriptor, DeviceDescriptor};
 
 fuzz_target!(|data: &[u8]| {
-    for config in nusb::descriptors::fuzz_parse_concatenated_config_descriptors(data) {
-        let config = nusb::descriptors::Configuration::new(config);
+    let mut buf = data;
+    while let Ok(config) = ConfigurationDescriptor::parse(buf) {
         let s = format!("{config:?}");
         std::hint::black_box(s);
+        buf = &buf[config.as_bytes().len()..];
+    }
+
+    if let Ok(device) = DeviceDescriptor::parse(data) {
+        std::hint::black_box(format!("{device:?}"));
+    }
+
+    if let Ok(bos) = BosDescriptor::parse(data) {
+        std::hint::black_box(format!("{bos:?}"));
     }
 });
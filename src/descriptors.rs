@@ -2,10 +2,9 @@
 //!
 //! Descriptors are blocks of data that describe the functionality of a USB device.
 
-use std::{
-    collections::BTreeMap,
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use core::{
     fmt::{Debug, Display},
-    io::ErrorKind,
     iter,
     num::NonZeroU8,
     ops::Deref,
@@ -13,10 +12,8 @@ use std::{
 
 use log::warn;
 
-use crate::{
-    transfer::{Direction, TransferType},
-    Error,
-};
+#[cfg(feature = "std")]
+use crate::Error;
 
 pub(crate) const DESCRIPTOR_TYPE_DEVICE: u8 = 0x01;
 pub(crate) const DESCRIPTOR_LEN_DEVICE: u8 = 18;
@@ -32,6 +29,39 @@ pub(crate) const DESCRIPTOR_LEN_ENDPOINT: u8 = 7;
 
 pub(crate) const DESCRIPTOR_TYPE_STRING: u8 = 0x03;
 
+pub(crate) const DESCRIPTOR_TYPE_DEVICE_QUALIFIER: u8 = 0x06;
+pub(crate) const DESCRIPTOR_LEN_DEVICE_QUALIFIER: u8 = 10;
+
+pub(crate) const DESCRIPTOR_TYPE_OTHER_SPEED_CONFIGURATION: u8 = 0x07;
+
+pub(crate) const DESCRIPTOR_TYPE_INTERFACE_ASSOCIATION: u8 = 0x0B;
+pub(crate) const DESCRIPTOR_LEN_INTERFACE_ASSOCIATION: u8 = 8;
+
+pub(crate) const DESCRIPTOR_TYPE_SUPERSPEED_COMPANION: u8 = 0x30;
+pub(crate) const DESCRIPTOR_LEN_SUPERSPEED_COMPANION: u8 = 6;
+
+pub(crate) const DESCRIPTOR_TYPE_SUPERSPEEDPLUS_ISOCHRONOUS_COMPANION: u8 = 0x31;
+
+pub(crate) use cdc::DESCRIPTOR_TYPE_CS_INTERFACE;
+
+/// Whether a descriptor type is defined by the USB specification itself,
+/// rather than by a class or vendor.
+fn is_standard_descriptor_type(descriptor_type: u8) -> bool {
+    matches!(
+        descriptor_type,
+        DESCRIPTOR_TYPE_DEVICE
+            | DESCRIPTOR_TYPE_CONFIGURATION
+            | DESCRIPTOR_TYPE_STRING
+            | DESCRIPTOR_TYPE_INTERFACE
+            | DESCRIPTOR_TYPE_ENDPOINT
+            | DESCRIPTOR_TYPE_DEVICE_QUALIFIER
+            | DESCRIPTOR_TYPE_OTHER_SPEED_CONFIGURATION
+            | DESCRIPTOR_TYPE_INTERFACE_ASSOCIATION
+            | DESCRIPTOR_TYPE_SUPERSPEED_COMPANION
+            | DESCRIPTOR_TYPE_SUPERSPEEDPLUS_ISOCHRONOUS_COMPANION
+    )
+}
+
 /// USB defined language IDs for string descriptors.
 ///
 /// In practice, different language IDs are not used,
@@ -55,7 +85,7 @@ impl<'a> Descriptor<'a> {
     /// Returns `None` if
     ///   * the slice length is not at least 2.
     ///   * the `bLength` field (first byte) is greater than the slice length.
-    pub fn new(buf: &[u8]) -> Option<Descriptor> {
+    pub fn new(buf: &[u8]) -> Option<Descriptor<'_>> {
         if buf.len() >= 2 && buf.len() >= buf[0] as usize {
             Some(Descriptor(buf))
         } else {
@@ -176,12 +206,59 @@ macro_rules! descriptor_fields {
             $(
                 $(#[$attr])*
                 #[inline]
-                $vis fn $name(&self) -> $ty { <$ty>::from_le_bytes(self.0[$pos..$pos + std::mem::size_of::<$ty>()].try_into().unwrap()) }
+                $vis fn $name(&self) -> $ty { <$ty>::from_le_bytes(self.0[$pos..$pos + core::mem::size_of::<$ty>()].try_into().unwrap()) }
             )*
         }
     }
 }
 
+pub mod bos;
+pub mod builder;
+pub mod cdc;
+pub mod dfu;
+pub mod msos;
+pub mod uac;
+pub mod uvc;
+pub mod webusb;
+
+/// Transfer direction
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum Direction {
+    /// Host to device
+    Out = 0,
+
+    /// Device to host
+    In = 0x80,
+}
+
+impl Direction {
+    pub(crate) fn from_address(addr: u8) -> Direction {
+        match addr & Self::MASK {
+            0 => Self::Out,
+            _ => Self::In,
+        }
+    }
+    pub(crate) const MASK: u8 = 0x80;
+}
+
+/// Endpoint type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum TransferType {
+    /// Control endpoint.
+    Control = 0,
+
+    /// Isochronous endpoint.
+    Isochronous = 1,
+
+    /// Bulk endpoint.
+    Bulk = 2,
+
+    /// Interrupt endpoint.
+    Interrupt = 3,
+}
+
 /// Information about a USB device.
 #[derive(Clone)]
 pub struct DeviceDescriptor([u8; DESCRIPTOR_LEN_DEVICE as usize]);
@@ -193,30 +270,21 @@ impl DeviceDescriptor {
     /// one from your own descriptor bytes for tests.
     ///
     /// This ignores any trailing data after the `bLength` specified in the descriptor.
+    /// Returns `None` and logs a warning if the descriptor is invalid; use
+    /// [`parse`][Self::parse] to get the reason.
     pub fn new(buf: &[u8]) -> Option<Self> {
-        let Some(buf) = buf.get(0..DESCRIPTOR_LEN_DEVICE as usize) else {
-            if buf.len() != 0 {
-                warn!(
-                    "device descriptor buffer is {} bytes, need {}",
-                    buf.len(),
-                    DESCRIPTOR_LEN_DEVICE
-                );
-            }
-            return None;
-        };
-        let buf: [u8; DESCRIPTOR_LEN_DEVICE as usize] = buf.try_into().ok()?;
-        if buf[0] < DESCRIPTOR_LEN_DEVICE {
-            warn!("invalid device descriptor bLength");
-            None
-        } else if buf[1] != DESCRIPTOR_TYPE_DEVICE {
-            warn!(
-                "device bDescriptorType is {}, not a device descriptor",
-                buf[1]
-            );
-            None
-        } else {
-            Some(Self(buf))
-        }
+        Self::parse(buf)
+            .map_err(|e| warn_parse_error("device", buf, e))
+            .ok()
+    }
+
+    /// Parse a buffer beginning with a device descriptor, such as a
+    /// descriptor dump from a capture or bug report.
+    pub fn parse(buf: &[u8]) -> Result<Self, ParseError> {
+        check_header(buf, DESCRIPTOR_LEN_DEVICE, DESCRIPTOR_TYPE_DEVICE)?;
+        Ok(Self(
+            buf[..DESCRIPTOR_LEN_DEVICE as usize].try_into().unwrap(),
+        ))
     }
 
     /// Get the bytes of the descriptor.
@@ -224,7 +292,7 @@ impl DeviceDescriptor {
         &self.0
     }
 
-    #[allow(unused)]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn from_fields(
         usb_version: u16,
         class: u8,
@@ -323,7 +391,7 @@ impl DeviceDescriptor {
     }
 }
 impl Debug for DeviceDescriptor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("DeviceDescriptor")
             .field("usb_version", &format_args!("0x{:04X}", self.usb_version()))
             .field("class", &format_args!("0x{:02X}", self.class()))
@@ -350,7 +418,91 @@ impl Debug for DeviceDescriptor {
     }
 }
 
+/// Device qualifier descriptor of a high-speed capable device, describing
+/// how the device would differ if it were operating at the other speed.
+///
+/// Obtain from [`Device::device_qualifier`][crate::Device::device_qualifier].
+#[derive(Clone)]
+pub struct DeviceQualifierDescriptor([u8; DESCRIPTOR_LEN_DEVICE_QUALIFIER as usize]);
+
+impl DeviceQualifierDescriptor {
+    /// Create a `DeviceQualifierDescriptor` from a buffer beginning with a
+    /// device qualifier descriptor.
+    ///
+    /// This ignores any trailing data after the `bLength` specified in the descriptor.
+    /// Returns `None` and logs a warning if the descriptor is invalid; use
+    /// [`parse`][Self::parse] to get the reason.
+    pub fn new(buf: &[u8]) -> Option<Self> {
+        Self::parse(buf)
+            .map_err(|e| warn_parse_error("device qualifier", buf, e))
+            .ok()
+    }
+
+    /// Parse a buffer beginning with a device qualifier descriptor.
+    pub fn parse(buf: &[u8]) -> Result<Self, ParseError> {
+        check_header(
+            buf,
+            DESCRIPTOR_LEN_DEVICE_QUALIFIER,
+            DESCRIPTOR_TYPE_DEVICE_QUALIFIER,
+        )?;
+        Ok(Self(
+            buf[..DESCRIPTOR_LEN_DEVICE_QUALIFIER as usize]
+                .try_into()
+                .unwrap(),
+        ))
+    }
+
+    /// Get the bytes of the descriptor.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+descriptor_fields! {
+    impl DeviceQualifierDescriptor {
+        /// `bcdUSB` descriptor field: USB Specification Number.
+        #[doc(alias = "bcdUSB")]
+        pub fn usb_version at 2 -> u16;
+
+        /// `bDeviceClass` descriptor field: Class code at the other speed.
+        #[doc(alias = "bDeviceClass")]
+        pub fn class at 4 -> u8;
+
+        /// `bDeviceSubClass` descriptor field: Subclass code at the other speed.
+        #[doc(alias = "bDeviceSubClass")]
+        pub fn subclass at 5 -> u8;
+
+        /// `bDeviceProtocol` descriptor field: Protocol code at the other speed.
+        #[doc(alias = "bDeviceProtocol")]
+        pub fn protocol at 6 -> u8;
+
+        /// `bMaxPacketSize0` descriptor field: Maximum packet size for 0 Endpoint at the other speed.
+        #[doc(alias = "bMaxPacketSize0")]
+        pub fn max_packet_size_0 at 7 -> u8;
+
+        /// `bNumConfigurations` descriptor field: Number of other-speed configurations.
+        #[doc(alias = "bNumConfigurations")]
+        pub fn num_configurations at 8 -> u8;
+    }
+}
+
+impl Debug for DeviceQualifierDescriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DeviceQualifierDescriptor")
+            .field("usb_version", &format_args!("0x{:04X}", self.usb_version()))
+            .field("class", &format_args!("0x{:02X}", self.class()))
+            .field("subclass", &format_args!("0x{:02X}", self.subclass()))
+            .field("protocol", &format_args!("0x{:02X}", self.protocol()))
+            .field("max_packet_size_0", &self.max_packet_size_0())
+            .field("num_configurations", &self.num_configurations())
+            .finish()
+    }
+}
+
 /// Information about a USB configuration with access to all associated interfaces, endpoints, and other descriptors.
+///
+/// This is also used for other speed configuration descriptors, which have
+/// the same layout. See [`ConfigurationDescriptor::new_other_speed`].
 #[derive(Clone)]
 pub struct ConfigurationDescriptor<'a>(&'a [u8]);
 
@@ -361,41 +513,49 @@ impl<'a> ConfigurationDescriptor<'a> {
     /// one from your own descriptor bytes for tests.
     ///
     /// This ignores any trailing data after the length specified in `wTotalLen`.
-    pub fn new(buf: &[u8]) -> Option<ConfigurationDescriptor> {
-        if buf.len() < DESCRIPTOR_LEN_CONFIGURATION as usize {
-            if buf.len() != 0 {
-                warn!(
-                    "config descriptor buffer is {} bytes, need {}",
-                    buf.len(),
-                    DESCRIPTOR_LEN_CONFIGURATION
-                );
-            }
-            return None;
-        }
+    /// Returns `None` and logs a warning if the descriptor is invalid; use
+    /// [`parse`][Self::parse] to get the reason.
+    pub fn new(buf: &[u8]) -> Option<ConfigurationDescriptor<'_>> {
+        Self::parse(buf)
+            .map_err(|e| warn_parse_error("configuration", buf, e))
+            .ok()
+    }
 
-        if buf[0] < DESCRIPTOR_LEN_CONFIGURATION {
-            warn!("invalid config descriptor bLength");
-            return None;
-        }
+    /// Parse a buffer beginning with a configuration descriptor and its
+    /// trailing descriptors, such as a descriptor dump from a capture or bug
+    /// report.
+    ///
+    /// Only the configuration descriptor itself is validated. Malformed
+    /// trailing descriptors are skipped when iterating.
+    pub fn parse(buf: &[u8]) -> Result<ConfigurationDescriptor<'_>, ParseError> {
+        Self::parse_with_type(buf, DESCRIPTOR_TYPE_CONFIGURATION)
+    }
 
-        if buf[1] != DESCRIPTOR_TYPE_CONFIGURATION {
-            warn!(
-                "config bDescriptorType is {}, not a configuration descriptor",
-                buf[0]
-            );
-            return None;
-        }
+    /// Create a `ConfigurationDescriptor` from a buffer containing an other
+    /// speed configuration descriptor and its trailing descriptors.
+    ///
+    /// This describes the configuration as it would be if a high-speed
+    /// capable device were operating at the other speed. Obtain the bytes
+    /// from [`Device::other_speed_configuration`][crate::Device::other_speed_configuration].
+    pub fn new_other_speed(buf: &[u8]) -> Option<ConfigurationDescriptor<'_>> {
+        Self::parse_other_speed(buf)
+            .map_err(|e| warn_parse_error("other speed configuration", buf, e))
+            .ok()
+    }
 
-        let total_len = u16::from_le_bytes(buf[2..4].try_into().unwrap()) as usize;
-        if total_len < buf[0] as usize || total_len > buf.len() {
-            warn!(
-                "invalid config descriptor wTotalLen of {total_len} (buffer size is {bufsize})",
-                bufsize = buf.len()
-            );
-            return None;
-        }
+    /// Parse a buffer beginning with an other speed configuration
+    /// descriptor and its trailing descriptors.
+    pub fn parse_other_speed(buf: &[u8]) -> Result<ConfigurationDescriptor<'_>, ParseError> {
+        Self::parse_with_type(buf, DESCRIPTOR_TYPE_OTHER_SPEED_CONFIGURATION)
+    }
 
-        Some(ConfigurationDescriptor(&buf[..total_len]))
+    fn parse_with_type(
+        buf: &[u8],
+        descriptor_type: u8,
+    ) -> Result<ConfigurationDescriptor<'_>, ParseError> {
+        check_header(buf, DESCRIPTOR_LEN_CONFIGURATION, descriptor_type)?;
+        let total_len = check_total_length(buf)?;
+        Ok(ConfigurationDescriptor(&buf[..total_len]))
     }
 
     #[allow(unused)]
@@ -420,6 +580,42 @@ impl<'a> ConfigurationDescriptor<'a> {
             .map(InterfaceDescriptor)
     }
 
+    /// Iterate the interface association descriptors of this configuration,
+    /// which group the interfaces of a function such as a video or CDC
+    /// device.
+    ///
+    /// Use [`association_interfaces`][Self::association_interfaces] to get
+    /// the interfaces grouped by an association.
+    #[doc(alias = "iter_associations")]
+    pub fn interface_associations(
+        &self,
+    ) -> impl Iterator<Item = InterfaceAssociationDescriptor<'a>> {
+        self.descriptors()
+            .filter(|d| d.descriptor_type() == DESCRIPTOR_TYPE_INTERFACE_ASSOCIATION)
+            .filter(|d| {
+                if d.descriptor_len() < DESCRIPTOR_LEN_INTERFACE_ASSOCIATION as usize {
+                    warn!(
+                        "ignoring interface association descriptor of length {}",
+                        d[0]
+                    );
+                    return false;
+                }
+                true
+            })
+            .map(|d| InterfaceAssociationDescriptor(d.0))
+    }
+
+    /// Iterate the interfaces of this configuration that are grouped by an
+    /// interface association descriptor.
+    pub fn association_interfaces(
+        &self,
+        association: &InterfaceAssociationDescriptor,
+    ) -> impl Iterator<Item = InterfaceDescriptors<'a>> {
+        let numbers = association.interface_numbers();
+        self.interfaces()
+            .filter(move |intf| numbers.contains(&intf.interface_number()))
+    }
+
     /// Iterate the interfaces of this configuration, grouping together alternate settings of the same interface.
     pub fn interfaces(&self) -> impl Iterator<Item = InterfaceDescriptors<'a>> {
         let mut interfaces = BTreeMap::new();
@@ -482,13 +678,13 @@ where
     I: Iterator,
     I::Item: Debug,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_list().entries(self.0()).finish()
     }
 }
 
 impl<'a> Debug for ConfigurationDescriptor<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Configuration")
             .field("configuration_value", &self.configuration_value())
             .field("num_interfaces", &self.num_interfaces())
@@ -503,6 +699,73 @@ impl<'a> Debug for ConfigurationDescriptor<'a> {
     }
 }
 
+/// Interface association descriptor, grouping consecutive interfaces that
+/// form a single function.
+///
+/// Obtain from [`ConfigurationDescriptor::interface_associations`].
+#[derive(Clone)]
+pub struct InterfaceAssociationDescriptor<'a>(&'a [u8]);
+
+impl<'a> InterfaceAssociationDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// The range of interface numbers in the association.
+    pub fn interface_numbers(&self) -> core::ops::RangeInclusive<u8> {
+        let first = self.first_interface();
+        first..=first.saturating_add(self.interface_count().saturating_sub(1))
+    }
+
+    /// Index of the string descriptor describing the function.
+    #[doc(alias = "iFunction")]
+    pub fn string_index(&self) -> Option<NonZeroU8> {
+        NonZeroU8::new(self.string_index_raw())
+    }
+}
+
+descriptor_fields! {
+    impl<'a> InterfaceAssociationDescriptor<'a> {
+        /// `bFirstInterface` descriptor field: Number of the first interface
+        /// in the association.
+        #[doc(alias = "bFirstInterface")]
+        pub fn first_interface at 2 -> u8;
+
+        /// `bInterfaceCount` descriptor field: Number of consecutive
+        /// interfaces in the association.
+        #[doc(alias = "bInterfaceCount")]
+        pub fn interface_count at 3 -> u8;
+
+        /// `bFunctionClass` descriptor field: Class of the function.
+        #[doc(alias = "bFunctionClass")]
+        pub fn function_class at 4 -> u8;
+
+        /// `bFunctionSubClass` descriptor field: Subclass of the function.
+        #[doc(alias = "bFunctionSubClass")]
+        pub fn function_subclass at 5 -> u8;
+
+        /// `bFunctionProtocol` descriptor field: Protocol of the function.
+        #[doc(alias = "bFunctionProtocol")]
+        pub fn function_protocol at 6 -> u8;
+
+        fn string_index_raw at 7 -> u8;
+    }
+}
+
+impl<'a> Debug for InterfaceAssociationDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InterfaceAssociation")
+            .field("first_interface", &self.first_interface())
+            .field("interface_count", &self.interface_count())
+            .field("function_class", &self.function_class())
+            .field("function_subclass", &self.function_subclass())
+            .field("function_protocol", &self.function_protocol())
+            .field("string_index", &self.string_index())
+            .finish()
+    }
+}
+
 /// Interface descriptors for alternate settings, grouped by the interface number.
 #[derive(Clone)]
 pub struct InterfaceDescriptors<'a> {
@@ -542,7 +805,7 @@ pub struct InterfaceDescriptor<'a>(&'a [u8]);
 
 impl<'a> InterfaceDescriptor<'a> {
     /// The bytes of the interface descriptor and all trailing descriptors.
-    pub fn as_bytes(&self) -> &[u8] {
+    pub fn as_bytes(&self) -> &'a [u8] {
         self.0
     }
 
@@ -552,6 +815,16 @@ impl<'a> InterfaceDescriptor<'a> {
         DescriptorIter(&self.0[self.0[0] as usize..])
     }
 
+    /// Iterate the class-specific and vendor-specific descriptors between
+    /// the interface descriptor and its first endpoint descriptor, as their
+    /// `bDescriptorType` and bytes.
+    pub fn class_descriptors(&self) -> impl Iterator<Item = (u8, &'a [u8])> {
+        self.descriptors()
+            .take_while(|d| d.descriptor_type() != DESCRIPTOR_TYPE_ENDPOINT)
+            .filter(|d| !is_standard_descriptor_type(d.descriptor_type()))
+            .map(|d| (d.descriptor_type(), d.0))
+    }
+
     /// Get the endpoints of this interface.
     pub fn endpoints(&self) -> impl Iterator<Item = EndpointDescriptor<'a>> {
         self.descriptors()
@@ -603,7 +876,7 @@ impl<'a> InterfaceDescriptor<'a> {
 }
 
 impl<'a> Debug for InterfaceDescriptor<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("InterfaceAltSetting")
             .field("interface_number", &self.interface_number())
             .field("alternate_setting", &self.alternate_setting())
@@ -631,6 +904,14 @@ impl<'a> EndpointDescriptor<'a> {
         DescriptorIter(&self.0[self.0[0] as usize..])
     }
 
+    /// Iterate the class-specific and vendor-specific descriptors following
+    /// the endpoint descriptor, as their `bDescriptorType` and bytes.
+    pub fn class_descriptors(&self) -> impl Iterator<Item = (u8, &'a [u8])> {
+        self.descriptors()
+            .filter(|d| !is_standard_descriptor_type(d.descriptor_type()))
+            .map(|d| (d.descriptor_type(), d.0))
+    }
+
     /// Get the endpoint's direction.
     pub fn direction(&self) -> Direction {
         Direction::from_address(self.address())
@@ -656,6 +937,15 @@ impl<'a> EndpointDescriptor<'a> {
     pub fn packets_per_microframe(&self) -> u8 {
         ((self.max_packet_size_raw() >> 11) & 0b11) as u8 + 1
     }
+
+    /// Get the SuperSpeed endpoint companion descriptor following this
+    /// endpoint, present when the device is operating at SuperSpeed or faster.
+    pub fn superspeed_companion(&self) -> Option<SuperSpeedCompanionDescriptor<'a>> {
+        self.descriptors()
+            .find(|d| d.descriptor_type() == DESCRIPTOR_TYPE_SUPERSPEED_COMPANION)
+            .filter(|d| d.descriptor_len() >= DESCRIPTOR_LEN_SUPERSPEED_COMPANION as usize)
+            .map(|d| SuperSpeedCompanionDescriptor(d.0))
+    }
 }
 
 descriptor_fields! {
@@ -684,7 +974,7 @@ descriptor_fields! {
 }
 
 impl<'a> Debug for EndpointDescriptor<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Endpoint")
             .field("address", &format_args!("0x{:02X}", self.address()))
             .field("direction", &self.direction())
@@ -692,10 +982,196 @@ impl<'a> Debug for EndpointDescriptor<'a> {
             .field("max_packet_size", &self.max_packet_size())
             .field("packets_per_microframe", &self.packets_per_microframe())
             .field("interval", &self.interval())
+            .field("superspeed_companion", &self.superspeed_companion())
+            .finish()
+    }
+}
+
+/// SuperSpeed endpoint companion descriptor, with the burst and bandwidth
+/// information of a USB 3 endpoint.
+///
+/// Obtain from [`EndpointDescriptor::superspeed_companion`].
+pub struct SuperSpeedCompanionDescriptor<'a>(&'a [u8]);
+
+impl<'a> SuperSpeedCompanionDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// For a bulk endpoint, the maximum number of streams it supports, from
+    /// the `MaxStreams` subfield of `bmAttributes`.
+    ///
+    /// Returns `0` if the endpoint does not support streams. The value is
+    /// meaningless for other endpoint types.
+    #[doc(alias = "MaxStreams")]
+    pub fn max_streams(&self) -> u32 {
+        match self.attributes() & 0x1F {
+            0 => 0,
+            n => 1 << n,
+        }
+    }
+
+    /// For an isochronous endpoint, the `Mult` subfield of `bmAttributes`:
+    /// the maximum number of bursts per service interval, minus one.
+    ///
+    /// The endpoint transfers up to `(max_burst() + 1) * (mult() + 1)`
+    /// packets per service interval. The value is meaningless for other
+    /// endpoint types.
+    #[doc(alias = "Mult")]
+    pub fn mult(&self) -> u8 {
+        self.attributes() & 0x03
+    }
+}
+
+descriptor_fields! {
+    impl<'a> SuperSpeedCompanionDescriptor<'a> {
+        /// Get the `bMaxBurst` descriptor field: Number of packets the
+        /// endpoint can send or receive in a burst, minus one.
+        #[doc(alias = "bMaxBurst")]
+        pub fn max_burst at 2 -> u8;
+
+        /// Get the raw value of the `bmAttributes` descriptor field.
+        ///
+        /// For bulk endpoints, this contains the maximum number of streams.
+        /// For isochronous endpoints, it contains the `Mult` field.
+        #[doc(alias = "bmAttributes")]
+        pub fn attributes at 3 -> u8;
+
+        /// Get the `wBytesPerInterval` descriptor field: Total number of
+        /// bytes a periodic endpoint transfers per service interval.
+        #[doc(alias = "wBytesPerInterval")]
+        pub fn bytes_per_interval at 4 -> u16;
+    }
+}
+
+impl<'a> Debug for SuperSpeedCompanionDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SuperSpeedCompanion")
+            .field("max_burst", &self.max_burst())
+            .field("attributes", &format_args!("0x{:02X}", self.attributes()))
+            .field("max_streams", &self.max_streams())
+            .field("mult", &self.mult())
+            .field("bytes_per_interval", &self.bytes_per_interval())
             .finish()
     }
 }
 
+/// Error from parsing a descriptor with a `parse` function such as
+/// [`ConfigurationDescriptor::parse`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The buffer is shorter than the fixed fields of the descriptor.
+    BufferTooShort {
+        /// Length of the buffer.
+        len: usize,
+
+        /// Length of the fixed fields of the descriptor.
+        needed: usize,
+    },
+
+    /// The `bLength` field is shorter than the fixed fields of the descriptor.
+    InvalidLength {
+        /// Value of `bLength`.
+        length: u8,
+
+        /// Length of the fixed fields of the descriptor.
+        needed: u8,
+    },
+
+    /// The `bDescriptorType` field is not the expected type.
+    WrongType {
+        /// Value of `bDescriptorType`.
+        found: u8,
+
+        /// Expected descriptor type.
+        expected: u8,
+    },
+
+    /// The `wTotalLength` field is shorter than the descriptor itself or
+    /// longer than the buffer.
+    InvalidTotalLength {
+        /// Value of `wTotalLength`.
+        total_len: usize,
+
+        /// Length of the buffer.
+        len: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            ParseError::BufferTooShort { len, needed } => {
+                write!(f, "buffer is {len} bytes, need {needed}")
+            }
+            ParseError::InvalidLength { length, needed } => {
+                write!(f, "bLength of {length} is less than {needed}")
+            }
+            ParseError::WrongType { found, expected } => {
+                write!(f, "bDescriptorType is {found}, expected {expected}")
+            }
+            ParseError::InvalidTotalLength { total_len, len } => {
+                write!(
+                    f,
+                    "invalid wTotalLength of {total_len} (buffer size is {len})"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Check the buffer length, `bLength` and `bDescriptorType` of the
+/// descriptor at the start of `buf`.
+pub(crate) fn check_header(buf: &[u8], needed: u8, descriptor_type: u8) -> Result<(), ParseError> {
+    if buf.len() < needed as usize {
+        return Err(ParseError::BufferTooShort {
+            len: buf.len(),
+            needed: needed as usize,
+        });
+    }
+
+    if buf[0] < needed {
+        return Err(ParseError::InvalidLength {
+            length: buf[0],
+            needed,
+        });
+    }
+
+    if buf[1] != descriptor_type {
+        return Err(ParseError::WrongType {
+            found: buf[1],
+            expected: descriptor_type,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check the `wTotalLength` field of a descriptor that contains others,
+/// returning it.
+pub(crate) fn check_total_length(buf: &[u8]) -> Result<usize, ParseError> {
+    let total_len = u16::from_le_bytes(buf[2..4].try_into().unwrap()) as usize;
+    if total_len < buf[0] as usize || total_len > buf.len() {
+        return Err(ParseError::InvalidTotalLength {
+            total_len,
+            len: buf.len(),
+        });
+    }
+    Ok(total_len)
+}
+
+/// Log the failure of a `new` function, other than for an empty buffer.
+pub(crate) fn warn_parse_error(name: &str, buf: &[u8], e: ParseError) {
+    if !buf.is_empty() {
+        warn!("invalid {name} descriptor: {e}");
+    }
+}
+
 /// Error from [`crate::Device::active_configuration`]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ActiveConfigurationError {
@@ -703,7 +1179,7 @@ pub struct ActiveConfigurationError {
 }
 
 impl Display for ActiveConfigurationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if self.configuration_value == 0 {
             write!(f, "device is not configured")
         } else {
@@ -716,11 +1192,13 @@ impl Display for ActiveConfigurationError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for ActiveConfigurationError {}
 
+#[cfg(feature = "std")]
 impl From<ActiveConfigurationError> for Error {
     fn from(value: ActiveConfigurationError) -> Self {
-        Error::new(ErrorKind::Other, value)
+        Error::other(value)
     }
 }
 
@@ -728,7 +1206,7 @@ impl From<ActiveConfigurationError> for Error {
 #[allow(unused)]
 pub(crate) fn parse_concatenated_config_descriptors(
     mut buf: &[u8],
-) -> impl Iterator<Item = ConfigurationDescriptor> {
+) -> impl Iterator<Item = ConfigurationDescriptor<'_>> {
     iter::from_fn(move || {
         let desc = ConfigurationDescriptor::new(buf)?;
         buf = &buf[desc.0.len()..];
@@ -736,10 +1214,12 @@ pub(crate) fn parse_concatenated_config_descriptors(
     })
 }
 
+#[cfg_attr(not(feature = "std"), allow(unused))]
 pub(crate) fn validate_string_descriptor(data: &[u8]) -> bool {
     data.len() >= 2 && data[0] as usize == data.len() && data[1] == DESCRIPTOR_TYPE_STRING
 }
 
+#[cfg_attr(not(feature = "std"), allow(unused))]
 pub(crate) fn decode_string_descriptor(data: &[u8]) -> Result<String, ()> {
     if !validate_string_descriptor(data) {
         return Err(());
@@ -754,10 +1234,76 @@ pub(crate) fn decode_string_descriptor(data: &[u8]) -> Result<String, ()> {
     .collect::<String>())
 }
 
-/// Make public when fuzzing
-#[cfg(fuzzing)]
-pub fn fuzz_parse_concatenated_config_descriptors(buf: &[u8]) -> impl Iterator<Item = &[u8]> {
-    parse_concatenated_config_descriptors(buf)
+/// Encode `s` as a string descriptor, or return `None` if it doesn't fit in
+/// the 255-byte maximum descriptor length.
+pub(crate) fn encode_string_descriptor(s: &str) -> Option<Vec<u8>> {
+    let mut data = vec![0, DESCRIPTOR_TYPE_STRING];
+    for c in s.encode_utf16() {
+        data.extend_from_slice(&c.to_le_bytes());
+    }
+    data[0] = data.len().try_into().ok()?;
+    Some(data)
+}
+
+/// With the `serde` feature, descriptors are serialized as their bytes, which
+/// can be parsed again with their `new` function.
+#[cfg(feature = "serde")]
+macro_rules! serialize_as_bytes {
+    ($($ty:ty),*) => {
+        $(
+            impl serde::Serialize for $ty {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_bytes(self.as_bytes())
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+serialize_as_bytes!(
+    DeviceDescriptor,
+    DeviceQualifierDescriptor,
+    ConfigurationDescriptor<'_>,
+    InterfaceAssociationDescriptor<'_>,
+    InterfaceDescriptor<'_>,
+    EndpointDescriptor<'_>,
+    SuperSpeedCompanionDescriptor<'_>
+);
+
+/// The other descriptors borrow their bytes, so only `DeviceDescriptor` can be
+/// deserialized directly.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DeviceDescriptor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = DeviceDescriptor;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("device descriptor bytes")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<DeviceDescriptor, E> {
+                DeviceDescriptor::new(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Bytes(v), &self))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<DeviceDescriptor, A::Error> {
+                let mut buf = Vec::new();
+                while let Some(b) = seq.next_element()? {
+                    buf.push(b);
+                }
+                self.visit_bytes(&buf)
+            }
+        }
+
+        deserializer.deserialize_bytes(Visitor)
+    }
 }
 
 #[cfg(test)]
@@ -1142,3 +1688,197 @@ fn test_dell_webcam() {
     assert!(alts.next().is_none());
     assert!(interfaces.next().is_none());
 }
+
+#[test]
+fn test_string_descriptor_roundtrip() {
+    let data = encode_string_descriptor("SN-0001 \u{1F50C}").unwrap();
+    assert_eq!(&data[..4], &[22, DESCRIPTOR_TYPE_STRING, b'S', 0]);
+    assert_eq!(
+        decode_string_descriptor(&data).unwrap(),
+        "SN-0001 \u{1F50C}"
+    );
+
+    assert!(encode_string_descriptor(&"x".repeat(126)).is_some());
+    assert!(encode_string_descriptor(&"x".repeat(127)).is_none());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_superspeed_companion() {
+    let c = ConfigurationDescriptor(&[
+        0x09, 0x02, 0x2c, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32,
+        0x09, 0x04, 0x00, 0x00, 0x02, 0x08, 0x06, 0x50, 0x00,
+        0x07, 0x05, 0x81, 0x02, 0x00, 0x04, 0x00,
+        0x06, 0x30, 0x0f, 0x00, 0x00, 0x00,
+        0x07, 0x05, 0x02, 0x02, 0x00, 0x04, 0x00,
+        0x06, 0x30, 0x0f, 0x04, 0x00, 0x00,
+    ]);
+
+    let alt = c.interface_alt_settings().next().unwrap();
+    let companions: Vec<_> = alt
+        .endpoints()
+        .map(|ep| {
+            let ss = ep.superspeed_companion().unwrap();
+            (ep.address(), ss.max_burst(), ss.attributes())
+        })
+        .collect();
+    assert_eq!(companions, [(0x81, 15, 0), (0x02, 15, 4)]);
+
+    let streams: Vec<_> = alt
+        .endpoints()
+        .map(|ep| ep.superspeed_companion().unwrap().max_streams())
+        .collect();
+    assert_eq!(streams, [0, 16]);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        DeviceDescriptor::parse(&[0x12, 0x01, 0x00]).unwrap_err(),
+        ParseError::BufferTooShort { len: 3, needed: 18 }
+    );
+
+    let mut dev = [0; 18];
+    dev[0] = 0x12;
+    dev[1] = 0x02;
+    assert_eq!(
+        DeviceDescriptor::parse(&dev).unwrap_err(),
+        ParseError::WrongType {
+            found: 2,
+            expected: 1
+        }
+    );
+    dev[0] = 0x11;
+    assert_eq!(
+        DeviceDescriptor::parse(&dev).unwrap_err(),
+        ParseError::InvalidLength {
+            length: 0x11,
+            needed: 18
+        }
+    );
+
+    let config = [0x09, 0x02, 0x12, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32];
+    assert_eq!(
+        ConfigurationDescriptor::parse(&config).unwrap_err(),
+        ParseError::InvalidTotalLength {
+            total_len: 18,
+            len: 9
+        }
+    );
+    assert_eq!(
+        ConfigurationDescriptor::parse(&config)
+            .unwrap_err()
+            .to_string(),
+        "invalid wTotalLength of 18 (buffer size is 9)"
+    );
+    assert!(ConfigurationDescriptor::new(&config).is_none());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_other_speed() {
+    let q = DeviceQualifierDescriptor::new(&[
+        0x0a, 0x06, 0x00, 0x02, 0x00, 0x00, 0x00, 0x40, 0x01, 0x00,
+    ]).unwrap();
+    assert_eq!(q.usb_version(), 0x0200);
+    assert_eq!(q.class(), 0);
+    assert_eq!(q.max_packet_size_0(), 64);
+    assert_eq!(q.num_configurations(), 1);
+    assert!(DeviceQualifierDescriptor::new(&[0x0a, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x40, 0x01, 0x00]).is_none());
+
+    let buf = [
+        0x09, 0x07, 0x20, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32,
+        0x09, 0x04, 0x00, 0x00, 0x02, 0x08, 0x06, 0x50, 0x00,
+        0x07, 0x05, 0x81, 0x02, 0x40, 0x00, 0x00,
+        0x07, 0x05, 0x02, 0x02, 0x40, 0x00, 0x00,
+    ];
+    assert!(ConfigurationDescriptor::new(&buf).is_none());
+    let c = ConfigurationDescriptor::new_other_speed(&buf).unwrap();
+    assert_eq!(c.configuration_value(), 1);
+    let packet_sizes: Vec<_> = c
+        .interface_alt_settings()
+        .flat_map(|alt| alt.endpoints())
+        .map(|ep| ep.max_packet_size())
+        .collect();
+    assert_eq!(packet_sizes, [64, 64]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_class_descriptors() {
+    let c = ConfigurationDescriptor(&[
+        0x09, 0x02, 0x31, 0x00, 0x01, 0x01, 0x00, 0x80, 0x32,
+        0x09, 0x04, 0x01, 0x01, 0x01, 0x01, 0x02, 0x00, 0x00,
+        0x07, 0x24, 0x01, 0x01, 0x01, 0x01, 0x00,
+        0x08, 0x24, 0x02, 0x01, 0x02, 0x02, 0x10, 0x01,
+        0x09, 0x05, 0x01, 0x09, 0xc8, 0x00, 0x01, 0x00, 0x00,
+        0x07, 0x25, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ]);
+
+    let alt = c.interface_alt_settings().next().unwrap();
+    let interface: Vec<_> = alt.class_descriptors().map(|(ty, buf)| (ty, buf.len())).collect();
+    assert_eq!(interface, [(0x24, 7), (0x24, 8)]);
+
+    let ep = alt.endpoints().next().unwrap();
+    let endpoint: Vec<_> = ep.class_descriptors().collect();
+    assert_eq!(endpoint, [(0x25, &[0x07, 0x25, 0x01, 0x00, 0x00, 0x00, 0x00][..])]);
+
+    // The bytes borrow from the configuration, not the interface.
+    let bytes = c.interface_alt_settings().next().unwrap().as_bytes();
+    assert_eq!(bytes, &c.as_bytes()[9..]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_interface_associations() {
+    let c = ConfigurationDescriptor(&[
+        0x09, 0x02, 0x38, 0x00, 0x03, 0x01, 0x00, 0x80, 0x32,
+        0x08, 0x0b, 0x01, 0x02, 0x02, 0x02, 0x01, 0x05,
+        0x09, 0x04, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00,
+        0x09, 0x04, 0x01, 0x00, 0x01, 0x02, 0x02, 0x01, 0x00,
+        0x07, 0x05, 0x83, 0x03, 0x08, 0x00, 0x10,
+        0x09, 0x04, 0x02, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x05, 0x0b, 0x03, 0x01, 0x02,
+    ]);
+
+    let mut associations = c.interface_associations();
+    let iad = associations.next().unwrap();
+    assert_eq!(iad.first_interface(), 1);
+    assert_eq!(iad.interface_numbers(), 1..=2);
+    assert_eq!(
+        (iad.function_class(), iad.function_subclass(), iad.function_protocol()),
+        (2, 2, 1)
+    );
+    assert_eq!(iad.string_index(), NonZeroU8::new(5));
+
+    let interfaces: Vec<_> = c
+        .association_interfaces(&iad)
+        .map(|intf| intf.interface_number())
+        .collect();
+    assert_eq!(interfaces, [1, 2]);
+
+    // The truncated second descriptor is ignored.
+    assert!(associations.next().is_none());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_deserialize_device_descriptor() {
+    use serde::{de::value::BytesDeserializer, Deserialize};
+
+    let bytes = [
+        0x12, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x40, 0x34, 0x12, 0x78, 0x56, 0x00, 0x01, 0x01,
+        0x02, 0x03, 0x01,
+    ];
+    let d =
+        DeviceDescriptor::deserialize(BytesDeserializer::<serde::de::value::Error>::new(&bytes))
+            .unwrap();
+    assert_eq!(d.as_bytes(), bytes);
+
+    assert!(
+        DeviceDescriptor::deserialize(BytesDeserializer::<serde::de::value::Error>::new(
+            &bytes[..8]
+        ))
+        .is_err()
+    );
+}
@@ -0,0 +1,628 @@
+//! Binary Object Store (BOS) descriptor, describing the device capabilities
+//! of USB 2.1 and later devices.
+//!
+//! Obtain from [`Device::bos`][crate::Device::bos].
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, num::NonZeroU8};
+
+use super::{
+    check_header, check_total_length, warn_parse_error, Descriptor, DescriptorIter, ParseError,
+};
+
+pub(crate) const DESCRIPTOR_TYPE_BOS: u8 = 0x0F;
+pub(crate) const DESCRIPTOR_LEN_BOS: u8 = 5;
+
+pub(crate) const DESCRIPTOR_TYPE_DEVICE_CAPABILITY: u8 = 0x10;
+
+const CAPABILITY_TYPE_USB2_EXTENSION: u8 = 0x02;
+const CAPABILITY_LEN_USB2_EXTENSION: u8 = 7;
+
+const CAPABILITY_TYPE_SUPERSPEED: u8 = 0x03;
+const CAPABILITY_LEN_SUPERSPEED: u8 = 10;
+
+const CAPABILITY_TYPE_CONTAINER_ID: u8 = 0x04;
+const CAPABILITY_LEN_CONTAINER_ID: u8 = 20;
+
+const CAPABILITY_TYPE_PLATFORM: u8 = 0x05;
+const CAPABILITY_LEN_PLATFORM: u8 = 20;
+
+const CAPABILITY_TYPE_BILLBOARD: u8 = 0x0D;
+const CAPABILITY_LEN_BILLBOARD: u8 = 44;
+
+/// Convert a UUID stored in a descriptor, where the first three fields are
+/// little-endian, to the order it is normally written in.
+pub(crate) fn uuid_from_le_bytes(b: &[u8]) -> u128 {
+    let mut be: [u8; 16] = b[..16].try_into().unwrap();
+    be[0..4].reverse();
+    be[4..6].reverse();
+    be[6..8].reverse();
+    u128::from_be_bytes(be)
+}
+
+/// Binary Object Store descriptor, the list of the device's capabilities.
+#[derive(Clone)]
+pub struct BosDescriptor<'a>(&'a [u8]);
+
+impl<'a> BosDescriptor<'a> {
+    /// Create a `BosDescriptor` from a buffer beginning with a BOS descriptor.
+    ///
+    /// You normally obtain a `BosDescriptor` from a [`Device`][crate::Device],
+    /// but this allows creating one from your own descriptor bytes for tests.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `wTotalLength`.
+    pub fn new(buf: &[u8]) -> Option<BosDescriptor<'_>> {
+        Self::parse(buf)
+            .map_err(|e| warn_parse_error("BOS", buf, e))
+            .ok()
+    }
+
+    /// Parse a buffer beginning with a BOS descriptor and the capability
+    /// descriptors it contains.
+    pub fn parse(buf: &[u8]) -> Result<BosDescriptor<'_>, ParseError> {
+        check_header(buf, DESCRIPTOR_LEN_BOS, DESCRIPTOR_TYPE_BOS)?;
+        let total_len = check_total_length(buf)?;
+        Ok(BosDescriptor(&buf[..total_len]))
+    }
+
+    /// The bytes of the BOS descriptor and the capability descriptors it
+    /// contains.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Iterate over the device capability descriptors.
+    pub fn capabilities(&self) -> impl Iterator<Item = DeviceCapability<'a>> {
+        DescriptorIter(&self.0[self.0[0] as usize..])
+            .filter(|d| d.descriptor_type() == DESCRIPTOR_TYPE_DEVICE_CAPABILITY)
+            .flat_map(DeviceCapability::new)
+    }
+
+    /// Get the USB 2.0 Extension capability, if the device has one.
+    pub fn usb2_extension(&self) -> Option<Usb2ExtensionCapability<'a>> {
+        self.capabilities().find_map(|c| match c {
+            DeviceCapability::Usb2Extension(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Get the SuperSpeed USB capability, if the device has one.
+    pub fn superspeed(&self) -> Option<SuperSpeedCapability<'a>> {
+        self.capabilities().find_map(|c| match c {
+            DeviceCapability::SuperSpeed(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Get the Container ID capability, if the device has one.
+    pub fn container_id(&self) -> Option<ContainerIdCapability<'a>> {
+        self.capabilities().find_map(|c| match c {
+            DeviceCapability::ContainerId(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Get the Billboard capability, if the device has one.
+    ///
+    /// USB Type-C devices expose it when they could not enter the
+    /// alternate mode requested by the host.
+    pub fn billboard(&self) -> Option<BillboardCapability<'a>> {
+        self.capabilities().find_map(|c| match c {
+            DeviceCapability::Billboard(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the platform capabilities, which identify their format
+    /// by UUID.
+    pub fn platform_capabilities(&self) -> impl Iterator<Item = PlatformCapability<'a>> {
+        self.capabilities().filter_map(|c| match c {
+            DeviceCapability::Platform(c) => Some(c),
+            _ => None,
+        })
+    }
+}
+
+descriptor_fields! {
+    impl<'a> BosDescriptor<'a> {
+        /// `wTotalLength` descriptor field: Length of the BOS descriptor and
+        /// all of its capability descriptors.
+        #[doc(alias = "wTotalLength")]
+        pub fn total_length at 2 -> u16;
+
+        /// `bNumDeviceCaps` descriptor field: Number of device capability
+        /// descriptors.
+        #[doc(alias = "bNumDeviceCaps")]
+        pub fn num_device_caps at 4 -> u8;
+    }
+}
+
+impl<'a> Debug for BosDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BosDescriptor")
+            .field("total_length", &self.total_length())
+            .field("num_device_caps", &self.num_device_caps())
+            .field("capabilities", &self.capabilities().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A device capability descriptor in a [`BosDescriptor`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum DeviceCapability<'a> {
+    /// USB 2.0 Extension capability
+    Usb2Extension(Usb2ExtensionCapability<'a>),
+
+    /// SuperSpeed USB capability
+    SuperSpeed(SuperSpeedCapability<'a>),
+
+    /// Container ID capability
+    ContainerId(ContainerIdCapability<'a>),
+
+    /// Platform capability
+    Platform(PlatformCapability<'a>),
+
+    /// Billboard capability
+    Billboard(BillboardCapability<'a>),
+
+    /// A capability of another type, or too short for its type
+    Other(Descriptor<'a>),
+}
+
+impl<'a> DeviceCapability<'a> {
+    fn new(desc: Descriptor<'a>) -> Option<DeviceCapability<'a>> {
+        let buf = desc.0.get(..desc.descriptor_len())?;
+        let capability_type = *buf.get(2)?;
+        let len = buf.len();
+
+        Some(match capability_type {
+            CAPABILITY_TYPE_USB2_EXTENSION if len >= CAPABILITY_LEN_USB2_EXTENSION as usize => {
+                DeviceCapability::Usb2Extension(Usb2ExtensionCapability(buf))
+            }
+            CAPABILITY_TYPE_SUPERSPEED if len >= CAPABILITY_LEN_SUPERSPEED as usize => {
+                DeviceCapability::SuperSpeed(SuperSpeedCapability(buf))
+            }
+            CAPABILITY_TYPE_CONTAINER_ID if len >= CAPABILITY_LEN_CONTAINER_ID as usize => {
+                DeviceCapability::ContainerId(ContainerIdCapability(buf))
+            }
+            CAPABILITY_TYPE_PLATFORM if len >= CAPABILITY_LEN_PLATFORM as usize => {
+                DeviceCapability::Platform(PlatformCapability(buf))
+            }
+            CAPABILITY_TYPE_BILLBOARD if len >= CAPABILITY_LEN_BILLBOARD as usize => {
+                DeviceCapability::Billboard(BillboardCapability(buf))
+            }
+            _ => DeviceCapability::Other(Descriptor(buf)),
+        })
+    }
+
+    /// `bDevCapabilityType` descriptor field: Type of the capability.
+    #[doc(alias = "bDevCapabilityType")]
+    pub fn capability_type(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+
+    /// The bytes of the capability descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            DeviceCapability::Usb2Extension(c) => c.0,
+            DeviceCapability::SuperSpeed(c) => c.0,
+            DeviceCapability::ContainerId(c) => c.0,
+            DeviceCapability::Platform(c) => c.0,
+            DeviceCapability::Billboard(c) => c.0,
+            DeviceCapability::Other(d) => d.0,
+        }
+    }
+}
+
+/// USB 2.0 Extension capability, describing Link Power Management support.
+#[derive(Clone)]
+pub struct Usb2ExtensionCapability<'a>(&'a [u8]);
+
+impl<'a> Usb2ExtensionCapability<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Whether the device supports Link Power Management.
+    pub fn lpm(&self) -> bool {
+        self.attributes() & 0x02 != 0
+    }
+
+    /// Whether the device supports the BESL (Best Effort Service Latency)
+    /// encoding of LPM latencies.
+    pub fn besl_lpm(&self) -> bool {
+        self.attributes() & 0x04 != 0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> Usb2ExtensionCapability<'a> {
+        /// `bmAttributes` descriptor field: Bitmap of supported features.
+        #[doc(alias = "bmAttributes")]
+        pub fn attributes at 3 -> u32;
+    }
+}
+
+impl<'a> Debug for Usb2ExtensionCapability<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Usb2ExtensionCapability")
+            .field("attributes", &format_args!("0x{:08X}", self.attributes()))
+            .finish()
+    }
+}
+
+/// SuperSpeed USB capability, describing the speeds and U1/U2 link states a
+/// USB 3 device supports.
+#[derive(Clone)]
+pub struct SuperSpeedCapability<'a>(&'a [u8]);
+
+impl<'a> SuperSpeedCapability<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Whether the device can generate Latency Tolerance Messages.
+    pub fn ltm(&self) -> bool {
+        self.attributes() & 0x02 != 0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> SuperSpeedCapability<'a> {
+        /// `bmAttributes` descriptor field: Bitmap of supported features.
+        #[doc(alias = "bmAttributes")]
+        pub fn attributes at 3 -> u8;
+
+        /// `wSpeedsSupported` descriptor field: Bitmap of the supported
+        /// speeds, with bit 0 for low speed, 1 for full speed, 2 for high
+        /// speed and 3 for 5 Gbps.
+        #[doc(alias = "wSpeedsSupported")]
+        pub fn speeds_supported at 4 -> u16;
+
+        /// `bFunctionalitySupport` descriptor field: Lowest speed at which
+        /// all the functionality of the device is available.
+        #[doc(alias = "bFunctionalitySupport")]
+        pub fn functionality_support at 6 -> u8;
+
+        /// `bU1DevExitLat` descriptor field: U1 exit latency, in
+        /// microseconds.
+        #[doc(alias = "bU1DevExitLat")]
+        pub fn u1_exit_latency at 7 -> u8;
+
+        /// `wU2DevExitLat` descriptor field: U2 exit latency, in
+        /// microseconds.
+        #[doc(alias = "wU2DevExitLat")]
+        pub fn u2_exit_latency at 8 -> u16;
+    }
+}
+
+impl<'a> Debug for SuperSpeedCapability<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SuperSpeedCapability")
+            .field("attributes", &format_args!("0x{:02X}", self.attributes()))
+            .field(
+                "speeds_supported",
+                &format_args!("0x{:04X}", self.speeds_supported()),
+            )
+            .field("functionality_support", &self.functionality_support())
+            .field("u1_exit_latency", &self.u1_exit_latency())
+            .field("u2_exit_latency", &self.u2_exit_latency())
+            .finish()
+    }
+}
+
+/// Container ID capability, a UUID identifying the physical device across
+/// all the interfaces it presents, e.g. on multiple buses.
+#[derive(Clone)]
+pub struct ContainerIdCapability<'a>(&'a [u8]);
+
+impl<'a> ContainerIdCapability<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `ContainerID` descriptor field, as a number written like the UUID,
+    /// e.g. `0x3408b638_09a9_47a0_8bfd_a0768815b665`.
+    #[doc(alias = "ContainerID")]
+    pub fn container_id(&self) -> u128 {
+        uuid_from_le_bytes(&self.0[4..20])
+    }
+}
+
+impl<'a> Debug for ContainerIdCapability<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ContainerIdCapability")
+            .field(
+                "container_id",
+                &format_args!("{:032x}", self.container_id()),
+            )
+            .finish()
+    }
+}
+
+/// Platform capability, defined by the platform or vendor identified by its
+/// UUID, such as WebUSB or Microsoft OS 2.0 descriptors.
+#[derive(Clone)]
+pub struct PlatformCapability<'a>(&'a [u8]);
+
+impl<'a> PlatformCapability<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `PlatformCapabilityUUID` descriptor field, as a number written like
+    /// the UUID, e.g. `0x3408b638_09a9_47a0_8bfd_a0768815b665` for WebUSB.
+    #[doc(alias = "PlatformCapabilityUUID")]
+    pub fn uuid(&self) -> u128 {
+        uuid_from_le_bytes(&self.0[4..20])
+    }
+
+    /// `CapabilityData` descriptor field: The data following the UUID,
+    /// whose format depends on the platform.
+    #[doc(alias = "CapabilityData")]
+    pub fn data(&self) -> &'a [u8] {
+        &self.0[20..]
+    }
+}
+
+impl<'a> Debug for PlatformCapability<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PlatformCapability")
+            .field("uuid", &format_args!("{:032x}", self.uuid()))
+            .field("data", &self.data())
+            .finish()
+    }
+}
+
+/// Billboard capability, describing the USB Type-C alternate modes of a
+/// device and whether it could enter them.
+#[derive(Clone)]
+pub struct BillboardCapability<'a>(&'a [u8]);
+
+impl<'a> BillboardCapability<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `iAddtionalInfoURL` descriptor field: Index of the string descriptor
+    /// with a URL for more information about the device's alternate modes.
+    #[doc(alias = "iAddtionalInfoURL", alias = "iAdditionalInfoURL")]
+    pub fn additional_info_url_string_index(&self) -> Option<NonZeroU8> {
+        NonZeroU8::new(self.0[3])
+    }
+
+    /// Whether the device does not require VCONN power, from bit 15 of
+    /// `VCONNPower`.
+    pub fn vconn_not_required(&self) -> bool {
+        self.vconn_power() & 0x8000 != 0
+    }
+
+    /// Iterate over the alternate modes of the device.
+    pub fn alternate_modes(&self) -> impl Iterator<Item = AlternateMode> + 'a {
+        let buf = self.0;
+        let available = (buf.len() - CAPABILITY_LEN_BILLBOARD as usize) / 4;
+        let num = usize::from(self.num_alternate_modes()).min(available);
+
+        (0..num).map(move |i| {
+            let mode = &buf[CAPABILITY_LEN_BILLBOARD as usize + i * 4..][..4];
+            let configured = buf[8 + i / 4] >> ((i % 4) * 2) & 0x03;
+            AlternateMode {
+                svid: u16::from_le_bytes([mode[0], mode[1]]),
+                mode: mode[2],
+                string_index: NonZeroU8::new(mode[3]),
+                state: match configured {
+                    0 => AlternateModeState::Error,
+                    1 => AlternateModeState::NotAttempted,
+                    2 => AlternateModeState::Unsuccessful,
+                    _ => AlternateModeState::Configured,
+                },
+            }
+        })
+    }
+}
+
+descriptor_fields! {
+    impl<'a> BillboardCapability<'a> {
+        /// `bNumberOfAlternateOrUSB4Modes` descriptor field: Number of
+        /// alternate modes.
+        #[doc(alias = "bNumberOfAlternateOrUSB4Modes")]
+        pub fn num_alternate_modes at 4 -> u8;
+
+        /// `bPreferredAlternateOrUSB4Mode` descriptor field: Index of the
+        /// preferred alternate mode.
+        #[doc(alias = "bPreferredAlternateOrUSB4Mode")]
+        pub fn preferred_alternate_mode at 5 -> u8;
+
+        /// `VCONNPower` descriptor field: VCONN power needed by the
+        /// device, with the power class in bits 0-2.
+        #[doc(alias = "VCONNPower")]
+        pub fn vconn_power at 6 -> u16;
+
+        /// `bcdVersion` descriptor field: Billboard specification version,
+        /// in binary-coded decimal.
+        #[doc(alias = "bcdVersion")]
+        pub fn version at 40 -> u16;
+
+        /// `bAdditionalFailureInfo` descriptor field: Bitmap of reasons why
+        /// the device failed to enter its alternate modes, such as
+        /// insufficient power.
+        #[doc(alias = "bAdditionalFailureInfo")]
+        pub fn additional_failure_info at 42 -> u8;
+    }
+}
+
+impl<'a> Debug for BillboardCapability<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BillboardCapability")
+            .field(
+                "additional_info_url_string_index",
+                &self.additional_info_url_string_index(),
+            )
+            .field("preferred_alternate_mode", &self.preferred_alternate_mode())
+            .field("vconn_power", &format_args!("0x{:04X}", self.vconn_power()))
+            .field("version", &format_args!("0x{:04X}", self.version()))
+            .field(
+                "additional_failure_info",
+                &format_args!("0x{:02X}", self.additional_failure_info()),
+            )
+            .field(
+                "alternate_modes",
+                &self.alternate_modes().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// An alternate mode listed in a [`BillboardCapability`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AlternateMode {
+    svid: u16,
+    mode: u8,
+    string_index: Option<NonZeroU8>,
+    state: AlternateModeState,
+}
+
+impl AlternateMode {
+    /// `wSVID` descriptor field: Standard or Vendor ID of the alternate
+    /// mode, e.g. `0xFF01` for DisplayPort.
+    #[doc(alias = "wSVID")]
+    pub fn svid(&self) -> u16 {
+        self.svid
+    }
+
+    /// `bAlternateOrUSB4Mode` descriptor field: Index of the mode within
+    /// the SVID.
+    #[doc(alias = "bAlternateOrUSB4Mode")]
+    pub fn mode(&self) -> u8 {
+        self.mode
+    }
+
+    /// `iAlternateOrUSB4ModeString` descriptor field: Index of the string
+    /// descriptor describing the mode.
+    ///
+    /// Use [`Device::billboard_alternate_modes`][crate::Device::billboard_alternate_modes]
+    /// to read the strings of all the modes.
+    #[doc(alias = "iAlternateOrUSB4ModeString")]
+    pub fn string_index(&self) -> Option<NonZeroU8> {
+        self.string_index
+    }
+
+    /// Whether the device entered the mode, from `bmConfigured`.
+    #[doc(alias = "bmConfigured")]
+    pub fn state(&self) -> AlternateModeState {
+        self.state
+    }
+}
+
+/// Configuration state of an [`AlternateMode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlternateModeState {
+    /// Unspecified error.
+    Error,
+
+    /// Entering the mode was not attempted or the mode was exited.
+    NotAttempted,
+
+    /// Entering the mode was attempted but failed.
+    Unsuccessful,
+
+    /// The mode was entered successfully.
+    Configured,
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_bos() {
+    let bos = BosDescriptor::new(&[
+        0x05, 0x0f, 0x42, 0x00, 0x04,
+        0x07, 0x10, 0x02, 0x06, 0x00, 0x00, 0x00,
+        0x0a, 0x10, 0x03, 0x00, 0x0e, 0x00, 0x01, 0x0a, 0xff, 0x07,
+        0x14, 0x10, 0x04, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+        0x18, 0x10, 0x05, 0x00, 0x38, 0xb6, 0x08, 0x34, 0xa9, 0x09, 0xa0, 0x47,
+        0x8b, 0xfd, 0xa0, 0x76, 0x88, 0x15, 0xb6, 0x65, 0x00, 0x01, 0x01, 0x01,
+        // trailing data after wTotalLength
+        0xff, 0xff,
+    ]).unwrap();
+
+    assert_eq!(bos.total_length(), 0x42);
+    assert_eq!(bos.as_bytes().len(), 0x42);
+    assert_eq!(bos.num_device_caps(), 4);
+    assert_eq!(bos.capabilities().count(), 4);
+
+    let usb2 = bos.usb2_extension().unwrap();
+    assert!(usb2.lpm());
+    assert!(usb2.besl_lpm());
+
+    let ss = bos.superspeed().unwrap();
+    assert!(!ss.ltm());
+    assert_eq!(ss.speeds_supported(), 0x0e);
+    assert_eq!(ss.functionality_support(), 1);
+    assert_eq!(ss.u1_exit_latency(), 10);
+    assert_eq!(ss.u2_exit_latency(), 0x07ff);
+
+    let container = bos.container_id().unwrap();
+    assert_eq!(container.container_id(), 0x04030201_0605_0807_090a_0b0c0d0e0f10);
+
+    let platform = bos.platform_capabilities().next().unwrap();
+    assert_eq!(platform.uuid(), 0x3408b638_09a9_47a0_8bfd_a0768815b665);
+    assert_eq!(platform.data(), [0x00, 0x01, 0x01, 0x01]);
+}
+
+#[test]
+fn test_bos_invalid() {
+    assert!(BosDescriptor::new(&[]).is_none());
+    assert!(BosDescriptor::new(&[0x05, 0x02, 0x05, 0x00, 0x00]).is_none());
+    assert!(BosDescriptor::new(&[0x05, 0x0f, 0x0a, 0x00, 0x01]).is_none());
+
+    // Truncated capabilities are ignored
+    let bos = BosDescriptor::new(&[0x05, 0x0f, 0x08, 0x00, 0x01, 0x07, 0x10, 0x02]).unwrap();
+    assert_eq!(bos.capabilities().count(), 0);
+
+    // Capabilities too short for their type are `Other`
+    let bos = BosDescriptor::new(&[0x05, 0x0f, 0x08, 0x00, 0x01, 0x03, 0x10, 0x02]).unwrap();
+    assert!(matches!(
+        bos.capabilities().next(),
+        Some(DeviceCapability::Other(_))
+    ));
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_billboard() {
+    let bos = BosDescriptor::new(&[
+        0x05, 0x0f, 0x39, 0x00, 0x01,
+        0x34, 0x10, 0x0d, 0x01, 0x02, 0x00, 0x00, 0x80,
+        // bmConfigured: mode 0 unsuccessful, mode 1 not attempted
+        0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x21, 0x01, 0x01, 0x00,
+        0x01, 0xff, 0x00, 0x02,
+        0x87, 0x80, 0x01, 0x00,
+    ]).unwrap();
+
+    let billboard = bos.billboard().unwrap();
+    assert_eq!(billboard.additional_info_url_string_index(), NonZeroU8::new(1));
+    assert_eq!(billboard.num_alternate_modes(), 2);
+    assert!(billboard.vconn_not_required());
+    assert_eq!(billboard.version(), 0x0121);
+    assert_eq!(billboard.additional_failure_info(), 1);
+
+    let modes: Vec<_> = billboard.alternate_modes().collect();
+    assert_eq!(modes.len(), 2);
+    assert_eq!(modes[0].svid(), 0xff01);
+    assert_eq!(modes[0].string_index(), NonZeroU8::new(2));
+    assert_eq!(modes[0].state(), AlternateModeState::Unsuccessful);
+    assert_eq!(modes[1].svid(), 0x8087);
+    assert_eq!(modes[1].mode(), 1);
+    assert_eq!(modes[1].state(), AlternateModeState::NotAttempted);
+}
@@ -0,0 +1,459 @@
+//! Builders for descriptor bytes, to generate fixtures for tests of code
+//! that parses descriptors.
+//!
+//! ```
+//! use nusb::descriptors::builder::{ConfigurationBuilder, EndpointBuilder, InterfaceBuilder};
+//! use nusb::descriptors::ConfigurationDescriptor;
+//! use nusb::transfer::TransferType;
+//!
+//! let bytes = ConfigurationBuilder::new(1)
+//!     .interface(
+//!         InterfaceBuilder::new(0, 0)
+//!             .class(0xff, 0x00, 0x00)
+//!             .endpoint(EndpointBuilder::new(0x81, TransferType::Bulk).max_packet_size(512))
+//!             .endpoint(EndpointBuilder::new(0x02, TransferType::Bulk).max_packet_size(512)),
+//!     )
+//!     .build();
+//!
+//! let config = ConfigurationDescriptor::new(&bytes).unwrap();
+//! assert_eq!(config.num_interfaces(), 1);
+//! ```
+
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use super::{
+    encode_string_descriptor, DeviceDescriptor, TransferType, DESCRIPTOR_LEN_CONFIGURATION,
+    DESCRIPTOR_LEN_ENDPOINT, DESCRIPTOR_LEN_INTERFACE, DESCRIPTOR_TYPE_CONFIGURATION,
+    DESCRIPTOR_TYPE_ENDPOINT, DESCRIPTOR_TYPE_INTERFACE, DESCRIPTOR_TYPE_STRING,
+};
+
+/// Builder for a [`DeviceDescriptor`].
+///
+/// Fields default to a USB 2.0 device with a 64 byte control endpoint, one
+/// configuration, and no strings.
+#[derive(Debug, Clone)]
+pub struct DeviceDescriptorBuilder {
+    usb_version: u16,
+    class: u8,
+    subclass: u8,
+    protocol: u8,
+    max_packet_size_0: u8,
+    vendor_id: u16,
+    product_id: u16,
+    device_version: u16,
+    manufacturer_string_index: u8,
+    product_string_index: u8,
+    serial_number_string_index: u8,
+    num_configurations: u8,
+}
+
+impl DeviceDescriptorBuilder {
+    /// Create a builder for a device with the specified vendor and product ID.
+    pub fn new(vendor_id: u16, product_id: u16) -> DeviceDescriptorBuilder {
+        DeviceDescriptorBuilder {
+            usb_version: 0x0200,
+            class: 0,
+            subclass: 0,
+            protocol: 0,
+            max_packet_size_0: 64,
+            vendor_id,
+            product_id,
+            device_version: 0,
+            manufacturer_string_index: 0,
+            product_string_index: 0,
+            serial_number_string_index: 0,
+            num_configurations: 1,
+        }
+    }
+
+    /// Set `bcdUSB`.
+    pub fn usb_version(mut self, usb_version: u16) -> DeviceDescriptorBuilder {
+        self.usb_version = usb_version;
+        self
+    }
+
+    /// Set `bDeviceClass`, `bDeviceSubClass` and `bDeviceProtocol`.
+    pub fn class(mut self, class: u8, subclass: u8, protocol: u8) -> DeviceDescriptorBuilder {
+        self.class = class;
+        self.subclass = subclass;
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set `bMaxPacketSize0`.
+    pub fn max_packet_size_0(mut self, max_packet_size_0: u8) -> DeviceDescriptorBuilder {
+        self.max_packet_size_0 = max_packet_size_0;
+        self
+    }
+
+    /// Set `bcdDevice`.
+    pub fn device_version(mut self, device_version: u16) -> DeviceDescriptorBuilder {
+        self.device_version = device_version;
+        self
+    }
+
+    /// Set `iManufacturer`, `iProduct` and `iSerialNumber`, using `0` for
+    /// no string.
+    pub fn string_indexes(
+        mut self,
+        manufacturer: u8,
+        product: u8,
+        serial_number: u8,
+    ) -> DeviceDescriptorBuilder {
+        self.manufacturer_string_index = manufacturer;
+        self.product_string_index = product;
+        self.serial_number_string_index = serial_number;
+        self
+    }
+
+    /// Set `bNumConfigurations`.
+    pub fn num_configurations(mut self, num_configurations: u8) -> DeviceDescriptorBuilder {
+        self.num_configurations = num_configurations;
+        self
+    }
+
+    /// Build the device descriptor.
+    pub fn build(&self) -> DeviceDescriptor {
+        DeviceDescriptor::from_fields(
+            self.usb_version,
+            self.class,
+            self.subclass,
+            self.protocol,
+            self.max_packet_size_0,
+            self.vendor_id,
+            self.product_id,
+            self.device_version,
+            self.manufacturer_string_index,
+            self.product_string_index,
+            self.serial_number_string_index,
+            self.num_configurations,
+        )
+    }
+}
+
+/// Builder for the bytes of a configuration descriptor and its interfaces.
+///
+/// `wTotalLength` and `bNumInterfaces` are computed when building.
+#[derive(Debug, Clone)]
+pub struct ConfigurationBuilder {
+    configuration_value: u8,
+    string_index: u8,
+    attributes: u8,
+    max_power: u8,
+    body: Vec<u8>,
+    interface_numbers: BTreeSet<u8>,
+}
+
+impl ConfigurationBuilder {
+    /// Create a builder for a bus-powered configuration with the specified
+    /// `bConfigurationValue`, drawing 100mA.
+    pub fn new(configuration_value: u8) -> ConfigurationBuilder {
+        ConfigurationBuilder {
+            configuration_value,
+            string_index: 0,
+            attributes: 0x80,
+            max_power: 50,
+            body: Vec::new(),
+            interface_numbers: BTreeSet::new(),
+        }
+    }
+
+    /// Set `iConfiguration`, using `0` for no string.
+    pub fn string_index(mut self, string_index: u8) -> ConfigurationBuilder {
+        self.string_index = string_index;
+        self
+    }
+
+    /// Set `bmAttributes`. Bit 7 is reserved and always set.
+    pub fn attributes(mut self, attributes: u8) -> ConfigurationBuilder {
+        self.attributes = attributes | 0x80;
+        self
+    }
+
+    /// Set `bMaxPower`, in units of 2 milliamps.
+    pub fn max_power(mut self, max_power: u8) -> ConfigurationBuilder {
+        self.max_power = max_power;
+        self
+    }
+
+    /// Append an interface alternate setting with its endpoints.
+    pub fn interface(mut self, interface: InterfaceBuilder) -> ConfigurationBuilder {
+        self.interface_numbers.insert(interface.interface_number);
+        interface.write(&mut self.body);
+        self
+    }
+
+    /// Append other descriptor bytes, such as an interface association
+    /// descriptor, at the current position.
+    pub fn descriptor(mut self, descriptor: &[u8]) -> ConfigurationBuilder {
+        self.body.extend_from_slice(descriptor);
+        self
+    }
+
+    /// Build the bytes of the configuration descriptor, which can be parsed
+    /// with [`ConfigurationDescriptor::new`][super::ConfigurationDescriptor::new].
+    ///
+    /// ### Panics
+    /// * if the total length exceeds 65535 bytes.
+    pub fn build(&self) -> Vec<u8> {
+        let total_len = u16::try_from(DESCRIPTOR_LEN_CONFIGURATION as usize + self.body.len())
+            .expect("configuration descriptor too long");
+        let mut buf = Vec::with_capacity(total_len as usize);
+        buf.extend_from_slice(&[DESCRIPTOR_LEN_CONFIGURATION, DESCRIPTOR_TYPE_CONFIGURATION]);
+        buf.extend_from_slice(&total_len.to_le_bytes());
+        buf.extend_from_slice(&[
+            self.interface_numbers.len() as u8,
+            self.configuration_value,
+            self.string_index,
+            self.attributes,
+            self.max_power,
+        ]);
+        buf.extend_from_slice(&self.body);
+        buf
+    }
+}
+
+/// Builder for an interface alternate setting, with its class-specific
+/// descriptors and endpoints.
+///
+/// `bNumEndpoints` is computed when building.
+#[derive(Debug, Clone)]
+pub struct InterfaceBuilder {
+    interface_number: u8,
+    alternate_setting: u8,
+    class: u8,
+    subclass: u8,
+    protocol: u8,
+    string_index: u8,
+    num_endpoints: u8,
+    body: Vec<u8>,
+}
+
+impl InterfaceBuilder {
+    /// Create a builder for the specified `bInterfaceNumber` and
+    /// `bAlternateSetting`.
+    pub fn new(interface_number: u8, alternate_setting: u8) -> InterfaceBuilder {
+        InterfaceBuilder {
+            interface_number,
+            alternate_setting,
+            class: 0,
+            subclass: 0,
+            protocol: 0,
+            string_index: 0,
+            num_endpoints: 0,
+            body: Vec::new(),
+        }
+    }
+
+    /// Set `bInterfaceClass`, `bInterfaceSubClass` and `bInterfaceProtocol`.
+    pub fn class(mut self, class: u8, subclass: u8, protocol: u8) -> InterfaceBuilder {
+        self.class = class;
+        self.subclass = subclass;
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set `iInterface`, using `0` for no string.
+    pub fn string_index(mut self, string_index: u8) -> InterfaceBuilder {
+        self.string_index = string_index;
+        self
+    }
+
+    /// Append a class-specific or vendor-specific descriptor.
+    ///
+    /// Descriptors appended before the first endpoint are returned by
+    /// [`InterfaceDescriptor::class_descriptors`][super::InterfaceDescriptor::class_descriptors].
+    pub fn descriptor(mut self, descriptor: &[u8]) -> InterfaceBuilder {
+        self.body.extend_from_slice(descriptor);
+        self
+    }
+
+    /// Append an endpoint.
+    pub fn endpoint(mut self, endpoint: EndpointBuilder) -> InterfaceBuilder {
+        self.num_endpoints += 1;
+        endpoint.write(&mut self.body);
+        self
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[
+            DESCRIPTOR_LEN_INTERFACE,
+            DESCRIPTOR_TYPE_INTERFACE,
+            self.interface_number,
+            self.alternate_setting,
+            self.num_endpoints,
+            self.class,
+            self.subclass,
+            self.protocol,
+            self.string_index,
+        ]);
+        buf.extend_from_slice(&self.body);
+    }
+}
+
+/// Builder for an endpoint descriptor and the descriptors following it.
+#[derive(Debug, Clone)]
+pub struct EndpointBuilder {
+    address: u8,
+    attributes: u8,
+    max_packet_size: u16,
+    interval: u8,
+    body: Vec<u8>,
+}
+
+impl EndpointBuilder {
+    /// Create a builder for an endpoint with the specified `bEndpointAddress`
+    /// and transfer type.
+    ///
+    /// The maximum packet size defaults to 64 bytes, and the interval to `1`
+    /// for interrupt and isochronous endpoints.
+    pub fn new(address: u8, transfer_type: TransferType) -> EndpointBuilder {
+        let interval = match transfer_type {
+            TransferType::Interrupt | TransferType::Isochronous => 1,
+            TransferType::Control | TransferType::Bulk => 0,
+        };
+        EndpointBuilder {
+            address,
+            attributes: transfer_type as u8,
+            max_packet_size: 64,
+            interval,
+            body: Vec::new(),
+        }
+    }
+
+    /// Set the maximum packet size in bytes, and the number of additional
+    /// transactions per microframe in bits 11-12 of `wMaxPacketSize`.
+    pub fn max_packet_size(mut self, max_packet_size: u16) -> EndpointBuilder {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Set `bmAttributes`, including the transfer type in bits 0-1.
+    pub fn attributes(mut self, attributes: u8) -> EndpointBuilder {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set `bInterval`.
+    pub fn interval(mut self, interval: u8) -> EndpointBuilder {
+        self.interval = interval;
+        self
+    }
+
+    /// Append a descriptor following the endpoint, such as a SuperSpeed
+    /// companion or class-specific endpoint descriptor.
+    pub fn descriptor(mut self, descriptor: &[u8]) -> EndpointBuilder {
+        self.body.extend_from_slice(descriptor);
+        self
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        let [mps_lo, mps_hi] = self.max_packet_size.to_le_bytes();
+        buf.extend_from_slice(&[
+            DESCRIPTOR_LEN_ENDPOINT,
+            DESCRIPTOR_TYPE_ENDPOINT,
+            self.address,
+            self.attributes,
+            mps_lo,
+            mps_hi,
+            self.interval,
+        ]);
+        buf.extend_from_slice(&self.body);
+    }
+}
+
+/// Encode `s` as a string descriptor, or return `None` if it doesn't fit in
+/// the 255-byte maximum descriptor length.
+pub fn string_descriptor(s: &str) -> Option<Vec<u8>> {
+    encode_string_descriptor(s)
+}
+
+/// Encode string descriptor zero, listing the supported language IDs.
+///
+/// ### Panics
+/// * if more than 126 language IDs are specified.
+pub fn language_ids_descriptor(language_ids: &[u16]) -> Vec<u8> {
+    let mut buf = vec![0, DESCRIPTOR_TYPE_STRING];
+    for id in language_ids {
+        buf.extend_from_slice(&id.to_le_bytes());
+    }
+    buf[0] = buf.len().try_into().expect("too many language IDs");
+    buf
+}
+
+#[test]
+fn test_build_configuration() {
+    use super::{
+        ConfigurationDescriptor, InterfaceAssociationDescriptor,
+        DESCRIPTOR_LEN_SUPERSPEED_COMPANION, DESCRIPTOR_TYPE_SUPERSPEED_COMPANION,
+    };
+    use crate::descriptors::language_id::US_ENGLISH;
+
+    let dev = DeviceDescriptorBuilder::new(0x1234, 0x5678)
+        .usb_version(0x0320)
+        .max_packet_size_0(9)
+        .string_indexes(1, 2, 0)
+        .build();
+    let dev = DeviceDescriptor::new(dev.as_bytes()).unwrap();
+    assert_eq!(dev.vendor_id(), 0x1234);
+    assert_eq!(dev.product_id(), 0x5678);
+    assert_eq!(dev.usb_version(), 0x0320);
+    assert!(dev.serial_number_string_index().is_none());
+
+    let iad = [0x08, 0x0b, 0x00, 0x02, 0x02, 0x02, 0x01, 0x00];
+    let bytes = ConfigurationBuilder::new(2)
+        .max_power(250)
+        .descriptor(&iad)
+        .interface(
+            InterfaceBuilder::new(0, 0)
+                .class(0x02, 0x02, 0x01)
+                .descriptor(&[0x05, 0x24, 0x00, 0x10, 0x01])
+                .endpoint(EndpointBuilder::new(0x83, TransferType::Interrupt).max_packet_size(16)),
+        )
+        .interface(
+            InterfaceBuilder::new(1, 0)
+                .class(0x0a, 0x00, 0x00)
+                .endpoint(
+                    EndpointBuilder::new(0x81, TransferType::Bulk)
+                        .max_packet_size(1024)
+                        .descriptor(&[
+                            DESCRIPTOR_LEN_SUPERSPEED_COMPANION,
+                            DESCRIPTOR_TYPE_SUPERSPEED_COMPANION,
+                            0x0f,
+                            0x00,
+                            0x00,
+                            0x00,
+                        ]),
+                )
+                .endpoint(EndpointBuilder::new(0x01, TransferType::Bulk).max_packet_size(1024)),
+        )
+        .build();
+
+    let c = ConfigurationDescriptor::new(&bytes).unwrap();
+    assert_eq!(c.num_interfaces(), 2);
+    assert_eq!(c.configuration_value(), 2);
+    assert_eq!(c.max_power(), 250);
+
+    let iad: Vec<InterfaceAssociationDescriptor> = c.interface_associations().collect();
+    assert_eq!(iad[0].interface_numbers(), 0..=1);
+
+    let alts: Vec<_> = c.interface_alt_settings().collect();
+    assert_eq!(alts.len(), 2);
+    assert_eq!(alts[0].class_descriptors().count(), 1);
+    assert_eq!(alts[0].num_endpoints(), 1);
+    let ep = alts[0].endpoints().next().unwrap();
+    assert_eq!(ep.transfer_type(), TransferType::Interrupt);
+    assert_eq!(ep.interval(), 1);
+
+    let eps: Vec<_> = alts[1].endpoints().collect();
+    assert_eq!(eps.len(), 2);
+    assert_eq!(eps[0].max_packet_size(), 1024);
+    assert_eq!(eps[0].superspeed_companion().unwrap().max_burst(), 15);
+    assert!(eps[1].superspeed_companion().is_none());
+
+    assert_eq!(
+        language_ids_descriptor(&[US_ENGLISH]),
+        [0x04, 0x03, 0x09, 0x04]
+    );
+    assert_eq!(string_descriptor("A").unwrap(), [0x04, 0x03, 0x41, 0x00]);
+}
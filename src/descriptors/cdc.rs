@@ -0,0 +1,417 @@
+//! Communications Device Class (CDC) functional descriptors, the
+//! class-specific descriptors following the interface descriptor of a CDC
+//! communication interface.
+//!
+//! The [`UnionDescriptor`] names the data interfaces controlled by a
+//! communication interface, which is needed to pair them when a device has
+//! several CDC functions.
+//!
+//! Obtain from [`functional_descriptors`].
+
+use core::fmt::Debug;
+
+use super::{Descriptor, InterfaceDescriptor};
+
+/// `bDescriptorType` of class-specific interface descriptors.
+pub const DESCRIPTOR_TYPE_CS_INTERFACE: u8 = 0x24;
+
+const SUBTYPE_HEADER: u8 = 0x00;
+const LEN_HEADER: usize = 5;
+
+const SUBTYPE_CALL_MANAGEMENT: u8 = 0x01;
+const LEN_CALL_MANAGEMENT: usize = 5;
+
+const SUBTYPE_ACM: u8 = 0x02;
+const LEN_ACM: usize = 4;
+
+const SUBTYPE_UNION: u8 = 0x06;
+const LEN_UNION: usize = 4;
+
+const SUBTYPE_ETHERNET_NETWORKING: u8 = 0x0F;
+const LEN_ETHERNET_NETWORKING: usize = 13;
+
+const SUBTYPE_NCM: u8 = 0x1A;
+const LEN_NCM: usize = 6;
+
+/// Iterate over the CDC functional descriptors of an interface.
+pub fn functional_descriptors<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = FunctionalDescriptor<'a>> {
+    interface
+        .class_descriptors()
+        .filter(|&(descriptor_type, _)| descriptor_type == DESCRIPTOR_TYPE_CS_INTERFACE)
+        .flat_map(|(_, buf)| FunctionalDescriptor::new(buf))
+}
+
+/// A CDC functional descriptor.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum FunctionalDescriptor<'a> {
+    /// Header functional descriptor
+    Header(HeaderDescriptor<'a>),
+
+    /// Call Management functional descriptor
+    CallManagement(CallManagementDescriptor<'a>),
+
+    /// Abstract Control Management functional descriptor
+    AbstractControlManagement(AcmDescriptor<'a>),
+
+    /// Union functional descriptor
+    Union(UnionDescriptor<'a>),
+
+    /// Ethernet Networking functional descriptor
+    EthernetNetworking(EthernetNetworkingDescriptor<'a>),
+
+    /// NCM functional descriptor
+    Ncm(NcmDescriptor<'a>),
+
+    /// A functional descriptor of another subtype, or too short for its
+    /// subtype
+    Other(Descriptor<'a>),
+}
+
+impl<'a> FunctionalDescriptor<'a> {
+    fn new(buf: &'a [u8]) -> Option<FunctionalDescriptor<'a>> {
+        let subtype = *buf.get(2)?;
+        let len = buf.len();
+
+        Some(match subtype {
+            SUBTYPE_HEADER if len >= LEN_HEADER => {
+                FunctionalDescriptor::Header(HeaderDescriptor(buf))
+            }
+            SUBTYPE_CALL_MANAGEMENT if len >= LEN_CALL_MANAGEMENT => {
+                FunctionalDescriptor::CallManagement(CallManagementDescriptor(buf))
+            }
+            SUBTYPE_ACM if len >= LEN_ACM => {
+                FunctionalDescriptor::AbstractControlManagement(AcmDescriptor(buf))
+            }
+            SUBTYPE_UNION if len >= LEN_UNION => FunctionalDescriptor::Union(UnionDescriptor(buf)),
+            SUBTYPE_ETHERNET_NETWORKING if len >= LEN_ETHERNET_NETWORKING => {
+                FunctionalDescriptor::EthernetNetworking(EthernetNetworkingDescriptor(buf))
+            }
+            SUBTYPE_NCM if len >= LEN_NCM => FunctionalDescriptor::Ncm(NcmDescriptor(buf)),
+            _ => FunctionalDescriptor::Other(Descriptor(buf)),
+        })
+    }
+
+    /// `bDescriptorSubtype` descriptor field: Type of the functional
+    /// descriptor.
+    #[doc(alias = "bDescriptorSubtype")]
+    pub fn descriptor_subtype(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+
+    /// The bytes of the functional descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            FunctionalDescriptor::Header(d) => d.0,
+            FunctionalDescriptor::CallManagement(d) => d.0,
+            FunctionalDescriptor::AbstractControlManagement(d) => d.0,
+            FunctionalDescriptor::Union(d) => d.0,
+            FunctionalDescriptor::EthernetNetworking(d) => d.0,
+            FunctionalDescriptor::Ncm(d) => d.0,
+            FunctionalDescriptor::Other(d) => d.0,
+        }
+    }
+}
+
+/// Header functional descriptor, which begins the functional descriptors
+/// of an interface.
+#[derive(Clone)]
+pub struct HeaderDescriptor<'a>(&'a [u8]);
+
+impl<'a> HeaderDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> HeaderDescriptor<'a> {
+        /// `bcdCDC` descriptor field: CDC specification version, in
+        /// binary-coded decimal.
+        #[doc(alias = "bcdCDC")]
+        pub fn cdc_version at 3 -> u16;
+    }
+}
+
+impl<'a> Debug for HeaderDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HeaderDescriptor")
+            .field("cdc_version", &format_args!("0x{:04X}", self.cdc_version()))
+            .finish()
+    }
+}
+
+/// Call Management functional descriptor, describing how calls are managed
+/// by an ACM device.
+#[derive(Clone)]
+pub struct CallManagementDescriptor<'a>(&'a [u8]);
+
+impl<'a> CallManagementDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> CallManagementDescriptor<'a> {
+        /// `bmCapabilities` descriptor field: Bitmap of call management
+        /// capabilities.
+        #[doc(alias = "bmCapabilities")]
+        pub fn capabilities at 3 -> u8;
+
+        /// `bDataInterface` descriptor field: Number of the data interface
+        /// used for call management.
+        #[doc(alias = "bDataInterface")]
+        pub fn data_interface at 4 -> u8;
+    }
+}
+
+impl<'a> Debug for CallManagementDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CallManagementDescriptor")
+            .field(
+                "capabilities",
+                &format_args!("0x{:02X}", self.capabilities()),
+            )
+            .field("data_interface", &self.data_interface())
+            .finish()
+    }
+}
+
+/// Abstract Control Management functional descriptor, describing the
+/// requests supported by an ACM (serial port) device.
+#[derive(Clone)]
+pub struct AcmDescriptor<'a>(&'a [u8]);
+
+impl<'a> AcmDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Whether the device supports `SET_LINE_CODING`, `GET_LINE_CODING`,
+    /// `SET_CONTROL_LINE_STATE` and the `SERIAL_STATE` notification.
+    pub fn line_coding(&self) -> bool {
+        self.capabilities() & 0x02 != 0
+    }
+
+    /// Whether the device supports `SEND_BREAK`.
+    pub fn send_break(&self) -> bool {
+        self.capabilities() & 0x04 != 0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> AcmDescriptor<'a> {
+        /// `bmCapabilities` descriptor field: Bitmap of supported requests.
+        #[doc(alias = "bmCapabilities")]
+        pub fn capabilities at 3 -> u8;
+    }
+}
+
+impl<'a> Debug for AcmDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AcmDescriptor")
+            .field(
+                "capabilities",
+                &format_args!("0x{:02X}", self.capabilities()),
+            )
+            .finish()
+    }
+}
+
+/// Union functional descriptor, grouping a communication interface with the
+/// interfaces it controls.
+#[derive(Clone)]
+pub struct UnionDescriptor<'a>(&'a [u8]);
+
+impl<'a> UnionDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `bSubordinateInterface` descriptor fields: Numbers of the interfaces
+    /// controlled by the communication interface, usually data interfaces.
+    #[doc(alias = "bSubordinateInterface0")]
+    pub fn subordinate_interfaces(&self) -> &'a [u8] {
+        &self.0[4..]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> UnionDescriptor<'a> {
+        /// `bControlInterface` descriptor field: Number of the
+        /// communication interface.
+        #[doc(alias = "bControlInterface")]
+        pub fn control_interface at 3 -> u8;
+    }
+}
+
+impl<'a> Debug for UnionDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UnionDescriptor")
+            .field("control_interface", &self.control_interface())
+            .field("subordinate_interfaces", &self.subordinate_interfaces())
+            .finish()
+    }
+}
+
+/// Ethernet Networking functional descriptor, describing an ECM or NCM
+/// network device.
+#[derive(Clone)]
+pub struct EthernetNetworkingDescriptor<'a>(&'a [u8]);
+
+impl<'a> EthernetNetworkingDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> EthernetNetworkingDescriptor<'a> {
+        /// `iMACAddress` descriptor field: Index of the string descriptor
+        /// containing the MAC address as 12 hexadecimal digits.
+        #[doc(alias = "iMACAddress")]
+        pub fn mac_address_string_index at 3 -> u8;
+
+        /// `bmEthernetStatistics` descriptor field: Bitmap of the supported
+        /// Ethernet statistics.
+        #[doc(alias = "bmEthernetStatistics")]
+        pub fn ethernet_statistics at 4 -> u32;
+
+        /// `wMaxSegmentSize` descriptor field: Maximum segment size, usually
+        /// 1514 bytes.
+        #[doc(alias = "wMaxSegmentSize")]
+        pub fn max_segment_size at 8 -> u16;
+
+        /// `wNumberMCFilters` descriptor field: Number of multicast filters,
+        /// with the high bit set if they are imperfect.
+        #[doc(alias = "wNumberMCFilters")]
+        pub fn number_mc_filters at 10 -> u16;
+
+        /// `bNumberPowerFilters` descriptor field: Number of pattern filters
+        /// for waking the host.
+        #[doc(alias = "bNumberPowerFilters")]
+        pub fn number_power_filters at 12 -> u8;
+    }
+}
+
+impl<'a> Debug for EthernetNetworkingDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EthernetNetworkingDescriptor")
+            .field("mac_address_string_index", &self.mac_address_string_index())
+            .field(
+                "ethernet_statistics",
+                &format_args!("0x{:08X}", self.ethernet_statistics()),
+            )
+            .field("max_segment_size", &self.max_segment_size())
+            .field("number_mc_filters", &self.number_mc_filters())
+            .field("number_power_filters", &self.number_power_filters())
+            .finish()
+    }
+}
+
+/// NCM functional descriptor, describing the optional features of an NCM
+/// network device.
+#[derive(Clone)]
+pub struct NcmDescriptor<'a>(&'a [u8]);
+
+impl<'a> NcmDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> NcmDescriptor<'a> {
+        /// `bcdNcmVersion` descriptor field: NCM specification version, in
+        /// binary-coded decimal.
+        #[doc(alias = "bcdNcmVersion")]
+        pub fn ncm_version at 3 -> u16;
+
+        /// `bmNetworkCapabilities` descriptor field: Bitmap of the
+        /// supported optional requests.
+        #[doc(alias = "bmNetworkCapabilities")]
+        pub fn network_capabilities at 5 -> u8;
+    }
+}
+
+impl<'a> Debug for NcmDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NcmDescriptor")
+            .field("ncm_version", &format_args!("0x{:04X}", self.ncm_version()))
+            .field(
+                "network_capabilities",
+                &format_args!("0x{:02X}", self.network_capabilities()),
+            )
+            .finish()
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_cdc_acm() {
+    use super::ConfigurationDescriptor;
+
+    let c = ConfigurationDescriptor::new(&[
+        0x09, 0x02, 0x43, 0x00, 0x02, 0x01, 0x00, 0x80, 0x32,
+        0x09, 0x04, 0x00, 0x00, 0x01, 0x02, 0x02, 0x01, 0x00,
+        0x05, 0x24, 0x00, 0x10, 0x01,
+        0x05, 0x24, 0x01, 0x00, 0x01,
+        0x04, 0x24, 0x02, 0x02,
+        0x05, 0x24, 0x06, 0x00, 0x01,
+        0x07, 0x05, 0x82, 0x03, 0x08, 0x00, 0xff,
+        0x09, 0x04, 0x01, 0x00, 0x02, 0x0a, 0x00, 0x00, 0x00,
+        0x07, 0x05, 0x01, 0x02, 0x40, 0x00, 0x00,
+        0x07, 0x05, 0x81, 0x02, 0x40, 0x00, 0x00,
+    ]).unwrap();
+
+    let control = c.interface_alt_settings().next().unwrap();
+    let descriptors: Vec<_> = functional_descriptors(&control).collect();
+    assert_eq!(descriptors.len(), 4);
+
+    let FunctionalDescriptor::Header(header) = &descriptors[0] else { panic!() };
+    assert_eq!(header.cdc_version(), 0x0110);
+
+    let FunctionalDescriptor::CallManagement(cm) = &descriptors[1] else { panic!() };
+    assert_eq!(cm.data_interface(), 1);
+
+    let FunctionalDescriptor::AbstractControlManagement(acm) = &descriptors[2] else { panic!() };
+    assert!(acm.line_coding());
+    assert!(!acm.send_break());
+
+    let FunctionalDescriptor::Union(union) = &descriptors[3] else { panic!() };
+    assert_eq!(union.control_interface(), 0);
+    assert_eq!(union.subordinate_interfaces(), [1]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_cdc_ncm() {
+    use super::InterfaceDescriptor;
+
+    let intf = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x01, 0x02, 0x0d, 0x00, 0x00,
+        0x0d, 0x24, 0x0f, 0x04, 0x00, 0x00, 0x00, 0x00, 0xea, 0x05, 0x00, 0x00, 0x00,
+        0x06, 0x24, 0x1a, 0x00, 0x01, 0x00,
+        // truncated union descriptor
+        0x03, 0x24, 0x06,
+    ]);
+
+    let descriptors: Vec<_> = functional_descriptors(&intf).collect();
+    let FunctionalDescriptor::EthernetNetworking(ecm) = &descriptors[0] else { panic!() };
+    assert_eq!(ecm.mac_address_string_index(), 4);
+    assert_eq!(ecm.max_segment_size(), 1514);
+
+    let FunctionalDescriptor::Ncm(ncm) = &descriptors[1] else { panic!() };
+    assert_eq!(ncm.ncm_version(), 0x0100);
+
+    assert!(matches!(descriptors[2], FunctionalDescriptor::Other(_)));
+}
@@ -0,0 +1,153 @@
+//! Device Firmware Upgrade (DFU) functional descriptor, describing the
+//! capabilities of the DFU interface of a device.
+//!
+//! Obtain from [`DfuFunctionalDescriptor::from_interface`].
+
+use core::{fmt::Debug, time::Duration};
+
+use super::InterfaceDescriptor;
+
+/// `bInterfaceClass` of DFU interfaces (application specific).
+pub const CLASS_APPLICATION_SPECIFIC: u8 = 0xFE;
+
+/// `bInterfaceSubClass` of DFU interfaces.
+pub const SUBCLASS_DFU: u8 = 0x01;
+
+const DESCRIPTOR_TYPE_DFU_FUNCTIONAL: u8 = 0x21;
+
+/// DFU 1.0 devices may omit `bcdDFUVersion`.
+const DESCRIPTOR_LEN_DFU_FUNCTIONAL_MIN: usize = 7;
+const DESCRIPTOR_LEN_DFU_FUNCTIONAL: usize = 9;
+
+/// DFU functional descriptor, following the interface descriptor of a DFU
+/// interface in both run-time and DFU mode.
+#[derive(Clone)]
+pub struct DfuFunctionalDescriptor<'a>(&'a [u8]);
+
+impl<'a> DfuFunctionalDescriptor<'a> {
+    /// Find the DFU functional descriptor following an interface
+    /// descriptor.
+    ///
+    /// Returns `None` if the interface is not a DFU interface or has no
+    /// valid DFU functional descriptor.
+    pub fn from_interface(
+        interface: &InterfaceDescriptor<'a>,
+    ) -> Option<DfuFunctionalDescriptor<'a>> {
+        if interface.class() != CLASS_APPLICATION_SPECIFIC || interface.subclass() != SUBCLASS_DFU {
+            return None;
+        }
+
+        interface
+            .class_descriptors()
+            .find(|&(descriptor_type, _)| descriptor_type == DESCRIPTOR_TYPE_DFU_FUNCTIONAL)
+            .filter(|(_, buf)| buf.len() >= DESCRIPTOR_LEN_DFU_FUNCTIONAL_MIN)
+            .map(|(_, buf)| DfuFunctionalDescriptor(buf))
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Whether the device supports downloading firmware to the device.
+    #[doc(alias = "bitCanDnload")]
+    pub fn can_download(&self) -> bool {
+        self.attributes() & 0x01 != 0
+    }
+
+    /// Whether the device supports uploading firmware from the device.
+    #[doc(alias = "bitCanUpload")]
+    pub fn can_upload(&self) -> bool {
+        self.attributes() & 0x02 != 0
+    }
+
+    /// Whether the device keeps communicating over USB after the
+    /// manifestation phase.
+    #[doc(alias = "bitManifestationTolerant")]
+    pub fn manifestation_tolerant(&self) -> bool {
+        self.attributes() & 0x04 != 0
+    }
+
+    /// Whether the device detaches and re-enumerates by itself on
+    /// `DFU_DETACH`, rather than waiting for a bus reset.
+    #[doc(alias = "bitWillDetach")]
+    pub fn will_detach(&self) -> bool {
+        self.attributes() & 0x08 != 0
+    }
+
+    /// `wDetachTimeOut` descriptor field as a `Duration`: Time the device
+    /// waits for a bus reset after `DFU_DETACH` before returning to normal
+    /// operation.
+    pub fn detach_timeout(&self) -> Duration {
+        Duration::from_millis(self.detach_timeout_raw().into())
+    }
+
+    /// `bcdDFUVersion` descriptor field: DFU specification version, in
+    /// binary-coded decimal, or `None` for DFU 1.0 devices that omit it.
+    #[doc(alias = "bcdDFUVersion")]
+    pub fn dfu_version(&self) -> Option<u16> {
+        (self.0.len() >= DESCRIPTOR_LEN_DFU_FUNCTIONAL)
+            .then(|| u16::from_le_bytes([self.0[7], self.0[8]]))
+    }
+}
+
+descriptor_fields! {
+    impl<'a> DfuFunctionalDescriptor<'a> {
+        /// `bmAttributes` descriptor field: Bitmap of DFU capabilities.
+        #[doc(alias = "bmAttributes")]
+        pub fn attributes at 2 -> u8;
+
+        /// `wDetachTimeOut` descriptor field: Detach timeout in
+        /// milliseconds.
+        #[doc(alias = "wDetachTimeOut")]
+        pub fn detach_timeout_raw at 3 -> u16;
+
+        /// `wTransferSize` descriptor field: Maximum number of bytes per
+        /// `DFU_DNLOAD` or `DFU_UPLOAD` request.
+        #[doc(alias = "wTransferSize")]
+        pub fn transfer_size at 5 -> u16;
+    }
+}
+
+impl<'a> Debug for DfuFunctionalDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DfuFunctionalDescriptor")
+            .field("attributes", &format_args!("0x{:02X}", self.attributes()))
+            .field("detach_timeout", &self.detach_timeout())
+            .field("transfer_size", &self.transfer_size())
+            .field("dfu_version", &self.dfu_version())
+            .finish()
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_dfu_functional() {
+    let intf = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x00, 0xfe, 0x01, 0x02, 0x00,
+        0x09, 0x21, 0x0b, 0xff, 0x00, 0x00, 0x08, 0x1a, 0x01,
+    ]);
+    let dfu = DfuFunctionalDescriptor::from_interface(&intf).unwrap();
+    assert!(dfu.can_download());
+    assert!(dfu.can_upload());
+    assert!(!dfu.manifestation_tolerant());
+    assert!(dfu.will_detach());
+    assert_eq!(dfu.detach_timeout(), Duration::from_millis(255));
+    assert_eq!(dfu.transfer_size(), 2048);
+    assert_eq!(dfu.dfu_version(), Some(0x011a));
+
+    let dfu_10 = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x00, 0xfe, 0x01, 0x01, 0x00,
+        0x07, 0x21, 0x01, 0x64, 0x00, 0x00, 0x04,
+    ]);
+    let dfu = DfuFunctionalDescriptor::from_interface(&dfu_10).unwrap();
+    assert_eq!(dfu.transfer_size(), 1024);
+    assert_eq!(dfu.dfu_version(), None);
+
+    // A HID descriptor has the same type but is not a DFU descriptor.
+    let hid = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x00, 0x00, 0x00,
+        0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00,
+    ]);
+    assert!(DfuFunctionalDescriptor::from_interface(&hid).is_none());
+}
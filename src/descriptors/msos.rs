@@ -0,0 +1,691 @@
+//! Microsoft OS descriptors, which tell Windows which driver to load for a
+//! device or function and which registry properties to set, without an INF
+//! file.
+//!
+//! * MS OS 2.0 descriptors are found through a [`PlatformCapability`] in the
+//!   BOS descriptor, which gives the vendor request code to read the
+//!   [`DescriptorSet`]. [`Device::ms_os_20_descriptor_set`] reads it.
+//! * The legacy MS OS 1.0 descriptors are found through the string
+//!   descriptor at index `0xEE`, which gives the vendor request code to read
+//!   the [`ExtendedCompatIdDescriptor`] and
+//!   [`ExtendedPropertiesDescriptor`].
+//!
+//! [`Device::ms_os_20_descriptor_set`]: crate::Device::ms_os_20_descriptor_set
+
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::fmt::Debug;
+
+use log::warn;
+
+use super::bos::{BosDescriptor, PlatformCapability};
+
+/// `PlatformCapabilityUUID` of the MS OS 2.0 platform capability,
+/// `{D8DD60DF-4589-4CC7-9CD2-659D9E648A9F}`.
+pub const MS_OS_20_PLATFORM_UUID: u128 = 0xd8dd60df_4589_4cc7_9cd2_659d9e648a9f;
+
+/// `wIndex` of the vendor request reading the MS OS 2.0 descriptor set.
+pub const MS_OS_20_DESCRIPTOR_INDEX: u16 = 0x07;
+
+/// Index of the MS OS 1.0 string descriptor.
+pub const MS_OS_10_STRING_INDEX: u8 = 0xEE;
+
+/// `wIndex` of the vendor request reading the MS OS 1.0 extended compat ID
+/// descriptor.
+pub const MS_OS_10_EXTENDED_COMPAT_ID_INDEX: u16 = 0x04;
+
+/// `wIndex` of the vendor request reading the MS OS 1.0 extended properties
+/// descriptor.
+pub const MS_OS_10_EXTENDED_PROPERTIES_INDEX: u16 = 0x05;
+
+const MS_OS_20_SET_HEADER_DESCRIPTOR: u16 = 0x00;
+const MS_OS_20_SUBSET_HEADER_CONFIGURATION: u16 = 0x01;
+const MS_OS_20_SUBSET_HEADER_FUNCTION: u16 = 0x02;
+const MS_OS_20_FEATURE_COMPATIBLE_ID: u16 = 0x03;
+const MS_OS_20_FEATURE_REG_PROPERTY: u16 = 0x04;
+const MS_OS_20_FEATURE_MIN_RESUME_TIME: u16 = 0x05;
+const MS_OS_20_FEATURE_MODEL_ID: u16 = 0x06;
+const MS_OS_20_FEATURE_CCGP_DEVICE: u16 = 0x07;
+const MS_OS_20_FEATURE_VENDOR_REVISION: u16 = 0x08;
+
+fn u16_at(buf: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(buf.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// Decode an ASCII ID padded with NUL bytes.
+fn ascii_id(buf: &[u8]) -> &str {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    core::str::from_utf8(&buf[..len]).unwrap_or("")
+}
+
+fn decode_utf16(buf: &[u8]) -> String {
+    char::decode_utf16(
+        buf.chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]])),
+    )
+    .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+    .collect::<String>()
+    .trim_end_matches('\0')
+    .to_owned()
+}
+
+/// Location of an MS OS 2.0 descriptor set for a range of Windows versions,
+/// from the MS OS 2.0 platform capability.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DescriptorSetInfo {
+    windows_version: u32,
+    total_length: u16,
+    vendor_code: u8,
+    alt_enum_code: u8,
+}
+
+impl DescriptorSetInfo {
+    /// Parse the descriptor sets listed in an MS OS 2.0 platform
+    /// capability, or return an empty iterator for another capability.
+    pub fn from_capability<'a>(
+        capability: &PlatformCapability<'a>,
+    ) -> impl Iterator<Item = DescriptorSetInfo> + 'a {
+        let data = if capability.uuid() == MS_OS_20_PLATFORM_UUID {
+            capability.data()
+        } else {
+            &[]
+        };
+
+        data.chunks_exact(8).map(|c| DescriptorSetInfo {
+            windows_version: u32::from_le_bytes(c[0..4].try_into().unwrap()),
+            total_length: u16::from_le_bytes(c[4..6].try_into().unwrap()),
+            vendor_code: c[6],
+            alt_enum_code: c[7],
+        })
+    }
+
+    /// Parse the descriptor sets listed in the MS OS 2.0 platform capability
+    /// of a BOS descriptor.
+    pub fn from_bos<'a>(bos: &BosDescriptor<'a>) -> impl Iterator<Item = DescriptorSetInfo> + 'a {
+        bos.platform_capabilities()
+            .flat_map(|c| DescriptorSetInfo::from_capability(&c).collect::<Vec<_>>())
+    }
+
+    /// `dwWindowsVersion` field: Minimum Windows version the descriptor set
+    /// applies to, e.g. `0x06030000` for Windows 8.1.
+    #[doc(alias = "dwWindowsVersion")]
+    pub fn windows_version(&self) -> u32 {
+        self.windows_version
+    }
+
+    /// `wMSOSDescriptorSetTotalLength` field: Length of the descriptor set.
+    #[doc(alias = "wMSOSDescriptorSetTotalLength")]
+    pub fn total_length(&self) -> u16 {
+        self.total_length
+    }
+
+    /// `bMS_VendorCode` field: `bRequest` of the vendor request reading the
+    /// descriptor set.
+    #[doc(alias = "bMS_VendorCode")]
+    pub fn vendor_code(&self) -> u8 {
+        self.vendor_code
+    }
+
+    /// `bAltEnumCode` field: `bRequest` of the vendor request to send to
+    /// enumerate an alternate device configuration, or `0` if none.
+    #[doc(alias = "bAltEnumCode")]
+    pub fn alt_enum_code(&self) -> u8 {
+        self.alt_enum_code
+    }
+}
+
+/// MS OS 2.0 descriptor set.
+#[derive(Clone)]
+pub struct DescriptorSet<'a>(&'a [u8]);
+
+impl<'a> DescriptorSet<'a> {
+    /// Create a `DescriptorSet` from a buffer beginning with the set header
+    /// descriptor.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `wTotalLength`.
+    pub fn new(buf: &[u8]) -> Option<DescriptorSet<'_>> {
+        let len = u16_at(buf, 0)? as usize;
+        let total_len = u16_at(buf, 8)? as usize;
+
+        if len != 10 || u16_at(buf, 2)? != MS_OS_20_SET_HEADER_DESCRIPTOR {
+            warn!("invalid MS OS 2.0 descriptor set header");
+            return None;
+        }
+
+        if total_len < len || total_len > buf.len() {
+            warn!(
+                "invalid MS OS 2.0 descriptor set wTotalLength of {total_len} (buffer size is {bufsize})",
+                bufsize = buf.len()
+            );
+            return None;
+        }
+
+        Some(DescriptorSet(&buf[..total_len]))
+    }
+
+    /// The bytes of the descriptor set.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `dwWindowsVersion` field: Minimum Windows version the descriptor set
+    /// applies to.
+    #[doc(alias = "dwWindowsVersion")]
+    pub fn windows_version(&self) -> u32 {
+        u32_at(self.0, 4).unwrap()
+    }
+
+    /// Iterate over the descriptors following the set header, in order.
+    pub fn descriptors(&self) -> impl Iterator<Item = MsOs20Descriptor<'a>> {
+        let mut buf = &self.0[10..];
+        core::iter::from_fn(move || {
+            let len = u16_at(buf, 0)? as usize;
+            if len < 4 || len > buf.len() {
+                warn!("invalid MS OS 2.0 descriptor wLength of {len}");
+                return None;
+            }
+            let (desc, next) = buf.split_at(len);
+            buf = next;
+            Some(MsOs20Descriptor::new(desc))
+        })
+    }
+
+    /// Iterate over the compatible ID features, with the first interface of
+    /// the function subset they are in, or `None` for the whole device.
+    pub fn compatible_ids(&self) -> impl Iterator<Item = (Option<u8>, CompatibleId<'a>)> {
+        let mut function = None;
+        self.descriptors().filter_map(move |d| match d {
+            MsOs20Descriptor::FunctionSubset { first_interface } => {
+                function = Some(first_interface);
+                None
+            }
+            MsOs20Descriptor::ConfigurationSubset { .. } => {
+                function = None;
+                None
+            }
+            MsOs20Descriptor::CompatibleId(id) => Some((function, id)),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the registry property features, with the first
+    /// interface of the function subset they are in, or `None` for the
+    /// whole device.
+    pub fn registry_properties(&self) -> impl Iterator<Item = (Option<u8>, RegistryProperty<'a>)> {
+        let mut function = None;
+        self.descriptors().filter_map(move |d| match d {
+            MsOs20Descriptor::FunctionSubset { first_interface } => {
+                function = Some(first_interface);
+                None
+            }
+            MsOs20Descriptor::ConfigurationSubset { .. } => {
+                function = None;
+                None
+            }
+            MsOs20Descriptor::RegistryProperty(p) => Some((function, p)),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> Debug for DescriptorSet<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DescriptorSet")
+            .field(
+                "windows_version",
+                &format_args!("0x{:08X}", self.windows_version()),
+            )
+            .field("descriptors", &self.descriptors().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A descriptor in an MS OS 2.0 [`DescriptorSet`].
+///
+/// The subset headers apply to the descriptors that follow them.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum MsOs20Descriptor<'a> {
+    /// Configuration subset header
+    ConfigurationSubset {
+        /// `bConfigurationValue` field, which is actually the index of the
+        /// configuration.
+        configuration_value: u8,
+    },
+
+    /// Function subset header
+    FunctionSubset {
+        /// `bFirstInterface` field: First interface of the function.
+        first_interface: u8,
+    },
+
+    /// Compatible ID feature
+    CompatibleId(CompatibleId<'a>),
+
+    /// Registry property feature
+    RegistryProperty(RegistryProperty<'a>),
+
+    /// Minimum USB resume time feature
+    MinResumeTime {
+        /// `bResumeRecoveryTime` field, in milliseconds.
+        resume_recovery_time: u8,
+        /// `bResumeSignalingTime` field, in milliseconds.
+        resume_signaling_time: u8,
+    },
+
+    /// Model ID feature
+    ModelId(u128),
+
+    /// CCGP device feature, asking Windows to treat the device as composite
+    CcgpDevice,
+
+    /// Vendor revision feature
+    VendorRevision(u16),
+
+    /// A descriptor of another type, or too short for its type
+    Other(&'a [u8]),
+}
+
+impl<'a> MsOs20Descriptor<'a> {
+    fn new(buf: &'a [u8]) -> MsOs20Descriptor<'a> {
+        let descriptor_type = u16_at(buf, 2).unwrap();
+        let len = buf.len();
+
+        match descriptor_type {
+            MS_OS_20_SUBSET_HEADER_CONFIGURATION if len >= 8 => {
+                MsOs20Descriptor::ConfigurationSubset {
+                    configuration_value: buf[4],
+                }
+            }
+            MS_OS_20_SUBSET_HEADER_FUNCTION if len >= 8 => MsOs20Descriptor::FunctionSubset {
+                first_interface: buf[4],
+            },
+            MS_OS_20_FEATURE_COMPATIBLE_ID if len >= 20 => {
+                MsOs20Descriptor::CompatibleId(CompatibleId(&buf[4..20]))
+            }
+            MS_OS_20_FEATURE_REG_PROPERTY => RegistryProperty::parse_ms_os_20(buf).map_or(
+                MsOs20Descriptor::Other(buf),
+                MsOs20Descriptor::RegistryProperty,
+            ),
+            MS_OS_20_FEATURE_MIN_RESUME_TIME if len >= 6 => MsOs20Descriptor::MinResumeTime {
+                resume_recovery_time: buf[4],
+                resume_signaling_time: buf[5],
+            },
+            MS_OS_20_FEATURE_MODEL_ID if len >= 20 => {
+                MsOs20Descriptor::ModelId(u128::from_be_bytes(buf[4..20].try_into().unwrap()))
+            }
+            MS_OS_20_FEATURE_CCGP_DEVICE => MsOs20Descriptor::CcgpDevice,
+            MS_OS_20_FEATURE_VENDOR_REVISION if len >= 6 => {
+                MsOs20Descriptor::VendorRevision(u16_at(buf, 4).unwrap())
+            }
+            _ => MsOs20Descriptor::Other(buf),
+        }
+    }
+}
+
+/// Compatible ID and sub-compatible ID, used by Windows to pick a driver,
+/// e.g. `WINUSB`.
+#[derive(Clone)]
+pub struct CompatibleId<'a>(&'a [u8]);
+
+impl<'a> CompatibleId<'a> {
+    /// `CompatibleID` field.
+    #[doc(alias = "CompatibleID")]
+    pub fn compatible_id(&self) -> &'a str {
+        ascii_id(&self.0[0..8])
+    }
+
+    /// `SubCompatibleID` field, empty if unused.
+    #[doc(alias = "SubCompatibleID")]
+    pub fn sub_compatible_id(&self) -> &'a str {
+        ascii_id(&self.0[8..16])
+    }
+}
+
+impl<'a> Debug for CompatibleId<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CompatibleId")
+            .field("compatible_id", &self.compatible_id())
+            .field("sub_compatible_id", &self.sub_compatible_id())
+            .finish()
+    }
+}
+
+/// Registry property Windows sets in the device's hardware key, e.g.
+/// `DeviceInterfaceGUIDs`.
+#[derive(Clone)]
+pub struct RegistryProperty<'a> {
+    data_type: u32,
+    name: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> RegistryProperty<'a> {
+    /// Registry value type `REG_SZ`
+    pub const REG_SZ: u32 = 1;
+    /// Registry value type `REG_EXPAND_SZ`
+    pub const REG_EXPAND_SZ: u32 = 2;
+    /// Registry value type `REG_BINARY`
+    pub const REG_BINARY: u32 = 3;
+    /// Registry value type `REG_DWORD_LITTLE_ENDIAN`
+    pub const REG_DWORD_LITTLE_ENDIAN: u32 = 4;
+    /// Registry value type `REG_DWORD_BIG_ENDIAN`
+    pub const REG_DWORD_BIG_ENDIAN: u32 = 5;
+    /// Registry value type `REG_LINK`
+    pub const REG_LINK: u32 = 6;
+    /// Registry value type `REG_MULTI_SZ`
+    pub const REG_MULTI_SZ: u32 = 7;
+
+    fn parse_ms_os_20(buf: &'a [u8]) -> Option<RegistryProperty<'a>> {
+        let data_type = u16_at(buf, 4)? as u32;
+        let name_len = u16_at(buf, 6)? as usize;
+        let name = buf.get(8..8 + name_len)?;
+        let data_len = u16_at(buf, 8 + name_len)? as usize;
+        let data = buf.get(10 + name_len..10 + name_len + data_len)?;
+        Some(RegistryProperty {
+            data_type,
+            name,
+            data,
+        })
+    }
+
+    fn parse_ms_os_10(buf: &'a [u8]) -> Option<RegistryProperty<'a>> {
+        let data_type = u32_at(buf, 4)?;
+        let name_len = u16_at(buf, 8)? as usize;
+        let name = buf.get(10..10 + name_len)?;
+        let data_len = u32_at(buf, 10 + name_len)? as usize;
+        let data = buf.get(14 + name_len..14 + name_len + data_len)?;
+        Some(RegistryProperty {
+            data_type,
+            name,
+            data,
+        })
+    }
+
+    /// `wPropertyDataType` field: Registry value type, one of the `REG_*`
+    /// constants.
+    #[doc(alias = "wPropertyDataType")]
+    pub fn data_type(&self) -> u32 {
+        self.data_type
+    }
+
+    /// `PropertyName` field: Name of the registry value.
+    #[doc(alias = "PropertyName")]
+    pub fn name(&self) -> String {
+        decode_utf16(self.name)
+    }
+
+    /// `PropertyData` field: Raw data of the registry value.
+    #[doc(alias = "PropertyData")]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The value as a string, for the `REG_SZ`, `REG_EXPAND_SZ` and
+    /// `REG_LINK` types.
+    pub fn string_value(&self) -> Option<String> {
+        match self.data_type {
+            Self::REG_SZ | Self::REG_EXPAND_SZ | Self::REG_LINK => Some(decode_utf16(self.data)),
+            _ => None,
+        }
+    }
+
+    /// The value as a list of strings, for the `REG_MULTI_SZ` type.
+    pub fn multi_string_value(&self) -> Option<Vec<String>> {
+        if self.data_type != Self::REG_MULTI_SZ {
+            return None;
+        }
+        Some(
+            decode_utf16(self.data)
+                .split('\0')
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        )
+    }
+
+    /// The value as a number, for the `REG_DWORD_LITTLE_ENDIAN` and
+    /// `REG_DWORD_BIG_ENDIAN` types.
+    pub fn dword_value(&self) -> Option<u32> {
+        let bytes = self.data.get(..4)?.try_into().ok()?;
+        match self.data_type {
+            Self::REG_DWORD_LITTLE_ENDIAN => Some(u32::from_le_bytes(bytes)),
+            Self::REG_DWORD_BIG_ENDIAN => Some(u32::from_be_bytes(bytes)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Debug for RegistryProperty<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RegistryProperty")
+            .field("data_type", &self.data_type)
+            .field("name", &self.name())
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// Parse the MS OS 1.0 string descriptor, read from string index
+/// [`MS_OS_10_STRING_INDEX`], and return the vendor code used to read the
+/// other MS OS 1.0 descriptors.
+///
+/// Returns `None` if the descriptor does not have the `MSFT100` signature.
+pub fn ms_os_10_vendor_code(string_descriptor: &[u8]) -> Option<u8> {
+    const SIGNATURE: &[u8] = b"M\0S\0F\0T\x001\x000\x000\0";
+    if string_descriptor.len() < 18
+        || string_descriptor[1] != super::DESCRIPTOR_TYPE_STRING
+        || &string_descriptor[2..16] != SIGNATURE
+    {
+        return None;
+    }
+    Some(string_descriptor[16])
+}
+
+/// MS OS 1.0 extended compat ID descriptor, read with the vendor request
+/// with `wIndex` [`MS_OS_10_EXTENDED_COMPAT_ID_INDEX`].
+#[derive(Clone)]
+pub struct ExtendedCompatIdDescriptor<'a>(&'a [u8]);
+
+impl<'a> ExtendedCompatIdDescriptor<'a> {
+    /// Create an `ExtendedCompatIdDescriptor` from a buffer beginning with
+    /// the descriptor header.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `dwLength`.
+    pub fn new(buf: &[u8]) -> Option<ExtendedCompatIdDescriptor<'_>> {
+        let len = u32_at(buf, 0)? as usize;
+        if len < 16 || len > buf.len() || u16_at(buf, 6)? != MS_OS_10_EXTENDED_COMPAT_ID_INDEX {
+            warn!("invalid MS OS 1.0 extended compat ID descriptor");
+            return None;
+        }
+        Some(ExtendedCompatIdDescriptor(&buf[..len]))
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Iterate over the functions, with their first interface and compatible
+    /// ID.
+    pub fn functions(&self) -> impl Iterator<Item = (u8, CompatibleId<'a>)> {
+        let count = self.0[8] as usize;
+        self.0[16..]
+            .chunks_exact(24)
+            .take(count)
+            .map(|f| (f[0], CompatibleId(&f[2..18])))
+    }
+}
+
+impl<'a> Debug for ExtendedCompatIdDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtendedCompatIdDescriptor")
+            .field("functions", &self.functions().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// MS OS 1.0 extended properties descriptor, read with the vendor request
+/// with `wIndex` [`MS_OS_10_EXTENDED_PROPERTIES_INDEX`].
+#[derive(Clone)]
+pub struct ExtendedPropertiesDescriptor<'a>(&'a [u8]);
+
+impl<'a> ExtendedPropertiesDescriptor<'a> {
+    /// Create an `ExtendedPropertiesDescriptor` from a buffer beginning with
+    /// the descriptor header.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `dwLength`.
+    pub fn new(buf: &[u8]) -> Option<ExtendedPropertiesDescriptor<'_>> {
+        let len = u32_at(buf, 0)? as usize;
+        if len < 10 || len > buf.len() || u16_at(buf, 6)? != MS_OS_10_EXTENDED_PROPERTIES_INDEX {
+            warn!("invalid MS OS 1.0 extended properties descriptor");
+            return None;
+        }
+        Some(ExtendedPropertiesDescriptor(&buf[..len]))
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Iterate over the registry properties.
+    pub fn properties(&self) -> impl Iterator<Item = RegistryProperty<'a>> {
+        let mut count = u16_at(self.0, 8).unwrap();
+        let mut buf = &self.0[10..];
+        core::iter::from_fn(move || {
+            if count == 0 {
+                return None;
+            }
+            count -= 1;
+
+            let len = u32_at(buf, 0)? as usize;
+            if len > buf.len() {
+                warn!("invalid MS OS 1.0 custom property dwSize of {len}");
+                return None;
+            }
+            let (property, next) = buf.split_at(len);
+            buf = next;
+            RegistryProperty::parse_ms_os_10(property)
+        })
+    }
+}
+
+impl<'a> Debug for ExtendedPropertiesDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtendedPropertiesDescriptor")
+            .field("properties", &self.properties().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+fn utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_descriptor_set() {
+    let name = utf16("DeviceInterfaceGUIDs\0");
+    let value = utf16("{12345678-1234-1234-1234-123456789ABC}\0\0");
+
+    let mut property = vec![0, 0, 0x04, 0x00, 0x07, 0x00];
+    property.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    property.extend_from_slice(&name);
+    property.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    property.extend_from_slice(&value);
+    property[0] = property.len() as u8;
+
+    let mut buf = vec![
+        0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x06, 0x00, 0x00,
+        0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x14, 0x00, 0x03, 0x00, b'W', b'I', b'N', b'U', b'S', b'B', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    buf.extend_from_slice(&property);
+    let total_len = buf.len() as u16;
+    buf[8..10].copy_from_slice(&total_len.to_le_bytes());
+
+    let set = DescriptorSet::new(&buf).unwrap();
+    assert_eq!(set.windows_version(), 0x06030000);
+    assert_eq!(set.descriptors().count(), 4);
+
+    let ids: Vec<_> = set
+        .compatible_ids()
+        .map(|(f, id)| (f, id.compatible_id(), id.sub_compatible_id()))
+        .collect();
+    assert_eq!(ids, [(Some(1), "WINUSB", "")]);
+
+    let (function, property) = set.registry_properties().next().unwrap();
+    assert_eq!(function, Some(1));
+    assert_eq!(property.name(), "DeviceInterfaceGUIDs");
+    assert_eq!(
+        property.multi_string_value().unwrap(),
+        ["{12345678-1234-1234-1234-123456789ABC}"]
+    );
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_ms_os_20_capability() {
+    let bos = BosDescriptor::new(&[
+        0x05, 0x0f, 0x21, 0x00, 0x01,
+        0x1c, 0x10, 0x05, 0x00, 0xdf, 0x60, 0xdd, 0xd8, 0x89, 0x45, 0xc7, 0x4c,
+        0x9c, 0xd2, 0x65, 0x9d, 0x9e, 0x64, 0x8a, 0x9f,
+        0x00, 0x00, 0x03, 0x06, 0xb2, 0x00, 0x21, 0x00,
+    ]).unwrap();
+
+    let sets: Vec<_> = DescriptorSetInfo::from_bos(&bos).collect();
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].windows_version(), 0x06030000);
+    assert_eq!(sets[0].total_length(), 0xb2);
+    assert_eq!(sets[0].vendor_code(), 0x21);
+    assert_eq!(sets[0].alt_enum_code(), 0);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_ms_os_10() {
+    let mut string = vec![0x12, 0x03];
+    string.extend_from_slice(&utf16("MSFT100"));
+    string.extend_from_slice(&[0x20, 0x00]);
+    assert_eq!(ms_os_10_vendor_code(&string), Some(0x20));
+    assert_eq!(ms_os_10_vendor_code(&[0x04, 0x03, 0x09, 0x04]), None);
+
+    let compat = [
+        0x28, 0x00, 0x00, 0x00, 0x00, 0x01, 0x04, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0,
+        0x00, 0x01, b'W', b'I', b'N', b'U', b'S', b'B', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0,
+    ];
+    let compat = ExtendedCompatIdDescriptor::new(&compat).unwrap();
+    let functions: Vec<_> = compat
+        .functions()
+        .map(|(i, id)| (i, id.compatible_id()))
+        .collect();
+    assert_eq!(functions, [(0, "WINUSB")]);
+
+    let name = utf16("Label\0");
+    let value = utf16("Test\0");
+    let mut property = vec![0, 0, 0, 0, 0x01, 0x00, 0x00, 0x00];
+    property.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    property.extend_from_slice(&name);
+    property.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    property.extend_from_slice(&value);
+    property[0] = property.len() as u8;
+
+    let mut buf = vec![0, 0, 0, 0, 0x00, 0x01, 0x05, 0x00, 0x01, 0x00];
+    buf.extend_from_slice(&property);
+    buf[0] = buf.len() as u8;
+
+    let properties = ExtendedPropertiesDescriptor::new(&buf).unwrap();
+    let property = properties.properties().next().unwrap();
+    assert_eq!(property.name(), "Label");
+    assert_eq!(property.string_value().unwrap(), "Test");
+}
@@ -0,0 +1,966 @@
+//! USB Audio Class (UAC) 1.0 and 2.0 descriptors, the class-specific
+//! descriptors of the AudioControl and AudioStreaming interfaces of an audio
+//! function.
+//!
+//! The AudioControl interface describes the clocks, terminals and units of
+//! the function, obtained from [`audio_control_descriptors`]. Each
+//! AudioStreaming interface describes the format of its alternate settings,
+//! obtained from [`audio_streaming_descriptors`], and its isochronous
+//! endpoint, obtained from [`audio_streaming_endpoint`].
+//!
+//! The layout of most descriptors differs between UAC 1.0 and 2.0, so they
+//! carry the [`AudioVersion`] of the interface they were read from, and
+//! fields only present in one version return `Option`.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use super::{Descriptor, EndpointDescriptor, InterfaceDescriptor, DESCRIPTOR_TYPE_CS_INTERFACE};
+
+/// `bInterfaceClass` of audio interfaces.
+pub const CLASS_AUDIO: u8 = 0x01;
+
+/// `bInterfaceSubClass` of the AudioControl interface.
+pub const SUBCLASS_AUDIO_CONTROL: u8 = 0x01;
+
+/// `bInterfaceSubClass` of AudioStreaming interfaces.
+pub const SUBCLASS_AUDIO_STREAMING: u8 = 0x02;
+
+/// `bInterfaceProtocol` of UAC 2.0 interfaces.
+const PROTOCOL_UAC2: u8 = 0x20;
+
+const DESCRIPTOR_TYPE_CS_ENDPOINT: u8 = 0x25;
+
+const AC_HEADER: u8 = 0x01;
+const AC_INPUT_TERMINAL: u8 = 0x02;
+const AC_OUTPUT_TERMINAL: u8 = 0x03;
+const AC_FEATURE_UNIT: u8 = 0x06;
+const AC_CLOCK_SOURCE: u8 = 0x0A;
+const AC_CLOCK_SELECTOR: u8 = 0x0B;
+
+const AS_GENERAL: u8 = 0x01;
+const AS_FORMAT_TYPE: u8 = 0x02;
+
+const EP_GENERAL: u8 = 0x01;
+
+/// Version of the audio class specification an interface follows.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AudioVersion {
+    /// USB Audio Class 1.0
+    Uac1,
+
+    /// USB Audio Class 2.0
+    Uac2,
+}
+
+impl AudioVersion {
+    /// Get the version of an audio interface, from its `bInterfaceProtocol`.
+    pub fn of_interface(interface: &InterfaceDescriptor) -> AudioVersion {
+        if interface.protocol() == PROTOCOL_UAC2 {
+            AudioVersion::Uac2
+        } else {
+            AudioVersion::Uac1
+        }
+    }
+}
+
+fn u16_at(buf: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(buf[pos..pos + 2].try_into().unwrap())
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap())
+}
+
+fn u24_at(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], 0])
+}
+
+fn class_specific<'a>(
+    descriptors: impl Iterator<Item = (u8, &'a [u8])>,
+    descriptor_type: u8,
+) -> impl Iterator<Item = (u8, &'a [u8])> {
+    descriptors
+        .filter(move |&(ty, _)| ty == descriptor_type)
+        .filter_map(|(_, buf)| Some((*buf.get(2)?, buf)))
+}
+
+/// Iterate over the class-specific descriptors of an AudioControl
+/// interface.
+pub fn audio_control_descriptors<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = AudioControlDescriptor<'a>> {
+    let version = AudioVersion::of_interface(interface);
+    class_specific(interface.class_descriptors(), DESCRIPTOR_TYPE_CS_INTERFACE)
+        .map(move |(subtype, buf)| AudioControlDescriptor::new(version, subtype, buf))
+}
+
+/// Iterate over the class-specific descriptors of an AudioStreaming
+/// interface.
+pub fn audio_streaming_descriptors<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = AudioStreamingDescriptor<'a>> {
+    let version = AudioVersion::of_interface(interface);
+    class_specific(interface.class_descriptors(), DESCRIPTOR_TYPE_CS_INTERFACE)
+        .map(move |(subtype, buf)| AudioStreamingDescriptor::new(version, subtype, buf))
+}
+
+/// Get the class-specific descriptor following the isochronous endpoint
+/// descriptor of an AudioStreaming interface.
+pub fn audio_streaming_endpoint<'a>(
+    version: AudioVersion,
+    endpoint: &EndpointDescriptor<'a>,
+) -> Option<AsEndpointDescriptor<'a>> {
+    let min_len = match version {
+        AudioVersion::Uac1 => 7,
+        AudioVersion::Uac2 => 8,
+    };
+    class_specific(endpoint.class_descriptors(), DESCRIPTOR_TYPE_CS_ENDPOINT)
+        .find(|&(subtype, buf)| subtype == EP_GENERAL && buf.len() >= min_len)
+        .map(|(_, buf)| AsEndpointDescriptor(version, buf))
+}
+
+/// A class-specific descriptor of an AudioControl interface.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum AudioControlDescriptor<'a> {
+    /// Class-specific AC interface header descriptor
+    Header(AcHeaderDescriptor<'a>),
+
+    /// Input terminal descriptor
+    InputTerminal(InputTerminalDescriptor<'a>),
+
+    /// Output terminal descriptor
+    OutputTerminal(OutputTerminalDescriptor<'a>),
+
+    /// Feature unit descriptor
+    FeatureUnit(FeatureUnitDescriptor<'a>),
+
+    /// Clock source descriptor (UAC 2.0)
+    ClockSource(ClockSourceDescriptor<'a>),
+
+    /// Clock selector descriptor (UAC 2.0)
+    ClockSelector(ClockSelectorDescriptor<'a>),
+
+    /// A descriptor of another subtype, or too short for its subtype
+    Other(Descriptor<'a>),
+}
+
+impl<'a> AudioControlDescriptor<'a> {
+    fn new(version: AudioVersion, subtype: u8, buf: &'a [u8]) -> AudioControlDescriptor<'a> {
+        use AudioVersion::*;
+
+        let len = buf.len();
+        let valid = match (version, subtype) {
+            (Uac1, AC_HEADER) => len >= 8 && len >= 8 + usize::from(buf[7]),
+            (Uac2, AC_HEADER) => len >= 9,
+            (Uac1, AC_INPUT_TERMINAL) => len >= 12,
+            (Uac2, AC_INPUT_TERMINAL) => len >= 17,
+            (Uac1, AC_OUTPUT_TERMINAL) => len >= 9,
+            (Uac2, AC_OUTPUT_TERMINAL) => len >= 12,
+            (Uac1, AC_FEATURE_UNIT) => len >= 7 && buf[5] > 0,
+            (Uac2, AC_FEATURE_UNIT) => len >= 10,
+            (Uac2, AC_CLOCK_SOURCE) => len >= 8,
+            (Uac2, AC_CLOCK_SELECTOR) => len >= 7 && len >= 7 + usize::from(buf[4]),
+            _ => false,
+        };
+
+        if !valid {
+            return AudioControlDescriptor::Other(Descriptor(buf));
+        }
+
+        match subtype {
+            AC_HEADER => AudioControlDescriptor::Header(AcHeaderDescriptor(version, buf)),
+            AC_INPUT_TERMINAL => {
+                AudioControlDescriptor::InputTerminal(InputTerminalDescriptor(version, buf))
+            }
+            AC_OUTPUT_TERMINAL => {
+                AudioControlDescriptor::OutputTerminal(OutputTerminalDescriptor(version, buf))
+            }
+            AC_FEATURE_UNIT => {
+                AudioControlDescriptor::FeatureUnit(FeatureUnitDescriptor(version, buf))
+            }
+            AC_CLOCK_SOURCE => AudioControlDescriptor::ClockSource(ClockSourceDescriptor(buf)),
+            _ => AudioControlDescriptor::ClockSelector(ClockSelectorDescriptor(buf)),
+        }
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            AudioControlDescriptor::Header(d) => d.1,
+            AudioControlDescriptor::InputTerminal(d) => d.1,
+            AudioControlDescriptor::OutputTerminal(d) => d.1,
+            AudioControlDescriptor::FeatureUnit(d) => d.1,
+            AudioControlDescriptor::ClockSource(d) => d.0,
+            AudioControlDescriptor::ClockSelector(d) => d.0,
+            AudioControlDescriptor::Other(d) => d.0,
+        }
+    }
+
+    /// `bDescriptorSubtype` descriptor field.
+    #[doc(alias = "bDescriptorSubtype")]
+    pub fn descriptor_subtype(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+}
+
+/// Class-specific AudioControl interface header descriptor.
+#[derive(Clone)]
+pub struct AcHeaderDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> AcHeaderDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bcdADC` descriptor field: Audio class specification version, in
+    /// binary-coded decimal.
+    #[doc(alias = "bcdADC")]
+    pub fn adc_version(&self) -> u16 {
+        u16_at(self.1, 3)
+    }
+
+    /// `wTotalLength` descriptor field: Length of the class-specific
+    /// AudioControl descriptors.
+    #[doc(alias = "wTotalLength")]
+    pub fn total_length(&self) -> u16 {
+        match self.0 {
+            AudioVersion::Uac1 => u16_at(self.1, 5),
+            AudioVersion::Uac2 => u16_at(self.1, 6),
+        }
+    }
+
+    /// `bCategory` descriptor field: Primary use of the function, such as
+    /// headset (UAC 2.0 only).
+    #[doc(alias = "bCategory")]
+    pub fn category(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac2).then(|| self.1[5])
+    }
+
+    /// `baInterfaceNr` descriptor field: Numbers of the AudioStreaming and
+    /// MIDIStreaming interfaces of the function (UAC 1.0 only; UAC 2.0 uses
+    /// an interface association descriptor).
+    #[doc(alias = "baInterfaceNr")]
+    pub fn streaming_interfaces(&self) -> Option<&'a [u8]> {
+        (self.0 == AudioVersion::Uac1).then(|| &self.1[8..8 + usize::from(self.1[7])])
+    }
+}
+
+impl<'a> Debug for AcHeaderDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AcHeaderDescriptor")
+            .field("adc_version", &format_args!("0x{:04X}", self.adc_version()))
+            .field("total_length", &self.total_length())
+            .field("category", &self.category())
+            .field("streaming_interfaces", &self.streaming_interfaces())
+            .finish()
+    }
+}
+
+/// Input terminal descriptor, such as a microphone or the USB stream from
+/// the host.
+#[derive(Clone)]
+pub struct InputTerminalDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> InputTerminalDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bTerminalID` descriptor field: ID of the terminal.
+    #[doc(alias = "bTerminalID")]
+    pub fn terminal_id(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `wTerminalType` descriptor field: Type of the terminal, e.g.
+    /// `0x0101` for USB streaming or `0x0201` for a microphone.
+    #[doc(alias = "wTerminalType")]
+    pub fn terminal_type(&self) -> u16 {
+        u16_at(self.1, 4)
+    }
+
+    /// `bAssocTerminal` descriptor field: ID of the associated output
+    /// terminal, or `0`.
+    #[doc(alias = "bAssocTerminal")]
+    pub fn assoc_terminal(&self) -> u8 {
+        self.1[6]
+    }
+
+    /// `bCSourceID` descriptor field: ID of the clock entity of the
+    /// terminal (UAC 2.0 only).
+    #[doc(alias = "bCSourceID")]
+    pub fn clock_source_id(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac2).then(|| self.1[7])
+    }
+
+    /// `bNrChannels` descriptor field: Number of logical channels.
+    #[doc(alias = "bNrChannels")]
+    pub fn nr_channels(&self) -> u8 {
+        match self.0 {
+            AudioVersion::Uac1 => self.1[7],
+            AudioVersion::Uac2 => self.1[8],
+        }
+    }
+
+    /// `wChannelConfig` or `bmChannelConfig` descriptor field: Bitmap of the
+    /// spatial locations of the channels.
+    #[doc(alias = "wChannelConfig")]
+    #[doc(alias = "bmChannelConfig")]
+    pub fn channel_config(&self) -> u32 {
+        match self.0 {
+            AudioVersion::Uac1 => u16_at(self.1, 8).into(),
+            AudioVersion::Uac2 => u32_at(self.1, 9),
+        }
+    }
+
+    /// `iTerminal` descriptor field: Index of the string descriptor
+    /// describing the terminal, or `0`.
+    #[doc(alias = "iTerminal")]
+    pub fn string_index(&self) -> u8 {
+        match self.0 {
+            AudioVersion::Uac1 => self.1[11],
+            AudioVersion::Uac2 => self.1[16],
+        }
+    }
+}
+
+impl<'a> Debug for InputTerminalDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InputTerminalDescriptor")
+            .field("terminal_id", &self.terminal_id())
+            .field(
+                "terminal_type",
+                &format_args!("0x{:04X}", self.terminal_type()),
+            )
+            .field("assoc_terminal", &self.assoc_terminal())
+            .field("clock_source_id", &self.clock_source_id())
+            .field("nr_channels", &self.nr_channels())
+            .field(
+                "channel_config",
+                &format_args!("0x{:08X}", self.channel_config()),
+            )
+            .finish()
+    }
+}
+
+/// Output terminal descriptor, such as a speaker or the USB stream to the
+/// host.
+#[derive(Clone)]
+pub struct OutputTerminalDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> OutputTerminalDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bTerminalID` descriptor field: ID of the terminal.
+    #[doc(alias = "bTerminalID")]
+    pub fn terminal_id(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `wTerminalType` descriptor field: Type of the terminal, e.g.
+    /// `0x0101` for USB streaming or `0x0301` for a speaker.
+    #[doc(alias = "wTerminalType")]
+    pub fn terminal_type(&self) -> u16 {
+        u16_at(self.1, 4)
+    }
+
+    /// `bAssocTerminal` descriptor field: ID of the associated input
+    /// terminal, or `0`.
+    #[doc(alias = "bAssocTerminal")]
+    pub fn assoc_terminal(&self) -> u8 {
+        self.1[6]
+    }
+
+    /// `bSourceID` descriptor field: ID of the unit or terminal connected
+    /// to this terminal.
+    #[doc(alias = "bSourceID")]
+    pub fn source_id(&self) -> u8 {
+        self.1[7]
+    }
+
+    /// `bCSourceID` descriptor field: ID of the clock entity of the
+    /// terminal (UAC 2.0 only).
+    #[doc(alias = "bCSourceID")]
+    pub fn clock_source_id(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac2).then(|| self.1[8])
+    }
+
+    /// `iTerminal` descriptor field: Index of the string descriptor
+    /// describing the terminal, or `0`.
+    #[doc(alias = "iTerminal")]
+    pub fn string_index(&self) -> u8 {
+        match self.0 {
+            AudioVersion::Uac1 => self.1[8],
+            AudioVersion::Uac2 => self.1[11],
+        }
+    }
+}
+
+impl<'a> Debug for OutputTerminalDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OutputTerminalDescriptor")
+            .field("terminal_id", &self.terminal_id())
+            .field(
+                "terminal_type",
+                &format_args!("0x{:04X}", self.terminal_type()),
+            )
+            .field("assoc_terminal", &self.assoc_terminal())
+            .field("source_id", &self.source_id())
+            .field("clock_source_id", &self.clock_source_id())
+            .finish()
+    }
+}
+
+/// Feature unit descriptor, describing controls such as volume and mute.
+#[derive(Clone)]
+pub struct FeatureUnitDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> FeatureUnitDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bUnitID` descriptor field: ID of the unit.
+    #[doc(alias = "bUnitID")]
+    pub fn unit_id(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `bSourceID` descriptor field: ID of the unit or terminal connected
+    /// to the input.
+    #[doc(alias = "bSourceID")]
+    pub fn source_id(&self) -> u8 {
+        self.1[4]
+    }
+
+    /// `bmaControls` descriptor field: Bitmap of the controls of each
+    /// channel, starting with the master channel `0`.
+    #[doc(alias = "bmaControls")]
+    pub fn channel_controls(&self) -> Vec<u32> {
+        match self.0 {
+            AudioVersion::Uac1 => {
+                let size = usize::from(self.1[5]);
+                self.1[6..self.1.len() - 1]
+                    .chunks_exact(size)
+                    .map(|c| {
+                        c.iter()
+                            .take(4)
+                            .rev()
+                            .fold(0, |acc, &b| (acc << 8) | u32::from(b))
+                    })
+                    .collect()
+            }
+            AudioVersion::Uac2 => self.1[5..self.1.len() - 1]
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        }
+    }
+
+    /// `iFeature` descriptor field: Index of the string descriptor
+    /// describing the unit, or `0`.
+    #[doc(alias = "iFeature")]
+    pub fn string_index(&self) -> u8 {
+        self.1[self.1.len() - 1]
+    }
+}
+
+impl<'a> Debug for FeatureUnitDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FeatureUnitDescriptor")
+            .field("unit_id", &self.unit_id())
+            .field("source_id", &self.source_id())
+            .field("channel_controls", &self.channel_controls())
+            .finish()
+    }
+}
+
+/// Clock source descriptor (UAC 2.0), describing a sampling clock.
+#[derive(Clone)]
+pub struct ClockSourceDescriptor<'a>(&'a [u8]);
+
+impl<'a> ClockSourceDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Type of the clock from `bmAttributes`: `0` external, `1` internal
+    /// fixed, `2` internal variable, `3` internal programmable.
+    pub fn clock_type(&self) -> u8 {
+        self.attributes() & 0x03
+    }
+}
+
+descriptor_fields! {
+    impl<'a> ClockSourceDescriptor<'a> {
+        /// `bClockID` descriptor field: ID of the clock entity.
+        #[doc(alias = "bClockID")]
+        pub fn clock_id at 3 -> u8;
+
+        /// `bmAttributes` descriptor field.
+        #[doc(alias = "bmAttributes")]
+        pub fn attributes at 4 -> u8;
+
+        /// `bmControls` descriptor field: Bitmap of the clock frequency and
+        /// validity controls.
+        #[doc(alias = "bmControls")]
+        pub fn controls at 5 -> u8;
+
+        /// `bAssocTerminal` descriptor field: ID of the terminal the clock
+        /// is derived from, or `0`.
+        #[doc(alias = "bAssocTerminal")]
+        pub fn assoc_terminal at 6 -> u8;
+
+        /// `iClockSource` descriptor field: Index of the string descriptor
+        /// describing the clock, or `0`.
+        #[doc(alias = "iClockSource")]
+        pub fn string_index at 7 -> u8;
+    }
+}
+
+impl<'a> Debug for ClockSourceDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ClockSourceDescriptor")
+            .field("clock_id", &self.clock_id())
+            .field("clock_type", &self.clock_type())
+            .field("controls", &format_args!("0x{:02X}", self.controls()))
+            .field("assoc_terminal", &self.assoc_terminal())
+            .finish()
+    }
+}
+
+/// Clock selector descriptor (UAC 2.0), selecting one of several clocks.
+#[derive(Clone)]
+pub struct ClockSelectorDescriptor<'a>(&'a [u8]);
+
+impl<'a> ClockSelectorDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `baCSourceID` descriptor field: IDs of the clock entities connected
+    /// to the inputs.
+    #[doc(alias = "baCSourceID")]
+    pub fn source_ids(&self) -> &'a [u8] {
+        &self.0[5..5 + usize::from(self.0[4])]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> ClockSelectorDescriptor<'a> {
+        /// `bClockID` descriptor field: ID of the clock entity.
+        #[doc(alias = "bClockID")]
+        pub fn clock_id at 3 -> u8;
+    }
+}
+
+impl<'a> Debug for ClockSelectorDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ClockSelectorDescriptor")
+            .field("clock_id", &self.clock_id())
+            .field("source_ids", &self.source_ids())
+            .finish()
+    }
+}
+
+/// A class-specific descriptor of an AudioStreaming interface.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum AudioStreamingDescriptor<'a> {
+    /// Class-specific AS interface descriptor
+    General(AsGeneralDescriptor<'a>),
+
+    /// Format type descriptor
+    FormatType(FormatTypeDescriptor<'a>),
+
+    /// A descriptor of another subtype, or too short for its subtype
+    Other(Descriptor<'a>),
+}
+
+impl<'a> AudioStreamingDescriptor<'a> {
+    fn new(version: AudioVersion, subtype: u8, buf: &'a [u8]) -> AudioStreamingDescriptor<'a> {
+        let len = buf.len();
+        match (version, subtype) {
+            (AudioVersion::Uac1, AS_GENERAL) if len >= 7 => {
+                AudioStreamingDescriptor::General(AsGeneralDescriptor(version, buf))
+            }
+            (AudioVersion::Uac2, AS_GENERAL) if len >= 16 => {
+                AudioStreamingDescriptor::General(AsGeneralDescriptor(version, buf))
+            }
+            (_, AS_FORMAT_TYPE) if FormatTypeDescriptor::valid(version, buf) => {
+                AudioStreamingDescriptor::FormatType(FormatTypeDescriptor(version, buf))
+            }
+            _ => AudioStreamingDescriptor::Other(Descriptor(buf)),
+        }
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            AudioStreamingDescriptor::General(d) => d.1,
+            AudioStreamingDescriptor::FormatType(d) => d.1,
+            AudioStreamingDescriptor::Other(d) => d.0,
+        }
+    }
+
+    /// `bDescriptorSubtype` descriptor field.
+    #[doc(alias = "bDescriptorSubtype")]
+    pub fn descriptor_subtype(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+}
+
+/// Class-specific AudioStreaming interface descriptor.
+#[derive(Clone)]
+pub struct AsGeneralDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> AsGeneralDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bTerminalLink` descriptor field: ID of the terminal the interface
+    /// is connected to.
+    #[doc(alias = "bTerminalLink")]
+    pub fn terminal_link(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `wFormatTag` descriptor field: Audio data format, e.g. `0x0001` for
+    /// PCM (UAC 1.0 only).
+    #[doc(alias = "wFormatTag")]
+    pub fn format_tag(&self) -> Option<u16> {
+        (self.0 == AudioVersion::Uac1).then(|| u16_at(self.1, 5))
+    }
+
+    /// `bmFormats` descriptor field: Bitmap of the audio data formats, with
+    /// bit 0 for PCM (UAC 2.0 only).
+    #[doc(alias = "bmFormats")]
+    pub fn formats(&self) -> Option<u32> {
+        (self.0 == AudioVersion::Uac2).then(|| u32_at(self.1, 6))
+    }
+
+    /// `bNrChannels` descriptor field: Number of channels (UAC 2.0 only; in
+    /// UAC 1.0 it is in the format type descriptor).
+    #[doc(alias = "bNrChannels")]
+    pub fn nr_channels(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac2).then(|| self.1[10])
+    }
+
+    /// `bmChannelConfig` descriptor field: Bitmap of the spatial locations
+    /// of the channels (UAC 2.0 only).
+    #[doc(alias = "bmChannelConfig")]
+    pub fn channel_config(&self) -> Option<u32> {
+        (self.0 == AudioVersion::Uac2).then(|| u32_at(self.1, 11))
+    }
+}
+
+impl<'a> Debug for AsGeneralDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsGeneralDescriptor")
+            .field("terminal_link", &self.terminal_link())
+            .field("format_tag", &self.format_tag())
+            .field("formats", &self.formats())
+            .field("nr_channels", &self.nr_channels())
+            .finish()
+    }
+}
+
+/// Sampling frequencies supported by an alternate setting, in Hz.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SampleRates {
+    /// Any frequency from `min` to `max`.
+    Continuous {
+        /// Lowest frequency.
+        min: u32,
+        /// Highest frequency.
+        max: u32,
+    },
+
+    /// A list of frequencies.
+    Discrete(Vec<u32>),
+}
+
+/// Format type descriptor, describing the sample layout of an alternate
+/// setting.
+#[derive(Clone)]
+pub struct FormatTypeDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> FormatTypeDescriptor<'a> {
+    fn valid(version: AudioVersion, buf: &[u8]) -> bool {
+        match version {
+            AudioVersion::Uac1 if buf.len() >= 8 => {
+                let rates_len = match buf[7] {
+                    0 => 6,
+                    n => 3 * usize::from(n),
+                };
+                buf.len() >= 8 + rates_len
+            }
+            AudioVersion::Uac2 => buf.len() >= 6,
+            _ => false,
+        }
+    }
+
+    fn is_type_i_or_iii(&self) -> bool {
+        matches!(self.format_type(), 1 | 3)
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bFormatType` descriptor field: `1` for Type I (PCM) formats.
+    #[doc(alias = "bFormatType")]
+    pub fn format_type(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `bNrChannels` descriptor field: Number of channels (UAC 1.0 Type I
+    /// and III only).
+    #[doc(alias = "bNrChannels")]
+    pub fn nr_channels(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac1 && self.is_type_i_or_iii()).then(|| self.1[4])
+    }
+
+    /// `bSubframeSize` or `bSubslotSize` descriptor field: Bytes occupied
+    /// by a sample of one channel (Type I and III only).
+    #[doc(alias = "bSubframeSize")]
+    #[doc(alias = "bSubslotSize")]
+    pub fn subslot_size(&self) -> Option<u8> {
+        if !self.is_type_i_or_iii() {
+            return None;
+        }
+        Some(match self.0 {
+            AudioVersion::Uac1 => self.1[5],
+            AudioVersion::Uac2 => self.1[4],
+        })
+    }
+
+    /// `bBitResolution` descriptor field: Number of significant bits of a
+    /// sample (Type I and III only).
+    #[doc(alias = "bBitResolution")]
+    pub fn bit_resolution(&self) -> Option<u8> {
+        if !self.is_type_i_or_iii() {
+            return None;
+        }
+        Some(match self.0 {
+            AudioVersion::Uac1 => self.1[6],
+            AudioVersion::Uac2 => self.1[5],
+        })
+    }
+
+    /// `bSamFreqType` and `tSamFreq` descriptor fields: Supported sampling
+    /// frequencies (UAC 1.0 Type I and III only; UAC 2.0 devices report
+    /// them through a request to the clock source).
+    #[doc(alias = "tSamFreq")]
+    pub fn sample_rates(&self) -> Option<SampleRates> {
+        if self.0 != AudioVersion::Uac1 || !self.is_type_i_or_iii() {
+            return None;
+        }
+        Some(match self.1[7] {
+            0 => SampleRates::Continuous {
+                min: u24_at(self.1, 8),
+                max: u24_at(self.1, 11),
+            },
+            n => SampleRates::Discrete(
+                (0..usize::from(n))
+                    .map(|i| u24_at(self.1, 8 + 3 * i))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl<'a> Debug for FormatTypeDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FormatTypeDescriptor")
+            .field("format_type", &self.format_type())
+            .field("nr_channels", &self.nr_channels())
+            .field("subslot_size", &self.subslot_size())
+            .field("bit_resolution", &self.bit_resolution())
+            .field("sample_rates", &self.sample_rates())
+            .finish()
+    }
+}
+
+/// Class-specific AudioStreaming isochronous endpoint descriptor.
+#[derive(Clone)]
+pub struct AsEndpointDescriptor<'a>(AudioVersion, &'a [u8]);
+
+impl<'a> AsEndpointDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.1
+    }
+
+    /// `bmAttributes` descriptor field.
+    #[doc(alias = "bmAttributes")]
+    pub fn attributes(&self) -> u8 {
+        self.1[3]
+    }
+
+    /// `bmControls` descriptor field: Bitmap of the endpoint controls (UAC
+    /// 2.0 only).
+    #[doc(alias = "bmControls")]
+    pub fn controls(&self) -> Option<u8> {
+        (self.0 == AudioVersion::Uac2).then(|| self.1[4])
+    }
+
+    /// `bLockDelayUnits` descriptor field: Units of
+    /// [`lock_delay`][Self::lock_delay], `1` for milliseconds and `2` for
+    /// decoded PCM samples.
+    #[doc(alias = "bLockDelayUnits")]
+    pub fn lock_delay_units(&self) -> u8 {
+        match self.0 {
+            AudioVersion::Uac1 => self.1[4],
+            AudioVersion::Uac2 => self.1[5],
+        }
+    }
+
+    /// `wLockDelay` descriptor field: Time for the endpoint to lock its
+    /// internal clock recovery circuitry.
+    #[doc(alias = "wLockDelay")]
+    pub fn lock_delay(&self) -> u16 {
+        match self.0 {
+            AudioVersion::Uac1 => u16_at(self.1, 5),
+            AudioVersion::Uac2 => u16_at(self.1, 6),
+        }
+    }
+}
+
+impl<'a> Debug for AsEndpointDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsEndpointDescriptor")
+            .field("attributes", &format_args!("0x{:02X}", self.attributes()))
+            .field("controls", &self.controls())
+            .field("lock_delay_units", &self.lock_delay_units())
+            .field("lock_delay", &self.lock_delay())
+            .finish()
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_uac1() {
+    use super::ConfigurationDescriptor;
+
+    let c = ConfigurationDescriptor::new(&[
+        0x09, 0x02, 0x67, 0x00, 0x02, 0x01, 0x00, 0x80, 0x32,
+        // AudioControl
+        0x09, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00,
+        0x09, 0x24, 0x01, 0x00, 0x01, 0x27, 0x00, 0x01, 0x01,
+        0x0c, 0x24, 0x02, 0x01, 0x01, 0x01, 0x00, 0x02, 0x03, 0x00, 0x00, 0x00,
+        0x09, 0x24, 0x06, 0x02, 0x01, 0x01, 0x03, 0x00, 0x00,
+        0x09, 0x24, 0x03, 0x03, 0x01, 0x03, 0x00, 0x02, 0x00,
+        // AudioStreaming, alt 1
+        0x09, 0x04, 0x01, 0x01, 0x01, 0x01, 0x02, 0x00, 0x00,
+        0x07, 0x24, 0x01, 0x01, 0x01, 0x01, 0x00,
+        0x0e, 0x24, 0x02, 0x01, 0x02, 0x02, 0x10, 0x02, 0x44, 0xac, 0x00, 0x80, 0xbb, 0x00,
+        0x09, 0x05, 0x01, 0x09, 0xc0, 0x00, 0x01, 0x00, 0x00,
+        0x07, 0x25, 0x01, 0x01, 0x01, 0x01, 0x00,
+    ]).unwrap();
+
+    let mut alts = c.interface_alt_settings();
+    let control = alts.next().unwrap();
+    assert_eq!(AudioVersion::of_interface(&control), AudioVersion::Uac1);
+
+    let descriptors: Vec<_> = audio_control_descriptors(&control).collect();
+    assert_eq!(descriptors.len(), 4);
+
+    let AudioControlDescriptor::Header(header) = &descriptors[0] else { panic!() };
+    assert_eq!(header.adc_version(), 0x0100);
+    assert_eq!(header.streaming_interfaces(), Some(&[1][..]));
+
+    let AudioControlDescriptor::InputTerminal(it) = &descriptors[1] else { panic!() };
+    assert_eq!(it.terminal_type(), 0x0101);
+    assert_eq!(it.nr_channels(), 2);
+    assert_eq!(it.channel_config(), 3);
+
+    let AudioControlDescriptor::FeatureUnit(fu) = &descriptors[2] else { panic!() };
+    assert_eq!(fu.source_id(), 1);
+    assert_eq!(fu.channel_controls(), [3, 0]);
+
+    let streaming = alts.next().unwrap();
+    let descriptors: Vec<_> = audio_streaming_descriptors(&streaming).collect();
+
+    let AudioStreamingDescriptor::General(general) = &descriptors[0] else { panic!() };
+    assert_eq!(general.terminal_link(), 1);
+    assert_eq!(general.format_tag(), Some(1));
+
+    let AudioStreamingDescriptor::FormatType(format) = &descriptors[1] else { panic!() };
+    assert_eq!(format.nr_channels(), Some(2));
+    assert_eq!(format.subslot_size(), Some(2));
+    assert_eq!(format.bit_resolution(), Some(16));
+    assert_eq!(format.sample_rates(), Some(SampleRates::Discrete(vec![44100, 48000])));
+
+    let ep = streaming.endpoints().next().unwrap();
+    let as_ep = audio_streaming_endpoint(AudioVersion::Uac1, &ep).unwrap();
+    assert_eq!(as_ep.attributes(), 1);
+    assert_eq!(as_ep.lock_delay_units(), 1);
+    assert_eq!(as_ep.lock_delay(), 1);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_uac2() {
+    let control = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x20, 0x00,
+        0x09, 0x24, 0x01, 0x00, 0x02, 0x04, 0x40, 0x00, 0x00,
+        0x08, 0x24, 0x0a, 0x29, 0x03, 0x07, 0x00, 0x00,
+        0x11, 0x24, 0x02, 0x01, 0x01, 0x01, 0x00, 0x29, 0x02, 0x03, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x12, 0x24, 0x06, 0x02, 0x01, 0x0f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00,
+    ]);
+    assert_eq!(AudioVersion::of_interface(&control), AudioVersion::Uac2);
+
+    let descriptors: Vec<_> = audio_control_descriptors(&control).collect();
+
+    let AudioControlDescriptor::Header(header) = &descriptors[0] else { panic!() };
+    assert_eq!(header.adc_version(), 0x0200);
+    assert_eq!(header.category(), Some(4));
+    assert_eq!(header.total_length(), 0x40);
+    assert_eq!(header.streaming_interfaces(), None);
+
+    let AudioControlDescriptor::ClockSource(clock) = &descriptors[1] else { panic!() };
+    assert_eq!(clock.clock_id(), 0x29);
+    assert_eq!(clock.clock_type(), 3);
+
+    let AudioControlDescriptor::InputTerminal(it) = &descriptors[2] else { panic!() };
+    assert_eq!(it.clock_source_id(), Some(0x29));
+    assert_eq!(it.nr_channels(), 2);
+    assert_eq!(it.channel_config(), 3);
+
+    let AudioControlDescriptor::FeatureUnit(fu) = &descriptors[3] else { panic!() };
+    assert_eq!(fu.channel_controls(), [0x0f, 0, 0]);
+
+    let streaming = InterfaceDescriptor(&[
+        0x09, 0x04, 0x01, 0x01, 0x01, 0x01, 0x02, 0x20, 0x00,
+        0x10, 0x24, 0x01, 0x01, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x02, 0x03, 0x00,
+        0x00, 0x00, 0x00,
+        0x06, 0x24, 0x02, 0x01, 0x03, 0x18,
+    ]);
+    let descriptors: Vec<_> = audio_streaming_descriptors(&streaming).collect();
+
+    let AudioStreamingDescriptor::General(general) = &descriptors[0] else { panic!() };
+    assert_eq!(general.formats(), Some(1));
+    assert_eq!(general.nr_channels(), Some(2));
+    assert_eq!(general.format_tag(), None);
+
+    let AudioStreamingDescriptor::FormatType(format) = &descriptors[1] else { panic!() };
+    assert_eq!(format.subslot_size(), Some(3));
+    assert_eq!(format.bit_resolution(), Some(24));
+    assert_eq!(format.sample_rates(), None);
+}
@@ -0,0 +1,896 @@
+//! USB Video Class (UVC) descriptors, the class-specific descriptors of the
+//! VideoControl and VideoStreaming interfaces of a camera.
+//!
+//! The VideoControl interface describes the terminals and units of the
+//! camera, obtained from [`video_control_descriptors`]. Each VideoStreaming
+//! interface lists the formats it supports, each followed by the frame
+//! sizes and intervals of the format, obtained from
+//! [`video_streaming_descriptors`] or grouped by [`streaming_formats`].
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Debug;
+
+use super::{
+    bos::uuid_from_le_bytes, Descriptor, InterfaceDescriptor, DESCRIPTOR_TYPE_CS_INTERFACE,
+};
+
+/// `bInterfaceClass` of video interfaces.
+pub const CLASS_VIDEO: u8 = 0x0E;
+
+/// `bInterfaceSubClass` of the VideoControl interface.
+pub const SUBCLASS_VIDEO_CONTROL: u8 = 0x01;
+
+/// `bInterfaceSubClass` of VideoStreaming interfaces.
+pub const SUBCLASS_VIDEO_STREAMING: u8 = 0x02;
+
+const VC_HEADER: u8 = 0x01;
+const VC_INPUT_TERMINAL: u8 = 0x02;
+const VC_OUTPUT_TERMINAL: u8 = 0x03;
+const VC_SELECTOR_UNIT: u8 = 0x04;
+const VC_PROCESSING_UNIT: u8 = 0x05;
+const VC_EXTENSION_UNIT: u8 = 0x06;
+
+const VS_INPUT_HEADER: u8 = 0x01;
+const VS_FORMAT_UNCOMPRESSED: u8 = 0x04;
+const VS_FRAME_UNCOMPRESSED: u8 = 0x05;
+const VS_FORMAT_MJPEG: u8 = 0x06;
+const VS_FRAME_MJPEG: u8 = 0x07;
+const VS_FORMAT_FRAME_BASED: u8 = 0x10;
+const VS_FRAME_FRAME_BASED: u8 = 0x11;
+
+/// `wTerminalType` of a camera input terminal.
+pub const TERMINAL_CAMERA: u16 = 0x0201;
+
+/// `wTerminalType` of a streaming output terminal.
+pub const TERMINAL_STREAMING: u16 = 0x0101;
+
+fn class_specific<'a>(interface: &InterfaceDescriptor<'a>) -> impl Iterator<Item = (u8, &'a [u8])> {
+    interface
+        .class_descriptors()
+        .filter(|&(descriptor_type, _)| descriptor_type == DESCRIPTOR_TYPE_CS_INTERFACE)
+        .filter_map(|(_, buf)| Some((*buf.get(2)?, buf)))
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap())
+}
+
+/// Iterate over the class-specific descriptors of a VideoControl interface.
+pub fn video_control_descriptors<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = VideoControlDescriptor<'a>> {
+    class_specific(interface).map(|(subtype, buf)| VideoControlDescriptor::new(subtype, buf))
+}
+
+/// Iterate over the class-specific descriptors of a VideoStreaming
+/// interface.
+pub fn video_streaming_descriptors<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = VideoStreamingDescriptor<'a>> {
+    class_specific(interface).map(|(subtype, buf)| VideoStreamingDescriptor::new(subtype, buf))
+}
+
+/// Iterate over the formats of a VideoStreaming interface, each with the
+/// frame descriptors that follow it.
+pub fn streaming_formats<'a>(
+    interface: &InterfaceDescriptor<'a>,
+) -> impl Iterator<Item = (FormatDescriptor<'a>, Vec<FrameDescriptor<'a>>)> {
+    let mut formats: Vec<(FormatDescriptor<'a>, Vec<FrameDescriptor<'a>>)> = Vec::new();
+    for d in video_streaming_descriptors(interface) {
+        match d {
+            VideoStreamingDescriptor::Format(format) => formats.push((format, Vec::new())),
+            VideoStreamingDescriptor::Frame(frame) => {
+                if let Some((_, frames)) = formats.last_mut() {
+                    frames.push(frame);
+                }
+            }
+            _ => {}
+        }
+    }
+    formats.into_iter()
+}
+
+/// A class-specific descriptor of a VideoControl interface.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VideoControlDescriptor<'a> {
+    /// Class-specific VC interface header descriptor
+    Header(VcHeaderDescriptor<'a>),
+
+    /// Input terminal descriptor, such as the camera sensor
+    InputTerminal(InputTerminalDescriptor<'a>),
+
+    /// Output terminal descriptor, such as the USB streaming endpoint
+    OutputTerminal(OutputTerminalDescriptor<'a>),
+
+    /// Selector unit descriptor
+    SelectorUnit(SelectorUnitDescriptor<'a>),
+
+    /// Processing unit descriptor
+    ProcessingUnit(ProcessingUnitDescriptor<'a>),
+
+    /// Extension unit descriptor
+    ExtensionUnit(ExtensionUnitDescriptor<'a>),
+
+    /// A descriptor of another subtype, or too short for its subtype
+    Other(Descriptor<'a>),
+}
+
+impl<'a> VideoControlDescriptor<'a> {
+    fn new(subtype: u8, buf: &'a [u8]) -> VideoControlDescriptor<'a> {
+        let len = buf.len();
+        let valid = match subtype {
+            VC_HEADER => len >= 12 && len >= 12 + usize::from(buf[11]),
+            VC_INPUT_TERMINAL => len >= 8,
+            VC_OUTPUT_TERMINAL => len >= 9,
+            VC_SELECTOR_UNIT => len >= 6 && len >= 6 + usize::from(buf[4]),
+            VC_PROCESSING_UNIT => len >= 8 && len >= 8 + usize::from(buf[7]),
+            VC_EXTENSION_UNIT => {
+                len >= 24 && {
+                    let p = usize::from(buf[21]);
+                    len >= 24 + p && len >= 24 + p + usize::from(buf[22 + p])
+                }
+            }
+            _ => false,
+        };
+
+        if !valid {
+            return VideoControlDescriptor::Other(Descriptor(buf));
+        }
+
+        match subtype {
+            VC_HEADER => VideoControlDescriptor::Header(VcHeaderDescriptor(buf)),
+            VC_INPUT_TERMINAL => {
+                VideoControlDescriptor::InputTerminal(InputTerminalDescriptor(buf))
+            }
+            VC_OUTPUT_TERMINAL => {
+                VideoControlDescriptor::OutputTerminal(OutputTerminalDescriptor(buf))
+            }
+            VC_SELECTOR_UNIT => VideoControlDescriptor::SelectorUnit(SelectorUnitDescriptor(buf)),
+            VC_PROCESSING_UNIT => {
+                VideoControlDescriptor::ProcessingUnit(ProcessingUnitDescriptor(buf))
+            }
+            _ => VideoControlDescriptor::ExtensionUnit(ExtensionUnitDescriptor(buf)),
+        }
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            VideoControlDescriptor::Header(d) => d.0,
+            VideoControlDescriptor::InputTerminal(d) => d.0,
+            VideoControlDescriptor::OutputTerminal(d) => d.0,
+            VideoControlDescriptor::SelectorUnit(d) => d.0,
+            VideoControlDescriptor::ProcessingUnit(d) => d.0,
+            VideoControlDescriptor::ExtensionUnit(d) => d.0,
+            VideoControlDescriptor::Other(d) => d.0,
+        }
+    }
+
+    /// `bDescriptorSubtype` descriptor field.
+    #[doc(alias = "bDescriptorSubtype")]
+    pub fn descriptor_subtype(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+}
+
+/// Class-specific VideoControl interface header descriptor.
+#[derive(Clone)]
+pub struct VcHeaderDescriptor<'a>(&'a [u8]);
+
+impl<'a> VcHeaderDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `baInterfaceNr` descriptor field: Numbers of the VideoStreaming
+    /// interfaces of the function.
+    #[doc(alias = "baInterfaceNr")]
+    pub fn streaming_interfaces(&self) -> &'a [u8] {
+        &self.0[12..12 + usize::from(self.0[11])]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> VcHeaderDescriptor<'a> {
+        /// `bcdUVC` descriptor field: UVC specification version, in
+        /// binary-coded decimal.
+        #[doc(alias = "bcdUVC")]
+        pub fn uvc_version at 3 -> u16;
+
+        /// `wTotalLength` descriptor field: Length of the class-specific
+        /// VideoControl descriptors.
+        #[doc(alias = "wTotalLength")]
+        pub fn total_length at 5 -> u16;
+
+        /// `dwClockFrequency` descriptor field: Device clock frequency in Hz,
+        /// used for timestamps.
+        #[doc(alias = "dwClockFrequency")]
+        pub fn clock_frequency at 7 -> u32;
+    }
+}
+
+impl<'a> Debug for VcHeaderDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VcHeaderDescriptor")
+            .field("uvc_version", &format_args!("0x{:04X}", self.uvc_version()))
+            .field("clock_frequency", &self.clock_frequency())
+            .field("streaming_interfaces", &self.streaming_interfaces())
+            .finish()
+    }
+}
+
+/// Input terminal descriptor.
+#[derive(Clone)]
+pub struct InputTerminalDescriptor<'a>(&'a [u8]);
+
+impl<'a> InputTerminalDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `bmControls` descriptor field of a camera terminal: Bitmap of the
+    /// supported camera controls, such as exposure and focus.
+    #[doc(alias = "bmControls")]
+    pub fn camera_controls(&self) -> Option<&'a [u8]> {
+        if self.terminal_type() != TERMINAL_CAMERA {
+            return None;
+        }
+        let size = usize::from(*self.0.get(14)?);
+        self.0.get(15..15 + size)
+    }
+}
+
+descriptor_fields! {
+    impl<'a> InputTerminalDescriptor<'a> {
+        /// `bTerminalID` descriptor field: ID of the terminal, referenced by
+        /// units and requests.
+        #[doc(alias = "bTerminalID")]
+        pub fn terminal_id at 3 -> u8;
+
+        /// `wTerminalType` descriptor field: Type of the terminal, e.g.
+        /// [`TERMINAL_CAMERA`].
+        #[doc(alias = "wTerminalType")]
+        pub fn terminal_type at 4 -> u16;
+
+        /// `bAssocTerminal` descriptor field: ID of the associated output
+        /// terminal, or `0`.
+        #[doc(alias = "bAssocTerminal")]
+        pub fn assoc_terminal at 6 -> u8;
+
+        /// `iTerminal` descriptor field: Index of the string descriptor
+        /// describing the terminal, or `0`.
+        #[doc(alias = "iTerminal")]
+        pub fn string_index at 7 -> u8;
+    }
+}
+
+impl<'a> Debug for InputTerminalDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InputTerminalDescriptor")
+            .field("terminal_id", &self.terminal_id())
+            .field(
+                "terminal_type",
+                &format_args!("0x{:04X}", self.terminal_type()),
+            )
+            .field("assoc_terminal", &self.assoc_terminal())
+            .field("camera_controls", &self.camera_controls())
+            .finish()
+    }
+}
+
+/// Output terminal descriptor.
+#[derive(Clone)]
+pub struct OutputTerminalDescriptor<'a>(&'a [u8]);
+
+impl<'a> OutputTerminalDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> OutputTerminalDescriptor<'a> {
+        /// `bTerminalID` descriptor field: ID of the terminal.
+        #[doc(alias = "bTerminalID")]
+        pub fn terminal_id at 3 -> u8;
+
+        /// `wTerminalType` descriptor field: Type of the terminal, e.g.
+        /// [`TERMINAL_STREAMING`].
+        #[doc(alias = "wTerminalType")]
+        pub fn terminal_type at 4 -> u16;
+
+        /// `bAssocTerminal` descriptor field: ID of the associated input
+        /// terminal, or `0`.
+        #[doc(alias = "bAssocTerminal")]
+        pub fn assoc_terminal at 6 -> u8;
+
+        /// `bSourceID` descriptor field: ID of the unit or terminal
+        /// connected to this terminal.
+        #[doc(alias = "bSourceID")]
+        pub fn source_id at 7 -> u8;
+
+        /// `iTerminal` descriptor field: Index of the string descriptor
+        /// describing the terminal, or `0`.
+        #[doc(alias = "iTerminal")]
+        pub fn string_index at 8 -> u8;
+    }
+}
+
+impl<'a> Debug for OutputTerminalDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OutputTerminalDescriptor")
+            .field("terminal_id", &self.terminal_id())
+            .field(
+                "terminal_type",
+                &format_args!("0x{:04X}", self.terminal_type()),
+            )
+            .field("assoc_terminal", &self.assoc_terminal())
+            .field("source_id", &self.source_id())
+            .finish()
+    }
+}
+
+/// Selector unit descriptor, selecting one of several inputs.
+#[derive(Clone)]
+pub struct SelectorUnitDescriptor<'a>(&'a [u8]);
+
+impl<'a> SelectorUnitDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `baSourceID` descriptor field: IDs of the units or terminals
+    /// connected to the inputs.
+    #[doc(alias = "baSourceID")]
+    pub fn source_ids(&self) -> &'a [u8] {
+        &self.0[5..5 + usize::from(self.0[4])]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> SelectorUnitDescriptor<'a> {
+        /// `bUnitID` descriptor field: ID of the unit.
+        #[doc(alias = "bUnitID")]
+        pub fn unit_id at 3 -> u8;
+    }
+}
+
+impl<'a> Debug for SelectorUnitDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SelectorUnitDescriptor")
+            .field("unit_id", &self.unit_id())
+            .field("source_ids", &self.source_ids())
+            .finish()
+    }
+}
+
+/// Processing unit descriptor, describing image controls such as
+/// brightness and white balance.
+#[derive(Clone)]
+pub struct ProcessingUnitDescriptor<'a>(&'a [u8]);
+
+impl<'a> ProcessingUnitDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `bmControls` descriptor field: Bitmap of the supported controls.
+    #[doc(alias = "bmControls")]
+    pub fn controls(&self) -> &'a [u8] {
+        &self.0[8..8 + usize::from(self.0[7])]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> ProcessingUnitDescriptor<'a> {
+        /// `bUnitID` descriptor field: ID of the unit.
+        #[doc(alias = "bUnitID")]
+        pub fn unit_id at 3 -> u8;
+
+        /// `bSourceID` descriptor field: ID of the unit or terminal
+        /// connected to the input.
+        #[doc(alias = "bSourceID")]
+        pub fn source_id at 4 -> u8;
+
+        /// `wMaxMultiplier` descriptor field: Maximum digital magnification,
+        /// multiplied by 100.
+        #[doc(alias = "wMaxMultiplier")]
+        pub fn max_multiplier at 5 -> u16;
+    }
+}
+
+impl<'a> Debug for ProcessingUnitDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ProcessingUnitDescriptor")
+            .field("unit_id", &self.unit_id())
+            .field("source_id", &self.source_id())
+            .field("max_multiplier", &self.max_multiplier())
+            .field("controls", &self.controls())
+            .finish()
+    }
+}
+
+/// Extension unit descriptor, exposing vendor-specific controls identified
+/// by a GUID.
+#[derive(Clone)]
+pub struct ExtensionUnitDescriptor<'a>(&'a [u8]);
+
+impl<'a> ExtensionUnitDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `guidExtensionCode` descriptor field, as a number written like the
+    /// GUID.
+    #[doc(alias = "guidExtensionCode")]
+    pub fn extension_code(&self) -> u128 {
+        uuid_from_le_bytes(&self.0[4..20])
+    }
+
+    /// `baSourceID` descriptor field: IDs of the units or terminals
+    /// connected to the inputs.
+    #[doc(alias = "baSourceID")]
+    pub fn source_ids(&self) -> &'a [u8] {
+        &self.0[22..22 + usize::from(self.0[21])]
+    }
+
+    /// `bmControls` descriptor field: Bitmap of the supported controls.
+    #[doc(alias = "bmControls")]
+    pub fn controls(&self) -> &'a [u8] {
+        let pos = 22 + usize::from(self.0[21]);
+        &self.0[pos + 1..pos + 1 + usize::from(self.0[pos])]
+    }
+}
+
+descriptor_fields! {
+    impl<'a> ExtensionUnitDescriptor<'a> {
+        /// `bUnitID` descriptor field: ID of the unit.
+        #[doc(alias = "bUnitID")]
+        pub fn unit_id at 3 -> u8;
+
+        /// `bNumControls` descriptor field: Number of controls.
+        #[doc(alias = "bNumControls")]
+        pub fn num_controls at 20 -> u8;
+    }
+}
+
+impl<'a> Debug for ExtensionUnitDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtensionUnitDescriptor")
+            .field("unit_id", &self.unit_id())
+            .field(
+                "extension_code",
+                &format_args!("{:032x}", self.extension_code()),
+            )
+            .field("num_controls", &self.num_controls())
+            .field("source_ids", &self.source_ids())
+            .field("controls", &self.controls())
+            .finish()
+    }
+}
+
+/// A class-specific descriptor of a VideoStreaming interface.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VideoStreamingDescriptor<'a> {
+    /// Class-specific VS interface input header descriptor
+    InputHeader(VsInputHeaderDescriptor<'a>),
+
+    /// Uncompressed, MJPEG or frame-based format descriptor
+    Format(FormatDescriptor<'a>),
+
+    /// Uncompressed, MJPEG or frame-based frame descriptor
+    Frame(FrameDescriptor<'a>),
+
+    /// A descriptor of another subtype, or too short for its subtype
+    Other(Descriptor<'a>),
+}
+
+impl<'a> VideoStreamingDescriptor<'a> {
+    fn new(subtype: u8, buf: &'a [u8]) -> VideoStreamingDescriptor<'a> {
+        let len = buf.len();
+        match subtype {
+            VS_INPUT_HEADER if len >= 13 => {
+                VideoStreamingDescriptor::InputHeader(VsInputHeaderDescriptor(buf))
+            }
+            VS_FORMAT_UNCOMPRESSED | VS_FORMAT_FRAME_BASED if len >= 27 => {
+                VideoStreamingDescriptor::Format(FormatDescriptor(buf))
+            }
+            VS_FORMAT_MJPEG if len >= 11 => VideoStreamingDescriptor::Format(FormatDescriptor(buf)),
+            VS_FRAME_UNCOMPRESSED | VS_FRAME_MJPEG | VS_FRAME_FRAME_BASED
+                if FrameDescriptor::valid(subtype, buf) =>
+            {
+                VideoStreamingDescriptor::Frame(FrameDescriptor(buf))
+            }
+            _ => VideoStreamingDescriptor::Other(Descriptor(buf)),
+        }
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        match self {
+            VideoStreamingDescriptor::InputHeader(d) => d.0,
+            VideoStreamingDescriptor::Format(d) => d.0,
+            VideoStreamingDescriptor::Frame(d) => d.0,
+            VideoStreamingDescriptor::Other(d) => d.0,
+        }
+    }
+
+    /// `bDescriptorSubtype` descriptor field.
+    #[doc(alias = "bDescriptorSubtype")]
+    pub fn descriptor_subtype(&self) -> u8 {
+        self.as_bytes()[2]
+    }
+}
+
+/// Class-specific VideoStreaming interface input header descriptor.
+#[derive(Clone)]
+pub struct VsInputHeaderDescriptor<'a>(&'a [u8]);
+
+impl<'a> VsInputHeaderDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+descriptor_fields! {
+    impl<'a> VsInputHeaderDescriptor<'a> {
+        /// `bNumFormats` descriptor field: Number of format descriptors.
+        #[doc(alias = "bNumFormats")]
+        pub fn num_formats at 3 -> u8;
+
+        /// `wTotalLength` descriptor field: Length of the class-specific
+        /// VideoStreaming descriptors.
+        #[doc(alias = "wTotalLength")]
+        pub fn total_length at 4 -> u16;
+
+        /// `bEndpointAddress` descriptor field: Address of the endpoint
+        /// carrying the video data.
+        #[doc(alias = "bEndpointAddress")]
+        pub fn endpoint_address at 6 -> u8;
+
+        /// `bmInfo` descriptor field: Capabilities of the interface.
+        #[doc(alias = "bmInfo")]
+        pub fn info at 7 -> u8;
+
+        /// `bTerminalLink` descriptor field: ID of the output terminal the
+        /// endpoint is connected to.
+        #[doc(alias = "bTerminalLink")]
+        pub fn terminal_link at 8 -> u8;
+
+        /// `bStillCaptureMethod` descriptor field: Still image capture
+        /// method, or `0` if not supported.
+        #[doc(alias = "bStillCaptureMethod")]
+        pub fn still_capture_method at 9 -> u8;
+    }
+}
+
+impl<'a> Debug for VsInputHeaderDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VsInputHeaderDescriptor")
+            .field("num_formats", &self.num_formats())
+            .field(
+                "endpoint_address",
+                &format_args!("0x{:02X}", self.endpoint_address()),
+            )
+            .field("terminal_link", &self.terminal_link())
+            .finish()
+    }
+}
+
+/// Kind of a video format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FormatKind {
+    /// Uncompressed video, such as YUY2 or NV12
+    Uncompressed,
+
+    /// Motion JPEG
+    Mjpeg,
+
+    /// Frame-based video, such as H.264
+    FrameBased,
+}
+
+/// Format descriptor of an uncompressed, MJPEG or frame-based format.
+#[derive(Clone)]
+pub struct FormatDescriptor<'a>(&'a [u8]);
+
+impl<'a> FormatDescriptor<'a> {
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Kind of the format.
+    pub fn kind(&self) -> FormatKind {
+        match self.0[2] {
+            VS_FORMAT_UNCOMPRESSED => FormatKind::Uncompressed,
+            VS_FORMAT_MJPEG => FormatKind::Mjpeg,
+            _ => FormatKind::FrameBased,
+        }
+    }
+
+    /// `guidFormat` descriptor field, as a number written like the GUID, or
+    /// `None` for MJPEG.
+    #[doc(alias = "guidFormat")]
+    pub fn guid(&self) -> Option<u128> {
+        (self.kind() != FormatKind::Mjpeg).then(|| uuid_from_le_bytes(&self.0[5..21]))
+    }
+
+    /// The FourCC code at the start of the format GUID, e.g. `YUY2` or
+    /// `H264`, or `None` for MJPEG.
+    pub fn fourcc(&self) -> Option<[u8; 4]> {
+        (self.kind() != FormatKind::Mjpeg).then(|| self.0[5..9].try_into().unwrap())
+    }
+
+    /// `bBitsPerPixel` descriptor field: Bits per pixel of the decoded
+    /// image, or `None` for MJPEG.
+    #[doc(alias = "bBitsPerPixel")]
+    pub fn bits_per_pixel(&self) -> Option<u8> {
+        (self.kind() != FormatKind::Mjpeg).then(|| self.0[21])
+    }
+
+    /// `bDefaultFrameIndex` descriptor field: Index of the default frame
+    /// descriptor.
+    #[doc(alias = "bDefaultFrameIndex")]
+    pub fn default_frame_index(&self) -> u8 {
+        match self.kind() {
+            FormatKind::Mjpeg => self.0[6],
+            _ => self.0[22],
+        }
+    }
+}
+
+descriptor_fields! {
+    impl<'a> FormatDescriptor<'a> {
+        /// `bFormatIndex` descriptor field: Index of the format, used in
+        /// stream negotiation.
+        #[doc(alias = "bFormatIndex")]
+        pub fn format_index at 3 -> u8;
+
+        /// `bNumFrameDescriptors` descriptor field: Number of frame
+        /// descriptors following this descriptor.
+        #[doc(alias = "bNumFrameDescriptors")]
+        pub fn num_frame_descriptors at 4 -> u8;
+    }
+}
+
+impl<'a> Debug for FormatDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FormatDescriptor")
+            .field("kind", &self.kind())
+            .field("format_index", &self.format_index())
+            .field("num_frame_descriptors", &self.num_frame_descriptors())
+            .field(
+                "fourcc",
+                &self
+                    .fourcc()
+                    .map(|c| String::from_utf8_lossy(&c).into_owned()),
+            )
+            .field("bits_per_pixel", &self.bits_per_pixel())
+            .field("default_frame_index", &self.default_frame_index())
+            .finish()
+    }
+}
+
+/// Frame intervals supported by a frame descriptor, in 100 ns units.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameIntervals {
+    /// Any interval from `min` to `max` in increments of `step`.
+    Continuous {
+        /// Shortest frame interval.
+        min: u32,
+        /// Longest frame interval.
+        max: u32,
+        /// Frame interval granularity.
+        step: u32,
+    },
+
+    /// A list of intervals.
+    Discrete(Vec<u32>),
+}
+
+/// Frame descriptor of an uncompressed, MJPEG or frame-based format,
+/// describing a frame size.
+#[derive(Clone)]
+pub struct FrameDescriptor<'a>(&'a [u8]);
+
+impl<'a> FrameDescriptor<'a> {
+    fn valid(subtype: u8, buf: &[u8]) -> bool {
+        let interval_type_pos = if subtype == VS_FRAME_FRAME_BASED {
+            21
+        } else {
+            25
+        };
+        let Some(&interval_type) = buf.get(interval_type_pos) else {
+            return false;
+        };
+        let intervals_len = match interval_type {
+            0 => 12,
+            n => 4 * usize::from(n),
+        };
+        buf.len() >= 26 + intervals_len
+    }
+
+    fn is_frame_based(&self) -> bool {
+        self.0[2] == VS_FRAME_FRAME_BASED
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `dwMaxVideoFrameBufferSize` descriptor field: Maximum size of a
+    /// frame, or `None` for frame-based formats.
+    #[doc(alias = "dwMaxVideoFrameBufferSize")]
+    pub fn max_frame_buffer_size(&self) -> Option<u32> {
+        (!self.is_frame_based()).then(|| u32_at(self.0, 17))
+    }
+
+    /// `dwDefaultFrameInterval` descriptor field: Default frame interval,
+    /// in 100 ns units.
+    #[doc(alias = "dwDefaultFrameInterval")]
+    pub fn default_frame_interval(&self) -> u32 {
+        u32_at(self.0, if self.is_frame_based() { 17 } else { 21 })
+    }
+
+    /// `dwBytesPerLine` descriptor field: Stride of a line, or `None`
+    /// except for frame-based formats.
+    #[doc(alias = "dwBytesPerLine")]
+    pub fn bytes_per_line(&self) -> Option<u32> {
+        self.is_frame_based().then(|| u32_at(self.0, 22))
+    }
+
+    /// `bFrameIntervalType` and the following fields: Supported frame
+    /// intervals.
+    #[doc(alias = "bFrameIntervalType")]
+    pub fn frame_intervals(&self) -> FrameIntervals {
+        let interval_type = self.0[if self.is_frame_based() { 21 } else { 25 }];
+        match interval_type {
+            0 => FrameIntervals::Continuous {
+                min: u32_at(self.0, 26),
+                max: u32_at(self.0, 30),
+                step: u32_at(self.0, 34),
+            },
+            n => FrameIntervals::Discrete(
+                (0..usize::from(n))
+                    .map(|i| u32_at(self.0, 26 + 4 * i))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+descriptor_fields! {
+    impl<'a> FrameDescriptor<'a> {
+        /// `bFrameIndex` descriptor field: Index of the frame, used in
+        /// stream negotiation.
+        #[doc(alias = "bFrameIndex")]
+        pub fn frame_index at 3 -> u8;
+
+        /// `bmCapabilities` descriptor field.
+        #[doc(alias = "bmCapabilities")]
+        pub fn capabilities at 4 -> u8;
+
+        /// `wWidth` descriptor field: Width of the frame in pixels.
+        #[doc(alias = "wWidth")]
+        pub fn width at 5 -> u16;
+
+        /// `wHeight` descriptor field: Height of the frame in pixels.
+        #[doc(alias = "wHeight")]
+        pub fn height at 7 -> u16;
+
+        /// `dwMinBitRate` descriptor field: Minimum bit rate in bits per
+        /// second.
+        #[doc(alias = "dwMinBitRate")]
+        pub fn min_bit_rate at 9 -> u32;
+
+        /// `dwMaxBitRate` descriptor field: Maximum bit rate in bits per
+        /// second.
+        #[doc(alias = "dwMaxBitRate")]
+        pub fn max_bit_rate at 13 -> u32;
+    }
+}
+
+impl<'a> Debug for FrameDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FrameDescriptor")
+            .field("frame_index", &self.frame_index())
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("default_frame_interval", &self.default_frame_interval())
+            .field("frame_intervals", &self.frame_intervals())
+            .finish()
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_video_control() {
+    let intf = InterfaceDescriptor(&[
+        0x09, 0x04, 0x00, 0x00, 0x01, 0x0e, 0x01, 0x00, 0x00,
+        0x0d, 0x24, 0x01, 0x00, 0x01, 0x33, 0x00, 0x80, 0x8d, 0x5b, 0x00, 0x01, 0x01,
+        0x12, 0x24, 0x02, 0x01, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x03, 0x0e, 0x00, 0x00,
+        0x0b, 0x24, 0x05, 0x02, 0x01, 0x00, 0x40, 0x02, 0x7f, 0x15, 0x00,
+        0x09, 0x24, 0x03, 0x03, 0x01, 0x01, 0x00, 0x02, 0x00,
+    ]);
+
+    let descriptors: Vec<_> = video_control_descriptors(&intf).collect();
+    assert_eq!(descriptors.len(), 4);
+
+    let VideoControlDescriptor::Header(header) = &descriptors[0] else { panic!() };
+    assert_eq!(header.uvc_version(), 0x0100);
+    assert_eq!(header.clock_frequency(), 6_000_000);
+    assert_eq!(header.streaming_interfaces(), [1]);
+
+    let VideoControlDescriptor::InputTerminal(camera) = &descriptors[1] else { panic!() };
+    assert_eq!(camera.terminal_type(), TERMINAL_CAMERA);
+    assert_eq!(camera.camera_controls(), Some(&[0x0e, 0x00, 0x00][..]));
+
+    let VideoControlDescriptor::ProcessingUnit(pu) = &descriptors[2] else { panic!() };
+    assert_eq!(pu.source_id(), 1);
+    assert_eq!(pu.max_multiplier(), 0x4000);
+    assert_eq!(pu.controls(), [0x7f, 0x15]);
+
+    let VideoControlDescriptor::OutputTerminal(ot) = &descriptors[3] else { panic!() };
+    assert_eq!(ot.terminal_type(), TERMINAL_STREAMING);
+    assert_eq!(ot.source_id(), 2);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_video_streaming() {
+    let intf = InterfaceDescriptor(&[
+        0x09, 0x04, 0x01, 0x00, 0x00, 0x0e, 0x02, 0x00, 0x00,
+        0x0e, 0x24, 0x01, 0x02, 0x77, 0x00, 0x81, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00,
+        // YUY2
+        0x1b, 0x24, 0x04, 0x01, 0x01, 0x59, 0x55, 0x59, 0x32, 0x00, 0x00, 0x10, 0x00,
+        0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x1e, 0x24, 0x05, 0x01, 0x00, 0x80, 0x02, 0xe0, 0x01, 0x00, 0x00, 0x77, 0x01,
+        0x00, 0x00, 0xca, 0x08, 0x00, 0x60, 0x09, 0x00, 0x15, 0x16, 0x05, 0x00, 0x01,
+        0x15, 0x16, 0x05, 0x00,
+        // MJPEG
+        0x0b, 0x24, 0x06, 0x02, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x26, 0x24, 0x07, 0x01, 0x00, 0x00, 0x05, 0xd0, 0x02, 0x00, 0x00, 0x77, 0x01,
+        0x00, 0x00, 0xca, 0x08, 0x00, 0x20, 0x1c, 0x00, 0x15, 0x16, 0x05, 0x00, 0x00,
+        0x15, 0x16, 0x05, 0x00, 0x2a, 0x2c, 0x0a, 0x00, 0x0a, 0x8b, 0x02, 0x00,
+    ]);
+
+    let header = video_streaming_descriptors(&intf).next().unwrap();
+    let VideoStreamingDescriptor::InputHeader(header) = header else { panic!() };
+    assert_eq!(header.num_formats(), 2);
+    assert_eq!(header.endpoint_address(), 0x81);
+    assert_eq!(header.terminal_link(), 3);
+
+    let formats: Vec<_> = streaming_formats(&intf).collect();
+    assert_eq!(formats.len(), 2);
+
+    let (yuy2, frames) = &formats[0];
+    assert_eq!(yuy2.kind(), FormatKind::Uncompressed);
+    assert_eq!(yuy2.fourcc(), Some(*b"YUY2"));
+    assert_eq!(yuy2.guid(), Some(0x32595559_0000_0010_8000_00aa00389b71));
+    assert_eq!(yuy2.bits_per_pixel(), Some(16));
+    assert_eq!((frames[0].width(), frames[0].height()), (640, 480));
+    assert_eq!(frames[0].max_frame_buffer_size(), Some(0x96000));
+    assert_eq!(frames[0].frame_intervals(), FrameIntervals::Discrete(vec![333333]));
+
+    let (mjpeg, frames) = &formats[1];
+    assert_eq!(mjpeg.kind(), FormatKind::Mjpeg);
+    assert_eq!(mjpeg.fourcc(), None);
+    assert_eq!((frames[0].width(), frames[0].height()), (1280, 720));
+    assert_eq!(
+        frames[0].frame_intervals(),
+        FrameIntervals::Continuous { min: 333333, max: 666666, step: 166666 }
+    );
+}
@@ -0,0 +1,176 @@
+//! WebUSB descriptors, which let a device advertise the web page that can
+//! drive it.
+//!
+//! The [`WebUsbCapability`] is found through a [`PlatformCapability`] in the
+//! BOS descriptor, and gives the vendor request code to read the
+//! [`UrlDescriptor`]s. [`Device::webusb_landing_page`] reads the landing page
+//! URL.
+//!
+//! [`Device::webusb_landing_page`]: crate::Device::webusb_landing_page
+
+use alloc::{format, string::String};
+use core::fmt::Debug;
+
+use log::warn;
+
+use super::bos::{BosDescriptor, PlatformCapability};
+
+/// `PlatformCapabilityUUID` of the WebUSB platform capability,
+/// `{3408B638-09A9-47A0-8BFD-A0768815B665}`.
+pub const WEBUSB_PLATFORM_UUID: u128 = 0x3408b638_09a9_47a0_8bfd_a0768815b665;
+
+/// `wIndex` of the vendor request reading a URL descriptor.
+pub const WEBUSB_GET_URL: u16 = 0x02;
+
+/// `bDescriptorType` of a URL descriptor.
+pub const DESCRIPTOR_TYPE_WEBUSB_URL: u8 = 0x03;
+
+/// WebUSB platform capability.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WebUsbCapability {
+    version: u16,
+    vendor_code: u8,
+    landing_page: u8,
+}
+
+impl WebUsbCapability {
+    /// Parse a WebUSB platform capability, or return `None` for another
+    /// capability.
+    pub fn from_capability(capability: &PlatformCapability) -> Option<WebUsbCapability> {
+        if capability.uuid() != WEBUSB_PLATFORM_UUID {
+            return None;
+        }
+
+        let data = capability.data();
+        if data.len() < 4 {
+            warn!("WebUSB platform capability is too short");
+            return None;
+        }
+
+        Some(WebUsbCapability {
+            version: u16::from_le_bytes([data[0], data[1]]),
+            vendor_code: data[2],
+            landing_page: data[3],
+        })
+    }
+
+    /// Find the WebUSB platform capability of a BOS descriptor.
+    pub fn from_bos(bos: &BosDescriptor) -> Option<WebUsbCapability> {
+        bos.platform_capabilities()
+            .find_map(|c| WebUsbCapability::from_capability(&c))
+    }
+
+    /// `bcdVersion` field: WebUSB version supported by the device, in
+    /// binary-coded decimal.
+    #[doc(alias = "bcdVersion")]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// `bVendorCode` field: `bRequest` of the vendor requests for WebUSB.
+    #[doc(alias = "bVendorCode")]
+    pub fn vendor_code(&self) -> u8 {
+        self.vendor_code
+    }
+
+    /// `iLandingPage` field: Index of the URL descriptor of the landing page,
+    /// or `None` if the device doesn't have one.
+    #[doc(alias = "iLandingPage")]
+    pub fn landing_page(&self) -> Option<u8> {
+        (self.landing_page != 0).then_some(self.landing_page)
+    }
+}
+
+/// WebUSB URL descriptor.
+#[derive(Clone)]
+pub struct UrlDescriptor<'a>(&'a [u8]);
+
+impl<'a> UrlDescriptor<'a> {
+    /// Create a `UrlDescriptor` from a buffer containing a URL descriptor.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `bLength`.
+    pub fn new(buf: &[u8]) -> Option<UrlDescriptor<'_>> {
+        if buf.len() < 3 || buf[1] != DESCRIPTOR_TYPE_WEBUSB_URL {
+            warn!("invalid WebUSB URL descriptor");
+            return None;
+        }
+
+        let len = buf[0] as usize;
+        if len < 3 || len > buf.len() {
+            warn!(
+                "invalid WebUSB URL descriptor bLength of {len} (buffer size is {bufsize})",
+                bufsize = buf.len()
+            );
+            return None;
+        }
+
+        Some(UrlDescriptor(&buf[..len]))
+    }
+
+    /// The bytes of the descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// `bScheme` field: `0` for `http://`, `1` for `https://`, and `255` when
+    /// the scheme is included in the URL.
+    #[doc(alias = "bScheme")]
+    pub fn scheme(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// `URL` field: The URL without its scheme prefix, unless
+    /// [`scheme`][Self::scheme] is `255`.
+    #[doc(alias = "URL")]
+    pub fn url_field(&self) -> &'a [u8] {
+        &self.0[3..]
+    }
+
+    /// The complete URL, with the prefix given by the scheme.
+    ///
+    /// Returns `None` for an unknown scheme or a URL that is not valid UTF-8.
+    pub fn url(&self) -> Option<String> {
+        let prefix = match self.scheme() {
+            0 => "http://",
+            1 => "https://",
+            255 => "",
+            _ => return None,
+        };
+        let url = core::str::from_utf8(self.url_field()).ok()?;
+        Some(format!("{prefix}{url}"))
+    }
+}
+
+impl<'a> Debug for UrlDescriptor<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UrlDescriptor")
+            .field("scheme", &self.scheme())
+            .field("url", &String::from_utf8_lossy(self.url_field()))
+            .finish()
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_webusb() {
+    let bos = BosDescriptor::new(&[
+        0x05, 0x0f, 0x1d, 0x00, 0x01,
+        0x18, 0x10, 0x05, 0x00, 0x38, 0xb6, 0x08, 0x34, 0xa9, 0x09, 0xa0, 0x47,
+        0x8b, 0xfd, 0xa0, 0x76, 0x88, 0x15, 0xb6, 0x65, 0x00, 0x01, 0x01, 0x01,
+    ]).unwrap();
+
+    let webusb = WebUsbCapability::from_bos(&bos).unwrap();
+    assert_eq!(webusb.version(), 0x0100);
+    assert_eq!(webusb.vendor_code(), 0x01);
+    assert_eq!(webusb.landing_page(), Some(1));
+
+    let url = UrlDescriptor::new(&[
+        0x0e, 0x03, 0x01, b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'c', b'o', b'm',
+    ]).unwrap();
+    assert_eq!(url.scheme(), 1);
+    assert_eq!(url.url().unwrap(), "https://example.com");
+
+    assert!(UrlDescriptor::new(&[0x0e, 0x03, 0x01]).is_none());
+    assert!(UrlDescriptor::new(&[0x03, 0x02, 0x01]).is_none());
+}
@@ -1,17 +1,121 @@
 use crate::{
     descriptors::{
-        decode_string_descriptor, validate_string_descriptor, ActiveConfigurationError,
-        ConfigurationDescriptor, DeviceDescriptor, InterfaceDescriptor, DESCRIPTOR_TYPE_STRING,
+        bos::{AlternateMode, BosDescriptor, DESCRIPTOR_TYPE_BOS},
+        decode_string_descriptor,
+        language_id::US_ENGLISH,
+        msos::{DescriptorSet, DescriptorSetInfo, MS_OS_20_DESCRIPTOR_INDEX},
+        validate_string_descriptor,
+        webusb::{UrlDescriptor, WebUsbCapability, WEBUSB_GET_URL},
+        ActiveConfigurationError, ConfigurationDescriptor, DeviceDescriptor,
+        DeviceQualifierDescriptor, EndpointDescriptor, InterfaceDescriptor,
+        DESCRIPTOR_TYPE_DEVICE_QUALIFIER, DESCRIPTOR_TYPE_OTHER_SPEED_CONFIGURATION,
+        DESCRIPTOR_TYPE_STRING,
     },
+    maybe_future::blocking::Blocking,
     platform,
     transfer::{
-        Control, ControlIn, ControlOut, Queue, RequestBuffer, RequestIsochronousBuffer,
-        TransferError, TransferFuture, TransferType,
+        get_device_status, get_endpoint_status, set_interface_control, synch_frame, wait_transfer,
+        BulkReader, BulkWriter, ChunkedIn, ChunkedOut, Completion, Control, ControlIn,
+        ControlInBuffer, ControlOut, ControlOutSetup, DeviceStatus, Endpoint, EndpointDirection,
+        EndpointStatus, EndpointType, Feature, OutBuffer, Queue, QueueStream, RequestBuffer,
+        ResponseBuffer, TransferError, TransferFuture, TransferObserver, TransferType,
     },
-    DeviceInfo, Error, MaybeFuture, Speed,
+    DeviceInfo, DeviceOpenOptions, Disconnected, Error, MaybeFuture, Recovered, RecoveryPolicy,
+    RecoveryStep, Speed,
 };
-use log::error;
-use std::{io::ErrorKind, num::NonZeroU8, sync::Arc, time::Duration};
+use log::{debug, error, warn};
+use once_cell::sync::OnceCell;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io::{ErrorKind, IoSlice},
+    num::NonZeroU8,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A kernel driver about to be detached, passed to the callback set with
+/// [`Device::set_detach_policy`].
+pub struct DetachRequest<'a> {
+    pub(crate) interface_number: u8,
+    pub(crate) driver: &'a str,
+    pub(crate) device_descriptor: DeviceDescriptor,
+    pub(crate) interface: Option<InterfaceDescriptor<'a>>,
+}
+
+impl<'a> DetachRequest<'a> {
+    /// Get the number of the interface the driver is bound to.
+    pub fn interface_number(&self) -> u8 {
+        self.interface_number
+    }
+
+    /// Get the name of the kernel driver, e.g. `usbhid`.
+    pub fn driver(&self) -> &str {
+        self.driver
+    }
+
+    /// Get the descriptor of the device.
+    pub fn device_descriptor(&self) -> &DeviceDescriptor {
+        &self.device_descriptor
+    }
+
+    /// Get the descriptor of the first alternate setting of the interface in
+    /// the active configuration, if it could be found.
+    pub fn interface_descriptor(&self) -> Option<&InterfaceDescriptor<'a>> {
+        self.interface.as_ref()
+    }
+}
+
+impl Debug for DetachRequest<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DetachRequest")
+            .field("interface_number", &self.interface_number)
+            .field("driver", &self.driver)
+            .field("vendor_id", &self.device_descriptor.vendor_id())
+            .field("product_id", &self.device_descriptor.product_id())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runtime power management state of a device, returned by
+/// [`Device::power_state`].
+///
+/// This is the kernel's `power/runtime_status` sysfs attribute.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PowerState {
+    /// The device is active.
+    Active,
+
+    /// The device is being suspended.
+    Suspending,
+
+    /// The device is suspended.
+    Suspended,
+
+    /// The device is being resumed.
+    Resuming,
+
+    /// Runtime power management is disabled for the device.
+    Unsupported,
+
+    /// A runtime power management operation failed.
+    Error,
+}
+
+/// Which USB 3.x link power management states a device may initiate.
+///
+/// Used with [`Device::link_power_management`] and
+/// [`Device::set_link_power_management`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LinkPowerManagement {
+    /// Whether the device may initiate transitions to U1.
+    pub u1: bool,
+
+    /// Whether the device may initiate transitions to U2.
+    pub u2: bool,
+}
 
 /// An opened USB device.
 ///
@@ -36,23 +140,294 @@ use std::{io::ErrorKind, num::NonZeroU8, sync::Arc, time::Duration};
 #[derive(Clone)]
 pub struct Device {
     backend: Arc<crate::platform::Device>,
+
+    /// Shared by clones so that a policy set on one applies to all.
+    detach_policy: Arc<Mutex<Option<DetachPolicy>>>,
+
+    /// The `DeviceInfo` the device was opened from, used to find it again
+    /// after a reset.
+    info: Option<Arc<DeviceInfo>>,
+
+    /// String descriptors read by `product_string` and friends.
+    strings: Arc<Mutex<StringCache>>,
+
+    /// BOS descriptor read by `bos`.
+    bos: Arc<OnceCell<Vec<u8>>>,
 }
 
+#[derive(Default)]
+struct StringCache {
+    language_id: Option<u16>,
+    strings: HashMap<u8, String>,
+
+    /// When reading the language list failed.
+    languages_failed: Option<Instant>,
+
+    /// When reading each string that isn't cached failed.
+    strings_failed: HashMap<u8, Instant>,
+}
+
+/// Timeout for the requests of the cached string accessors.
+const STRING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long the cached string accessors wait before retrying a request that
+/// failed, so that a device that doesn't answer doesn't cost a
+/// `STRING_TIMEOUT` on every call.
+const STRING_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+fn failed_recently(failed: Option<&Instant>) -> bool {
+    failed.is_some_and(|t| t.elapsed() < STRING_RETRY_INTERVAL)
+}
+
+/// Callback deciding whether a kernel driver may be detached.
+pub(crate) type DetachPolicy = Arc<dyn Fn(&DetachRequest<'_>) -> bool + Send + Sync>;
+
 impl Device {
-    pub(crate) fn wrap(backend: Arc<platform::Device>) -> Device {
-        Device { backend }
+    pub(crate) fn wrap(backend: Arc<platform::Device>, info: Option<Arc<DeviceInfo>>) -> Device {
+        Device {
+            backend,
+            detach_policy: Arc::new(Mutex::new(None)),
+            info,
+            strings: Arc::new(Mutex::new(StringCache::default())),
+            bos: Arc::new(OnceCell::new()),
+        }
+    }
+
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+    fn detach_policy(&self) -> Option<DetachPolicy> {
+        self.detach_policy.lock().unwrap().clone()
+    }
+
+    /// Set a callback that decides whether a kernel driver may be detached
+    /// from an interface of this device.
+    ///
+    /// Before [`detach_and_claim_interface`][Self::detach_and_claim_interface]
+    /// or [`detach_kernel_driver`][Self::detach_kernel_driver] detach a
+    /// kernel driver, `policy` is called with a [`DetachRequest`] identifying
+    /// the driver, the interface, and the device. If it returns `false`, the
+    /// driver is left attached and the operation fails with an error of kind
+    /// [`PermissionDenied`][std::io::ErrorKind::PermissionDenied].
+    ///
+    /// Tools that select devices generically, such as by a VID/PID list,
+    /// can use this to avoid detaching e.g. `usbhid` from the user's keyboard.
+    ///
+    /// The policy applies to all clones of this `Device`.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Kernel drivers are only detached on Linux, so the policy is never
+    ///   called on other platforms.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// # use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// device.set_detach_policy(|req| {
+    ///     let is_keyboard = req
+    ///         .interface_descriptor()
+    ///         .is_some_and(|i| (i.class(), i.subclass(), i.protocol()) == (3, 1, 1));
+    ///     !(req.driver() == "usbhid" && is_keyboard)
+    /// });
+    /// let interface = device.detach_and_claim_interface(0).wait();
+    /// ```
+    pub fn set_detach_policy<F>(&self, policy: F)
+    where
+        F: Fn(&DetachRequest<'_>) -> bool + Send + Sync + 'static,
+    {
+        *self.detach_policy.lock().unwrap() = Some(Arc::new(policy));
+    }
+
+    /// Remove a callback set with [`set_detach_policy`][Self::set_detach_policy],
+    /// allowing any kernel driver to be detached.
+    pub fn clear_detach_policy(&self) {
+        *self.detach_policy.lock().unwrap() = None;
+    }
+
+    /// Set an observer to receive an event when each transfer on the device
+    /// is submitted and when it completes, replacing any previous observer.
+    ///
+    /// The observer applies to all clones of this `Device` and to its
+    /// [`Interface`]s. A transfer is reported to the observer that was set
+    /// when it was submitted, so transfers already pending are not reported.
+    pub fn set_transfer_observer(&self, observer: impl TransferObserver + 'static) {
+        self.backend.observer.set(Some(Arc::new(observer)));
+    }
+
+    /// Remove an observer set with
+    /// [`set_transfer_observer`][Self::set_transfer_observer].
+    pub fn clear_transfer_observer(&self) {
+        self.backend.observer.set(None);
     }
 
     pub(crate) fn open(
         d: &DeviceInfo,
+        shared: bool,
     ) -> impl MaybeFuture<Output = Result<Device, std::io::Error>> {
-        platform::Device::from_device_info(d).map(|d| d.map(Device::wrap))
+        let info = Arc::new(d.clone());
+        platform::Device::from_device_info(d, shared)
+            .map(|d| d.map(|d| Device::wrap(d, Some(info))))
+    }
+
+    pub(crate) fn open_with(
+        d: &DeviceInfo,
+        options: DeviceOpenOptions,
+    ) -> impl MaybeFuture<Output = Result<(Device, Vec<Interface>), Error>> {
+        let info = d.clone();
+        Blocking::new(move || {
+            let device = Device::open(&info, options.shared).wait()?;
+            let previous = device.backend.active_configuration_value();
+
+            // Interfaces of the previous configuration whose kernel drivers
+            // were detached to change it.
+            let mut detached = Vec::new();
+            if let Some(configuration) = options.configuration {
+                if configuration != previous {
+                    detached =
+                        device.change_configuration(configuration, options.detach_drivers)?;
+                }
+            }
+
+            let claim = if options.detach_drivers {
+                device.detach_and_claim_interfaces(&options.claim).wait()
+            } else {
+                device.claim_interfaces(&options.claim).wait()
+            };
+
+            match claim {
+                Ok(interfaces) => Ok((device, interfaces)),
+                Err(e) => {
+                    if device.backend.active_configuration_value() != previous {
+                        match device.set_configuration(previous).wait() {
+                            Ok(()) => device.reattach_kernel_drivers(&detached),
+                            Err(e) => warn!("Failed to restore configuration {previous}: {e}"),
+                        }
+                    }
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// Set the configuration, first detaching the kernel drivers of the
+    /// active configuration's interfaces if `detach` is set. The drivers are
+    /// reattached if the configuration can't be changed.
+    ///
+    /// Returns the interfaces whose drivers were detached.
+    fn change_configuration(&self, configuration: u8, detach: bool) -> Result<Vec<u8>, Error> {
+        let mut detached = Vec::new();
+        if detach {
+            let interfaces: Vec<u8> = self
+                .active_configuration()
+                .map(|c| c.interfaces().map(|i| i.interface_number()).collect())
+                .unwrap_or_default();
+
+            // Interfaces without a driver fail to detach, and need no reattaching.
+            for interface in interfaces {
+                if self.detach_kernel_driver(interface).is_ok() {
+                    detached.push(interface);
+                }
+            }
+        }
+
+        match self.set_configuration(configuration).wait() {
+            Ok(()) => Ok(detached),
+            Err(e) => {
+                self.reattach_kernel_drivers(&detached);
+                Err(e)
+            }
+        }
+    }
+
+    fn reattach_kernel_drivers(&self, interfaces: &[u8]) {
+        for &interface in interfaces {
+            if let Err(e) = self.attach_kernel_driver(interface) {
+                warn!("Failed to reattach kernel driver to interface {interface}: {e}");
+            }
+        }
     }
 
     /// Wraps a device that is already open.
     #[cfg(any(target_os = "android", target_os = "linux"))]
     pub fn from_fd(fd: std::os::fd::OwnedFd) -> impl MaybeFuture<Output = Result<Device, Error>> {
-        platform::Device::from_fd(fd).map(|d| d.map(Device::wrap))
+        platform::Device::from_fd(fd).map(|d| d.map(|d| Device::wrap(d, None)))
+    }
+
+    /// Get the IOKit `IOUSBDeviceInterface` of the device, for operations not
+    /// covered by this crate.
+    ///
+    /// The pointer remains valid while the device is open. It must not be
+    /// released, and changes made through it, like selecting a
+    /// configuration, are not tracked by this crate.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on macOS. The interface is an
+    ///   `IOUSBDeviceStruct500`.
+    #[cfg(target_os = "macos")]
+    pub fn iokit_device_interface(&self) -> *mut *mut std::ffi::c_void {
+        self.backend.iokit_device_interface()
+    }
+
+    /// Allocate a buffer of `len` bytes that the kernel can transfer data to
+    /// and from without copying it.
+    ///
+    /// See [`MmapBuffer`][crate::transfer::MmapBuffer] for details.
+    ///
+    /// Returns an error of kind
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] if the kernel doesn't
+    /// support `usbfs` mmap (`USBDEVFS_CAP_MMAP`, Linux 4.6 and later). The
+    /// kernel also limits the total size of these buffers through the
+    /// `usbfs_memory_mb` module parameter.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn alloc_mmap_buffer(&self, len: usize) -> Result<crate::transfer::MmapBuffer, Error> {
+        self.backend.alloc_mmap_buffer(len)
+    }
+
+    /// Allow or prevent the kernel from suspending the device when it is idle.
+    ///
+    /// This sets the `power/control` sysfs attribute of the device to `auto`
+    /// or `on`. The previous value is restored when the device is closed.
+    ///
+    /// Changing it usually requires root. Returns an error of kind
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] for a device opened
+    /// with [`from_fd`][Self::from_fd].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_autosuspend(&self, enable: bool) -> Result<(), Error> {
+        self.backend.set_autosuspend(enable)
+    }
+
+    /// Set how long the device must be idle before the kernel suspends it,
+    /// if autosuspend is enabled.
+    ///
+    /// This sets the `power/autosuspend_delay_ms` sysfs attribute of the
+    /// device, and is not restored when the device is closed.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_autosuspend_delay(&self, delay: Duration) -> Result<(), Error> {
+        self.backend.set_autosuspend_delay(delay)
+    }
+
+    /// Get the runtime power management state of the device.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn power_state(&self) -> Result<PowerState, Error> {
+        self.backend.power_state()
     }
 
     /// Open an interface of the device and claim it for exclusive use.
@@ -60,35 +435,208 @@ impl Device {
         &self,
         interface: u8,
     ) -> impl MaybeFuture<Output = Result<Interface, Error>> {
+        let device = self.clone();
         self.backend
             .clone()
             .claim_interface(interface)
-            .map(|i| i.map(Interface::wrap))
+            .map(move |i| i.map(|i| Interface::wrap(i, device)))
     }
 
     /// Detach kernel drivers and open an interface of the device and claim it for exclusive use.
     ///
+    /// The kernel driver is reattached when the interface is released, unless
+    /// disabled with [`Interface::set_reattach_on_drop`].
+    ///
     /// ### Platform notes
-    /// This function can only detach kernel drivers on Linux. Calling on other platforms has
-    /// the same effect as [`claim_interface`][`Device::claim_interface`].
+    /// This function can only detach kernel drivers on Linux and FreeBSD. Calling on other
+    /// platforms has the same effect as [`claim_interface`][`Device::claim_interface`].
+    ///
+    /// On FreeBSD, the kernel driver is not reattached when the interface is released.
+    ///
+    /// On Linux, the driver is detached and the interface claimed in one
+    /// `USBDEVFS_DISCONNECT_CLAIM` operation, so the kernel can't rebind the
+    /// driver in between. Kernels before 3.8 fall back to two steps.
     pub fn detach_and_claim_interface(
         &self,
         interface: u8,
     ) -> impl MaybeFuture<Output = Result<Interface, Error>> {
-        self.backend
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let claim = self
+            .backend
             .clone()
-            .detach_and_claim_interface(interface)
-            .map(|i| i.map(Interface::wrap))
+            .detach_and_claim_interface(interface, self.detach_policy());
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let claim = self.backend.clone().detach_and_claim_interface(interface);
+
+        let device = self.clone();
+        claim.map(move |i| i.map(|i| Interface::wrap(i, device)))
+    }
+
+    /// Open several interfaces of the device and claim them all for exclusive
+    /// use, such as the control and data interfaces of a CDC-ACM function.
+    ///
+    /// The interfaces are claimed in order, and returned in the same order.
+    /// If any of them can't be claimed, the ones already claimed are released
+    /// and the error is returned, so either all or none are claimed.
+    ///
+    /// Returns an error of kind [`InvalidInput`][ErrorKind::InvalidInput] if
+    /// an interface number is repeated.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// let interfaces = device.claim_interfaces(&[0, 1]).wait()?;
+    /// let (control, data) = (&interfaces[0], &interfaces[1]);
+    /// # Ok(()) }
+    /// ```
+    pub fn claim_interfaces(
+        &self,
+        interfaces: &[u8],
+    ) -> impl MaybeFuture<Output = Result<Vec<Interface>, Error>> {
+        self.claim_all(interfaces, false)
+    }
+
+    /// Detach kernel drivers and claim several interfaces of the device for
+    /// exclusive use.
+    ///
+    /// Like [`claim_interfaces`][`Device::claim_interfaces`], but each
+    /// interface is claimed with [`detach_and_claim_interface`][`Device::detach_and_claim_interface`].
+    /// If any of them fails, the kernel drivers detached from the interfaces
+    /// already claimed are reattached as they are released.
+    ///
+    /// ### Platform notes
+    /// This function can only detach kernel drivers on Linux. Calling on other platforms has
+    /// the same effect as [`claim_interfaces`][`Device::claim_interfaces`].
+    pub fn detach_and_claim_interfaces(
+        &self,
+        interfaces: &[u8],
+    ) -> impl MaybeFuture<Output = Result<Vec<Interface>, Error>> {
+        self.claim_all(interfaces, true)
+    }
+
+    /// Claim all the interfaces of the function described by the interface
+    /// association descriptor that starts at `first_interface`, as one
+    /// handle.
+    ///
+    /// The interfaces are claimed as by [`claim_interfaces`][`Device::claim_interfaces`],
+    /// so either all or none are claimed.
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] if the
+    /// active configuration has no interface association starting at
+    /// `first_interface`.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// let function = device.claim_association(0).wait()?;
+    /// for ep in function.endpoints() {
+    ///     println!("{:02X}", ep.address());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn claim_association(
+        &self,
+        first_interface: u8,
+    ) -> impl MaybeFuture<Output = Result<InterfaceAssociation, Error>> {
+        let association = self
+            .active_configuration()
+            .map_err(Error::from)
+            .and_then(|c| {
+                c.interface_associations()
+                    .find(|a| a.first_interface() == first_interface)
+                    .map(|a| {
+                        (
+                            a.interface_numbers().collect::<Vec<u8>>(),
+                            [
+                                a.function_class(),
+                                a.function_subclass(),
+                                a.function_protocol(),
+                            ],
+                        )
+                    })
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::NotFound,
+                            format!(
+                                "no interface association starts at interface {first_interface}"
+                            ),
+                        )
+                    })
+            });
+
+        let device = self.clone();
+        Blocking::new(move || {
+            let (numbers, [class, subclass, protocol]) = association?;
+            let interfaces = device.claim_interfaces(&numbers).wait()?;
+            Ok(InterfaceAssociation {
+                interfaces,
+                class,
+                subclass,
+                protocol,
+            })
+        })
+    }
+
+    fn claim_all(
+        &self,
+        interfaces: &[u8],
+        detach: bool,
+    ) -> impl MaybeFuture<Output = Result<Vec<Interface>, Error>> {
+        let device = self.clone();
+        let interfaces = interfaces.to_vec();
+        Blocking::new(move || {
+            if let Some(i) = first_duplicate(&interfaces) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("interface {i} requested more than once"),
+                ));
+            }
+
+            let mut claimed = Vec::with_capacity(interfaces.len());
+            for &interface in &interfaces {
+                let result = if detach {
+                    device.detach_and_claim_interface(interface).wait()
+                } else {
+                    device.claim_interface(interface).wait()
+                };
+
+                match result {
+                    Ok(i) => claimed.push(i),
+                    Err(e) => {
+                        // Release in the reverse order of claiming.
+                        while let Some(i) = claimed.pop() {
+                            drop(i);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(claimed)
+        })
     }
 
     /// Detach kernel drivers for the specified interface.
     ///
+    /// The driver is not reattached automatically. Call
+    /// [`attach_kernel_driver`][`Device::attach_kernel_driver`] when done, or
+    /// enable [`Interface::set_reattach_on_drop`] on the claimed interface.
+    ///
     /// ### Platform notes
     /// This function can only detach kernel drivers on Linux. Calling on other platforms has
     /// no effect.
     pub fn detach_kernel_driver(&self, interface: u8) -> Result<(), Error> {
         #[cfg(target_os = "linux")]
-        self.backend.detach_kernel_driver(interface)?;
+        self.backend
+            .detach_kernel_driver(interface, self.detach_policy())?;
         let _ = interface;
 
         Ok(())
@@ -107,6 +655,29 @@ impl Device {
         Ok(())
     }
 
+    /// Bind the named kernel driver to the specified interface.
+    ///
+    /// Unlike [`attach_kernel_driver`][Self::attach_kernel_driver], which
+    /// binds whichever driver matches, this binds `driver`, e.g. `ftdi_sio`,
+    /// through its sysfs `bind` file. A driver already bound to the interface
+    /// is unbound first, subject to the [detach
+    /// policy][Self::set_detach_policy]. The interface must not be claimed.
+    ///
+    /// The driver must support the device: it can be made to with the
+    /// driver's sysfs `new_id` file. Writing to sysfs usually requires root.
+    /// Returns an error of kind
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] for a device opened
+    /// with [`from_fd`][Self::from_fd].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn bind_kernel_driver(&self, interface: u8, driver: &str) -> Result<(), Error> {
+        self.backend
+            .bind_kernel_driver(interface, driver, self.detach_policy())
+    }
+
     /// Get the device descriptor.
     ///
     /// This returns cached data and does not perform IO.
@@ -123,23 +694,57 @@ impl Device {
     ///
     /// This returns cached data and does not perform IO. However, it can fail if the
     /// device is unconfigured, or if it can't find a configuration descriptor for
-    /// the configuration reported as active by the OS.
+    /// the configuration reported as active by the OS. Use
+    /// [`get_configuration`][Self::get_configuration] to query the device
+    /// when the OS value may be stale.
     pub fn active_configuration(
         &self,
-    ) -> Result<ConfigurationDescriptor, ActiveConfigurationError> {
+    ) -> Result<ConfigurationDescriptor<'_>, ActiveConfigurationError> {
         let active = self.backend.active_configuration_value();
 
         self.configurations()
             .find(|c| c.configuration_value() == active)
-            .ok_or_else(|| ActiveConfigurationError {
+            .ok_or(ActiveConfigurationError {
                 configuration_value: active,
             })
     }
 
+    /// Query the active configuration value from the device with a standard
+    /// `GET_CONFIGURATION` request.
+    ///
+    /// This updates the value used by [`active_configuration`][Self::active_configuration],
+    /// which may be stale if the configuration was changed by another process
+    /// or by the device itself. Returns `0` if the device is unconfigured.
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, this queries the hub driver for the active configuration
+    ///   instead, which doesn't require opening a WinUSB interface handle.
+    pub fn get_configuration(&self, timeout: Duration) -> Result<u8, Error> {
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "android",
+            target_os = "freebsd"
+        ))]
+        {
+            let configuration = crate::transfer::get_configuration(|c, buf| {
+                self.control_in_blocking(c, buf, timeout)
+            })?;
+            self.backend.update_active_configuration(configuration);
+            Ok(configuration)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = timeout;
+            self.backend.refresh_active_configuration()
+        }
+    }
+
     /// Get an iterator returning information about each configuration of the device.
     ///
     /// This returns cached data and does not perform IO.
-    pub fn configurations(&self) -> impl Iterator<Item = ConfigurationDescriptor> {
+    pub fn configurations(&self) -> impl Iterator<Item = ConfigurationDescriptor<'_>> {
         self.backend.configuration_descriptors()
     }
 
@@ -150,7 +755,12 @@ impl Device {
     /// unconfigure the device.
     ///
     /// ### Platform-specific notes
-    /// * Not supported on Windows
+    /// * Not supported on Windows, which selects the configuration when the
+    ///   device is connected, usually the first one. WinUSB rejects
+    ///   `SET_CONFIGURATION`, so this always returns an error of kind
+    ///   [`Unsupported`][ErrorKind::Unsupported]. Use
+    ///   [`active_configuration`][Self::active_configuration] to check which
+    ///   configuration is selected.
     pub fn set_configuration(
         &self,
         configuration: u8,
@@ -204,78 +814,704 @@ impl Device {
         }
     }
 
-    /// Request the list of supported languages for string descriptors.
+    /// Get the Binary Object Store descriptor, listing the capabilities of
+    /// the device.
+    ///
+    /// The descriptor is requested from the device the first time this is
+    /// called, and cached for the clones of this `Device`.
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] without
+    /// performing IO if the device's `bcdUSB` is lower than 2.01, as older
+    /// devices don't have a BOS descriptor.
     ///
     /// ### Platform-specific details
     ///
     /// See notes on [`get_descriptor`][`Self::get_descriptor`].
-    pub fn get_string_descriptor_supported_languages(
-        &self,
-        timeout: Duration,
-    ) -> Result<impl Iterator<Item = u16>, Error> {
-        let data = self.get_descriptor(DESCRIPTOR_TYPE_STRING, 0, 0, timeout)?;
+    pub fn bos(&self, timeout: Duration) -> Result<BosDescriptor<'_>, Error> {
+        let data = self.bos.get_or_try_init(|| {
+            if self.device_descriptor().usb_version() < 0x0201 {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    "device does not have a BOS descriptor",
+                ));
+            }
 
-        if !validate_string_descriptor(&data) {
-            error!("String descriptor language list read {data:?}, not a valid string descriptor");
+            let data = self.get_descriptor(DESCRIPTOR_TYPE_BOS, 0, 0, timeout)?;
+            if BosDescriptor::new(&data).is_none() {
+                error!("BOS descriptor read {data:?}, not a valid BOS descriptor");
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "BOS descriptor data was invalid",
+                ));
+            }
+            Ok(data)
+        })?;
+
+        Ok(BosDescriptor::new(data).expect("validated when cached"))
+    }
+
+    /// Read the device qualifier descriptor of a high-speed capable device,
+    /// describing how it would present itself at the other speed.
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] without
+    /// performing IO if the device's `bcdUSB` is lower than 2.0. Full-speed
+    /// only USB 2.0 devices respond to the request with a stall.
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`get_descriptor`][`Self::get_descriptor`].
+    pub fn device_qualifier(&self, timeout: Duration) -> Result<DeviceQualifierDescriptor, Error> {
+        if self.device_descriptor().usb_version() < 0x0200 {
             return Err(Error::new(
-                ErrorKind::InvalidData,
-                "string descriptor data was invalid",
+                ErrorKind::NotFound,
+                "device does not have a device qualifier descriptor",
             ));
         }
 
-        //TODO: Use array_chunks once stable
-        let mut iter = data.into_iter().skip(2);
-        Ok(std::iter::from_fn(move || {
-            Some(u16::from_le_bytes([iter.next()?, iter.next()?]))
-        }))
+        let data = self.get_descriptor(DESCRIPTOR_TYPE_DEVICE_QUALIFIER, 0, 0, timeout)?;
+        DeviceQualifierDescriptor::new(&data).ok_or_else(|| {
+            error!("device qualifier descriptor read {data:?}, not a valid descriptor");
+            Error::new(
+                ErrorKind::InvalidData,
+                "device qualifier descriptor data was invalid",
+            )
+        })
     }
 
-    /// Request a string descriptor from the device.
-    ///
-    /// Almost all devices support only the language ID [`US_ENGLISH`][`crate::descriptors::language_id::US_ENGLISH`].
+    /// Read an other speed configuration descriptor and its trailing
+    /// descriptors, describing the configuration at `index` as it would be if
+    /// the device were operating at the other speed.
     ///
-    /// Unpaired UTF-16 surrogates will be replaced with `�`, like [`String::from_utf16_lossy`].
+    /// Parse the result with [`ConfigurationDescriptor::new_other_speed`].
+    /// The number of configurations is given by
+    /// [`DeviceQualifierDescriptor::num_configurations`].
     ///
     /// ### Platform-specific details
     ///
     /// See notes on [`get_descriptor`][`Self::get_descriptor`].
-    pub fn get_string_descriptor(
+    pub fn other_speed_configuration(
         &self,
-        desc_index: NonZeroU8,
-        language_id: u16,
+        index: u8,
         timeout: Duration,
-    ) -> Result<String, Error> {
-        let data = self.get_descriptor(
-            DESCRIPTOR_TYPE_STRING,
-            desc_index.get(),
-            language_id,
+    ) -> Result<Vec<u8>, Error> {
+        let data =
+            self.get_descriptor(DESCRIPTOR_TYPE_OTHER_SPEED_CONFIGURATION, index, 0, timeout)?;
+        if ConfigurationDescriptor::new_other_speed(&data).is_none() {
+            error!("other speed configuration descriptor read {data:?}, not a valid descriptor");
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "other speed configuration descriptor data was invalid",
+            ));
+        }
+        Ok(data)
+    }
+
+    /// Read the Microsoft OS 2.0 descriptor set of the device.
+    ///
+    /// The vendor code and length of the descriptor set are taken from the
+    /// MS OS 2.0 platform capability in the [BOS descriptor][Self::bos]. Parse
+    /// the result with [`DescriptorSet::new`].
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] if the
+    /// device does not have an MS OS 2.0 platform capability.
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`control_in_blocking`][`Self::control_in_blocking`].
+    pub fn ms_os_20_descriptor_set(&self, timeout: Duration) -> Result<Vec<u8>, Error> {
+        let info = DescriptorSetInfo::from_bos(&self.bos(timeout)?)
+            .next()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "device does not have an MS OS 2.0 platform capability",
+                )
+            })?;
+
+        use crate::transfer::{ControlType, Recipient};
+
+        let mut buf = vec![0; info.total_length() as usize];
+        let len = self.control_in_blocking(
+            Control {
+                control_type: ControlType::Vendor,
+                recipient: Recipient::Device,
+                request: info.vendor_code(),
+                value: 0,
+                index: MS_OS_20_DESCRIPTOR_INDEX,
+            },
+            &mut buf,
             timeout,
         )?;
+        buf.truncate(len);
 
-        decode_string_descriptor(&data)
-            .map_err(|_| Error::new(ErrorKind::InvalidData, "string descriptor data was invalid"))
+        if DescriptorSet::new(&buf).is_none() {
+            error!("MS OS 2.0 descriptor set read {buf:?}, not a valid descriptor set");
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "MS OS 2.0 descriptor set data was invalid",
+            ));
+        }
+        Ok(buf)
     }
 
-    /// Reset the device, forcing it to re-enumerate.
+    /// Read the WebUSB landing page URL of the device.
     ///
-    /// This `Device` will no longer be usable, and you should drop it and call
-    /// [`super::list_devices`] to find and re-open it again.
+    /// The vendor code and URL index are taken from the WebUSB platform
+    /// capability in the [BOS descriptor][Self::bos].
     ///
-    /// ### Platform-specific notes
-    /// * Not supported on Windows
-    pub fn reset(&self) -> impl MaybeFuture<Output = Result<(), Error>> {
-        self.backend.clone().reset()
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] if the
+    /// device does not have a WebUSB platform capability, or does not
+    /// specify a landing page.
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`control_in_blocking`][`Self::control_in_blocking`].
+    pub fn webusb_landing_page(&self, timeout: Duration) -> Result<String, Error> {
+        let not_found = || {
+            Error::new(
+                ErrorKind::NotFound,
+                "device does not have a WebUSB landing page",
+            )
+        };
+
+        let webusb = WebUsbCapability::from_bos(&self.bos(timeout)?).ok_or_else(not_found)?;
+        let index = webusb.landing_page().ok_or_else(not_found)?;
+
+        use crate::transfer::{ControlType, Recipient};
+
+        let mut buf = vec![0; 255];
+        let len = self.control_in_blocking(
+            Control {
+                control_type: ControlType::Vendor,
+                recipient: Recipient::Device,
+                request: webusb.vendor_code(),
+                value: index as u16,
+                index: WEBUSB_GET_URL,
+            },
+            &mut buf,
+            timeout,
+        )?;
+
+        UrlDescriptor::new(&buf[..len])
+            .and_then(|d| d.url())
+            .ok_or_else(|| {
+                error!(
+                    "WebUSB URL descriptor read {:?}, not a valid URL descriptor",
+                    &buf[..len]
+                );
+                Error::new(
+                    ErrorKind::InvalidData,
+                    "WebUSB URL descriptor data was invalid",
+                )
+            })
+    }
+
+    /// Read the USB Type-C alternate modes of the device with their
+    /// description strings.
+    ///
+    /// The alternate modes are taken from the Billboard capability in the
+    /// [BOS descriptor][Self::bos]. Each mode's string descriptor is requested
+    /// in `language_id`, and is `None` if the mode does not have one.
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] if the
+    /// device does not have a Billboard capability.
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`get_descriptor`][`Self::get_descriptor`].
+    pub fn billboard_alternate_modes(
+        &self,
+        language_id: u16,
+        timeout: Duration,
+    ) -> Result<Vec<(AlternateMode, Option<String>)>, Error> {
+        let billboard = self.bos(timeout)?.billboard().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                "device does not have a Billboard capability",
+            )
+        })?;
+
+        billboard
+            .alternate_modes()
+            .map(|mode| {
+                let string = mode
+                    .string_index()
+                    .map(|i| self.get_string_descriptor(i, language_id, timeout))
+                    .transpose()?;
+                Ok((mode, string))
+            })
+            .collect()
+    }
+
+    /// Request the list of supported languages for string descriptors.
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`get_descriptor`][`Self::get_descriptor`].
+    pub fn get_string_descriptor_supported_languages(
+        &self,
+        timeout: Duration,
+    ) -> Result<impl Iterator<Item = u16>, Error> {
+        let data = self.get_descriptor(DESCRIPTOR_TYPE_STRING, 0, 0, timeout)?;
+
+        if !validate_string_descriptor(&data) {
+            error!("String descriptor language list read {data:?}, not a valid string descriptor");
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "string descriptor data was invalid",
+            ));
+        }
+
+        //TODO: Use array_chunks once stable
+        let mut iter = data.into_iter().skip(2);
+        Ok(std::iter::from_fn(move || {
+            Some(u16::from_le_bytes([iter.next()?, iter.next()?]))
+        }))
+    }
+
+    /// Request a string descriptor from the device.
+    ///
+    /// Almost all devices support only the language ID [`US_ENGLISH`][`crate::descriptors::language_id::US_ENGLISH`].
+    ///
+    /// Unpaired UTF-16 surrogates will be replaced with `�`, like [`String::from_utf16_lossy`].
+    ///
+    /// ### Platform-specific details
+    ///
+    /// See notes on [`get_descriptor`][`Self::get_descriptor`].
+    pub fn get_string_descriptor(
+        &self,
+        desc_index: NonZeroU8,
+        language_id: u16,
+        timeout: Duration,
+    ) -> Result<String, Error> {
+        let data = self.get_descriptor(
+            DESCRIPTOR_TYPE_STRING,
+            desc_index.get(),
+            language_id,
+            timeout,
+        )?;
+
+        decode_string_descriptor(&data)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "string descriptor data was invalid"))
+    }
+
+    /// Get the manufacturer string of the device.
+    ///
+    /// See [`product_string`][Self::product_string] for details.
+    pub fn manufacturer_string(&self) -> Option<String> {
+        self.cached_string(
+            self.device_descriptor().manufacturer_string_index(),
+            DeviceInfo::manufacturer_string,
+        )
+    }
+
+    /// Get the product string of the device.
+    ///
+    /// The string descriptor is requested in US English if the device
+    /// supports it, or else in its first supported language, and is cached
+    /// for later calls. If the request fails, this falls back to the string
+    /// read by the OS when the device was listed, if any, and the request is
+    /// not retried for 10 seconds. Returns `None` if the device has no such
+    /// string.
+    pub fn product_string(&self) -> Option<String> {
+        self.cached_string(
+            self.device_descriptor().product_string_index(),
+            DeviceInfo::product_string,
+        )
+    }
+
+    /// Get the serial number string of the device.
+    ///
+    /// See [`product_string`][Self::product_string] for details.
+    pub fn serial_number(&self) -> Option<String> {
+        self.cached_string(
+            self.device_descriptor().serial_number_string_index(),
+            DeviceInfo::serial_number,
+        )
+    }
+
+    fn cached_string(
+        &self,
+        index: Option<NonZeroU8>,
+        fallback: impl FnOnce(&DeviceInfo) -> Option<&str>,
+    ) -> Option<String> {
+        let index = index?;
+
+        // The lock isn't held while reading from the device, so that callers
+        // for cached strings don't wait behind the IO. Concurrent callers for
+        // the same missing string may both read it.
+        let (language_id, languages_failed) = {
+            let cache = self.strings.lock().unwrap();
+            if let Some(s) = cache.strings.get(&index.get()) {
+                return Some(s.clone());
+            }
+            if failed_recently(cache.strings_failed.get(&index.get())) {
+                return self.info.as_deref().and_then(fallback).map(str::to_owned);
+            }
+            (cache.language_id, cache.languages_failed)
+        };
+
+        let language_id = match language_id {
+            Some(language_id) => language_id,
+            None if failed_recently(languages_failed.as_ref()) => US_ENGLISH,
+            None => match self.get_string_descriptor_supported_languages(STRING_TIMEOUT) {
+                Ok(languages) => {
+                    let languages: Vec<u16> = languages.collect();
+                    let language_id = if languages.contains(&US_ENGLISH) {
+                        US_ENGLISH
+                    } else {
+                        languages.first().copied().unwrap_or(US_ENGLISH)
+                    };
+                    *self
+                        .strings
+                        .lock()
+                        .unwrap()
+                        .language_id
+                        .get_or_insert(language_id)
+                }
+                Err(e) => {
+                    debug!("Failed to read string descriptor languages: {e}");
+                    self.strings.lock().unwrap().languages_failed = Some(Instant::now());
+                    US_ENGLISH
+                }
+            },
+        };
+
+        match self.get_string_descriptor(index, language_id, STRING_TIMEOUT) {
+            Ok(s) => {
+                let mut cache = self.strings.lock().unwrap();
+                cache.strings_failed.remove(&index.get());
+                cache.strings.insert(index.get(), s.clone());
+                Some(s)
+            }
+            Err(e) => {
+                debug!("Failed to read string descriptor {index}: {e}");
+                self.strings
+                    .lock()
+                    .unwrap()
+                    .strings_failed
+                    .insert(index.get(), Instant::now());
+                self.info.as_deref().and_then(fallback).map(str::to_owned)
+            }
+        }
+    }
+
+    /// Write a descriptor to the device with a `SET_DESCRIPTOR` request.
+    ///
+    /// `data` is the complete descriptor, including its `bLength` and
+    /// `bDescriptorType` header. The `language_id` should be `0` unless you
+    /// are writing a string descriptor.
+    ///
+    /// `SET_DESCRIPTOR` is optional, and most devices respond with a stall
+    /// ([`ErrorKind::BrokenPipe`][std::io::ErrorKind::BrokenPipe]). Devices
+    /// that implement it (typically to allow serial numbers or other
+    /// identifiers to be written during manufacturing) may store the
+    /// descriptor in non-volatile memory, and may not validate it. A malformed
+    /// descriptor, or one that contradicts the rest of the device's
+    /// descriptors, can leave the device unable to enumerate. The OS's cached
+    /// copy of the descriptors is not updated until the device is reset or
+    /// re-enumerates.
+    pub fn set_descriptor(
+        &self,
+        desc_type: u8,
+        desc_index: u8,
+        language_id: u16,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        const STANDARD_REQUEST_SET_DESCRIPTOR: u8 = 0x07;
+        use crate::transfer::{ControlType, Recipient};
+
+        self.control_out_blocking(
+            Control {
+                control_type: ControlType::Standard,
+                recipient: Recipient::Device,
+                request: STANDARD_REQUEST_SET_DESCRIPTOR,
+                value: ((desc_type as u16) << 8) | desc_index as u16,
+                index: language_id,
+            },
+            data,
+            timeout,
+        )?;
+        Ok(())
+    }
+
+    /// Write a string descriptor to the device with a `SET_DESCRIPTOR` request.
+    ///
+    /// Encodes `s` as UTF-16 and writes it as string descriptor `desc_index`
+    /// for the language `language_id`. Returns an error of kind
+    /// [`InvalidInput`][std::io::ErrorKind::InvalidInput] if `s` is longer
+    /// than the 126 UTF-16 code units that fit in a descriptor.
+    ///
+    /// See [`set_descriptor`][Self::set_descriptor] for caveats.
+    pub fn set_string_descriptor(
+        &self,
+        desc_index: NonZeroU8,
+        language_id: u16,
+        s: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let data = crate::descriptors::encode_string_descriptor(s)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "string is too long"))?;
+        self.set_descriptor(
+            DESCRIPTOR_TYPE_STRING,
+            desc_index.get(),
+            language_id,
+            &data,
+            timeout,
+        )
+    }
+
+    /// Get the status of the device with a standard `GET_STATUS` request.
+    pub fn device_status(&self, timeout: Duration) -> Result<DeviceStatus, Error> {
+        get_device_status(|c, buf| self.control_in_blocking(c, buf, timeout))
+    }
+
+    /// Get the status of an endpoint with a standard `GET_STATUS` request.
+    pub fn endpoint_status(
+        &self,
+        endpoint: u8,
+        timeout: Duration,
+    ) -> Result<EndpointStatus, Error> {
+        get_endpoint_status(|c, buf| self.control_in_blocking(c, buf, timeout), endpoint)
+    }
+
+    /// Enable a feature with a standard `SET_FEATURE` request.
+    pub fn set_feature(&self, feature: Feature, timeout: Duration) -> Result<(), Error> {
+        self.control_out_blocking(feature.set_control(), &[], timeout)?;
+        Ok(())
+    }
+
+    /// Disable a feature with a standard `CLEAR_FEATURE` request.
+    pub fn clear_feature(&self, feature: Feature, timeout: Duration) -> Result<(), Error> {
+        self.control_out_blocking(feature.clear_control(), &[], timeout)?;
+        Ok(())
+    }
+
+    /// Arm or disarm the device to wake the host from suspend.
+    ///
+    /// This sets or clears the [`RemoteWakeup`][Feature::RemoteWakeup]
+    /// feature. Returns an error of kind
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] if the active
+    /// configuration does not support remote wakeup.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this also sets the `power/wakeup` sysfs attribute of the
+    ///   device, which usually requires root. The kernel sets the feature
+    ///   again according to this attribute whenever it suspends the device.
+    ///   For a device opened with `Device::from_fd`, which has no sysfs
+    ///   path, only the request is sent.
+    pub fn set_remote_wakeup(&self, enable: bool, timeout: Duration) -> Result<(), Error> {
+        if self.active_configuration()?.attributes() & 0x20 == 0 {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "configuration does not support remote wakeup",
+            ));
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.backend.set_remote_wakeup(enable)?;
+
+        if enable {
+            self.set_feature(Feature::RemoteWakeup, timeout)
+        } else {
+            self.clear_feature(Feature::RemoteWakeup, timeout)
+        }
+    }
+
+    /// Query which link power management states the device may initiate,
+    /// with a standard `GET_STATUS` request.
+    pub fn link_power_management(&self, timeout: Duration) -> Result<LinkPowerManagement, Error> {
+        let status = self.device_status(timeout)?;
+        Ok(LinkPowerManagement {
+            u1: status.u1_enabled(),
+            u2: status.u2_enabled(),
+        })
+    }
+
+    /// Allow or prevent the device from initiating U1 and U2 link power
+    /// management transitions.
+    ///
+    /// This sets or clears the [`U1Enable`][Feature::U1Enable] and
+    /// [`U2Enable`][Feature::U2Enable] features. Returns an error of kind
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] if the device is not
+    /// operating at SuperSpeed.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this also sets the `usb3_lpm_permit` sysfs attribute of
+    ///   the hub port the device is connected to, which usually requires
+    ///   root. The kernel disables LPM on both the port and the device for
+    ///   states that aren't permitted, and sets the features again according
+    ///   to this attribute, for example after a reset. Returns an error of
+    ///   kind [`Unsupported`][std::io::ErrorKind::Unsupported] for a device
+    ///   opened with `Device::from_fd`.
+    pub fn set_link_power_management(
+        &self,
+        lpm: LinkPowerManagement,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        if matches!(self.speed(), Some(Speed::Low | Speed::Full | Speed::High)) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "U1 and U2 are only supported at SuperSpeed",
+            ));
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.backend.set_usb3_lpm_permit(lpm.u1, lpm.u2)?;
+
+        for (enable, feature) in [(lpm.u1, Feature::U1Enable), (lpm.u2, Feature::U2Enable)] {
+            if enable {
+                self.set_feature(feature, timeout)?;
+            } else {
+                self.clear_feature(feature, timeout)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reset the device, forcing it to re-enumerate.
+    ///
+    /// This `Device` will no longer be usable, and you should drop it and call
+    /// [`super::list_devices`] to find and re-open it again.
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, this cycles the port of the parent hub with
+    ///   `IOCTL_USB_HUB_CYCLE_PORT`, which simulates unplugging the device.
+    ///   Windows may require the process to run as an administrator for this
+    ///   request.
+    pub fn reset(&self) -> impl MaybeFuture<Output = Result<(), Error>> {
+        self.backend.clone().reset()
+    }
+
+    /// Reset the device, then wait for it to re-enumerate and open it again.
+    ///
+    /// The device is found again by the port where it is connected, or by
+    /// its serial number if the port is unknown, so this works for devices
+    /// whose descriptors change after the reset, as in a firmware update.
+    /// This `Device` and its interfaces should be dropped, like after
+    /// [`reset`][Self::reset].
+    ///
+    /// Returns an error of kind [`TimedOut`][std::io::ErrorKind::TimedOut]
+    /// if the device can't be opened again before `timeout` elapses, and of
+    /// kind [`Unsupported`][std::io::ErrorKind::Unsupported] without
+    /// resetting if there is no way to find the device again, e.g. because
+    /// it was not opened from a [`DeviceInfo`].
+    ///
+    /// ### Platform-specific notes
+    /// * On Linux and FreeBSD, the device is usually reset in place, keeping
+    ///   its address, and is not disconnected. It is searched for after
+    ///   waiting up to 500ms for a disconnect, so that the old device isn't
+    ///   found if it re-enumerates. On other platforms, this first waits for
+    ///   the device to be disconnected.
+    pub fn reset_and_reopen(
+        &self,
+        timeout: Duration,
+    ) -> impl MaybeFuture<Output = Result<Device, Error>> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+        let device = self.clone();
+        Blocking::new(move || {
+            let deadline = Instant::now() + timeout;
+            let timed_out =
+                || Error::new(ErrorKind::TimedOut, "device did not reappear after reset");
+
+            let info = device
+                .info
+                .clone()
+                .filter(|info| !info.port_chain().is_empty() || info.serial_number().is_some())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unsupported,
+                        "device can't be found again without a port path or serial number",
+                    )
+                })?;
+
+            device.reset().wait()?;
+
+            // Linux and FreeBSD usually reset the device in place, but it is
+            // disconnected and re-enumerated if its descriptors changed.
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+            {
+                const DISCONNECT_GRACE: Duration = Duration::from_millis(500);
+                let grace = deadline.min(Instant::now() + DISCONNECT_GRACE);
+                if device.disconnected().wait_until(grace).is_some() {
+                    debug!("Device disconnected after reset");
+                }
+            }
+
+            #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+            {
+                device
+                    .disconnected()
+                    .wait_until(deadline)
+                    .ok_or_else(timed_out)?;
+            }
+            drop(device);
+
+            loop {
+                let found = crate::list_devices()
+                    .wait()?
+                    .find(|d| info.is_same_physical_device(d));
+
+                if let Some(found) = found {
+                    match found.open().wait() {
+                        Ok(device) => return Ok(device),
+                        Err(e) => debug!("Device found after reset, but failed to open: {e}"),
+                    }
+                }
+
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(timed_out());
+                }
+                thread::sleep(POLL_INTERVAL.min(deadline - now));
+            }
+        })
+    }
+
+    /// Wait for the device to be disconnected.
+    ///
+    /// The returned future resolves when the OS reports that the device was
+    /// removed, without waiting for a transfer to fail with
+    /// [`TransferError::Disconnected`]. If the removal was already detected,
+    /// it resolves immediately. Any number of these futures can be waiting at the same
+    /// time, e.g. one per task that should stop when the device is unplugged.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::future::block_on;
+    /// # use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    ///
+    /// let disconnected = device.disconnected();
+    /// std::thread::spawn(move || {
+    ///     block_on(disconnected);
+    ///     println!("device unplugged");
+    /// });
+    /// ```
+    ///
+    /// ### Platform-specific notes
+    /// * On Linux, the removal is detected on the usbfs file descriptor.
+    /// * On macOS, this uses an IOKit `kIOGeneralInterest` notification.
+    /// * On Windows, this uses `CM_Register_Notification` for the device
+    ///   instance.
+    pub fn disconnected(&self) -> Disconnected {
+        Disconnected::new(self.backend.clone())
     }
 
     /// Synchronously perform a single **IN (device-to-host)** transfer on the default **control** endpoint.
     ///
     /// ### Platform-specific notes
     ///
-    /// * Not supported on Windows. You must [claim an interface][`Device::claim_interface`]
-    ///   and use the interface handle to submit transfers.
+    /// * On Windows, the transfer is sent through an interface handle. See the
+    ///   notes on [`control_in`][Self::control_in].
     /// * On Linux, this takes a device-wide lock, so if you have multiple threads, you
     ///   are better off using the async methods.
-    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "android"))]
     pub fn control_in_blocking(
         &self,
         control: Control,
@@ -289,11 +1525,10 @@ impl Device {
     ///
     /// ### Platform-specific notes
     ///
-    /// * Not supported on Windows. You must [claim an interface][`Device::claim_interface`]
-    ///   and use the interface handle to submit transfers.
+    /// * On Windows, the transfer is sent through an interface handle. See the
+    ///   notes on [`control_in`][Self::control_in].
     /// * On Linux, this takes a device-wide lock, so if you have multiple threads, you
     ///   are better off using the async methods.
-    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "android"))]
     pub fn control_out_blocking(
         &self,
         control: Control,
@@ -328,15 +1563,34 @@ impl Device {
     ///
     /// ### Platform-specific notes
     ///
-    /// * Not supported on Windows. You must [claim an interface][`Device::claim_interface`]
-    ///   and use the interface handle to submit transfers.
-    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "android"))]
+    /// * On Windows, WinUSB only allows control transfers on an interface
+    ///   handle. The transfer is sent through the handle of a claimed
+    ///   interface if there is one, and otherwise through the first interface
+    ///   of the active configuration that uses the WinUSB driver, which is
+    ///   opened without claiming it. WinUSB replaces the `index` of requests
+    ///   with the `Interface` recipient with that interface's number.
     pub fn control_in(&self, data: ControlIn) -> TransferFuture<ControlIn> {
         let mut t = self.backend.make_control_transfer();
         t.submit::<ControlIn>(data);
         TransferFuture::new(t)
     }
 
+    /// Asynchronously submit a single **IN (device-to-host)** transfer on the
+    /// default **control** endpoint, receiving the data into a reusable
+    /// buffer.
+    ///
+    /// See [`Interface::control_in_buffer`].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Windows, the transfer is sent through an interface handle. See the
+    ///   notes on [`control_in`][Self::control_in].
+    pub fn control_in_buffer(&self, data: ControlInBuffer) -> TransferFuture<ControlInBuffer> {
+        let mut t = self.backend.make_control_transfer();
+        t.submit::<ControlInBuffer>(data);
+        TransferFuture::new(t)
+    }
+
     /// Submit a single **OUT (host-to-device)** transfer on the default **control** endpoint.
     ///
     /// ### Example
@@ -362,14 +1616,28 @@ impl Device {
     ///
     /// ### Platform-specific notes
     ///
-    /// * Not supported on Windows. You must [claim an interface][`Device::claim_interface`]
-    ///   and use the interface handle to submit transfers.
-    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "android"))]
-    pub fn control_out(&self, data: ControlOut) -> TransferFuture<ControlOut> {
+    /// * On Windows, the transfer is sent through an interface handle. See the
+    ///   notes on [`control_in`][Self::control_in].
+    pub fn control_out(&self, data: ControlOut) -> TransferFuture<ControlOut<'_>> {
         let mut t = self.backend.make_control_transfer();
         t.submit::<ControlOut>(data);
         TransferFuture::new(t)
     }
+
+    /// Submit a single **OUT (host-to-device)** transfer on the default
+    /// **control** endpoint with a raw SETUP packet.
+    ///
+    /// See [`Interface::control_out_setup`].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Windows, the transfer is sent through an interface handle. See the
+    ///   notes on [`control_in`][Self::control_in].
+    pub fn control_out_setup(&self, data: ControlOutSetup) -> TransferFuture<ControlOutSetup<'_>> {
+        let mut t = self.backend.make_control_transfer();
+        t.submit::<ControlOutSetup>(data);
+        TransferFuture::new(t)
+    }
 }
 
 /// An opened interface of a USB device.
@@ -382,11 +1650,14 @@ impl Device {
 #[derive(Clone)]
 pub struct Interface {
     backend: Arc<platform::Interface>,
+
+    /// The device the interface was claimed from, sharing its string cache.
+    device: Device,
 }
 
 impl Interface {
-    pub(crate) fn wrap(backend: Arc<platform::Interface>) -> Self {
-        Interface { backend }
+    pub(crate) fn wrap(backend: Arc<platform::Interface>, device: Device) -> Self {
+        Interface { backend, device }
     }
     /// Select the alternate setting of this interface.
     ///
@@ -397,11 +1668,60 @@ impl Interface {
         self.backend.clone().set_alt_setting(alt_setting)
     }
 
+    /// Select an alternate setting until the returned guard is dropped, then
+    /// restore the current one.
+    ///
+    /// This suits alternate settings that enable power-hungry modes, like
+    /// streaming, which should not be left selected.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// let streaming = interface.with_alt_setting(1).wait().unwrap();
+    /// // ... stream from the endpoints of alternate setting 1
+    /// streaming.restore().unwrap();
+    /// ```
+    pub fn with_alt_setting(
+        &self,
+        alt_setting: u8,
+    ) -> impl MaybeFuture<Output = Result<AltSettingGuard, Error>> {
+        let interface = self.clone();
+        Blocking::new(move || {
+            let previous = interface.get_alt_setting();
+            interface.set_alt_setting(alt_setting).wait()?;
+            Ok(AltSettingGuard {
+                interface,
+                previous,
+                restored: false,
+            })
+        })
+    }
+
     /// Get the current alternate setting of this interface.
+    ///
+    /// This returns the alternate setting last selected or read by this
+    /// `Interface`. Use [`refresh_alt_setting`][Self::refresh_alt_setting]
+    /// to pick up a change made outside of it.
     pub fn get_alt_setting(&self) -> u8 {
         self.backend.get_alt_setting()
     }
 
+    /// Query the OS for the current alternate setting of this interface and
+    /// update the value returned by [`get_alt_setting`][Self::get_alt_setting].
+    ///
+    /// ### Platform-specific notes
+    /// * On Linux, this reads sysfs, or sends a `GET_INTERFACE` request if the
+    ///   device was opened with `Device::from_fd`.
+    /// * On Windows, this uses `WinUsb_GetCurrentAlternateSetting`.
+    #[doc(alias = "GET_INTERFACE")]
+    pub fn refresh_alt_setting(&self) -> Result<u8, Error> {
+        self.backend.refresh_alt_setting()
+    }
+
     /// Synchronously perform a single **IN (device-to-host)** transfer on the default **control** endpoint.
     ///
     /// ### Platform-specific notes
@@ -442,32 +1762,115 @@ impl Interface {
         self.backend.control_out_blocking(control, data, timeout)
     }
 
-    /// Submit a single **IN (device-to-host)** transfer on the default **control** endpoint.
+    /// Get the status of the device with a standard `GET_STATUS` request.
     ///
     /// ### Example
     ///
     /// ```no_run
-    /// use futures_lite::future::block_on;
-    /// use nusb::transfer::{ ControlIn, ControlType, Recipient };
+    /// use std::time::Duration;
     /// # use nusb::MaybeFuture;
     /// # fn main() -> Result<(), std::io::Error> {
     /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
     /// # let device = di.open().wait().unwrap();
     /// # let interface = device.claim_interface(0).wait().unwrap();
-    ///
-    /// let data: Vec<u8> = block_on(interface.control_in(ControlIn {
-    ///     control_type: ControlType::Vendor,
-    ///     recipient: Recipient::Device,
-    ///     request: 0x30,
-    ///     value: 0x0,
-    ///     index: 0x0,
-    ///     length: 64,
-    /// })).into_result()?;
+    /// let status = interface.device_status(Duration::from_millis(100))?;
+    /// println!("self-powered: {}", status.self_powered());
     /// # Ok(()) }
     /// ```
+    pub fn device_status(&self, timeout: Duration) -> Result<DeviceStatus, Error> {
+        get_device_status(|c, buf| self.control_in_blocking(c, buf, timeout))
+    }
+
+    /// Get the status of an endpoint with a standard `GET_STATUS` request,
+    /// which tells whether the endpoint is halted.
+    pub fn endpoint_status(
+        &self,
+        endpoint: u8,
+        timeout: Duration,
+    ) -> Result<EndpointStatus, Error> {
+        get_endpoint_status(|c, buf| self.control_in_blocking(c, buf, timeout), endpoint)
+    }
+
+    /// Enable a feature with a standard `SET_FEATURE` request.
     ///
-    /// ### Platform-specific notes
-    /// * On Windows, if the `recipient` is `Interface`, the WinUSB driver sends
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use nusb::transfer::Feature;
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// interface.set_feature(Feature::RemoteWakeup, Duration::from_millis(100))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn set_feature(&self, feature: Feature, timeout: Duration) -> Result<(), Error> {
+        self.control_out_blocking(feature.set_control(), &[], timeout)?;
+        Ok(())
+    }
+
+    /// Disable a feature with a standard `CLEAR_FEATURE` request.
+    pub fn clear_feature(&self, feature: Feature, timeout: Duration) -> Result<(), Error> {
+        self.control_out_blocking(feature.clear_control(), &[], timeout)?;
+        Ok(())
+    }
+
+    /// Get the frame number in which an isochronous endpoint's
+    /// synchronization pattern repeats, with a standard `SYNCH_FRAME`
+    /// request.
+    ///
+    /// This is used with endpoints that have an implicit pattern of
+    /// transfer sizes, such as adaptive isochronous endpoints.
+    pub fn synch_frame(&self, endpoint: u8, timeout: Duration) -> Result<u16, Error> {
+        synch_frame(|c, buf| self.control_in_blocking(c, buf, timeout), endpoint)
+    }
+
+    /// Send a standard `SET_INTERFACE` request for this interface directly on
+    /// the control endpoint.
+    ///
+    /// Unlike [`set_alt_setting`][Self::set_alt_setting], this does not go
+    /// through the OS, so the OS does not update its endpoint state and
+    /// [`get_alt_setting`][Self::get_alt_setting] is not changed. This is
+    /// only useful for devices that need the request sent again, or at a
+    /// point the OS would not send it. Prefer `set_alt_setting` otherwise.
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, WinUSB may reject standard requests that it handles
+    ///   itself.
+    pub fn set_interface_raw(&self, alt_setting: u8, timeout: Duration) -> Result<(), Error> {
+        let control = set_interface_control(self.interface_number(), alt_setting);
+        self.control_out_blocking(control, &[], timeout)?;
+        Ok(())
+    }
+
+    /// Submit a single **IN (device-to-host)** transfer on the default **control** endpoint.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::future::block_on;
+    /// use nusb::transfer::{ ControlIn, ControlType, Recipient };
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    ///
+    /// let data: Vec<u8> = block_on(interface.control_in(ControlIn {
+    ///     control_type: ControlType::Vendor,
+    ///     recipient: Recipient::Device,
+    ///     request: 0x30,
+    ///     value: 0x0,
+    ///     index: 0x0,
+    ///     length: 64,
+    /// })).into_result()?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, if the `recipient` is `Interface`, the WinUSB driver sends
     ///   the interface number in the least significant byte of `index`,
     ///   overriding any value passed. A warning is logged if the passed `index`
     ///   least significant byte differs from the interface number, and this may
@@ -478,6 +1881,52 @@ impl Interface {
         TransferFuture::new(t)
     }
 
+    /// Submit a single **IN (device-to-host)** transfer on the default
+    /// **control** endpoint, receiving the data into a reusable buffer.
+    ///
+    /// Like [`control_in`][Self::control_in], but the data is returned in
+    /// the buffer passed in the [`ControlInBuffer`], which can be reused for
+    /// the next request. This avoids an allocation and a copy per transfer
+    /// for protocols that make many small requests.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::future::block_on;
+    /// use nusb::transfer::{ControlIn, ControlInBuffer, ControlType, Recipient};
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// # fn handle_register(_: u16, _: &[u8]) {}
+    /// let mut buf = Vec::new();
+    /// for register in 0..16 {
+    ///     let request = ControlIn {
+    ///         control_type: ControlType::Vendor,
+    ///         recipient: Recipient::Device,
+    ///         request: 0x30,
+    ///         value: register,
+    ///         index: 0x0,
+    ///         length: 4,
+    ///     };
+    ///     let data = block_on(interface.control_in_buffer(ControlInBuffer::reuse(request, buf)))
+    ///         .into_result()?;
+    ///     handle_register(register, &data); // your function
+    ///     buf = data.reuse();
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, the `index` is overridden for the `Interface` recipient,
+    ///   as described for [`control_in`][Self::control_in].
+    pub fn control_in_buffer(&self, data: ControlInBuffer) -> TransferFuture<ControlInBuffer> {
+        let mut t = self.backend.make_transfer(0, TransferType::Control);
+        t.submit::<ControlInBuffer>(data);
+        TransferFuture::new(t)
+    }
+
     /// Submit a single **OUT (host-to-device)** transfer on the default **control** endpoint.
     ///
     /// ### Example
@@ -508,12 +1957,46 @@ impl Interface {
     ///   overriding any value passed. A warning is logged if the passed `index`
     ///   least significant byte differs from the interface number, and this may
     ///   become an error in the future.
-    pub fn control_out(&self, data: ControlOut) -> TransferFuture<ControlOut> {
+    pub fn control_out(&self, data: ControlOut) -> TransferFuture<ControlOut<'_>> {
         let mut t = self.backend.make_transfer(0, TransferType::Control);
         t.submit::<ControlOut>(data);
         TransferFuture::new(t)
     }
 
+    /// Submit a single **OUT (host-to-device)** transfer on the default
+    /// **control** endpoint with a raw SETUP packet.
+    ///
+    /// This allows any value of `bmRequestType`, including reserved types
+    /// and recipients that can't be expressed with [`ControlOut`]. For the
+    /// IN direction, submit a [`ControlInBuffer::from_setup`] request with
+    /// [`Interface::control_in_buffer`].
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::future::block_on;
+    /// use nusb::transfer::{ControlOutSetup, SetupPacket};
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    ///
+    /// // Vendor request to a reserved recipient
+    /// let setup = SetupPacket::new(0x44, 0x10).with_value(1);
+    /// block_on(interface.control_out_setup(ControlOutSetup::new(setup, &[0xAA]))).into_result()?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, the WinUSB driver may override the `index` of requests
+    ///   to the interface recipient, as for [`Interface::control_out`].
+    pub fn control_out_setup(&self, data: ControlOutSetup) -> TransferFuture<ControlOutSetup<'_>> {
+        let mut t = self.backend.make_transfer(0, TransferType::Control);
+        t.submit::<ControlOutSetup>(data);
+        TransferFuture::new(t)
+    }
+
     /// Submit a single **IN (device-to-host)** transfer on the specified **bulk** endpoint.
     ///
     /// * The requested length must be a multiple of the endpoint's maximum packet size
@@ -533,6 +2016,189 @@ impl Interface {
         TransferFuture::new(t)
     }
 
+    /// Perform an **IN (device-to-host)** transfer of up to `len` bytes on the
+    /// specified **bulk** endpoint, split into transfers of at most
+    /// `chunk_size` bytes.
+    ///
+    /// Use this for transfers too large to submit at once. The chunks are
+    /// submitted together, and the data received by all of them is returned
+    /// in a single completion. Like a single transfer, the transfer ends
+    /// early if the device sends a short packet, and the completion contains
+    /// the data received up to that point. If a chunk fails, the remaining
+    /// chunks are cancelled and the completion has the error and the data
+    /// received before it.
+    ///
+    /// * `chunk_size` must be a non-zero multiple of the endpoint's maximum
+    ///   packet size.
+    /// * An IN endpoint address must have the top (`0x80`) bit set.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, the chunks are submitted as separate URBs linked with
+    ///   `USBDEVFS_URB_BULK_CONTINUATION`, so that the kernel cancels the
+    ///   rest of the transfer after a short packet or an error.
+    /// * On FreeBSD, the chunks are submitted as separate transfers, which
+    ///   the kernel doesn't link. A chunk already started when an earlier one
+    ///   ends with a short packet may consume data that follows it, which is
+    ///   discarded.
+    /// * On Windows and macOS, the OS splits large transfers itself, so this
+    ///   submits a single transfer and `chunk_size` is ignored.
+    pub fn bulk_in_chunked(
+        &self,
+        endpoint: u8,
+        len: usize,
+        chunk_size: usize,
+    ) -> impl MaybeFuture<Output = Completion<Vec<u8>>> {
+        let queue = Queue::new(self.backend.clone(), endpoint, TransferType::Bulk);
+        ChunkedIn::new(queue, len, chunk_size)
+    }
+
+    /// Perform an **OUT (host-to-device)** transfer of `buf` on the specified
+    /// **bulk** endpoint, split into transfers of at most `chunk_size` bytes.
+    ///
+    /// Use this for transfers too large to submit at once. The chunks are
+    /// sent from `buf` without copying it, and a single completion returns
+    /// the buffer and the total number of bytes sent. If a chunk fails, the
+    /// remaining chunks are cancelled. When
+    /// [zero-length packet termination][Self::set_send_zlp] is enabled, it
+    /// applies to the end of the whole transfer only.
+    ///
+    /// * `chunk_size` must be a non-zero multiple of the endpoint's maximum
+    ///   packet size.
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, the chunks are submitted as separate URBs linked with
+    ///   `USBDEVFS_URB_BULK_CONTINUATION`.
+    /// * On FreeBSD, the chunks are submitted as separate transfers.
+    /// * On Windows and macOS, the OS splits large transfers itself, so this
+    ///   submits a single transfer and `chunk_size` is ignored.
+    pub fn bulk_out_chunked(
+        &self,
+        endpoint: u8,
+        buf: Vec<u8>,
+        chunk_size: usize,
+    ) -> impl MaybeFuture<Output = Completion<ResponseBuffer>> {
+        let queue = Queue::new(self.backend.clone(), endpoint, TransferType::Bulk);
+        ChunkedOut::new(queue, buf, chunk_size)
+    }
+
+    /// Submit a single **OUT (host-to-device)** transfer on the specified **bulk** endpoint,
+    /// sending the concatenation of `bufs`.
+    ///
+    /// The slices are gathered into a single buffer for the transfer, which
+    /// the completion returns as a [`ResponseBuffer`][crate::transfer::ResponseBuffer]
+    /// for re-use. See [`Queue::submit_vectored`] to gather into re-used
+    /// buffers.
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn bulk_out_vectored(&self, endpoint: u8, bufs: &[IoSlice<'_>]) -> TransferFuture<Vec<u8>> {
+        let mut buf = Vec::with_capacity(bufs.iter().map(|b| b.len()).sum());
+        for b in bufs {
+            buf.extend_from_slice(b);
+        }
+        self.bulk_out(endpoint, buf)
+    }
+
+    /// Submit a single **OUT (host-to-device)** transfer on the specified **bulk** endpoint,
+    /// sending the data directly from `buf` without copying it.
+    ///
+    /// The completion returns `buf` in an [`OutResponse`][crate::transfer::OutResponse].
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn bulk_out_buffer<B: AsRef<[u8]> + Send + 'static>(
+        &self,
+        endpoint: u8,
+        buf: B,
+    ) -> TransferFuture<OutBuffer<B>> {
+        let mut t = self.backend.make_transfer(endpoint, TransferType::Bulk);
+        t.submit(OutBuffer(buf));
+        TransferFuture::new(t)
+    }
+
+    /// Submit a single transfer on the specified **bulk** endpoint using a
+    /// buffer from [`Device::alloc_mmap_buffer`].
+    ///
+    /// The direction of the transfer is determined by the endpoint address. The
+    /// completion returns the buffer, with its length set to the number of
+    /// bytes transferred. See [`MmapBuffer`][crate::transfer::MmapBuffer].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bulk_mmap(
+        &self,
+        endpoint: u8,
+        buf: crate::transfer::MmapBuffer,
+    ) -> TransferFuture<crate::transfer::MmapBuffer> {
+        let mut t = self.backend.make_transfer(endpoint, TransferType::Bulk);
+        t.submit(buf);
+        TransferFuture::new(t)
+    }
+
+    /// Synchronously perform a single **IN (device-to-host)** transfer on the specified **bulk** endpoint.
+    ///
+    /// Reads up to `data.len()` bytes and returns the number of bytes
+    /// received. If the transfer doesn't complete within `timeout`, it is
+    /// cancelled and `TransferError::TimedOut` is returned.
+    ///
+    /// * The length of `data` must be a multiple of the endpoint's maximum packet size
+    /// * An IN endpoint address must have the top (`0x80`) bit set.
+    pub fn bulk_in_blocking(
+        &self,
+        endpoint: u8,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.transfer_in_blocking(endpoint, TransferType::Bulk, data, timeout)
+    }
+
+    /// Synchronously perform a single **OUT (host-to-device)** transfer on the specified **bulk** endpoint.
+    ///
+    /// Returns the number of bytes sent. If the transfer doesn't complete
+    /// within `timeout`, it is cancelled and `TransferError::TimedOut` is
+    /// returned.
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn bulk_out_blocking(
+        &self,
+        endpoint: u8,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.transfer_out_blocking(endpoint, TransferType::Bulk, data, timeout)
+    }
+
+    fn transfer_in_blocking(
+        &self,
+        endpoint: u8,
+        ep_type: TransferType,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        let mut t = self.backend.make_transfer(endpoint, ep_type);
+        t.submit(RequestBuffer::new(data.len()));
+        let completion = wait_transfer::<RequestBuffer>(t, timeout);
+        let len = completion.data.len();
+        data[..len].copy_from_slice(&completion.data);
+        completion.status.map(|()| len)
+    }
+
+    fn transfer_out_blocking(
+        &self,
+        endpoint: u8,
+        ep_type: TransferType,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        let mut t = self.backend.make_transfer(endpoint, ep_type);
+        t.submit(data.to_vec());
+        let completion = wait_transfer::<Vec<u8>>(t, timeout);
+        completion.status.map(|()| completion.data.actual_length())
+    }
+
     /// Create a queue for managing multiple **IN (device-to-host)** transfers on a **bulk** endpoint.
     ///
     /// * An IN endpoint address must have the top (`0x80`) bit set.
@@ -547,15 +2213,61 @@ impl Interface {
         Queue::new(self.backend.clone(), endpoint, TransferType::Bulk)
     }
 
+    /// Create a queue for managing multiple **OUT (host-to-device)** transfers on a **bulk** endpoint,
+    /// sending data directly from buffers of type `B` without copying it.
+    ///
+    /// Submit buffers wrapped in [`OutBuffer`].
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn bulk_out_buffer_queue<B: AsRef<[u8]> + Send + Sync + 'static>(
+        &self,
+        endpoint: u8,
+    ) -> Queue<OutBuffer<B>> {
+        Queue::new(self.backend.clone(), endpoint, TransferType::Bulk)
+    }
+
+    /// Create a queue for managing multiple transfers on a **bulk** endpoint
+    /// using buffers from [`Device::alloc_mmap_buffer`].
+    ///
+    /// See [`bulk_mmap`][Self::bulk_mmap].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bulk_mmap_queue(&self, endpoint: u8) -> Queue<crate::transfer::MmapBuffer> {
+        Queue::new(self.backend.clone(), endpoint, TransferType::Bulk)
+    }
+
+    /// Create a blocking [`Read`][std::io::Read] wrapper for a **bulk IN
+    /// (device-to-host)** endpoint.
+    ///
+    /// * An IN endpoint address must have the top (`0x80`) bit set.
+    pub fn bulk_reader(&self, endpoint: u8) -> BulkReader {
+        BulkReader::new(self.bulk_in_queue(endpoint))
+    }
+
+    /// Create a blocking [`Write`][std::io::Write] wrapper for a **bulk OUT
+    /// (host-to-device)** endpoint.
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn bulk_writer(&self, endpoint: u8) -> BulkWriter {
+        BulkWriter::new(self.bulk_out_queue(endpoint))
+    }
+
     /// Submit a single **IN (device-to-host)** transfer on the specified **isochronous** endpoint.
     ///
     /// * The requested length must be a multiple of the endpoint's maximum packet size
     /// * An IN endpoint address must have the top (`0x80`) bit set.
+    ///
+    /// ### Platform-specific notes
+    /// * Isochronous transfers are only supported on Linux and Android.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     pub fn isochronous_in(
         &self,
         endpoint: u8,
-        buf: RequestIsochronousBuffer,
-    ) -> TransferFuture<RequestIsochronousBuffer> {
+        buf: crate::transfer::RequestIsochronousBuffer,
+    ) -> TransferFuture<crate::transfer::RequestIsochronousBuffer> {
         let mut t = self
             .backend
             .make_transfer(endpoint, TransferType::Isochronous);
@@ -566,7 +2278,14 @@ impl Interface {
     /// Create a queue for managing multiple **IN (device-to-host)** transfers on a **isochronous** endpoint.
     ///
     /// * An IN endpoint address must have the top (`0x80`) bit set.
-    pub fn isochronous_in_queue(&self, endpoint: u8) -> Queue<RequestIsochronousBuffer> {
+    ///
+    /// ### Platform-specific notes
+    /// * Isochronous transfers are only supported on Linux and Android.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn isochronous_in_queue(
+        &self,
+        endpoint: u8,
+    ) -> Queue<crate::transfer::RequestIsochronousBuffer> {
         Queue::new(self.backend.clone(), endpoint, TransferType::Isochronous)
     }
 
@@ -593,6 +2312,39 @@ impl Interface {
         TransferFuture::new(t)
     }
 
+    /// Synchronously perform a single **IN (device-to-host)** transfer on the specified **interrupt** endpoint.
+    ///
+    /// Reads up to `data.len()` bytes and returns the number of bytes
+    /// received. If the transfer doesn't complete within `timeout`, it is
+    /// cancelled and `TransferError::TimedOut` is returned.
+    ///
+    /// * The length of `data` must be a multiple of the endpoint's maximum packet size
+    /// * An IN endpoint address must have the top (`0x80`) bit set.
+    pub fn interrupt_in_blocking(
+        &self,
+        endpoint: u8,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.transfer_in_blocking(endpoint, TransferType::Interrupt, data, timeout)
+    }
+
+    /// Synchronously perform a single **OUT (host-to-device)** transfer on the specified **interrupt** endpoint.
+    ///
+    /// Returns the number of bytes sent. If the transfer doesn't complete
+    /// within `timeout`, it is cancelled and `TransferError::TimedOut` is
+    /// returned.
+    ///
+    /// * An OUT endpoint address must have the top (`0x80`) bit clear.
+    pub fn interrupt_out_blocking(
+        &self,
+        endpoint: u8,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.transfer_out_blocking(endpoint, TransferType::Interrupt, data, timeout)
+    }
+
     /// Create a queue for managing multiple **IN (device-to-host)** transfers on an **interrupt** endpoint.
     ///
     /// * An IN endpoint address must have the top (`0x80`) bit set.
@@ -607,6 +2359,50 @@ impl Interface {
         Queue::new(self.backend.clone(), endpoint, TransferType::Interrupt)
     }
 
+    /// Continuously poll an **IN (device-to-host)** **interrupt** endpoint,
+    /// returning a [`Stream`][futures_core::Stream] of the received reports.
+    ///
+    /// The stream keeps `depth` transfers of `packet_size` bytes pending, and
+    /// re-submits each one as it completes, so that no report is missed
+    /// between transfers. The host controller polls the endpoint at the rate
+    /// set by the endpoint descriptor's `bInterval`; a transfer completes
+    /// when the device answers a poll with data, and each report is yielded
+    /// in order as it arrives.
+    ///
+    /// This is [`interrupt_in_queue`][Self::interrupt_in_queue] followed by
+    /// [`Queue::into_stream`]; see there for error handling.
+    ///
+    /// * `packet_size` should be the endpoint's maximum packet size, or the
+    ///   size of the largest report.
+    /// * An IN endpoint address must have the top (`0x80`) bit set.
+    ///
+    /// Panics if `depth` is 0.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::{future::block_on, StreamExt};
+    /// # use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// # fn handle_report(_: &[u8]) {}
+    /// let mut reports = interface.interrupt_in_stream(0x81, 64, 4);
+    ///
+    /// while let Some(Ok(report)) = block_on(reports.next()) {
+    ///     handle_report(&report); // your function
+    /// }
+    /// ```
+    pub fn interrupt_in_stream(
+        &self,
+        endpoint: u8,
+        packet_size: usize,
+        depth: usize,
+    ) -> QueueStream {
+        self.interrupt_in_queue(endpoint)
+            .into_stream(packet_size, depth)
+    }
+
     /// Clear a bulk or interrupt endpoint's halt / stall condition.
     ///
     /// Sends a `CLEAR_FEATURE` `ENDPOINT_HALT` control transfer to tell the
@@ -617,10 +2413,288 @@ impl Interface {
     /// resume use of the endpoint.
     ///
     /// This should not be called when transfers are pending on the endpoint.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this uses `USBDEVFS_CLEAR_HALT`.
+    /// * On Windows, this uses `WinUsb_ResetPipe`.
+    /// * On macOS, this uses `ClearPipeStallBothEnds`.
+    /// * On FreeBSD, this sends the request as a control transfer, which ugen
+    ///   intercepts to also reset the host-side data toggle.
+    ///
+    /// All of these send the request to the device and reset the host-side
+    /// data toggle, so the next transfer uses `DATA0` on every platform.
     pub fn clear_halt(&self, endpoint: u8) -> impl MaybeFuture<Output = Result<(), Error>> {
         self.backend.clone().clear_halt(endpoint)
     }
 
+    /// Abort pending transfers and clear the halt condition on every bulk and
+    /// interrupt endpoint of the current alternate setting.
+    ///
+    /// This is the usual way to resynchronize with the device after a
+    /// protocol error, without walking the endpoints by hand. Each endpoint
+    /// is reset as by [`clear_halt`][Self::clear_halt].
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, usbfs can't abort all transfers on an endpoint, so each
+    ///   pending transfer is cancelled with `USBDEVFS_DISCARDURB`.
+    /// * On Windows, pending transfers are aborted with `WinUsb_AbortPipe`.
+    /// * On macOS, `ClearPipeStallBothEnds` aborts pending transfers.
+    /// * On FreeBSD, each pending transfer is stopped with `USB_FS_STOP`.
+    ///
+    /// The aborted transfers complete with [`TransferError::Cancelled`].
+    pub fn clear_all_halts(&self) -> impl MaybeFuture<Output = Result<(), Error>> {
+        let backend = self.backend.clone();
+        let endpoints: Vec<u8> = self
+            .endpoints()
+            .filter(|e| {
+                matches!(
+                    e.transfer_type(),
+                    TransferType::Bulk | TransferType::Interrupt
+                )
+            })
+            .map(|e| e.address())
+            .collect();
+
+        Blocking::new(move || {
+            for endpoint in endpoints {
+                backend.abort_pipe(endpoint)?;
+                backend.clone().clear_halt(endpoint).wait()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reset the host-side state of a bulk or interrupt endpoint.
+    ///
+    /// Clears the host's halt state for the endpoint and resets its host-side
+    /// data toggle to `DATA0`, without sending a request to the device. Use this
+    /// when the device-side endpoint state has already been reset by other
+    /// means (for example, by a class-specific reset request), so that the
+    /// two sides agree on the next data toggle. To recover from a
+    /// [`TransferError::Stall`], use [`clear_halt`][Self::clear_halt] instead.
+    ///
+    /// This should not be called when transfers are pending on the endpoint.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this uses `USBDEVFS_RESETEP`.
+    /// * On macOS, this uses `ClearPipeStall`.
+    /// * On Windows, WinUSB has no host-only reset, so this is the same as
+    ///   [`clear_halt`][Self::clear_halt] and also sends
+    ///   `CLEAR_FEATURE(ENDPOINT_HALT)` to the device.
+    pub fn reset_pipe(&self, endpoint: u8) -> impl MaybeFuture<Output = Result<(), Error>> {
+        self.backend.clone().reset_pipe(endpoint)
+    }
+
+    /// Set whether the kernel driver is reattached to the interface when it is
+    /// released, after this `Interface` and all its clones, transfers and
+    /// queues are dropped.
+    ///
+    /// This is enabled for interfaces claimed with
+    /// [`Device::detach_and_claim_interface`], and disabled for
+    /// [`Device::claim_interface`]. Enable it after claiming an interface
+    /// whose driver was detached with [`Device::detach_kernel_driver`] to
+    /// restore the driver when the program is done with the interface.
+    ///
+    /// The kernel probes the drivers again for the interface, which binds
+    /// the driver that was detached unless another one has since been
+    /// registered for the device. Nothing is reattached if the process exits
+    /// without dropping the `Interface`, such as with
+    /// [`std::process::exit`] or a panic that aborts.
+    ///
+    /// ### Platform-specific notes
+    /// * Kernel drivers can only be detached on Linux and Android. On other
+    ///   platforms this has no effect.
+    pub fn set_reattach_on_drop(&self, reattach: bool) {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.backend.set_reattach_on_drop(reattach);
+        let _ = reattach;
+    }
+
+    /// Get whether the kernel driver is reattached to the interface when it
+    /// is released. See [`set_reattach_on_drop`][Self::set_reattach_on_drop].
+    ///
+    /// Always returns `false` on platforms other than Linux and Android.
+    pub fn reattach_on_drop(&self) -> bool {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        return self.backend.reattach_on_drop();
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        false
+    }
+
+    /// Enable or disable terminating `OUT` transfers on an endpoint with a
+    /// zero-length packet.
+    ///
+    /// When enabled, a transfer whose length is a multiple of the endpoint's
+    /// maximum packet size is followed by a zero-length packet, so that
+    /// protocols that delimit messages with short packets don't need to submit
+    /// empty transfers manually. Transfers whose last packet is already short
+    /// are sent as-is.
+    ///
+    /// This applies to transfers submitted after the call, and is disabled by
+    /// default. Returns an error of kind
+    /// [`InvalidInput`][std::io::ErrorKind::InvalidInput] if `endpoint` is not
+    /// an `OUT` endpoint.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this sets `USBDEVFS_URB_ZERO_PACKET` on each transfer.
+    /// * On Windows, this sets the `SHORT_PACKET_TERMINATE` pipe policy.
+    /// * On macOS, enabling it is not supported and returns an error of kind
+    ///   [`Unsupported`][std::io::ErrorKind::Unsupported].
+    pub fn set_send_zlp(&self, endpoint: u8, enable: bool) -> Result<(), Error> {
+        if endpoint & 0x80 != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "zero-length packet termination only applies to OUT endpoints",
+            ));
+        }
+        self.backend.set_send_zlp(endpoint, enable)
+    }
+
+    /// Get the current frame number of the bus the device is connected to.
+    ///
+    /// The frame number counts 1 ms frames, and can be used to schedule
+    /// isochronous and interrupt transfers against the bus clock.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, usbfs does not provide the frame number, so this returns
+    ///   an error of kind [`Unsupported`][std::io::ErrorKind::Unsupported].
+    /// * On Windows, this uses `WinUsb_GetCurrentFrameNumber`, which requires
+    ///   Windows 8.1 or later and returns a 32-bit frame number.
+    /// * On macOS, this uses `GetBusFrameNumber`.
+    pub fn frame_number(&self) -> Result<u64, Error> {
+        self.backend.frame_number()
+    }
+
+    /// Try to bring the interface back into a working state after an error,
+    /// escalating through progressively more disruptive steps.
+    ///
+    /// The steps are run in order, up to the policy's
+    /// [`max_step`][RecoveryPolicy::max_step], until one of them recovers the
+    /// device:
+    ///
+    /// 1. [`RecoveryStep::ClearHalt`] clears the halt condition of each of the
+    ///    policy's endpoints. It is skipped if the policy has no endpoints.
+    /// 2. [`RecoveryStep::SetAltSetting`] re-selects the current alternate
+    ///    setting.
+    /// 3. [`RecoveryStep::ResetDevice`] resets and reopens the device with
+    ///    [`Device::reset_and_reopen`], then claims this interface again and
+    ///    re-selects its alternate setting. The new interface is returned in
+    ///    [`Recovered::interface`], and this one can no longer be used.
+    ///
+    /// After each step, the policy's [`on_step`][RecoveryPolicy::on_step]
+    /// function decides whether the device has recovered; by default, the
+    /// first step that succeeds ends the ladder. Returns the step that
+    /// recovered the device, or the error of the last step run.
+    ///
+    /// This should not be called when transfers are pending on the interface.
+    pub fn recover(
+        &self,
+        mut policy: RecoveryPolicy,
+    ) -> impl MaybeFuture<Output = Result<Recovered, Error>> {
+        let backend = self.backend.clone();
+        let device = self.device.clone();
+        Blocking::new(move || {
+            let steps = [
+                RecoveryStep::ClearHalt,
+                RecoveryStep::SetAltSetting,
+                RecoveryStep::ResetDevice,
+            ];
+
+            let max_step = policy.max_step;
+            let mut last_result = Ok(());
+            for step in steps.into_iter().take_while(|&s| s <= max_step) {
+                if step == RecoveryStep::ClearHalt && policy.endpoints.is_empty() {
+                    continue;
+                }
+
+                debug!(
+                    "Recovering interface {}: {step:?}",
+                    backend.interface_number
+                );
+
+                let mut interface = None;
+                let result = match step {
+                    RecoveryStep::ClearHalt => policy
+                        .endpoints
+                        .iter()
+                        .try_for_each(|&ep| backend.clone().clear_halt(ep).wait()),
+                    RecoveryStep::SetAltSetting => {
+                        let alt_setting = backend.get_alt_setting();
+                        backend.clone().set_alt_setting(alt_setting).wait()
+                    }
+                    RecoveryStep::ResetDevice => {
+                        Self::reopen(&device, &backend, policy.reopen_timeout)
+                            .map(|i| interface = Some(i))
+                    }
+                };
+
+                if policy.step_done(step, &result) {
+                    return Ok(Recovered { step, interface });
+                }
+                last_result = result;
+            }
+
+            Err(last_result.err().unwrap_or_else(|| {
+                Error::other("device did not recover after the last recovery step")
+            }))
+        })
+    }
+
+    /// Reset and reopen `device`, and claim the interface of `backend` again
+    /// with the same alternate setting.
+    fn reopen(
+        device: &Device,
+        backend: &platform::Interface,
+        timeout: Duration,
+    ) -> Result<Interface, Error> {
+        let alt_setting = backend.get_alt_setting();
+        let device = device.reset_and_reopen(timeout).wait()?;
+        let interface = device
+            .detach_and_claim_interface(backend.interface_number)
+            .wait()?;
+        if alt_setting != 0 {
+            interface.set_alt_setting(alt_setting).wait()?;
+        }
+        Ok(interface)
+    }
+
+    /// Get the WinUSB interface handle, for operations not covered by this
+    /// crate, like setting pipe policies with `WinUsb_SetPipePolicy`.
+    ///
+    /// The handle remains valid while the interface is claimed. It must not
+    /// be freed, and changes made through it, like selecting an alternate
+    /// setting, are not tracked by this crate.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on Windows.
+    #[cfg(target_os = "windows")]
+    pub fn winusb_handle(&self) -> *mut std::ffi::c_void {
+        self.backend.winusb_handle
+    }
+
+    /// Get the IOKit `IOUSBInterfaceInterface` of the interface, for
+    /// operations not covered by this crate.
+    ///
+    /// The pointer remains valid while the interface is claimed. It must not
+    /// be released, and changes made through it, like selecting an alternate
+    /// setting, are not tracked by this crate.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * Only available on macOS. The interface is an
+    ///   `IOUSBInterfaceStruct500`.
+    #[cfg(target_os = "macos")]
+    pub fn iokit_interface(&self) -> *mut *mut std::ffi::c_void {
+        self.backend.interface.raw.cast()
+    }
+
     /// Get the interface number.
     pub fn interface_number(&self) -> u8 {
         self.backend.interface_number
@@ -629,7 +2703,7 @@ impl Interface {
     /// Get the interface descriptors for the alternate settings of this interface.
     ///
     /// This returns cached data and does not perform IO.
-    pub fn descriptors(&self) -> impl Iterator<Item = InterfaceDescriptor> {
+    pub fn descriptors(&self) -> impl Iterator<Item = InterfaceDescriptor<'_>> {
         let active = self.backend.device.active_configuration_value();
 
         let configuration = self
@@ -645,15 +2719,244 @@ impl Interface {
     }
 
     /// Get the interface descriptor for the current alternate setting.
-    pub fn descriptor(&self) -> Option<InterfaceDescriptor> {
+    pub fn descriptor(&self) -> Option<InterfaceDescriptor<'_>> {
         self.descriptors()
             .find(|i| i.alternate_setting() == self.get_alt_setting())
     }
+
+    /// Get the interface string of the current alternate setting.
+    ///
+    /// The string descriptor is read from the device on first use and
+    /// cached, like [`Device::product_string`]. If the request fails, this
+    /// falls back to [`InterfaceInfo::interface_string`][crate::InterfaceInfo::interface_string]
+    /// from when the device was listed. Returns `None` if the interface has
+    /// no string.
+    pub fn interface_string(&self) -> Option<String> {
+        let index = self.descriptor()?.string_index();
+        let interface_number = self.interface_number();
+        self.device.cached_string(index, |info| {
+            info.interfaces()
+                .find(|i| i.interface_number() == interface_number)
+                .and_then(|i| i.interface_string())
+        })
+    }
+
+    /// Get the endpoints of the current alternate setting.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use nusb::transfer::{Direction, TransferType};
+    /// # use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// let bulk_in = interface
+    ///     .endpoints()
+    ///     .find(|ep| ep.transfer_type() == TransferType::Bulk && ep.direction() == Direction::In)
+    ///     .map(|ep| ep.address());
+    /// ```
+    pub fn endpoints(&self) -> impl Iterator<Item = EndpointDescriptor<'_>> {
+        self.descriptor().into_iter().flat_map(|d| d.endpoints())
+    }
+
+    /// Get a handle to an endpoint of the current alternate setting, checking
+    /// that it has the transfer type `T` and direction `D`.
+    ///
+    /// Returns an error of kind [`NotFound`][ErrorKind::NotFound] if the
+    /// current alternate setting has no endpoint with the address, or
+    /// [`InvalidInput`][ErrorKind::InvalidInput] if the endpoint's type or
+    /// direction differ.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use nusb::transfer::{Interrupt, Out};
+    /// # use nusb::MaybeFuture;
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// let ep = interface.endpoint::<Interrupt, Out>(0x02)?;
+    /// let mut queue = ep.queue();
+    /// queue.submit(vec![0x01, 0x02]);
+    /// # Ok(()) }
+    /// ```
+    pub fn endpoint<T: EndpointType, D: EndpointDirection>(
+        &self,
+        address: u8,
+    ) -> Result<Endpoint<T, D>, Error> {
+        let descriptor = self
+            .endpoints()
+            .find(|ep| ep.address() == address)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("endpoint 0x{address:02X} not found in current alternate setting"),
+                )
+            })?;
+
+        if descriptor.transfer_type() != T::TRANSFER_TYPE || descriptor.direction() != D::DIRECTION
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "endpoint 0x{address:02X} is {:?} {:?}, not {:?} {:?}",
+                    descriptor.transfer_type(),
+                    descriptor.direction(),
+                    T::TRANSFER_TYPE,
+                    D::DIRECTION,
+                ),
+            ));
+        }
+
+        Ok(Endpoint::new(
+            self.backend.clone(),
+            address,
+            descriptor.max_packet_size(),
+        ))
+    }
+}
+
+/// Guard returned by [`Interface::with_alt_setting`] that restores the
+/// previous alternate setting when dropped.
+///
+/// Dropping the guard blocks while the setting is restored, and only logs a
+/// failure. Call [`restore`][Self::restore] to handle the error instead.
+pub struct AltSettingGuard {
+    interface: Interface,
+    previous: u8,
+    restored: bool,
+}
+
+impl AltSettingGuard {
+    /// Get the interface.
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Get the alternate setting that will be restored.
+    pub fn previous_alt_setting(&self) -> u8 {
+        self.previous
+    }
+
+    /// Restore the previous alternate setting now.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.interface.set_alt_setting(self.previous).wait()
+    }
+}
+
+impl Drop for AltSettingGuard {
+    fn drop(&mut self) {
+        if !self.restored {
+            if let Err(e) = self.interface.set_alt_setting(self.previous).wait() {
+                warn!("Failed to restore alternate setting {}: {e}", self.previous);
+            }
+        }
+    }
+}
+
+impl Debug for AltSettingGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AltSettingGuard")
+            .field("interface_number", &self.interface.interface_number())
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+/// The claimed interfaces of a function grouped by an interface association
+/// descriptor.
+///
+/// Obtain an `InterfaceAssociation` with [`Device::claim_association`]. The
+/// interfaces are released when it and all clones of its interfaces are
+/// dropped.
+#[derive(Clone)]
+pub struct InterfaceAssociation {
+    interfaces: Vec<Interface>,
+    class: u8,
+    subclass: u8,
+    protocol: u8,
+}
+
+impl InterfaceAssociation {
+    /// Get the interfaces of the association, in order of interface number.
+    pub fn interfaces(&self) -> &[Interface] {
+        &self.interfaces
+    }
+
+    /// Get the interface of the association with the specified number.
+    pub fn interface(&self, interface_number: u8) -> Option<&Interface> {
+        self.interfaces
+            .iter()
+            .find(|i| i.interface_number() == interface_number)
+    }
+
+    /// Get the endpoints of the current alternate settings of all the
+    /// interfaces in the association.
+    pub fn endpoints(&self) -> impl Iterator<Item = EndpointDescriptor<'_>> {
+        self.interfaces.iter().flat_map(|i| i.endpoints())
+    }
+
+    /// `bFunctionClass` field of the interface association descriptor.
+    pub fn function_class(&self) -> u8 {
+        self.class
+    }
+
+    /// `bFunctionSubClass` field of the interface association descriptor.
+    pub fn function_subclass(&self) -> u8 {
+        self.subclass
+    }
+
+    /// `bFunctionProtocol` field of the interface association descriptor.
+    pub fn function_protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    /// Split into the individual interfaces.
+    pub fn into_interfaces(self) -> Vec<Interface> {
+        self.interfaces
+    }
+}
+
+fn first_duplicate(interfaces: &[u8]) -> Option<u8> {
+    let mut seen = [false; 256];
+    interfaces
+        .iter()
+        .copied()
+        .find(|&i| std::mem::replace(&mut seen[i as usize], true))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::os::fd::AsFd for Device {
+    /// Get the usbfs file descriptor of the device, for operations not
+    /// covered by this crate.
+    ///
+    /// Changes made through it, like claiming interfaces or selecting a
+    /// configuration, are not tracked by this crate.
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.backend.as_fd()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::os::fd::AsRawFd for Device {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.backend.as_fd().as_raw_fd()
+    }
+}
+
+#[test]
+fn test_first_duplicate() {
+    assert_eq!(first_duplicate(&[0, 1, 2]), None);
+    assert_eq!(first_duplicate(&[3, 1, 3, 1]), Some(3));
 }
 
 #[test]
 fn assert_send_sync() {
     fn require_send_sync<T: Send + Sync>() {}
     require_send_sync::<Interface>();
+    require_send_sync::<InterfaceAssociation>();
     require_send_sync::<Device>();
 }
@@ -0,0 +1,116 @@
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+use crate::{platform, transfer::poll_blocking, MaybeFuture};
+
+/// Set by the platform backend when the device is removed, and shared by the
+/// [`Disconnected`] futures of the device.
+#[derive(Default)]
+pub(crate) struct DisconnectSignal {
+    disconnected: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl DisconnectSignal {
+    pub(crate) fn notify(&self) {
+        self.disconnected.store(true, Ordering::SeqCst);
+        let wakers = mem::take(&mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner));
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    fn poll(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.disconnected.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // `notify` sets the flag before taking the wakers, so checking again
+        // with the lock held can't miss it.
+        if self.disconnected.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// Future that resolves when a device is disconnected.
+///
+/// Returned by [`Device::disconnected`][crate::Device::disconnected]. It keeps
+/// the device open until it is dropped.
+pub struct Disconnected {
+    device: Arc<platform::Device>,
+}
+
+impl Disconnected {
+    pub(crate) fn new(device: Arc<platform::Device>) -> Disconnected {
+        Disconnected { device }
+    }
+
+    /// Block until the device is disconnected, or return `None` if
+    /// `deadline` passes first.
+    pub(crate) fn wait_until(self, deadline: Instant) -> Option<()> {
+        poll_blocking(|cx| self.device.disconnect.poll(cx), Some(deadline))
+    }
+}
+
+impl Future for Disconnected {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.device.disconnect.poll(cx)
+    }
+}
+
+impl MaybeFuture for Disconnected {
+    fn wait(self) -> Self::Output {
+        poll_blocking(|cx| self.device.disconnect.poll(cx), None)
+            .expect("wait without deadline can't time out")
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<Disconnected>();
+}
+
+#[test]
+fn signal_wakes_pending() {
+    use std::{sync::atomic::AtomicUsize, task::Wake};
+
+    #[derive(Default)]
+    struct CountWaker(AtomicUsize);
+
+    impl Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let count = Arc::new(CountWaker::default());
+    let waker = Waker::from(count.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let signal = DisconnectSignal::default();
+    assert!(signal.poll(&mut cx).is_pending());
+    assert!(signal.poll(&mut cx).is_pending());
+    signal.notify();
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    assert!(signal.poll(&mut cx).is_ready());
+}
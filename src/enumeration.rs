@@ -4,12 +4,84 @@ use std::ffi::{OsStr, OsString};
 #[cfg(any(target_os = "linux", target_os = "android"))]
 use crate::platform::SysfsPath;
 
-use crate::{Device, Error, MaybeFuture};
+use crate::{Device, DeviceOpenOptions, Error, Interface, MaybeFuture};
 
 /// Opaque device identifier
+///
+/// A `DeviceId` identifies a device for as long as it stays connected. With
+/// the `serde` feature, it can be serialized to pass to another process on the
+/// same system, which can then open the device with [`crate::open_by_id`]. The
+/// serialized form is platform-specific.
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct DeviceId(pub(crate) crate::platform::DeviceId);
 
+impl DeviceId {
+    /// Open the device with this ID.
+    ///
+    /// This is equivalent to [`crate::open_by_id`], and is useful to open a
+    /// device from a [`HotplugEvent`][crate::hotplug::HotplugEvent] or a
+    /// saved ID without keeping its [`DeviceInfo`].
+    pub fn open(&self) -> impl MaybeFuture<Output = Result<Device, Error>> {
+        crate::open_by_id(*self)
+    }
+}
+
+/// Identifies a physical device across disconnection and re-enumeration.
+///
+/// Unlike a [`DeviceId`], which changes when the device is reconnected, a
+/// fingerprint is derived from the device's vendor ID, product ID, and either
+/// its serial number or the port it is connected to:
+///
+/// * A device with a serial number is identified by it, so it keeps its
+///   fingerprint when moved to another port.
+/// * A device without one is identified by its [bus][DeviceInfo::bus_id] and
+///   [port chain][DeviceInfo::port_chain], so it keeps its fingerprint as
+///   long as it is reconnected to the same port. Two identical devices
+///   without a serial number are told apart by their ports.
+///
+/// With the `serde` feature, it can be serialized to remember a device, e.g.
+/// in a configuration file, and found again later with
+/// [`find_device`][crate::find_device].
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceFingerprint {
+    vendor_id: u16,
+    product_id: u16,
+    serial_number: Option<String>,
+    bus_id: Option<String>,
+    port_chain: Vec<u8>,
+}
+
+impl DeviceFingerprint {
+    /// The vendor ID of the device.
+    pub fn vendor_id(&self) -> u16 {
+        self.vendor_id
+    }
+
+    /// The product ID of the device.
+    pub fn product_id(&self) -> u16 {
+        self.product_id
+    }
+
+    /// The serial number of the device, if it has one.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.serial_number.as_deref()
+    }
+
+    /// The bus the device is connected to, if it is identified by its port.
+    pub fn bus_id(&self) -> Option<&str> {
+        self.bus_id.as_deref()
+    }
+
+    /// The port chain of the device if it is identified by its port, or
+    /// empty.
+    pub fn port_chain(&self) -> &[u8] {
+        &self.port_chain
+    }
+}
+
 /// Information about a device that can be obtained without opening it.
 ///
 /// Found in the results of [`crate::list_devices`].
@@ -18,19 +90,30 @@ pub struct DeviceId(pub(crate) crate::platform::DeviceId);
 ///
 /// * Some fields are platform-specific
 ///     * Linux: `sysfs_path`
-///     * Windows: `instance_id`, `parent_instance_id`, `port_number`, `driver`
-///     * macOS: `registry_id`, `location_id`
+///     * Windows: `instance_id`, `container_id`, `hardware_ids`, `parent_instance_id`, `port_number`, `driver`
+///     * macOS: `registry_id`, `registry_entry_path`, `location_id`, `property`
+///
+/// With the `serde` feature, it can be serialized and deserialized, e.g. to
+/// save a device list as JSON or load device data in tests. The serialized
+/// form is platform-specific.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceInfo {
     #[cfg(any(target_os = "linux", target_os = "android"))]
     pub(crate) path: SysfsPath,
 
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
     pub(crate) busnum: u8,
 
     #[cfg(target_os = "windows")]
     pub(crate) instance_id: OsString,
 
+    #[cfg(target_os = "windows")]
+    pub(crate) container_id: Option<String>,
+
+    #[cfg(target_os = "windows")]
+    pub(crate) hardware_ids: Vec<OsString>,
+
     #[cfg(target_os = "windows")]
     pub(crate) location_paths: Vec<OsString>,
 
@@ -49,6 +132,9 @@ pub struct DeviceInfo {
     #[cfg(target_os = "macos")]
     pub(crate) registry_id: u64,
 
+    #[cfg(target_os = "macos")]
+    pub(crate) registry_path: String,
+
     #[cfg(target_os = "macos")]
     pub(crate) location_id: u32,
 
@@ -58,6 +144,7 @@ pub struct DeviceInfo {
 
     pub(crate) vendor_id: u16,
     pub(crate) product_id: u16,
+    pub(crate) usb_version: u16,
     pub(crate) device_version: u16,
 
     pub(crate) class: u8,
@@ -83,7 +170,7 @@ impl DeviceInfo {
             DeviceId(self.devinst)
         }
 
-        #[cfg(any(target_os = "linux", target_os = "android"))]
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
         {
             DeviceId(crate::platform::DeviceId {
                 bus: self.busnum,
@@ -111,12 +198,51 @@ impl DeviceInfo {
         &self.path.0
     }
 
-    /// *(Linux-only)* Bus number.
+    /// *(Linux-only)* Read and parse a sysfs attribute of the device.
+    ///
+    /// `name` is relative to [`sysfs_path`][Self::sysfs_path], and may be in
+    /// a subdirectory like `power/wakeup`. Surrounding whitespace is trimmed
+    /// before parsing. This reads the current value, so it fails with
+    /// [`ErrorKind::NotFound`][std::io::ErrorKind::NotFound] if the device has
+    /// been disconnected.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use nusb::MaybeFuture;
+    /// let device = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// let removable: String = device.read_attribute("removable").unwrap();
+    /// let wakeup: String = device.read_attribute("power/wakeup").unwrap();
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn read_attribute<T: std::str::FromStr>(&self, name: &str) -> Result<T, Error> {
+        Ok(self.path.read_attr(name)?)
+    }
+
+    /// *(Linux, macOS, and FreeBSD)* Bus number.
     ///
     /// On Linux, the `bus_id` is an integer and this provides the value as `u8`.
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///
+    /// On macOS, this is the high byte of the IOKit location ID, which the
+    /// `bus_id` contains in hexadecimal.
+    ///
+    /// On FreeBSD, this is the number of the `usbus` device in the `bus_id`.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd"
+    ))]
     pub fn busnum(&self) -> u8 {
-        self.busnum
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        {
+            self.busnum
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            (self.location_id >> 24) as u8
+        }
     }
 
     /// *(Windows-only)* Instance ID path of this device
@@ -125,6 +251,24 @@ impl DeviceInfo {
         &self.instance_id
     }
 
+    /// *(Windows-only)* Container ID, formatted like
+    /// `{00000000-0000-0000-ffff-ffffffffffff}`
+    ///
+    /// Windows gives all the device nodes of a physical device the same
+    /// container ID, so it can be used to find e.g. the volumes, COM ports
+    /// or HID collections of this device.
+    #[cfg(target_os = "windows")]
+    pub fn container_id(&self) -> Option<&str> {
+        self.container_id.as_deref()
+    }
+
+    /// *(Windows-only)* Hardware IDs property, e.g.
+    /// `USB\VID_1234&PID_5678&REV_0100`, most specific first
+    #[cfg(target_os = "windows")]
+    pub fn hardware_ids(&self) -> &[OsString] {
+        &self.hardware_ids
+    }
+
     /// *(Windows-only)* Location paths property
     #[cfg(target_os = "windows")]
     pub fn location_paths(&self) -> &[OsString] {
@@ -150,6 +294,25 @@ impl DeviceInfo {
     ///
     /// Since USB SuperSpeed is a separate topology from USB 2.0 speeds, a
     /// physical port may be identified differently depending on speed.
+    ///
+    /// ### Example
+    ///
+    /// Find the device plugged into port 4 of the hub on port 2 of bus `003`:
+    ///
+    /// ```no_run
+    /// use nusb::MaybeFuture;
+    /// let device = nusb::list_devices()
+    ///     .wait()
+    ///     .unwrap()
+    ///     .find(|d| d.bus_id() == "003" && d.port_chain() == [2, 4]);
+    /// ```
+    ///
+    /// ### Platform-specific notes
+    /// * On Linux, this is parsed from the `devpath` sysfs attribute.
+    /// * On macOS, this is decoded from the IOKit `locationID`, which has one
+    ///   4-bit port number per hub tier after the bus number.
+    /// * On Windows, this is parsed from the `#USB(n)` components of the
+    ///   device's location path, and is empty if the device has none.
     pub fn port_chain(&self) -> &[u8] {
         &self.port_chain
     }
@@ -172,7 +335,29 @@ impl DeviceInfo {
         self.registry_id
     }
 
+    /// *(macOS-only)* Path of the IOKit registry entry in the service plane,
+    /// like `IOService:/AppleARMPE/.../USB3 Gen2 Hub@01100000`
+    #[cfg(target_os = "macos")]
+    pub fn registry_entry_path(&self) -> &str {
+        &self.registry_path
+    }
+
+    /// *(macOS-only)* Read a property of the device's IOKit registry entry,
+    /// as shown by `ioreg -p IOUSB -l`.
+    ///
+    /// This reads the current value from the registry, and returns `None` if
+    /// the device is no longer connected or doesn't have the property.
+    /// Values of types other than those of [`PlistValue`] are left out.
+    #[cfg(target_os = "macos")]
+    pub fn property(&self, name: &str) -> Option<PlistValue> {
+        crate::platform::get_registry_property(self.registry_id, name)
+    }
+
     /// Identifier for the bus / host controller where the device is connected.
+    ///
+    /// Devices on the same bus have the same `bus_id` as the [`BusInfo`] of
+    /// the bus, and the [`device_address`][Self::device_address] is unique
+    /// among them.
     pub fn bus_id(&self) -> &str {
         &self.bus_id
     }
@@ -194,6 +379,16 @@ impl DeviceInfo {
         self.product_id
     }
 
+    /// The USB specification version the device supports, encoded as BCD,
+    /// from the `bcdUSB` device descriptor field.
+    ///
+    /// Use [`Version::from_bcd`] to decode it, e.g. to tell a USB 2.1 device
+    /// from a USB 3.2 device.
+    #[doc(alias = "bcdUSB")]
+    pub fn usb_version(&self) -> u16 {
+        self.usb_version
+    }
+
     /// The device version, normally encoded as BCD, from the `bcdDevice` device descriptor field.
     #[doc(alias = "bcdDevice")]
     pub fn device_version(&self) -> u16 {
@@ -254,6 +449,25 @@ impl DeviceInfo {
         self.serial_number.as_deref()
     }
 
+    /// Get a [`DeviceFingerprint`] identifying this physical device across
+    /// reconnection.
+    pub fn fingerprint(&self) -> DeviceFingerprint {
+        let serial_number = self.serial_number.clone().filter(|s| !s.is_empty());
+        let (bus_id, port_chain) = if serial_number.is_some() {
+            (None, Vec::new())
+        } else {
+            (Some(self.bus_id.clone()), self.port_chain.clone())
+        };
+
+        DeviceFingerprint {
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            serial_number,
+            bus_id,
+            port_chain,
+        }
+    }
+
     /// Iterator over the device's interfaces.
     ///
     /// This returns summary information about the interfaces in the device's
@@ -276,9 +490,32 @@ impl DeviceInfo {
         self.interfaces.iter()
     }
 
+    /// Whether `other` is the same physical device as `self`, possibly
+    /// re-enumerated: connected to the same port if the port is known, or
+    /// else with the same serial number.
+    pub(crate) fn is_same_physical_device(&self, other: &DeviceInfo) -> bool {
+        if !self.port_chain.is_empty() {
+            self.bus_id == other.bus_id && self.port_chain == other.port_chain
+        } else {
+            self.serial_number.is_some() && self.serial_number == other.serial_number
+        }
+    }
+
     /// Open the device
     pub fn open(&self) -> impl MaybeFuture<Output = Result<Device, Error>> {
-        Device::open(self)
+        Device::open(self, false)
+    }
+
+    /// Open the device, then select its configuration and claim interfaces
+    /// as specified by `options`.
+    ///
+    /// Returns the device and the claimed interfaces, in the order passed to
+    /// [`DeviceOpenOptions::claim`].
+    pub fn open_with(
+        &self,
+        options: DeviceOpenOptions,
+    ) -> impl MaybeFuture<Output = Result<(Device, Vec<Interface>), Error>> {
+        Device::open_with(self, options)
     }
 }
 
@@ -292,6 +529,7 @@ impl std::fmt::Debug for DeviceInfo {
             .field("port_chain", &format_args!("{:?}", self.port_chain))
             .field("vendor_id", &format_args!("0x{:04X}", self.vendor_id))
             .field("product_id", &format_args!("0x{:04X}", self.product_id))
+            .field("usb_version", &format_args!("0x{:04X}", self.usb_version))
             .field(
                 "device_version",
                 &format_args!("0x{:04X}", self.device_version),
@@ -309,7 +547,7 @@ impl std::fmt::Debug for DeviceInfo {
         {
             s.field("sysfs_path", &self.path);
         }
-        #[cfg(any(target_os = "linux", target_os = "android"))]
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
         {
             s.field("busnum", &self.busnum);
         }
@@ -317,6 +555,8 @@ impl std::fmt::Debug for DeviceInfo {
         #[cfg(target_os = "windows")]
         {
             s.field("instance_id", &self.instance_id);
+            s.field("container_id", &self.container_id);
+            s.field("hardware_ids", &self.hardware_ids);
             s.field("parent_instance_id", &self.parent_instance_id);
             s.field("location_paths", &self.location_paths);
             s.field("port_number", &self.port_number);
@@ -330,6 +570,7 @@ impl std::fmt::Debug for DeviceInfo {
                 "registry_entry_id",
                 &format_args!("0x{:08X}", self.registry_id),
             );
+            s.field("registry_entry_path", &self.registry_path);
         }
 
         s.field("interfaces", &self.interfaces);
@@ -341,6 +582,7 @@ impl std::fmt::Debug for DeviceInfo {
 /// USB connection speed
 #[derive(Copy, Clone, Eq, PartialOrd, Ord, PartialEq, Hash, Debug)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Speed {
     /// Low speed (1.5 Mbit)
     Low,
@@ -372,14 +614,108 @@ impl Speed {
     }
 }
 
+/// A version number encoded as BCD in a descriptor, such as the USB
+/// specification version from [`DeviceInfo::usb_version`] or the device
+/// release number from [`DeviceInfo::device_version`].
+///
+/// Versions are ordered by major, minor, then subminor version, and are
+/// displayed like `3.2` or `2.0.1`.
+///
+/// ### Example
+///
+/// ```
+/// use nusb::Version;
+/// let version = Version::from_bcd(0x0210);
+/// assert_eq!((version.major(), version.minor()), (2, 1));
+/// assert!(version < Version::from_bcd(0x0320));
+/// assert_eq!(version.to_string(), "2.1");
+/// ```
+#[derive(Copy, Clone, Eq, PartialOrd, Ord, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    major: u8,
+    minor: u8,
+    subminor: u8,
+}
+
+impl Version {
+    /// Decode a `0xJJMN` BCD version as major `JJ`, minor `M` and subminor
+    /// `N`.
+    ///
+    /// Digits that are not valid BCD are kept as their binary value.
+    pub fn from_bcd(bcd: u16) -> Version {
+        let [lo, hi] = bcd.to_le_bytes();
+        Version {
+            major: (hi >> 4) * 10 + (hi & 0xF),
+            minor: lo >> 4,
+            subminor: lo & 0xF,
+        }
+    }
+
+    /// The major version, e.g. `3` for USB 3.2.
+    pub fn major(&self) -> u8 {
+        self.major
+    }
+
+    /// The minor version, e.g. `2` for USB 3.2.
+    pub fn minor(&self) -> u8 {
+        self.minor
+    }
+
+    /// The subminor version, normally `0`.
+    pub fn subminor(&self) -> u8 {
+        self.subminor
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.subminor != 0 {
+            write!(f, ".{}", self.subminor)?;
+        }
+        Ok(())
+    }
+}
+
+/// *(macOS-only)* A value of an IOKit registry property, returned by
+/// [`DeviceInfo::property`].
+#[cfg(target_os = "macos")]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PlistValue {
+    /// `CFString`
+    String(String),
+
+    /// `CFNumber` of an integer type
+    Integer(i64),
+
+    /// `CFNumber` of a floating point type
+    Float(f64),
+
+    /// `CFBoolean`
+    Boolean(bool),
+
+    /// `CFData`
+    Data(Vec<u8>),
+
+    /// `CFArray`
+    Array(Vec<PlistValue>),
+
+    /// `CFDictionary` with string keys
+    Dictionary(std::collections::BTreeMap<String, PlistValue>),
+}
+
 /// Summary information about a device's interface, available before opening a device.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceInfo {
     pub(crate) interface_number: u8,
     pub(crate) class: u8,
     pub(crate) subclass: u8,
     pub(crate) protocol: u8,
     pub(crate) interface_string: Option<String>,
+    pub(crate) driver: Option<String>,
 }
 
 impl InterfaceInfo {
@@ -407,6 +743,29 @@ impl InterfaceInfo {
     pub fn interface_string(&self) -> Option<&str> {
         self.interface_string.as_deref()
     }
+
+    /// Name of the driver bound to the interface, if any.
+    ///
+    /// This is the driver that would have to be detached to claim the
+    /// interface. A claimed [`Interface`][crate::Interface] is always bound
+    /// to the driver that gives userspace access to it, so the name is not
+    /// reported for it. On Linux, it is also reported by
+    /// [`DetachRequest::driver`][crate::DetachRequest::driver], and in the
+    /// error when claiming an interface fails because a kernel driver is
+    /// bound to it.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this is the name of the kernel driver, e.g. `cdc_acm`, or
+    ///   `usbfs` for an interface claimed through usbfs.
+    /// * On Windows, this is the driver service name, e.g. `WinUSB` or
+    ///   `usbser`. For a device that is not composite, it is the driver of
+    ///   the device, which handles all of its interfaces.
+    /// * On macOS, this is the IOKit class of the matched driver, e.g.
+    ///   `AppleUSBACMData`, or the class name of a DriverKit extension.
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_deref()
+    }
 }
 
 // Not derived so that we can format some fields in hex
@@ -418,6 +777,7 @@ impl std::fmt::Debug for InterfaceInfo {
             .field("subclass", &format_args!("0x{:02X}", self.subclass))
             .field("protocol", &format_args!("0x{:02X}", self.protocol))
             .field("interface_string", &self.interface_string)
+            .field("driver", &self.driver)
             .finish()
     }
 }
@@ -425,6 +785,7 @@ impl std::fmt::Debug for InterfaceInfo {
 /// USB host controller type
 #[derive(Copy, Clone, Eq, PartialOrd, Ord, PartialEq, Hash, Debug)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UsbControllerType {
     /// xHCI controller (USB 3.0+)
     XHCI,
@@ -449,7 +810,7 @@ impl UsbControllerType {
         match lower_s
             .find("hci")
             .filter(|i| *i > 0)
-            .and_then(|i| lower_s.bytes().nth(i - 1))
+            .and_then(|i| lower_s.as_bytes().get(i - 1).copied())
         {
             Some(b'x') => Some(UsbControllerType::XHCI),
             Some(b'e') => Some(UsbControllerType::EHCI),
@@ -467,6 +828,8 @@ impl UsbControllerType {
 /// * Linux: `path`, `parent_path`, `busnum`, `root_hub`
 /// * Windows: `instance_id`, `parent_instance_id`, `location_paths`, `devinst`, `root_hub_description`
 /// * macOS: `registry_id`, `location_id`, `name`, `provider_class_name`, `class_name`
+/// * FreeBSD: `busnum`, `root_hub`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BusInfo {
     #[cfg(any(target_os = "linux", target_os = "android"))]
     pub(crate) path: SysfsPath,
@@ -475,10 +838,10 @@ pub struct BusInfo {
     pub(crate) parent_path: SysfsPath,
 
     /// The phony root hub device
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
     pub(crate) root_hub: DeviceInfo,
 
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
     pub(crate) busnum: u8,
 
     #[cfg(target_os = "windows")]
@@ -533,16 +896,34 @@ impl BusInfo {
         &self.parent_path.0
     }
 
-    /// *(Linux-only)* Bus number.
+    /// *(Linux, macOS, and FreeBSD)* Bus number.
     ///
     /// On Linux, the `bus_id` is an integer and this provides the value as `u8`.
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///
+    /// On macOS, this is the high byte of the IOKit location ID, which the
+    /// `bus_id` contains in hexadecimal.
+    ///
+    /// On FreeBSD, this is the number of the `usbus` device in the `bus_id`.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd"
+    ))]
     pub fn busnum(&self) -> u8 {
-        self.busnum
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        {
+            self.busnum
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            (self.location_id >> 24) as u8
+        }
     }
 
-    /// *(Linux-only)* The root hub [`DeviceInfo`] representing the bus.
-    #[cfg(any(target_os = "linux", target_os = "android"))]
+    /// *(Linux and FreeBSD)* The root hub [`DeviceInfo`] representing the bus.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
     pub fn root_hub(&self) -> &DeviceInfo {
         &self.root_hub
     }
@@ -618,6 +999,7 @@ impl BusInfo {
     /// ### Platform-specific notes
     ///
     /// * Linux: Parsed from driver in use.
+    /// * FreeBSD: Parsed from the controller the `usbus` device is attached to.
     /// * macOS: The IOService entry matched.
     /// * Windows: Parsed from the numbers following ROOT_HUB in the instance_id.
     pub fn controller_type(&self) -> Option<UsbControllerType> {
@@ -628,11 +1010,11 @@ impl BusInfo {
     ///
     /// ### Platform-specific notes
     ///
-    /// * Linux: The root hub product string.
+    /// * Linux and FreeBSD: The root hub product string.
     /// * macOS: The [IONameMatched](https://developer.apple.com/documentation/bundleresources/information_property_list/ionamematch) key of the IOService entry.
     /// * Windows: Description field of the root hub device. How the bus will appear in Device Manager.
     pub fn system_name(&self) -> Option<&str> {
-        #[cfg(any(target_os = "linux", target_os = "android"))]
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
         {
             self.root_hub.product_string()
         }
@@ -660,6 +1042,11 @@ impl std::fmt::Debug for BusInfo {
             s.field("busnum", &self.busnum);
         }
 
+        #[cfg(target_os = "freebsd")]
+        {
+            s.field("busnum", &self.busnum);
+        }
+
         #[cfg(target_os = "windows")]
         {
             s.field("instance_id", &self.instance_id);
@@ -686,3 +1073,12 @@ impl std::fmt::Debug for BusInfo {
         s.finish()
     }
 }
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<DeviceId>();
+    require_send_sync::<DeviceInfo>();
+    require_send_sync::<InterfaceInfo>();
+    require_send_sync::<BusInfo>();
+}
@@ -0,0 +1,106 @@
+//! Spawning and health of the background threads that deliver transfer
+//! completions and hotplug events.
+//!
+//! If one of these threads panics, pending transfers will never complete. The
+//! panic is caught and recorded so that later operations that rely on the
+//! event thread return an error describing it, rather than hanging, and
+//! polling a transfer that was already pending logs an error.
+
+use std::{
+    any::Any,
+    io,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use log::error;
+
+/// Name given to the threads spawned by this module.
+pub(crate) const THREAD_NAME: &str = "nusb-events";
+
+/// Message of the panic that ended an event thread, if any.
+static PANICKED: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set along with `PANICKED`, to check it without locking.
+static DEAD: AtomicBool = AtomicBool::new(false);
+
+/// Set once a poll of a stranded transfer has been logged.
+static REPORTED_STRANDED: AtomicBool = AtomicBool::new(false);
+
+/// Spawn a named event thread running `f`, catching and recording a panic.
+pub(crate) fn spawn<F>(f: F) -> thread::JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    thread::Builder::new()
+        .name(THREAD_NAME.into())
+        .spawn(move || {
+            if let Err(payload) = catch_unwind(AssertUnwindSafe(f)) {
+                let msg = panic_message(&*payload);
+                error!("{THREAD_NAME} thread panicked, pending transfers will not complete: {msg}");
+                PANICKED
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get_or_insert(msg);
+                DEAD.store(true, Ordering::Release);
+            }
+        })
+        .expect("failed to spawn event thread")
+}
+
+/// Return an error if an event thread has panicked.
+pub(crate) fn check() -> Result<(), io::Error> {
+    if !DEAD.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    match &*PANICKED.lock().unwrap_or_else(|e| e.into_inner()) {
+        None => Ok(()),
+        Some(msg) => Err(panicked_error(msg)),
+    }
+}
+
+/// Log an error the first time a transfer to `endpoint` that can no longer
+/// complete is polled, since the event thread that would have completed it
+/// has panicked.
+pub(crate) fn report_stranded(endpoint: u8) {
+    if let Err(e) = check() {
+        if !REPORTED_STRANDED.swap(true, Ordering::Relaxed) {
+            error!("Transfer on endpoint {endpoint:02x} will never complete: {e}");
+        }
+    }
+}
+
+fn panicked_error(msg: &str) -> io::Error {
+    io::Error::other(format!(
+        "{THREAD_NAME} thread panicked, transfers and events can no longer be delivered: {msg}"
+    ))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".into()
+    }
+}
+
+#[test]
+fn panic_is_reported() {
+    // Only the message formatting is tested: a panic in a spawned event
+    // thread would be recorded for the rest of the test binary.
+    let payload = catch_unwind(|| panic!("test panic")).unwrap_err();
+    let err = panicked_error(&panic_message(&*payload));
+    assert!(err.to_string().contains("test panic"));
+
+    let payload = catch_unwind(|| panic!("test panic {}", 2)).unwrap_err();
+    assert_eq!(panic_message(&*payload), "test panic 2");
+
+    let payload = catch_unwind(|| std::panic::panic_any(2)).unwrap_err();
+    assert_eq!(panic_message(&*payload), "unknown panic payload");
+}
@@ -0,0 +1,870 @@
+//! HID class descriptors and report descriptor parsing, for decoding the
+//! reports of HID interfaces.
+//!
+//! Claim the HID interface like any other interface and wrap it in a
+//! [`HidInterface`] to read its report descriptor. The parsed
+//! [`ReportDescriptor`] lists the reports with the layout of their fields,
+//! which locates the values in the reports read from the interrupt
+//! endpoint.
+//!
+//! Requires the `hid` feature.
+//!
+//! ### Example
+//!
+//! Print the values of the input reports of a HID interface:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use nusb::{hid::HidInterface, MaybeFuture};
+//! # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+//! # let device = di.open().wait().unwrap();
+//!
+//! let hid = HidInterface::new(device.claim_interface(0).wait().unwrap()).unwrap();
+//! let report_descriptor = hid.report_descriptor(Duration::from_secs(1)).unwrap();
+//!
+//! let mut buf = [0; 64];
+//! let len = hid
+//!     .interface()
+//!     .interrupt_in_blocking(0x81, &mut buf, Duration::from_secs(1))
+//!     .unwrap();
+//! let report = &buf[..len];
+//!
+//! if let Some(layout) = report_descriptor.input_report(report) {
+//!     for field in layout.fields().iter().filter(|f| !f.is_constant()) {
+//!         let values: Vec<_> = field.values(report).collect();
+//!         println!("{:?}: {values:?}", field.usage(0));
+//!     }
+//! }
+//! ```
+//!
+//! ### Platform-specific notes
+//! * On Windows and macOS, the OS HID driver is normally bound to HID
+//!   interfaces, so they can't be claimed unless another driver is
+//!   installed for the device.
+
+use std::{fmt::Debug, io::ErrorKind, time::Duration};
+
+use crate::{
+    descriptors::InterfaceDescriptor,
+    transfer::{Control, ControlType, Recipient},
+    Error, Interface,
+};
+
+const CLASS_HID: u8 = 0x03;
+
+const DESCRIPTOR_TYPE_HID: u8 = 0x21;
+const DESCRIPTOR_TYPE_REPORT: u8 = 0x22;
+
+const REQUEST_GET_DESCRIPTOR: u8 = 0x06;
+
+/// A claimed HID interface, for reading its class descriptors.
+///
+/// See the [module documentation][self] for an example.
+pub struct HidInterface {
+    interface: Interface,
+    hid_descriptor: HidDescriptor,
+}
+
+impl HidInterface {
+    /// Wrap a claimed interface that is a HID interface.
+    ///
+    /// Returns an error of kind [`InvalidInput`][ErrorKind::InvalidInput] if
+    /// the interface class is not HID or the interface has no HID
+    /// descriptor.
+    pub fn new(interface: Interface) -> Result<HidInterface, Error> {
+        let descriptor = interface
+            .descriptor()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "interface descriptor not found"))?;
+
+        if descriptor.class() != CLASS_HID {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "interface is not a HID interface",
+            ));
+        }
+
+        let hid_descriptor = HidDescriptor::from_interface(&descriptor).ok_or_else(|| {
+            Error::new(ErrorKind::InvalidInput, "interface has no HID descriptor")
+        })?;
+
+        Ok(HidInterface {
+            interface,
+            hid_descriptor,
+        })
+    }
+
+    /// Get the wrapped interface.
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Get the HID descriptor, from the configuration descriptor.
+    pub fn hid_descriptor(&self) -> &HidDescriptor {
+        &self.hid_descriptor
+    }
+
+    /// Read the bytes of the report descriptor.
+    pub fn report_descriptor_bytes(&self, timeout: Duration) -> Result<Vec<u8>, Error> {
+        let len = self
+            .hid_descriptor
+            .report_descriptor_length()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "HID descriptor does not list a report descriptor",
+                )
+            })?;
+
+        let mut buf = vec![0; usize::from(len)];
+        let len = self.interface.control_in_blocking(
+            Control {
+                control_type: ControlType::Standard,
+                recipient: Recipient::Interface,
+                request: REQUEST_GET_DESCRIPTOR,
+                value: u16::from(DESCRIPTOR_TYPE_REPORT) << 8,
+                index: self.interface.interface_number().into(),
+            },
+            &mut buf,
+            timeout,
+        )?;
+
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Read and parse the report descriptor.
+    pub fn report_descriptor(&self, timeout: Duration) -> Result<ReportDescriptor, Error> {
+        ReportDescriptor::parse(&self.report_descriptor_bytes(timeout)?)
+    }
+}
+
+impl Debug for HidInterface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HidInterface")
+            .field("interface_number", &self.interface.interface_number())
+            .field("hid_descriptor", &self.hid_descriptor)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A HID descriptor, found after the interface descriptor of a HID
+/// interface.
+#[derive(Clone)]
+pub struct HidDescriptor(Vec<u8>);
+
+impl HidDescriptor {
+    /// Parse a HID descriptor.
+    ///
+    /// This ignores any trailing data after the length specified in
+    /// `bLength`.
+    pub fn new(buf: &[u8]) -> Option<HidDescriptor> {
+        let len = usize::from(*buf.first()?);
+        let buf = buf.get(..len)?;
+        if len < 6 || buf[1] != DESCRIPTOR_TYPE_HID || len < 6 + 3 * usize::from(buf[5]) {
+            return None;
+        }
+        Some(HidDescriptor(buf.to_vec()))
+    }
+
+    /// Find the HID descriptor following an interface descriptor.
+    pub fn from_interface(interface: &InterfaceDescriptor) -> Option<HidDescriptor> {
+        interface
+            .class_descriptors()
+            .find(|&(descriptor_type, _)| descriptor_type == DESCRIPTOR_TYPE_HID)
+            .and_then(|(_, buf)| HidDescriptor::new(buf))
+    }
+
+    /// Get the bytes of the descriptor.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// `bcdHID` descriptor field: HID specification version, in
+    /// binary-coded decimal.
+    #[doc(alias = "bcdHID")]
+    pub fn hid_version(&self) -> u16 {
+        u16::from_le_bytes([self.0[2], self.0[3]])
+    }
+
+    /// `bCountryCode` descriptor field: Country of localized hardware, or
+    /// `0` if not localized.
+    #[doc(alias = "bCountryCode")]
+    pub fn country_code(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// `bNumDescriptors` descriptor field: Number of class descriptors.
+    #[doc(alias = "bNumDescriptors")]
+    pub fn num_descriptors(&self) -> u8 {
+        self.0[5]
+    }
+
+    /// Iterate over the type and length of the class descriptors.
+    pub fn class_descriptors(&self) -> impl Iterator<Item = (u8, u16)> + '_ {
+        self.0[6..]
+            .chunks_exact(3)
+            .take(usize::from(self.num_descriptors()))
+            .map(|c| (c[0], u16::from_le_bytes([c[1], c[2]])))
+    }
+
+    /// Length of the report descriptor.
+    pub fn report_descriptor_length(&self) -> Option<u16> {
+        self.class_descriptors()
+            .find(|&(ty, _)| ty == DESCRIPTOR_TYPE_REPORT)
+            .map(|(_, len)| len)
+    }
+}
+
+impl Debug for HidDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HidDescriptor")
+            .field("hid_version", &format_args!("0x{:04X}", self.hid_version()))
+            .field("country_code", &self.country_code())
+            .field(
+                "class_descriptors",
+                &self.class_descriptors().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// A HID usage: a usage page and a usage ID within the page.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Usage(u32);
+
+impl Usage {
+    /// Create a usage from its page and ID.
+    pub fn new(page: u16, id: u16) -> Usage {
+        Usage((u32::from(page) << 16) | u32::from(id))
+    }
+
+    /// Usage page, e.g. `0x01` for Generic Desktop.
+    pub fn page(&self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    /// Usage ID within the page, e.g. `0x30` for X in Generic Desktop.
+    pub fn id(&self) -> u16 {
+        self.0 as u16
+    }
+}
+
+impl Debug for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Usage(0x{:04X}:0x{:04X})", self.page(), self.id())
+    }
+}
+
+/// Kind of a HID report.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ReportKind {
+    /// Input report, sent by the device on the interrupt IN endpoint.
+    Input,
+
+    /// Output report, sent to the device.
+    Output,
+
+    /// Feature report, read and written with control requests.
+    Feature,
+}
+
+/// A collection in a report descriptor, grouping fields.
+#[derive(Clone, Debug)]
+pub struct Collection {
+    kind: u8,
+    usage: Option<Usage>,
+    parent: Option<usize>,
+}
+
+impl Collection {
+    /// Collection type: `0x00` physical, `0x01` application, `0x02` logical,
+    /// and so on.
+    pub fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    /// Usage of the collection, e.g. Mouse for an application collection.
+    pub fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+
+    /// Index of the enclosing collection in
+    /// [`ReportDescriptor::collections`].
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// A field of a report: `report_count` values of `report_size` bits each.
+#[derive(Clone, Debug)]
+pub struct Field {
+    flags: u32,
+    usages: Vec<(u32, u32)>,
+    bit_offset: u32,
+    report_size: u32,
+    report_count: u32,
+    logical_minimum: i32,
+    logical_maximum: i32,
+    physical_minimum: i32,
+    physical_maximum: i32,
+    unit: u32,
+    unit_exponent: i32,
+    collection: Option<usize>,
+}
+
+impl Field {
+    /// Data of the Input, Output or Feature item.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Whether the field is constant, usually padding.
+    pub fn is_constant(&self) -> bool {
+        self.flags & (1 << 0) != 0
+    }
+
+    /// Whether each value is the state of the usage at the same position,
+    /// rather than an array of indexes of the active usages.
+    pub fn is_variable(&self) -> bool {
+        self.flags & (1 << 1) != 0
+    }
+
+    /// Whether the values are relative to the previous report.
+    pub fn is_relative(&self) -> bool {
+        self.flags & (1 << 2) != 0
+    }
+
+    /// Offset of the first value in the report, in bits, including the
+    /// report ID byte if the report has one.
+    pub fn bit_offset(&self) -> u32 {
+        self.bit_offset
+    }
+
+    /// Size of each value, in bits.
+    pub fn report_size(&self) -> u32 {
+        self.report_size
+    }
+
+    /// Number of values.
+    pub fn report_count(&self) -> u32 {
+        self.report_count
+    }
+
+    /// Minimum value.
+    pub fn logical_minimum(&self) -> i32 {
+        self.logical_minimum
+    }
+
+    /// Maximum value.
+    pub fn logical_maximum(&self) -> i32 {
+        self.logical_maximum
+    }
+
+    /// Physical quantity corresponding to the minimum value.
+    pub fn physical_minimum(&self) -> i32 {
+        self.physical_minimum
+    }
+
+    /// Physical quantity corresponding to the maximum value.
+    pub fn physical_maximum(&self) -> i32 {
+        self.physical_maximum
+    }
+
+    /// Unit of the physical quantity, in the HID unit encoding.
+    pub fn unit(&self) -> u32 {
+        self.unit
+    }
+
+    /// Base 10 exponent of the physical quantity.
+    pub fn unit_exponent(&self) -> i32 {
+        self.unit_exponent
+    }
+
+    /// Index of the enclosing collection in
+    /// [`ReportDescriptor::collections`].
+    pub fn collection(&self) -> Option<usize> {
+        self.collection
+    }
+
+    /// Iterate over the usages of the field, in order.
+    pub fn usages(&self) -> impl Iterator<Item = Usage> + '_ {
+        self.usages
+            .iter()
+            .flat_map(|&(min, max)| (min..=max).map(Usage))
+    }
+
+    /// Get the usage at an index of the usage list.
+    ///
+    /// For a variable field, this is the usage of the value at that index,
+    /// with the last usage repeated for the remaining values. For an array
+    /// field, values are indexes into the usage list; see
+    /// [`array_usage`][Self::array_usage].
+    pub fn usage(&self, index: u32) -> Option<Usage> {
+        let mut index = index;
+        let mut last = None;
+        for &(min, max) in &self.usages {
+            if index <= max - min {
+                return Some(Usage(min + index));
+            }
+            index -= max - min + 1;
+            last = Some(Usage(max));
+        }
+
+        if self.is_variable() {
+            last
+        } else {
+            None
+        }
+    }
+
+    /// Get the usage selected by a value of an array field, or `None` if no
+    /// usage is selected.
+    pub fn array_usage(&self, value: i32) -> Option<Usage> {
+        let index = value.checked_sub(self.logical_minimum)?;
+        self.usage(u32::try_from(index).ok()?)
+    }
+
+    /// Extract a value from a report, including the report ID byte if the
+    /// report has one.
+    ///
+    /// The value is sign-extended if the logical minimum is negative.
+    /// Returns `None` if the index is out of range, the report is too short,
+    /// or the field is wider than 32 bits.
+    pub fn value(&self, report: &[u8], index: u32) -> Option<i32> {
+        if index >= self.report_count || self.report_size > 32 {
+            return None;
+        }
+
+        let start = index
+            .checked_mul(self.report_size)?
+            .checked_add(self.bit_offset)?;
+        let mut value: u64 = 0;
+        for bit in 0..self.report_size {
+            let pos = start.checked_add(bit)?;
+            let byte = *report.get((pos / 8) as usize)?;
+            if byte & (1 << (pos % 8)) != 0 {
+                value |= 1 << bit;
+            }
+        }
+
+        let size = self.report_size;
+        if self.logical_minimum < 0 && size > 0 && value & (1 << (size - 1)) != 0 {
+            value |= !0 << size;
+        }
+
+        Some(value as u32 as i32)
+    }
+
+    /// Iterate over the values of the field in a report, including the
+    /// report ID byte if the report has one.
+    pub fn values<'a>(&'a self, report: &'a [u8]) -> impl Iterator<Item = i32> + 'a {
+        (0..self.report_count).map_while(move |i| self.value(report, i))
+    }
+}
+
+/// The layout of a report.
+#[derive(Clone, Debug)]
+pub struct Report {
+    kind: ReportKind,
+    report_id: Option<u8>,
+    fields: Vec<Field>,
+    bit_len: u32,
+}
+
+impl Report {
+    /// Kind of the report.
+    pub fn kind(&self) -> ReportKind {
+        self.kind
+    }
+
+    /// Report ID, the first byte of the report, if the device uses report
+    /// IDs.
+    pub fn report_id(&self) -> Option<u8> {
+        self.report_id
+    }
+
+    /// Fields of the report, in order.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Length of the report in bytes, including the report ID byte.
+    pub fn len(&self) -> usize {
+        self.bit_len.div_ceil(8) as usize
+    }
+
+    /// Whether the report has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// A parsed report descriptor.
+#[derive(Clone, Debug)]
+pub struct ReportDescriptor {
+    reports: Vec<Report>,
+    collections: Vec<Collection>,
+}
+
+#[derive(Clone, Default)]
+struct GlobalState {
+    usage_page: u16,
+    logical_minimum: i32,
+    logical_maximum: (i32, u32),
+    physical_minimum: i32,
+    physical_maximum: i32,
+    unit_exponent: i32,
+    unit: u32,
+    report_size: u32,
+    report_id: Option<u8>,
+    report_count: u32,
+}
+
+#[derive(Default)]
+struct LocalState {
+    usages: Vec<(u32, u32)>,
+    usage_minimum: Option<u32>,
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid report descriptor: {msg}"),
+    )
+}
+
+impl ReportDescriptor {
+    /// Parse a report descriptor.
+    ///
+    /// Returns an error of kind [`InvalidData`][ErrorKind::InvalidData] if
+    /// the descriptor is malformed.
+    pub fn parse(buf: &[u8]) -> Result<ReportDescriptor, Error> {
+        let mut reports: Vec<Report> = Vec::new();
+        let mut collections = Vec::new();
+        let mut collection_stack: Vec<usize> = Vec::new();
+        let mut global = GlobalState::default();
+        let mut global_stack = Vec::new();
+        let mut local = LocalState::default();
+
+        let mut pos = 0;
+        while pos < buf.len() {
+            let prefix = buf[pos];
+
+            // Long items are reserved, skip them.
+            if prefix == 0xFE {
+                let size = *buf
+                    .get(pos + 1)
+                    .ok_or_else(|| invalid("truncated long item"))?;
+                pos += 3 + usize::from(size);
+                continue;
+            }
+
+            let size = [0, 1, 2, 4][usize::from(prefix & 0x03)];
+            let data = buf
+                .get(pos + 1..pos + 1 + size)
+                .ok_or_else(|| invalid("truncated item"))?;
+            pos += 1 + size;
+
+            let mut bytes = [0; 4];
+            bytes[..size].copy_from_slice(data);
+            let unsigned = u32::from_le_bytes(bytes);
+            let signed = match size {
+                1 => i32::from(data[0] as i8),
+                2 => i32::from(i16::from_le_bytes([data[0], data[1]])),
+                _ => unsigned as i32,
+            };
+            let usage = if size == 4 {
+                unsigned
+            } else {
+                (u32::from(global.usage_page) << 16) | unsigned
+            };
+
+            let item_type = (prefix >> 2) & 0x03;
+            let tag = prefix >> 4;
+            match (item_type, tag) {
+                // Main items
+                (0, 0x8 | 0x9 | 0xB) => {
+                    let kind = match tag {
+                        0x8 => ReportKind::Input,
+                        0x9 => ReportKind::Output,
+                        _ => ReportKind::Feature,
+                    };
+
+                    let index = match reports
+                        .iter()
+                        .position(|r| r.kind == kind && r.report_id == global.report_id)
+                    {
+                        Some(index) => index,
+                        None => {
+                            reports.push(Report {
+                                kind,
+                                report_id: global.report_id,
+                                fields: Vec::new(),
+                                bit_len: if global.report_id.is_some() { 8 } else { 0 },
+                            });
+                            reports.len() - 1
+                        }
+                    };
+                    let report = &mut reports[index];
+
+                    let bits = global
+                        .report_size
+                        .checked_mul(global.report_count)
+                        .ok_or_else(|| invalid("report too long"))?;
+                    if bits > 0 {
+                        let logical_maximum = if global.logical_minimum < 0 {
+                            global.logical_maximum.0
+                        } else {
+                            global.logical_maximum.1 as i32
+                        };
+                        let (physical_minimum, physical_maximum) =
+                            if global.physical_minimum == 0 && global.physical_maximum == 0 {
+                                (global.logical_minimum, logical_maximum)
+                            } else {
+                                (global.physical_minimum, global.physical_maximum)
+                            };
+
+                        report.fields.push(Field {
+                            flags: unsigned,
+                            usages: std::mem::take(&mut local.usages),
+                            bit_offset: report.bit_len,
+                            report_size: global.report_size,
+                            report_count: global.report_count,
+                            logical_minimum: global.logical_minimum,
+                            logical_maximum,
+                            physical_minimum,
+                            physical_maximum,
+                            unit: global.unit,
+                            unit_exponent: global.unit_exponent,
+                            collection: collection_stack.last().copied(),
+                        });
+                        report.bit_len = report
+                            .bit_len
+                            .checked_add(bits)
+                            .ok_or_else(|| invalid("report too long"))?;
+                    }
+                    local = LocalState::default();
+                }
+                (0, 0xA) => {
+                    collections.push(Collection {
+                        kind: unsigned as u8,
+                        usage: local.usages.first().map(|&(min, _)| Usage(min)),
+                        parent: collection_stack.last().copied(),
+                    });
+                    collection_stack.push(collections.len() - 1);
+                    local = LocalState::default();
+                }
+                (0, 0xC) => {
+                    collection_stack
+                        .pop()
+                        .ok_or_else(|| invalid("unbalanced End Collection"))?;
+                    local = LocalState::default();
+                }
+
+                // Global items
+                (1, 0x0) => global.usage_page = unsigned as u16,
+                (1, 0x1) => global.logical_minimum = signed,
+                (1, 0x2) => global.logical_maximum = (signed, unsigned),
+                (1, 0x3) => global.physical_minimum = signed,
+                (1, 0x4) => global.physical_maximum = signed,
+                (1, 0x5) => {
+                    // Commonly encoded as a 4-bit signed value.
+                    global.unit_exponent = if unsigned < 0x10 {
+                        ((unsigned as i32) << 28) >> 28
+                    } else {
+                        signed
+                    }
+                }
+                (1, 0x6) => global.unit = unsigned,
+                (1, 0x7) => global.report_size = unsigned,
+                (1, 0x8) => {
+                    if unsigned == 0 || unsigned > 0xFF {
+                        return Err(invalid("Report ID out of range"));
+                    }
+                    global.report_id = Some(unsigned as u8);
+                }
+                (1, 0x9) => global.report_count = unsigned,
+                (1, 0xA) => global_stack.push(global.clone()),
+                (1, 0xB) => {
+                    global = global_stack
+                        .pop()
+                        .ok_or_else(|| invalid("Pop without Push"))?;
+                }
+
+                // Local items
+                (2, 0x0) => local.usages.push((usage, usage)),
+                (2, 0x1) => local.usage_minimum = Some(usage),
+                (2, 0x2) => {
+                    let min = local
+                        .usage_minimum
+                        .take()
+                        .ok_or_else(|| invalid("Usage Maximum without Usage Minimum"))?;
+                    if usage < min {
+                        return Err(invalid("Usage Maximum less than Usage Minimum"));
+                    }
+                    local.usages.push((min, usage));
+                }
+
+                _ => {}
+            }
+        }
+
+        if !collection_stack.is_empty() {
+            return Err(invalid("unterminated collection"));
+        }
+
+        Ok(ReportDescriptor {
+            reports,
+            collections,
+        })
+    }
+
+    /// All reports, in the order they first appear in the descriptor.
+    pub fn reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    /// All collections, in the order they appear in the descriptor.
+    pub fn collections(&self) -> &[Collection] {
+        &self.collections
+    }
+
+    /// Find a report by kind and report ID.
+    pub fn report(&self, kind: ReportKind, report_id: Option<u8>) -> Option<&Report> {
+        self.reports
+            .iter()
+            .find(|r| r.kind == kind && r.report_id == report_id)
+    }
+
+    /// Find the layout of an input report read from the device, by its
+    /// report ID byte if the device uses report IDs.
+    pub fn input_report(&self, data: &[u8]) -> Option<&Report> {
+        if self.reports.iter().any(|r| r.report_id.is_some()) {
+            self.report(ReportKind::Input, Some(*data.first()?))
+        } else {
+            self.report(ReportKind::Input, None)
+        }
+    }
+}
+
+#[test]
+fn test_hid_descriptor() {
+    let d = HidDescriptor::new(&[0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00]).unwrap();
+    assert_eq!(d.hid_version(), 0x0111);
+    assert_eq!(d.country_code(), 0);
+    assert_eq!(d.report_descriptor_length(), Some(0x34));
+
+    assert!(HidDescriptor::new(&[0x09, 0x21, 0x11, 0x01, 0x00, 0x02, 0x22, 0x34, 0x00]).is_none());
+    assert!(HidDescriptor::new(&[0x09, 0x22, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00]).is_none());
+    assert!(HidDescriptor::new(&[0x04, 0x21, 0x11, 0x01]).is_none());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_report_descriptor_mouse() {
+    let d = ReportDescriptor::parse(&[
+        0x05, 0x01,       // Usage Page (Generic Desktop)
+        0x09, 0x02,       // Usage (Mouse)
+        0xa1, 0x01,       // Collection (Application)
+        0x09, 0x01,       //   Usage (Pointer)
+        0xa1, 0x00,       //   Collection (Physical)
+        0x05, 0x09,       //     Usage Page (Button)
+        0x19, 0x01,       //     Usage Minimum (1)
+        0x29, 0x03,       //     Usage Maximum (3)
+        0x15, 0x00,       //     Logical Minimum (0)
+        0x25, 0x01,       //     Logical Maximum (1)
+        0x95, 0x03,       //     Report Count (3)
+        0x75, 0x01,       //     Report Size (1)
+        0x81, 0x02,       //     Input (Data, Variable, Absolute)
+        0x95, 0x01,       //     Report Count (1)
+        0x75, 0x05,       //     Report Size (5)
+        0x81, 0x01,       //     Input (Constant)
+        0x05, 0x01,       //     Usage Page (Generic Desktop)
+        0x09, 0x30,       //     Usage (X)
+        0x09, 0x31,       //     Usage (Y)
+        0x15, 0x81,       //     Logical Minimum (-127)
+        0x25, 0x7f,       //     Logical Maximum (127)
+        0x75, 0x08,       //     Report Size (8)
+        0x95, 0x02,       //     Report Count (2)
+        0x81, 0x06,       //     Input (Data, Variable, Relative)
+        0xc0,             //   End Collection
+        0xc0,             // End Collection
+    ]).unwrap();
+
+    assert_eq!(d.collections().len(), 2);
+    assert_eq!(d.collections()[0].usage(), Some(Usage::new(0x01, 0x02)));
+    assert_eq!(d.collections()[1].parent(), Some(0));
+
+    let report = d.input_report(&[0x05, 0xff, 0x02]).unwrap();
+    assert_eq!(report.report_id(), None);
+    assert_eq!(report.len(), 3);
+    assert_eq!(report.fields().len(), 3);
+
+    let buttons = &report.fields()[0];
+    assert_eq!(buttons.usage(2), Some(Usage::new(0x09, 0x03)));
+    assert_eq!(buttons.values(&[0x05, 0xff, 0x02]).collect::<Vec<_>>(), [1, 0, 1]);
+    assert!(report.fields()[1].is_constant());
+
+    let xy = &report.fields()[2];
+    assert_eq!(xy.bit_offset(), 8);
+    assert!(xy.is_relative());
+    assert_eq!(xy.usages().collect::<Vec<_>>(), [Usage::new(0x01, 0x30), Usage::new(0x01, 0x31)]);
+    assert_eq!(xy.values(&[0x05, 0xff, 0x02]).collect::<Vec<_>>(), [-1, 2]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_report_descriptor_report_ids() {
+    let d = ReportDescriptor::parse(&[
+        0x06, 0x00, 0xff, // Usage Page (Vendor)
+        0x09, 0x01,       // Usage (1)
+        0xa1, 0x01,       // Collection (Application)
+        0x85, 0x01,       //   Report ID (1)
+        0x15, 0x00,       //   Logical Minimum (0)
+        0x26, 0xff, 0x00, //   Logical Maximum (255)
+        0x75, 0x08,       //   Report Size (8)
+        0x95, 0x02,       //   Report Count (2)
+        0x09, 0x02,       //   Usage (2)
+        0x81, 0x00,       //   Input (Data, Array)
+        0x85, 0x02,       //   Report ID (2)
+        0x09, 0x03,       //   Usage (3)
+        0xb1, 0x02,       //   Feature (Data, Variable)
+        0xc0,             // End Collection
+    ]).unwrap();
+
+    assert_eq!(d.reports().len(), 2);
+    let input = d.input_report(&[0x01, 0x00, 0xff]).unwrap();
+    assert_eq!(input.len(), 3);
+    assert_eq!(input.fields()[0].bit_offset(), 8);
+    assert_eq!(input.fields()[0].logical_maximum(), 255);
+    assert_eq!(input.fields()[0].values(&[0x01, 0x00, 0xff]).collect::<Vec<_>>(), [0, 255]);
+    assert_eq!(input.fields()[0].array_usage(0), Some(Usage::new(0xff00, 0x02)));
+    assert_eq!(input.fields()[0].array_usage(1), None);
+    assert!(d.input_report(&[0x02, 0x00, 0x00]).is_none());
+
+    let feature = d.report(ReportKind::Feature, Some(2)).unwrap();
+    assert_eq!(feature.fields()[0].usage(1), Some(Usage::new(0xff00, 0x03)));
+
+    assert!(ReportDescriptor::parse(&[0xa1, 0x01]).is_err());
+    assert!(ReportDescriptor::parse(&[0xc0]).is_err());
+    assert!(ReportDescriptor::parse(&[0x26, 0xff]).is_err());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_report_descriptor_overflow() {
+    assert!(ReportDescriptor::parse(&[
+        0x77, 0x00, 0x00, 0x01, 0x00, // Report Size (65536)
+        0x97, 0x00, 0x00, 0x01, 0x00, // Report Count (65536)
+        0x81, 0x00,                   // Input (Data, Array)
+    ]).is_err());
+
+    let d = ReportDescriptor::parse(&[
+        0x75, 0x08,                   // Report Size (8)
+        0x97, 0x00, 0x00, 0x00, 0x10, // Report Count (0x10000000)
+        0x81, 0x02,                   // Input (Data, Variable)
+    ]).unwrap();
+    let field = &d.input_report(&[0x01]).unwrap().fields()[0];
+    assert_eq!(field.value(&[0x01], 0), Some(1));
+    assert_eq!(field.value(&[0x01], 0x0fff_ffff), None);
+}
@@ -3,29 +3,101 @@
 //!
 //! See [`super::watch_devices`] for a usage example.
 
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
 use futures_core::Stream;
 
-use crate::{DeviceId, DeviceInfo};
+use crate::{list_devices_filtered, DeviceId, DeviceInfo, Error, MaybeFuture};
 
 /// Stream of device connection / disconnection events.
 ///
-/// Call [`super::watch_devices`] to begin watching device
-/// events and create a `HotplugWatch`.
-pub struct HotplugWatch(pub(crate) crate::platform::HotplugWatch);
+/// Call [`super::watch_devices`] or [`super::watch_devices_filtered`] to
+/// begin watching device events and create a `HotplugWatch`.
+pub struct HotplugWatch {
+    inner: crate::platform::HotplugWatch,
+    filter: Option<Filter>,
+
+    /// Matching devices connected before the watch was created, not yet
+    /// returned.
+    existing: VecDeque<DeviceInfo>,
+
+    /// Devices returned in `Connected` events when filtering, so that only
+    /// their `Disconnected` events are returned.
+    connected: HashSet<DeviceId>,
+}
+
+impl HotplugWatch {
+    pub(crate) fn new(filter: Option<Filter>) -> Result<HotplugWatch, Error> {
+        // Listed after the watch is created so that no device is missed. A
+        // device connected in between is listed and also reported by the
+        // watch, and is only returned once.
+        let inner = crate::platform::HotplugWatch::new()?;
+
+        let existing = match &filter {
+            Some(filter) if filter.existing => {
+                list_devices_filtered(filter.clone()).wait()?.collect()
+            }
+            _ => VecDeque::new(),
+        };
+
+        Ok(HotplugWatch {
+            inner,
+            filter,
+            existing,
+            connected: HashSet::new(),
+        })
+    }
+}
 
 impl Stream for HotplugWatch {
     type Item = HotplugEvent;
 
-    fn poll_next(
-        mut self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Option<Self::Item>> {
-        self.0.poll_next(cx).map(Some)
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if let Some(device) = this.existing.pop_front() {
+            this.connected.insert(device.id());
+            return Poll::Ready(Some(HotplugEvent::Connected(device)));
+        }
+
+        loop {
+            let event = match this.inner.poll_next(cx) {
+                Poll::Ready(event) => event,
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let Some(filter) = &this.filter else {
+                return Poll::Ready(Some(event));
+            };
+
+            match event {
+                #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
+                HotplugEvent::Connected(mut device) => {
+                    if filter.matches(&device) && this.connected.insert(device.id()) {
+                        #[cfg(target_os = "windows")]
+                        if filter.fetch_strings {
+                            crate::platform::fetch_missing_strings(&mut device);
+                        }
+                        return Poll::Ready(Some(HotplugEvent::Connected(device)));
+                    }
+                }
+                HotplugEvent::Disconnected(id) => {
+                    if this.connected.remove(&id) {
+                        return Poll::Ready(Some(HotplugEvent::Disconnected(id)));
+                    }
+                }
+            }
+        }
     }
 }
 
 /// Event returned from the [`HotplugWatch`] stream.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum HotplugEvent {
     /// A device has been connected.
     Connected(DeviceInfo),
@@ -34,6 +106,155 @@ pub enum HotplugEvent {
     Disconnected(DeviceId),
 }
 
+/// Selects the devices reported by [`watch_devices_filtered`][crate::watch_devices_filtered]
+/// and [`list_devices_filtered`].
+///
+/// A device matches if it matches all of the criteria that are set. An empty
+/// filter matches all devices. The filter also holds options for listing
+/// devices, like [`fetch_strings`][Self::fetch_strings].
+///
+/// ### Example
+///
+/// ```
+/// use nusb::hotplug::Filter;
+/// let filter = Filter::new().vendor(0x1234).class(0xFF).existing(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+    class: Option<u8>,
+    bus_id: Option<String>,
+    root_hubs: bool,
+    fetch_strings: bool,
+    existing: bool,
+}
+
+impl Filter {
+    /// Create a filter matching all devices.
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    /// Match devices with the specified vendor ID.
+    pub fn vendor(mut self, vendor_id: u16) -> Filter {
+        self.vendor_id = Some(vendor_id);
+        self
+    }
+
+    /// Match devices with the specified product ID.
+    pub fn product(mut self, product_id: u16) -> Filter {
+        self.product_id = Some(product_id);
+        self
+    }
+
+    /// Match devices with the specified class code, either as the device
+    /// class or as the class of one of their interfaces.
+    pub fn class(mut self, class: u8) -> Filter {
+        self.class = Some(class);
+        self
+    }
+
+    /// Match devices on the bus with the specified
+    /// [bus ID][DeviceInfo::bus_id].
+    pub fn bus(mut self, bus_id: impl Into<String>) -> Filter {
+        self.bus_id = Some(bus_id.into());
+        self
+    }
+
+    /// Also list root hubs with [`list_devices_filtered`], and report their
+    /// connection with [`watch_devices_filtered`][crate::watch_devices_filtered],
+    /// as devices of the hub class with an empty
+    /// [port chain][DeviceInfo::port_chain].
+    ///
+    /// Other hubs are always included.
+    ///
+    /// ### Platform-specific notes
+    /// * Only Linux and FreeBSD represent root hubs as devices. On other
+    ///   platforms, this has no effect, and [`list_buses`][crate::list_buses]
+    ///   provides the information about the host controllers instead.
+    pub fn root_hubs(mut self, root_hubs: bool) -> Filter {
+        self.root_hubs = root_hubs;
+        self
+    }
+
+    /// Request the manufacturer, product and serial number strings from
+    /// the device when [`list_devices_filtered`] or
+    /// [`watch_devices_filtered`][crate::watch_devices_filtered] finds that
+    /// the operating system didn't cache them.
+    ///
+    /// This performs IO with each matching device missing a string, so it
+    /// makes enumeration and the delivery of `Connected` events slower, but
+    /// it makes [`DeviceInfo::serial_number`] and the other strings
+    /// available on all platforms for devices that have them.
+    ///
+    /// ### Platform-specific notes
+    /// * On Windows, the strings are requested through the parent hub, in
+    ///   the first language the device supports. Windows does not cache the
+    ///   manufacturer string, and sometimes the others.
+    /// * On Linux, macOS and FreeBSD, the operating system reads all the
+    ///   strings when the device is connected, so this has no effect.
+    pub fn fetch_strings(mut self, fetch_strings: bool) -> Filter {
+        self.fetch_strings = fetch_strings;
+        self
+    }
+
+    /// Also return a `Connected` event for each matching device that is
+    /// already connected when the watch is created, before any other event.
+    pub fn existing(mut self, existing: bool) -> Filter {
+        self.existing = existing;
+        self
+    }
+
+    /// Whether `device` matches the filter.
+    pub fn matches(&self, device: &DeviceInfo) -> bool {
+        self.matches_root_hub(device)
+            && self.matches_ids(device.vendor_id(), device.product_id())
+            && self.matches_bus(device.bus_id())
+            && self.class.map_or(true, |c| {
+                device.class() == c || device.interfaces().any(|i| i.class() == c)
+            })
+    }
+
+    /// Whether a device with these IDs may match, used to skip probing the
+    /// rest of its properties when enumerating.
+    pub(crate) fn matches_ids(&self, vendor_id: u16, product_id: u16) -> bool {
+        self.vendor_id.map_or(true, |v| vendor_id == v)
+            && self.product_id.map_or(true, |p| product_id == p)
+    }
+
+    /// Whether `device` is not a root hub, or root hubs are included.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn matches_root_hub(&self, device: &DeviceInfo) -> bool {
+        self.root_hubs || device.class() != crate::hub::CLASS_HUB || !device.port_chain().is_empty()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    fn matches_root_hub(&self, _device: &DeviceInfo) -> bool {
+        true
+    }
+
+    /// Whether root hubs are listed.
+    #[cfg_attr(
+        not(any(target_os = "linux", target_os = "android", target_os = "freebsd")),
+        allow(dead_code)
+    )]
+    pub(crate) fn includes_root_hubs(&self) -> bool {
+        self.root_hubs
+    }
+
+    /// Whether missing strings are requested from the device.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) fn fetches_strings(&self) -> bool {
+        self.fetch_strings
+    }
+
+    /// Whether a device on this bus may match.
+    pub(crate) fn matches_bus(&self, bus_id: &str) -> bool {
+        self.bus_id.as_deref().map_or(true, |b| bus_id == b)
+    }
+}
+
 #[test]
 fn assert_send_sync() {
     fn require_send_sync<T: Send + Sync>() {}
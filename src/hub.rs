@@ -0,0 +1,486 @@
+//! Hub class requests, for inspecting and controlling the ports of a hub.
+//!
+//! Open the hub like any other device and wrap it in a [`Hub`]. The OS hub
+//! driver remains in control of the hub, so port changes made here are
+//! noticed by the OS like a physical change: powering off a port
+//! disconnects the device connected to it, and powering it on again lets
+//! the device enumerate again.
+//!
+//! ### Example
+//!
+//! Power-cycle the port of a wedged device:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use nusb::{hub::Hub, MaybeFuture};
+//! # let devices: Vec<_> = nusb::list_devices().wait().unwrap().collect();
+//! # let (hub_info, port) = (&devices[0], 1);
+//!
+//! let hub = Hub::new(hub_info.open().wait().unwrap()).unwrap();
+//! hub.power_cycle_port(port, Duration::from_secs(1), Duration::from_millis(100))
+//!     .unwrap();
+//! ```
+//!
+//! ### Platform-specific notes
+//! * On Windows, hubs use the OS hub driver rather than WinUSB, so hub class
+//!   requests are not supported.
+
+use std::{fmt::Debug, io::ErrorKind, thread, time::Duration};
+
+use crate::{
+    transfer::{Control, ControlType, Recipient},
+    Device, Error, Speed,
+};
+
+pub(crate) const CLASS_HUB: u8 = 0x09;
+
+const DESCRIPTOR_TYPE_HUB: u8 = 0x29;
+const DESCRIPTOR_TYPE_SUPERSPEED_HUB: u8 = 0x2A;
+
+const REQUEST_GET_STATUS: u8 = 0x00;
+const REQUEST_CLEAR_FEATURE: u8 = 0x01;
+const REQUEST_SET_FEATURE: u8 = 0x03;
+const REQUEST_GET_DESCRIPTOR: u8 = 0x06;
+
+const FEATURE_PORT_POWER: u16 = 8;
+const FEATURE_PORT_INDICATOR: u16 = 22;
+
+/// An opened hub, for issuing hub class requests.
+///
+/// See the [module documentation][self] for an example.
+pub struct Hub {
+    device: Device,
+    superspeed: bool,
+}
+
+impl Hub {
+    /// Wrap an opened device that is a hub.
+    ///
+    /// Returns an error of kind [`InvalidInput`][ErrorKind::InvalidInput] if
+    /// the device class is not hub.
+    pub fn new(device: Device) -> Result<Hub, Error> {
+        if device.device_descriptor().class() != CLASS_HUB {
+            return Err(Error::new(ErrorKind::InvalidInput, "device is not a hub"));
+        }
+
+        let superspeed = matches!(device.speed(), Some(Speed::Super | Speed::SuperPlus));
+        Ok(Hub { device, superspeed })
+    }
+
+    /// Get the wrapped device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Read the hub descriptor.
+    ///
+    /// The SuperSpeed hub descriptor is requested from SuperSpeed hubs, and
+    /// the USB 2.0 hub descriptor from others.
+    pub fn hub_descriptor(&self, timeout: Duration) -> Result<HubDescriptor, Error> {
+        let desc_type = if self.superspeed {
+            DESCRIPTOR_TYPE_SUPERSPEED_HUB
+        } else {
+            DESCRIPTOR_TYPE_HUB
+        };
+
+        let mut buf = [0; 64];
+        let len = self.device.control_in_blocking(
+            Control {
+                control_type: ControlType::Class,
+                recipient: Recipient::Device,
+                request: REQUEST_GET_DESCRIPTOR,
+                value: u16::from(desc_type) << 8,
+                index: 0,
+            },
+            &mut buf,
+            timeout,
+        )?;
+
+        HubDescriptor::new(&buf[..len])
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid hub descriptor"))
+    }
+
+    /// Get the status of a port, numbered from 1.
+    pub fn port_status(&self, port: u8, timeout: Duration) -> Result<PortStatus, Error> {
+        let mut buf = [0; 4];
+        let len = self.device.control_in_blocking(
+            port_control(REQUEST_GET_STATUS, 0, port),
+            &mut buf,
+            timeout,
+        )?;
+
+        if len != 4 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "GET_STATUS returned a short response",
+            ));
+        }
+
+        Ok(PortStatus {
+            status: u16::from_le_bytes([buf[0], buf[1]]),
+            change: u16::from_le_bytes([buf[2], buf[3]]),
+            superspeed: self.superspeed,
+        })
+    }
+
+    /// Enable a feature of a port with a `SET_FEATURE` request.
+    pub fn set_port_feature(
+        &self,
+        port: u8,
+        feature: PortFeature,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let control = port_control(REQUEST_SET_FEATURE, feature.selector(), port);
+        self.device.control_out_blocking(control, &[], timeout)?;
+        Ok(())
+    }
+
+    /// Disable a feature of a port, or acknowledge a change, with a
+    /// `CLEAR_FEATURE` request.
+    pub fn clear_port_feature(
+        &self,
+        port: u8,
+        feature: PortFeature,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let control = port_control(REQUEST_CLEAR_FEATURE, feature.selector(), port);
+        self.device.control_out_blocking(control, &[], timeout)?;
+        Ok(())
+    }
+
+    /// Turn off the power of a port, wait for `off_time`, and turn it on
+    /// again.
+    ///
+    /// Only hubs that switch power per port actually remove power from a
+    /// single port; check [`HubDescriptor::power_switching`]. Others ignore
+    /// the request or switch all ports together.
+    pub fn power_cycle_port(
+        &self,
+        port: u8,
+        off_time: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.clear_port_feature(port, PortFeature::Power, timeout)?;
+        thread::sleep(off_time);
+        self.set_port_feature(port, PortFeature::Power, timeout)
+    }
+
+    /// Set the indicator LED of a port.
+    ///
+    /// Only supported by hubs with port indicators; check
+    /// [`HubDescriptor::port_indicators`]. SuperSpeed hubs have no port
+    /// indicators.
+    pub fn set_port_indicator(
+        &self,
+        port: u8,
+        indicator: PortIndicator,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let mut control = port_control(REQUEST_SET_FEATURE, FEATURE_PORT_INDICATOR, port);
+        control.index |= (indicator as u16) << 8;
+        self.device.control_out_blocking(control, &[], timeout)?;
+        Ok(())
+    }
+}
+
+impl Debug for Hub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hub")
+            .field("superspeed", &self.superspeed)
+            .finish_non_exhaustive()
+    }
+}
+
+fn port_control(request: u8, value: u16, port: u8) -> Control {
+    Control {
+        control_type: ControlType::Class,
+        recipient: Recipient::Other,
+        request,
+        value,
+        index: port.into(),
+    }
+}
+
+/// A port feature selector for [`Hub::set_port_feature`] and
+/// [`Hub::clear_port_feature`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PortFeature {
+    /// `PORT_ENABLE`: can only be cleared, to disable the port.
+    Enable,
+
+    /// `PORT_SUSPEND`: suspend or resume the device on a USB 2.0 port.
+    Suspend,
+
+    /// `PORT_RESET`: can only be set, to reset the device on the port.
+    Reset,
+
+    /// `PORT_POWER`: power on or off the port.
+    Power,
+
+    /// `C_PORT_CONNECTION`: acknowledge a connection change.
+    ConnectionChange,
+
+    /// `C_PORT_ENABLE`: acknowledge an enable change.
+    EnableChange,
+
+    /// `C_PORT_SUSPEND`: acknowledge a suspend change.
+    SuspendChange,
+
+    /// `C_PORT_OVER_CURRENT`: acknowledge an over-current change.
+    OverCurrentChange,
+
+    /// `C_PORT_RESET`: acknowledge the completion of a reset.
+    ResetChange,
+}
+
+impl PortFeature {
+    fn selector(self) -> u16 {
+        match self {
+            PortFeature::Enable => 1,
+            PortFeature::Suspend => 2,
+            PortFeature::Reset => 4,
+            PortFeature::Power => FEATURE_PORT_POWER,
+            PortFeature::ConnectionChange => 16,
+            PortFeature::EnableChange => 17,
+            PortFeature::SuspendChange => 18,
+            PortFeature::OverCurrentChange => 19,
+            PortFeature::ResetChange => 20,
+        }
+    }
+}
+
+/// Selector for [`Hub::set_port_indicator`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PortIndicator {
+    /// The indicator shows the port status, controlled by the hub.
+    Automatic = 0,
+
+    /// Amber.
+    Amber = 1,
+
+    /// Green.
+    Green = 2,
+
+    /// Off.
+    Off = 3,
+}
+
+/// Status of a hub port, returned by [`Hub::port_status`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PortStatus {
+    status: u16,
+    change: u16,
+    superspeed: bool,
+}
+
+impl PortStatus {
+    /// Get the raw `wPortStatus` bits.
+    pub fn status_bits(&self) -> u16 {
+        self.status
+    }
+
+    /// Get the raw `wPortChange` bits.
+    pub fn change_bits(&self) -> u16 {
+        self.change
+    }
+
+    /// Whether a device is connected to the port.
+    pub fn connected(&self) -> bool {
+        self.status & (1 << 0) != 0
+    }
+
+    /// Whether the port is enabled.
+    pub fn enabled(&self) -> bool {
+        self.status & (1 << 1) != 0
+    }
+
+    /// Whether the device on the port is suspended. Always `false` for
+    /// SuperSpeed ports, which report link states instead.
+    pub fn suspended(&self) -> bool {
+        !self.superspeed && self.status & (1 << 2) != 0
+    }
+
+    /// Whether the port has an over-current condition.
+    pub fn over_current(&self) -> bool {
+        self.status & (1 << 3) != 0
+    }
+
+    /// Whether the port is being reset.
+    pub fn resetting(&self) -> bool {
+        self.status & (1 << 4) != 0
+    }
+
+    /// Whether the port is powered.
+    pub fn powered(&self) -> bool {
+        let bit = if self.superspeed { 9 } else { 8 };
+        self.status & (1 << bit) != 0
+    }
+
+    /// Whether the connection status changed, see
+    /// [`PortFeature::ConnectionChange`].
+    pub fn connection_changed(&self) -> bool {
+        self.change & (1 << 0) != 0
+    }
+
+    /// Whether the over-current status changed, see
+    /// [`PortFeature::OverCurrentChange`].
+    pub fn over_current_changed(&self) -> bool {
+        self.change & (1 << 3) != 0
+    }
+
+    /// Whether a reset completed, see [`PortFeature::ResetChange`].
+    pub fn reset_completed(&self) -> bool {
+        self.change & (1 << 4) != 0
+    }
+}
+
+/// A hub descriptor, returned by [`Hub::hub_descriptor`].
+///
+/// Either a USB 2.0 hub descriptor or a SuperSpeed hub descriptor, which
+/// differ in the fields after `bHubContrCurrent`.
+#[derive(Clone)]
+pub struct HubDescriptor(Vec<u8>);
+
+impl HubDescriptor {
+    fn new(buf: &[u8]) -> Option<HubDescriptor> {
+        let len = usize::from(*buf.first()?);
+        let buf = buf.get(..len)?;
+        let min_len = match *buf.get(1)? {
+            DESCRIPTOR_TYPE_HUB => 7,
+            DESCRIPTOR_TYPE_SUPERSPEED_HUB => 12,
+            _ => return None,
+        };
+        (len >= min_len).then(|| HubDescriptor(buf.to_vec()))
+    }
+
+    /// Get the bytes of the descriptor.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Whether this is a SuperSpeed hub descriptor.
+    pub fn is_superspeed(&self) -> bool {
+        self.0[1] == DESCRIPTOR_TYPE_SUPERSPEED_HUB
+    }
+
+    /// `bNbrPorts` descriptor field: Number of downstream ports.
+    #[doc(alias = "bNbrPorts")]
+    pub fn num_ports(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// `wHubCharacteristics` descriptor field.
+    #[doc(alias = "wHubCharacteristics")]
+    pub fn characteristics(&self) -> u16 {
+        u16::from_le_bytes([self.0[3], self.0[4]])
+    }
+
+    /// Power switching mode, from `wHubCharacteristics`.
+    pub fn power_switching(&self) -> PowerSwitching {
+        match self.characteristics() & 0x03 {
+            0 => PowerSwitching::Ganged,
+            1 => PowerSwitching::PerPort,
+            _ => PowerSwitching::None,
+        }
+    }
+
+    /// Whether the hub is part of a compound device, from
+    /// `wHubCharacteristics`.
+    pub fn compound_device(&self) -> bool {
+        self.characteristics() & (1 << 2) != 0
+    }
+
+    /// Whether the ports have indicator LEDs, from `wHubCharacteristics`.
+    pub fn port_indicators(&self) -> bool {
+        !self.is_superspeed() && self.characteristics() & (1 << 7) != 0
+    }
+
+    /// `bPwrOn2PwrGood` descriptor field: Time from powering on a port until
+    /// power is good.
+    #[doc(alias = "bPwrOn2PwrGood")]
+    pub fn power_on_to_power_good(&self) -> Duration {
+        Duration::from_millis(u64::from(self.0[5]) * 2)
+    }
+
+    /// `bHubContrCurrent` descriptor field: Maximum current requirement of
+    /// the hub controller, in mA.
+    #[doc(alias = "bHubContrCurrent")]
+    pub fn controller_current(&self) -> u8 {
+        self.0[6]
+    }
+
+    /// Whether the device on a port, numbered from 1, is non-removable, from
+    /// the `DeviceRemovable` bitmap.
+    pub fn non_removable(&self, port: u8) -> bool {
+        let offset = if self.is_superspeed() { 10 } else { 7 };
+        let byte = offset + usize::from(port / 8);
+        self.0.get(byte).is_some_and(|b| b & (1 << (port % 8)) != 0)
+    }
+}
+
+impl Debug for HubDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HubDescriptor")
+            .field("is_superspeed", &self.is_superspeed())
+            .field("num_ports", &self.num_ports())
+            .field(
+                "characteristics",
+                &format_args!("0x{:04X}", self.characteristics()),
+            )
+            .field("power_on_to_power_good", &self.power_on_to_power_good())
+            .field("controller_current", &self.controller_current())
+            .finish()
+    }
+}
+
+/// Power switching mode of a hub, from [`HubDescriptor::power_switching`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PowerSwitching {
+    /// All ports are powered on and off together.
+    Ganged,
+
+    /// Each port is powered on and off individually.
+    PerPort,
+
+    /// Ports are always powered while the hub is.
+    None,
+}
+
+#[test]
+fn test_hub_descriptor() {
+    let d = HubDescriptor::new(&[0x09, 0x29, 0x04, 0x89, 0x00, 0x32, 0x64, 0x04, 0xff]).unwrap();
+    assert!(!d.is_superspeed());
+    assert_eq!(d.num_ports(), 4);
+    assert_eq!(d.power_switching(), PowerSwitching::PerPort);
+    assert!(d.port_indicators());
+    assert_eq!(d.power_on_to_power_good(), Duration::from_millis(100));
+    assert!(d.non_removable(2) && !d.non_removable(1));
+
+    let d = HubDescriptor::new(&[
+        0x0c, 0x2a, 0x04, 0x09, 0x00, 0x32, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00,
+    ])
+    .unwrap();
+    assert!(d.is_superspeed());
+    assert!(d.non_removable(1) && !d.non_removable(2));
+
+    assert!(HubDescriptor::new(&[0x09, 0x04, 0x04, 0x89, 0x00, 0x32, 0x64, 0x04, 0xff]).is_none());
+    assert!(HubDescriptor::new(&[0x0c, 0x2a, 0x04]).is_none());
+}
+
+#[test]
+fn test_port_status() {
+    let usb2 = PortStatus {
+        status: 0x0103,
+        change: 0x0001,
+        superspeed: false,
+    };
+    assert!(usb2.connected() && usb2.enabled() && usb2.powered());
+    assert!(usb2.connection_changed() && !usb2.reset_completed());
+
+    let ss = PortStatus {
+        status: 0x0203,
+        change: 0,
+        superspeed: true,
+    };
+    assert!(ss.powered());
+}
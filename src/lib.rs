@@ -1,4 +1,5 @@
 #![warn(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 //! A new library for cross-platform low-level access to USB devices.
 //!
 //! `nusb` is comparable to the C library [libusb] and its Rust bindings [rusb],
@@ -45,6 +46,35 @@
 //! Nutshell](https://beyondlogic.org/usbnutshell/usb1.shtml) is a good
 //! overview.*
 //!
+//! ## Thread safety
+//!
+//! All public types are [`Send`], and all except [`RecoveryPolicy`] are also
+//! [`Sync`]. No external locking is needed to use them from multiple threads
+//! or tasks:
+//!
+//!  * [`Device`] and [`Interface`] are cheap to clone, and all of their methods
+//!    take `&self`. Control transfers may be submitted concurrently on the same
+//!    device or interface; the OS queues them and executes them one at a time.
+//!    Different interfaces of a device may be claimed and released
+//!    concurrently. Claim each interface once and clone the resulting
+//!    `Interface` to share it; claiming an interface that is already claimed
+//!    through the same `Device` is not supported.
+//!
+//!  * The methods of `Interface` that return a
+//!    [`TransferFuture`][`transfer::TransferFuture`], such as
+//!    [`bulk_out`][`Interface::bulk_out`], may be called concurrently for the
+//!    same endpoint. Transfers on an endpoint execute in the order they were
+//!    submitted, but the order between concurrent callers is unspecified.
+//!
+//!  * A [`Queue`][`transfer::Queue`] takes `&mut self` to submit transfers and
+//!    to receive completions, because its completions are returned in the
+//!    order of submission to a single owner. Rather than sharing one queue
+//!    behind a `Mutex`, give each task its own endpoint and queue, or have one
+//!    task own the queue and exchange data with the others over a channel.
+//!    The same applies to [`QueueStream`][`transfer::QueueStream`],
+//!    [`QueueSink`][`transfer::QueueSink`], [`BulkReader`][`transfer::BulkReader`]
+//!    and [`BulkWriter`][`transfer::BulkWriter`], which wrap a `Queue`.
+//!
 //! ## Logging
 //!
 //! `nusb` uses the [`log`](https://docs.rs/log) crate to log debug and error
@@ -57,6 +87,13 @@
 //!
 //! [gh-issues]: https://github.com/kevinmehall/nusb/issues
 //!
+//! ## `no_std` descriptor parsing
+//!
+//! The OS backends are behind the default `std` feature. With
+//! `default-features = false`, the crate is `no_std` and only requires
+//! `alloc`, and contains only the [`descriptors`] module, so embedded host
+//! stacks and firmware tests can reuse its parsers.
+//!
 //! ## Platform support
 //!
 //! ### Linux
@@ -113,26 +150,108 @@
 //!
 //! Users have access to USB devices by default, with no permission configuration needed.
 //! Devices with a kernel driver are not accessible.
+//!
+//! IOKit delivers transfer completions and hotplug notifications through a
+//! `CFRunLoop`. By default, `nusb` runs one on a background thread while any
+//! devices are open. Applications that already run a run loop can call
+//! `use_current_run_loop()` to receive events on it instead.
+//!
+//! ### FreeBSD
+//!
+//! `nusb` uses the [ugen(4)] device nodes and their `usb_fs` transfer
+//! ioctls on FreeBSD.
+//!
+//! A user must have write access on the `/dev/ugenX.Y` nodes to
+//! successfully open a device, which can be granted with [devfs.rules(5)].
+//! Hotplug events are received from [devd(8)], which must be running. If
+//! devd exits, a [`HotplugWatch`][hotplug::HotplugWatch] stops receiving
+//! events.
+//! Isochronous transfers are not supported.
+//!
+//! [ugen(4)]: https://man.freebsd.org/cgi/man.cgi?query=ugen&sektion=4
+//! [devfs.rules(5)]: https://man.freebsd.org/cgi/man.cgi?query=devfs.rules&sektion=5
+//! [devd(8)]: https://man.freebsd.org/cgi/man.cgi?query=devd&sektion=8
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::io;
 
+#[cfg(feature = "std")]
 mod platform;
 
 pub mod descriptors;
+#[cfg(feature = "std")]
 mod enumeration;
-pub use enumeration::{BusInfo, DeviceId, DeviceInfo, InterfaceInfo, Speed, UsbControllerType};
+#[cfg(feature = "std")]
+pub use enumeration::{
+    BusInfo, DeviceFingerprint, DeviceId, DeviceInfo, InterfaceInfo, Speed, UsbControllerType,
+    Version,
+};
+
+#[cfg(target_os = "macos")]
+#[cfg(feature = "std")]
+pub use enumeration::PlistValue;
 
+#[cfg(feature = "std")]
 mod device;
-pub use device::{Device, Interface};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(feature = "std")]
+pub use device::PowerState;
+#[cfg(feature = "std")]
+pub use device::{
+    AltSettingGuard, DetachRequest, Device, Interface, InterfaceAssociation, LinkPowerManagement,
+};
 
+#[cfg(feature = "std")]
 pub mod transfer;
 
+#[cfg(feature = "std")]
+mod open_options;
+#[cfg(feature = "std")]
+pub use open_options::DeviceOpenOptions;
+
+#[cfg(feature = "std")]
+mod disconnect;
+#[cfg(feature = "std")]
+pub use disconnect::Disconnected;
+
+#[cfg(feature = "std")]
+mod recovery;
+#[cfg(feature = "std")]
+pub use recovery::{Recovered, RecoveryPolicy, RecoveryStep};
+
+#[cfg(feature = "std")]
+mod topology;
+#[cfg(feature = "std")]
+pub use topology::{BusTree, DeviceNode};
+
+#[cfg(feature = "std")]
 pub mod hotplug;
 
+#[cfg(feature = "std")]
+pub mod hub;
+
+#[cfg(feature = "hid")]
+pub mod hid;
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "freebsd"
+))]
+#[cfg(feature = "std")]
+mod event_thread;
+
+#[cfg(feature = "std")]
 mod maybe_future;
+#[cfg(feature = "std")]
 pub use maybe_future::MaybeFuture;
 
 /// OS error returned from operations other than transfers.
+#[cfg(feature = "std")]
 pub type Error = io::Error;
 
 /// Get an iterator listing the connected devices.
@@ -145,11 +264,127 @@ pub type Error = io::Error;
 ///     .find(|dev| dev.vendor_id() == 0xAAAA && dev.product_id() == 0xBBBB)
 ///     .expect("device not connected");
 /// ```
+#[cfg(feature = "std")]
 pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>>
 {
     platform::list_devices()
 }
 
+/// Get an iterator listing the connected devices that match `filter`.
+///
+/// Like filtering the result of [`list_devices`], but the vendor ID, product
+/// ID and bus of each device are checked first, and the rest of its
+/// properties are only read if they match. This makes finding a device much
+/// faster when many devices are connected.
+///
+/// [`Filter::existing`][hotplug::Filter::existing] is ignored. Unlike
+/// [`list_devices`], this can also list root hubs, with
+/// [`Filter::root_hubs`][hotplug::Filter::root_hubs].
+///
+/// ### Example
+///
+/// ```no_run
+/// use nusb::{hotplug::Filter, MaybeFuture};
+/// let filter = Filter::new().vendor(0xAAAA).product(0xBBBB);
+/// let device = nusb::list_devices_filtered(filter).wait().unwrap()
+///     .next()
+///     .expect("device not connected");
+/// ```
+#[cfg(feature = "std")]
+pub fn list_devices_filtered(
+    filter: hotplug::Filter,
+) -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>> {
+    platform::list_devices_filtered(filter)
+}
+
+/// Get a [`Stream`][`futures_core::Stream`] of the connected devices,
+/// enumerated on a background thread.
+///
+/// Like [`list_devices`], but the filesystem, registry, or IOKit queries
+/// needed to enumerate the devices run on the thread pool of the `blocking`
+/// crate, so they never block an async executor. Devices are yielded as they
+/// are discovered, rather than once the whole bus has been scanned.
+///
+/// If the devices can't be listed, the stream yields the error and ends.
+///
+/// ### Example
+///
+/// ```no_run
+/// use futures_lite::{future::block_on, StreamExt};
+/// let mut devices = nusb::list_devices_async();
+/// block_on(async {
+///     while let Some(device) = devices.next().await {
+///         println!("{:?}", device.unwrap());
+///     }
+/// });
+/// ```
+#[cfg(feature = "std")]
+pub fn list_devices_async(
+) -> impl futures_core::Stream<Item = Result<DeviceInfo, Error>> + Send + Unpin {
+    let devices = std::iter::once_with(|| platform::list_devices().wait()).flat_map(
+        |res| -> Box<dyn Iterator<Item = Result<DeviceInfo, Error>> + Send> {
+            match res {
+                Ok(devices) => Box::new(devices.map(Ok)),
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            }
+        },
+    );
+    blocking::Unblock::new(devices)
+}
+
+/// Open the device with the specified [`DeviceId`].
+///
+/// Returns an error of kind [`NotFound`][io::ErrorKind::NotFound] if no
+/// connected device has that ID, for example because it was disconnected.
+///
+/// ### Example
+///
+/// ```no_run
+/// use nusb::MaybeFuture;
+/// let id = nusb::list_devices().wait().unwrap().next().unwrap().id();
+/// let device = nusb::open_by_id(id).wait().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn open_by_id(id: DeviceId) -> impl MaybeFuture<Output = Result<Device, Error>> {
+    maybe_future::blocking::Blocking::new(move || {
+        let device = list_devices()
+            .wait()?
+            .find(|d| d.id() == id)
+            .ok_or_else(|| Error::new(io::ErrorKind::NotFound, "device not found"))?;
+        device.open().wait()
+    })
+}
+
+/// Find the connected device with the specified [`DeviceFingerprint`].
+///
+/// Returns an error of kind [`NotFound`][io::ErrorKind::NotFound] if no
+/// connected device has that fingerprint. If several do, for example identical
+/// devices reporting the same serial number, the first one listed is returned.
+///
+/// ### Example
+///
+/// ```no_run
+/// use nusb::MaybeFuture;
+/// let fingerprint = nusb::list_devices().wait().unwrap().next().unwrap().fingerprint();
+/// // ...later, after the device may have been reconnected
+/// let device = nusb::find_device(&fingerprint).wait().unwrap().open().wait().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn find_device(
+    fingerprint: &DeviceFingerprint,
+) -> impl MaybeFuture<Output = Result<DeviceInfo, Error>> {
+    let fingerprint = fingerprint.clone();
+    maybe_future::blocking::Blocking::new(move || {
+        let filter = hotplug::Filter::new()
+            .vendor(fingerprint.vendor_id())
+            .product(fingerprint.product_id());
+        list_devices_filtered(filter)
+            .wait()?
+            .find(|d| d.fingerprint() == fingerprint)
+            .ok_or_else(|| Error::new(io::ErrorKind::NotFound, "device not found"))
+    })
+}
+
 /// Get an iterator listing the system USB buses.
 ///
 /// ### Example
@@ -170,12 +405,51 @@ pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = D
 /// ```
 ///
 /// ### Platform-specific notes
-/// * On Linux, the abstraction of the "bus" is a phony device known as the root hub. This device is available at bus.root_hub()
+/// * On Linux and FreeBSD, the abstraction of the "bus" is a phony device known as the root hub. This device is available at bus.root_hub()
 /// * On Android, this will only work on rooted devices due to sysfs path usage
+#[cfg(feature = "std")]
 pub fn list_buses() -> impl MaybeFuture<Output = Result<impl Iterator<Item = BusInfo>, Error>> {
     platform::list_buses()
 }
 
+/// Get the tree of hubs and devices connected to each bus.
+///
+/// The tree is built from the [port chains][DeviceInfo::port_chain] of the
+/// devices returned by [`list_devices`], so devices whose port chain is
+/// unknown are left out.
+///
+/// ### Example
+///
+/// Print an `lsusb -t`-style view:
+///
+/// ```no_run
+/// use nusb::{DeviceNode, MaybeFuture};
+///
+/// fn print_node(node: &DeviceNode, depth: usize) {
+///     let name = node.device().and_then(|d| d.product_string()).unwrap_or("?");
+///     println!("{:indent$}Port {}: {name}", "", node.port(), indent = depth * 4);
+///     for child in node.children() {
+///         print_node(child, depth + 1);
+///     }
+/// }
+///
+/// for tree in nusb::topology().wait().unwrap() {
+///     println!("Bus {}", tree.bus().bus_id());
+///     for node in tree.children() {
+///         print_node(node, 1);
+///     }
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn topology() -> impl MaybeFuture<Output = Result<Vec<BusTree>, Error>> {
+    maybe_future::blocking::Blocking::new(|| {
+        Ok(topology::build(
+            list_buses().wait()?,
+            list_devices().wait()?,
+        ))
+    })
+}
+
 /// Get a [`Stream`][`futures_core::Stream`] that yields an
 /// [event][`hotplug::HotplugEvent`] when a USB device is connected or
 /// disconnected from the system.
@@ -211,6 +485,61 @@ pub fn list_buses() -> impl MaybeFuture<Output = Result<impl Iterator<Item = Bus
 ///     when the `Connected` event is emitted. If you are immediately opening the device
 ///     and claiming an interface when receiving a `Connected` event,
 ///     you should retry after a short delay if opening or claiming fails.
+///   * On Linux, events are received from udev if it is running, and
+///     otherwise directly from the kernel, e.g. in containers without udev.
+///     Events from the kernel arrive before udev rules would have set the
+///     permissions of the device node, so opening the device may fail.
+#[cfg(feature = "std")]
 pub fn watch_devices() -> Result<hotplug::HotplugWatch, Error> {
-    Ok(hotplug::HotplugWatch(platform::HotplugWatch::new()?))
+    hotplug::HotplugWatch::new(None)
+}
+
+/// Get a [`Stream`][`futures_core::Stream`] that yields an
+/// [event][`hotplug::HotplugEvent`] when a USB device matching `filter` is
+/// connected or disconnected from the system.
+///
+/// Like [`watch_devices`], but devices that don't match the
+/// [`Filter`][hotplug::Filter] are left out, and with
+/// [`Filter::existing`][hotplug::Filter::existing] the stream first yields
+/// a `Connected` event for each matching device that is already connected.
+/// A `Disconnected` event is only returned for a device that was returned
+/// in a `Connected` event.
+///
+/// ## Example
+///
+/// ```no_run
+/// use nusb::hotplug::{Filter, HotplugEvent};
+/// let filter = Filter::new().vendor(0x1234).class(0xFF).existing(true);
+/// let watch = nusb::watch_devices_filtered(filter).unwrap();
+/// for event in futures_lite::stream::block_on(watch) {
+///     match event {
+///         HotplugEvent::Connected(d) => println!("connected: {d:?}"),
+///         HotplugEvent::Disconnected(id) => println!("disconnected: {id:?}"),
+///     }
+/// }
+/// ```
+///
+/// ### Platform-specific notes:
+///
+///   * See [`watch_devices`].
+#[cfg(feature = "std")]
+pub fn watch_devices_filtered(filter: hotplug::Filter) -> Result<hotplug::HotplugWatch, Error> {
+    hotplug::HotplugWatch::new(Some(filter))
+}
+
+/// Deliver IOKit events on the calling thread's `CFRunLoop` instead of a
+/// background thread started by `nusb`.
+///
+/// Transfer completions and hotplug events for devices opened and watches
+/// created after this call are dispatched from the calling thread's run loop
+/// (in the common modes), so the application must keep it running, for
+/// example as the main thread's run loop of a Cocoa application. Transfers
+/// will not complete while it is not running.
+///
+/// Returns an error if devices are already open or hotplug watches exist,
+/// because their events are bound to the previous run loop.
+#[cfg(target_os = "macos")]
+#[cfg(feature = "std")]
+pub fn use_current_run_loop() -> Result<(), Error> {
+    platform::use_current_run_loop()
 }
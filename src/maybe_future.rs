@@ -29,7 +29,8 @@ pub trait MaybeFuture: IntoFuture {
     target_os = "linux",
     target_os = "android",
     target_os = "windows",
-    target_os = "macos"
+    target_os = "macos",
+    target_os = "freebsd"
 ))]
 pub mod blocking {
     use super::MaybeFuture;
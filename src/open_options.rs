@@ -0,0 +1,96 @@
+/// Options for opening a device and preparing it for use with
+/// [`DeviceInfo::open_with`][crate::DeviceInfo::open_with].
+///
+/// The steps are performed in order: open the device, select the
+/// configuration, then claim the interfaces. If a step fails, the previous
+/// ones are undone as far as possible before the error is returned.
+///
+/// ### Example
+///
+/// ```no_run
+/// use nusb::{DeviceOpenOptions, MaybeFuture};
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// let (device, interfaces) = di
+///     .open_with(
+///         DeviceOpenOptions::new()
+///             .detach_drivers(true)
+///             .set_configuration(1)
+///             .claim(&[0, 1]),
+///     )
+///     .wait()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeviceOpenOptions {
+    pub(crate) detach_drivers: bool,
+    pub(crate) configuration: Option<u8>,
+    pub(crate) claim: Vec<u8>,
+    pub(crate) shared: bool,
+}
+
+impl DeviceOpenOptions {
+    /// Create options that only open the device.
+    pub fn new() -> DeviceOpenOptions {
+        DeviceOpenOptions::default()
+    }
+
+    /// Detach kernel drivers from the interfaces before changing the
+    /// configuration or claiming them.
+    ///
+    /// The device's [detach policy][crate::Device::set_detach_policy] does
+    /// not apply, since the device is not available before it is opened.
+    ///
+    /// ### Platform-specific notes
+    /// * Kernel drivers can only be detached on Linux and FreeBSD. On other
+    ///   platforms this option has no effect.
+    pub fn detach_drivers(mut self, detach: bool) -> DeviceOpenOptions {
+        self.detach_drivers = detach;
+        self
+    }
+
+    /// Select the configuration with the specified `bConfigurationValue`, if
+    /// it is not already active.
+    ///
+    /// See [`Device::set_configuration`][crate::Device::set_configuration]
+    /// for platform support.
+    pub fn set_configuration(mut self, configuration: u8) -> DeviceOpenOptions {
+        self.configuration = Some(configuration);
+        self
+    }
+
+    /// Claim the specified interfaces, as by
+    /// [`Device::claim_interfaces`][crate::Device::claim_interfaces].
+    ///
+    /// The interfaces are returned by `open_with` in the same order.
+    pub fn claim(mut self, interfaces: &[u8]) -> DeviceOpenOptions {
+        self.claim = interfaces.to_vec();
+        self
+    }
+
+    /// Open the device without taking exclusive access to it, so that other
+    /// processes can open it and claim its interfaces at the same time.
+    ///
+    /// Without claiming any interface, the device can be used for
+    /// control-only access, e.g. by a diagnostic tool reading descriptors
+    /// with [`Device::get_descriptor`][crate::Device::get_descriptor] or
+    /// issuing requests with
+    /// [`Device::control_in`][crate::Device::control_in] while another
+    /// process streams data from an interface.
+    ///
+    /// ### Platform-specific notes
+    /// * On macOS, devices are otherwise opened for exclusive access if
+    ///   possible. With this option, exclusive access is only requested by
+    ///   the methods that require it, like
+    ///   [`Device::set_configuration`][crate::Device::set_configuration] and
+    ///   [`Device::reset`][crate::Device::reset].
+    /// * On Linux, devices are never opened for exclusive access, so this
+    ///   option has no effect. The kernel claims an interface for the
+    ///   requests that have it as recipient, so these fail if another
+    ///   process has claimed it.
+    /// * On Windows, this option has no effect. Control transfers go through
+    ///   a WinUSB interface handle, which is exclusive.
+    pub fn shared(mut self, shared: bool) -> DeviceOpenOptions {
+        self.shared = shared;
+        self
+    }
+}
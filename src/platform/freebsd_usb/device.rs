@@ -0,0 +1,734 @@
+use std::{
+    cell::UnsafeCell,
+    ffi::c_void,
+    io::ErrorKind,
+    ptr::null_mut,
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, AtomicU16, AtomicU8, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
+};
+
+use log::{debug, error, warn};
+use rustix::{
+    event::PollFlags,
+    fd::{AsRawFd, OwnedFd},
+    io::Errno,
+};
+use slab::Slab;
+
+use super::{
+    enumeration::{open_device, speed_from_raw},
+    events,
+    ugen::{self, FsEndpoint},
+};
+use crate::descriptors::{
+    parse_concatenated_config_descriptors, ConfigurationDescriptor, DeviceDescriptor,
+    DESCRIPTOR_LEN_DEVICE,
+};
+use crate::maybe_future::{blocking::Blocking, MaybeFuture};
+use crate::transfer::{
+    notify_completion, wait_transfer, Control, ControlIn, ControlOut, ControlType, ObserverSlot,
+    Recipient, TransferError, TransferHandle, TransferType,
+};
+use crate::{disconnect::DisconnectSignal, DeviceInfo, Error, Speed};
+
+static DEVICES: Mutex<Slab<Weak<FreeBsdDevice>>> = Mutex::new(Slab::new());
+
+pub(crate) struct FreeBsdDevice {
+    /// Used for `usb_fs` transfers, and polled by the event thread.
+    fd: Arc<OwnedFd>,
+
+    /// Used for all other requests. Changing the configuration or an
+    /// alternate setting tears down the `usb_fs` state of the file descriptor
+    /// it is done on, so like libusb, these go through a second one.
+    ctrl_fd: OwnedFd,
+
+    events_id: usize,
+    registration: usize,
+
+    /// Device descriptor followed by the configuration descriptors, in
+    /// order of their index.
+    descriptors: Vec<u8>,
+
+    active_config: AtomicU8,
+    speed: Option<Speed>,
+
+    /// Transfer slots registered with `USB_FS_INIT`, which the kernel reads
+    /// when a transfer is started and writes when it is completed.
+    endpoints: Box<[UnsafeCell<FsEndpoint>]>,
+
+    /// `user_data` of the transfer pending on each slot, or null.
+    pending: Box<[AtomicPtr<c_void>]>,
+
+    free_slots: Mutex<Vec<u8>>,
+
+    /// Endpoint each slot is opened for, so that the transfers of an
+    /// endpoint can be stopped by `abort_pipe`. Locked while stopping them,
+    /// so that no slot is reopened for another endpoint in the meantime.
+    slot_endpoints: Mutex<Box<[Option<u8>]>>,
+
+    disconnected: AtomicBool,
+
+    pub(crate) observer: ObserverSlot,
+    pub(crate) disconnect: DisconnectSignal,
+}
+
+// SAFETY: a slot in `endpoints` is only accessed by the owner of its
+// `TransferData` while idle, and by the kernel and the event thread while
+// the transfer is pending, as tracked by `pending`.
+unsafe impl Send for FreeBsdDevice {}
+unsafe impl Sync for FreeBsdDevice {}
+
+impl FreeBsdDevice {
+    pub(crate) fn from_device_info(
+        d: &DeviceInfo,
+        _shared: bool,
+    ) -> impl MaybeFuture<Output = Result<Arc<FreeBsdDevice>, Error>> {
+        let busnum = d.busnum();
+        let addr = d.device_address();
+
+        Blocking::new(move || {
+            let fd = open_device(busnum, addr)?;
+            let ctrl_fd = open_device(busnum, addr)?;
+            Self::create_inner(fd, ctrl_fd)
+        })
+    }
+
+    fn create_inner(fd: OwnedFd, ctrl_fd: OwnedFd) -> Result<Arc<FreeBsdDevice>, Error> {
+        let info = ugen::get_device_info(&ctrl_fd)?;
+        let mut descriptors = ugen::get_device_desc(&ctrl_fd)?.to_vec();
+        let Some(device_descriptor) = DeviceDescriptor::new(&descriptors) else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "invalid device descriptor",
+            ));
+        };
+
+        for index in 0..device_descriptor.num_configurations() {
+            let config = ugen::get_full_config_desc(&ctrl_fd, index)
+                .inspect_err(|e| warn!("Failed to read configuration descriptor {index}: {e}"))?;
+            descriptors.extend_from_slice(&config);
+        }
+
+        let endpoints: Box<[_]> = (0..ugen::FS_MAX_SLOTS)
+            .map(|_| UnsafeCell::new(FsEndpoint::default()))
+            .collect();
+
+        // SAFETY: `endpoints` is owned by the device, which calls
+        // `fs_uninit` before it is freed.
+        unsafe {
+            ugen::fs_init(
+                &fd,
+                UnsafeCell::raw_get(endpoints.as_ptr()),
+                ugen::FS_MAX_SLOTS as u8,
+            )?;
+        }
+
+        let fd = Arc::new(fd);
+        let mut devices = DEVICES.lock().unwrap();
+        let entry = devices.vacant_entry();
+        let events_id = entry.key();
+        let registration = events::register_fd(
+            fd.clone(),
+            events::Tag::Device(events_id),
+            PollFlags::IN | PollFlags::OUT,
+        )?;
+
+        let arc = Arc::new_cyclic(|weak| {
+            entry.insert(weak.clone());
+            FreeBsdDevice {
+                fd,
+                ctrl_fd,
+                events_id,
+                registration,
+                descriptors,
+                active_config: AtomicU8::new(0),
+                speed: speed_from_raw(info.speed),
+                endpoints,
+                pending: (0..ugen::FS_MAX_SLOTS)
+                    .map(|_| AtomicPtr::new(null_mut()))
+                    .collect(),
+                free_slots: Mutex::new((0..ugen::FS_MAX_SLOTS as u8).rev().collect()),
+                slot_endpoints: Mutex::new(vec![None; ugen::FS_MAX_SLOTS].into()),
+                disconnected: AtomicBool::new(false),
+                observer: ObserverSlot::default(),
+                disconnect: DisconnectSignal::default(),
+            }
+        });
+        drop(devices);
+
+        arc.active_config
+            .store(arc.config_value(info.config_index), Ordering::SeqCst);
+
+        debug!(
+            "Opened device fd={} with id {}",
+            arc.fd.as_raw_fd(),
+            arc.events_id
+        );
+
+        Ok(arc)
+    }
+
+    pub(crate) fn handle_usb_events(id: usize) {
+        let device = DEVICES.lock().unwrap().get(id).and_then(|w| w.upgrade());
+        if let Some(device) = device {
+            device.handle_events();
+        }
+    }
+
+    fn handle_events(&self) {
+        loop {
+            match ugen::fs_complete(&*self.fd) {
+                Ok(slot) => {
+                    let Some(pending) = self.pending.get(usize::from(slot)) else {
+                        error!("USB_FS_COMPLETE returned invalid slot {slot}");
+                        continue;
+                    };
+                    let user_data = pending.swap(null_mut(), Ordering::SeqCst);
+                    debug!(
+                        "Transfer on slot {slot} of device {} completed",
+                        self.events_id
+                    );
+                    if !user_data.is_null() {
+                        // SAFETY: the transfer was pending, and the kernel is
+                        // done with it.
+                        unsafe { notify_completion::<super::TransferData>(user_data) }
+                    }
+                }
+                Err(Errno::BUSY) => break,
+                Err(Errno::NXIO | Errno::NODEV) => {
+                    self.handle_disconnect();
+                    break;
+                }
+                Err(e) => {
+                    error!("Unexpected error {e} from USB_FS_COMPLETE");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_disconnect(&self) {
+        if self.disconnected.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        debug!("Device {} disconnected", self.events_id);
+
+        // poll returns events continuously on a disconnected device, so stop
+        // polling it.
+        events::unregister_fd(self.registration);
+        self.disconnect.notify();
+
+        // The kernel won't complete the transfers that are still pending.
+        for (slot, pending) in self.pending.iter().enumerate() {
+            let user_data = pending.swap(null_mut(), Ordering::SeqCst);
+            if !user_data.is_null() {
+                // SAFETY: taking `user_data` gives us ownership of the slot
+                unsafe {
+                    (*self.endpoints[slot].get()).status = -Errno::NODEV.raw_os_error();
+                    notify_completion::<super::TransferData>(user_data)
+                }
+            }
+        }
+    }
+
+    pub(crate) fn device_descriptor(&self) -> DeviceDescriptor {
+        DeviceDescriptor::new(&self.descriptors).unwrap()
+    }
+
+    pub(crate) fn configuration_descriptors(
+        &self,
+    ) -> impl Iterator<Item = ConfigurationDescriptor<'_>> {
+        parse_concatenated_config_descriptors(&self.descriptors[DESCRIPTOR_LEN_DEVICE as usize..])
+    }
+
+    /// Map a configuration index used by the ugen ioctls to its
+    /// `bConfigurationValue`, or `0` if unconfigured.
+    fn config_value(&self, index: u8) -> u8 {
+        if index == ugen::UNCONFIG_INDEX {
+            return 0;
+        }
+        self.configuration_descriptors()
+            .nth(index.into())
+            .map_or(0, |c| c.configuration_value())
+    }
+
+    /// Find the position of an interface in the active configuration, which
+    /// selects it in the ugen ioctls.
+    fn interface_index(&self, interface_number: u8) -> Result<u8, Error> {
+        let active_config = self.active_configuration_value();
+        self.configuration_descriptors()
+            .find(|c| c.configuration_value() == active_config)
+            .and_then(|c| {
+                c.interfaces()
+                    .position(|i| i.interface_number() == interface_number)
+            })
+            .map(|i| i as u8)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("interface {interface_number} not found in active configuration"),
+                )
+            })
+    }
+
+    pub(crate) fn update_active_configuration(&self, configuration: u8) {
+        self.active_config.store(configuration, Ordering::SeqCst);
+    }
+
+    pub(crate) fn active_configuration_value(&self) -> u8 {
+        self.active_config.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_configuration(
+        self: Arc<Self>,
+        configuration: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            let index = if configuration == 0 {
+                ugen::UNCONFIG_INDEX
+            } else {
+                self.configuration_descriptors()
+                    .position(|c| c.configuration_value() == configuration)
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::NotFound,
+                            format!("configuration {configuration} not found"),
+                        )
+                    })? as u8
+            };
+            ugen::set_config(&self.ctrl_fd, index)?;
+            self.active_config.store(configuration, Ordering::SeqCst);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn reset(self: Arc<Self>) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            ugen::re_enumerate(&self.ctrl_fd)?;
+            Ok(())
+        })
+    }
+
+    // `USB_DO_REQUEST` always applies the kernel's default timeout, so
+    // blocking control transfers go through a `usb_fs` slot like async ones,
+    // and are cancelled if they don't complete within `timeout`.
+    pub fn control_in_blocking(
+        self: &Arc<Self>,
+        control: Control,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        let mut t = self.make_control_transfer();
+        t.submit(ControlIn {
+            control_type: control.control_type,
+            recipient: control.recipient,
+            request: control.request,
+            value: control.value,
+            index: control.index,
+            length: data.len().try_into().expect("length must fit in u16"),
+        });
+        let c = wait_transfer::<ControlIn>(t, timeout);
+        c.status?;
+        let len = c.data.len().min(data.len());
+        data[..len].copy_from_slice(&c.data[..len]);
+        Ok(len)
+    }
+
+    pub fn control_out_blocking(
+        self: &Arc<Self>,
+        control: Control,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        let mut t = self.make_control_transfer();
+        t.submit(ControlOut {
+            control_type: control.control_type,
+            recipient: control.recipient,
+            request: control.request,
+            value: control.value,
+            index: control.index,
+            data,
+        });
+        let c = wait_transfer::<ControlOut>(t, timeout);
+        c.status?;
+        Ok(c.data.actual_length())
+    }
+
+    pub(crate) fn make_control_transfer(self: &Arc<Self>) -> TransferHandle<super::TransferData> {
+        TransferHandle::new(super::TransferData::new(
+            self.clone(),
+            None,
+            0,
+            TransferType::Control,
+        ))
+    }
+
+    pub(crate) fn claim_interface(
+        self: Arc<Self>,
+        interface_number: u8,
+    ) -> impl MaybeFuture<Output = Result<Arc<FreeBsdInterface>, Error>> {
+        Blocking::new(move || {
+            let interface_index = self.interface_index(interface_number)?;
+
+            // ugen has no claim operation, but a kernel driver attached to
+            // the interface would conflict with its use.
+            match ugen::iface_driver_active(&self.ctrl_fd, interface_index) {
+                Ok(()) => return Err(self.claim_error(interface_number, interface_index)),
+                Err(Errno::NOENT) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            debug!(
+                "Claimed interface {interface_number} on device id {dev}",
+                dev = self.events_id
+            );
+            Ok(Arc::new(FreeBsdInterface::new(
+                self,
+                interface_number,
+                interface_index,
+            )))
+        })
+    }
+
+    /// Name the kernel driver attached to the interface in the error.
+    fn claim_error(&self, interface_number: u8, interface_index: u8) -> Error {
+        let kind = Error::from(Errno::BUSY).kind();
+        match ugen::get_iface_driver(&self.ctrl_fd, interface_index) {
+            Ok(driver) => Error::new(
+                kind,
+                format!("interface {interface_number} is in use by kernel driver `{driver}`"),
+            ),
+            Err(_) => Error::new(
+                kind,
+                format!("interface {interface_number} is in use by a kernel driver"),
+            ),
+        }
+    }
+
+    pub(crate) fn detach_and_claim_interface(
+        self: Arc<Self>,
+        interface_number: u8,
+    ) -> impl MaybeFuture<Output = Result<Arc<FreeBsdInterface>, Error>> {
+        Blocking::new(move || {
+            let interface_index = self.interface_index(interface_number)?;
+            ugen::iface_driver_detach(&self.ctrl_fd, interface_index)?;
+            debug!(
+                "Detached and claimed interface {interface_number} on device id {dev}",
+                dev = self.events_id
+            );
+            Ok(Arc::new(FreeBsdInterface::new(
+                self,
+                interface_number,
+                interface_index,
+            )))
+        })
+    }
+
+    pub(crate) fn speed(&self) -> Option<Speed> {
+        self.speed
+    }
+
+    /// Take a free transfer slot, or `None` if all are in use.
+    pub(super) fn alloc_slot(&self) -> Option<u8> {
+        self.free_slots.lock().unwrap().pop()
+    }
+
+    /// Return a slot taken with `alloc_slot`, closing it if it was opened.
+    pub(super) fn release_slot(&self, slot: u8, opened: bool) {
+        if opened {
+            self.close_slot(slot);
+        }
+        self.free_slots.lock().unwrap().push(slot);
+    }
+
+    pub(super) fn close_slot(&self, slot: u8) {
+        self.slot_endpoints.lock().unwrap()[usize::from(slot)] = None;
+        if let Err(e) = ugen::fs_close(&*self.fd, slot) {
+            debug!("Failed to close slot {slot}: {e}");
+        }
+    }
+
+    /// Bind `slot` to `endpoint`, with a kernel buffer of `max_bufsize`
+    /// bytes.
+    pub(super) fn open_slot(
+        &self,
+        slot: u8,
+        endpoint: u8,
+        max_bufsize: u32,
+        max_frames: u32,
+    ) -> Result<(), Errno> {
+        let mut slot_endpoints = self.slot_endpoints.lock().unwrap();
+        ugen::fs_open(&*self.fd, slot, endpoint, max_bufsize, max_frames)
+            .inspect_err(|e| debug!("Failed to open slot {slot} for ep {endpoint:02x}: {e}"))?;
+        slot_endpoints[usize::from(slot)] = Some(endpoint);
+        Ok(())
+    }
+
+    /// SAFETY: requires that no transfer is pending on `slot`, and that the
+    /// caller owns it.
+    #[allow(clippy::mut_from_ref)]
+    pub(super) unsafe fn slot_mut(&self, slot: u8) -> &mut FsEndpoint {
+        unsafe { &mut *self.endpoints[usize::from(slot)].get() }
+    }
+
+    /// Start the transfer described by `slot`, completing it with an error
+    /// if it can't be started.
+    ///
+    /// SAFETY: the slot and the buffers it references must be filled in and
+    /// remain valid until the transfer completes.
+    pub(super) unsafe fn start_transfer(&self, slot: u8, user_data: *mut c_void) {
+        let pending = &self.pending[usize::from(slot)];
+        pending.store(user_data, Ordering::SeqCst);
+
+        // Checked after storing `user_data`, so that either this or the
+        // disconnect handler completes the transfer.
+        let res = if self.disconnected.load(Ordering::SeqCst) {
+            Err(Errno::NODEV)
+        } else {
+            unsafe { ugen::fs_start(&*self.fd, slot) }
+        };
+
+        match res {
+            Ok(()) => debug!("Started transfer on slot {slot}"),
+            Err(e) => {
+                let user_data = pending.swap(null_mut(), Ordering::SeqCst);
+                if !user_data.is_null() {
+                    debug!("Failed to start transfer on slot {slot}: {e}");
+                    // SAFETY: the transfer was not started, so we still own
+                    // the slot and can complete it in place of the handler.
+                    unsafe {
+                        self.slot_mut(slot).status = -e.raw_os_error();
+                        notify_completion::<super::TransferData>(user_data)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stop the transfers pending on `endpoint`.
+    pub(super) fn stop_endpoint_transfers(&self, endpoint: u8) {
+        let slot_endpoints = self.slot_endpoints.lock().unwrap();
+        for (slot, ep) in slot_endpoints.iter().enumerate() {
+            if *ep == Some(endpoint) && !self.pending[slot].load(Ordering::SeqCst).is_null() {
+                self.stop_transfer(slot as u8);
+            }
+        }
+    }
+
+    /// Stop the transfer pending on `slot`, which then completes with
+    /// `USB_ERR_CANCELLED`.
+    pub(super) fn stop_transfer(&self, slot: u8) {
+        if let Err(e) = ugen::fs_stop(&*self.fd, slot) {
+            debug!("Failed to stop transfer on slot {slot}: {e}");
+        }
+    }
+}
+
+impl Drop for FreeBsdDevice {
+    fn drop(&mut self) {
+        debug!("Closing device {}", self.events_id);
+        if !*self.disconnected.get_mut() {
+            events::unregister_fd(self.registration);
+        }
+
+        // The event thread may still hold the file descriptor, so detach the
+        // slots before they are freed.
+        if let Err(e) = ugen::fs_uninit(&*self.fd) {
+            debug!("USB_FS_UNINIT failed: {e}");
+        }
+        DEVICES.lock().unwrap().remove(self.events_id);
+    }
+}
+
+pub(crate) struct FreeBsdInterface {
+    pub(crate) interface_number: u8,
+    pub(crate) device: Arc<FreeBsdDevice>,
+
+    /// Position of the interface in the active configuration, which selects
+    /// it in the ugen ioctls.
+    interface_index: u8,
+    state: Mutex<InterfaceState>,
+
+    /// Bit `n` is set if `OUT` transfers on endpoint `n` are terminated with
+    /// a zero-length packet.
+    send_zlp: AtomicU16,
+}
+
+#[derive(Default)]
+struct InterfaceState {
+    alt_setting: u8,
+}
+
+impl FreeBsdInterface {
+    fn new(
+        device: Arc<FreeBsdDevice>,
+        interface_number: u8,
+        interface_index: u8,
+    ) -> FreeBsdInterface {
+        FreeBsdInterface {
+            interface_number,
+            device,
+            interface_index,
+            state: Mutex::new(Default::default()),
+            send_zlp: AtomicU16::new(0),
+        }
+    }
+
+    pub(crate) fn make_transfer(
+        self: &Arc<Self>,
+        endpoint: u8,
+        ep_type: TransferType,
+    ) -> TransferHandle<super::TransferData> {
+        TransferHandle::new(super::TransferData::new(
+            self.device.clone(),
+            Some(self.clone()),
+            endpoint,
+            ep_type,
+        ))
+    }
+
+    pub fn control_in_blocking(
+        &self,
+        control: Control,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.device.control_in_blocking(control, data, timeout)
+    }
+
+    pub fn control_out_blocking(
+        &self,
+        control: Control,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.device.control_out_blocking(control, data, timeout)
+    }
+
+    /// Map between an alternate setting's position in the interface's
+    /// descriptors, as used by the ugen ioctls, and its `bAlternateSetting`.
+    fn alt_settings(&self) -> Vec<u8> {
+        let active_config = self.device.active_configuration_value();
+        self.device
+            .configuration_descriptors()
+            .find(|c| c.configuration_value() == active_config)
+            .and_then(|c| {
+                c.interfaces()
+                    .find(|i| i.interface_number() == self.interface_number)
+                    .map(|i| i.alt_settings().map(|a| a.alternate_setting()).collect())
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_alt_setting(&self) -> u8 {
+        self.state.lock().unwrap().alt_setting
+    }
+
+    pub fn refresh_alt_setting(&self) -> Result<u8, Error> {
+        let mut state = self.state.lock().unwrap();
+        let alt_index = ugen::get_alt_interface(&self.device.ctrl_fd, self.interface_index)?;
+        let alt_setting = self
+            .alt_settings()
+            .get(usize::from(alt_index))
+            .copied()
+            .unwrap_or(alt_index);
+        state.alt_setting = alt_setting;
+        Ok(alt_setting)
+    }
+
+    pub fn set_alt_setting(
+        self: Arc<Self>,
+        alt_setting: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            let mut state = self.state.lock().unwrap();
+            let alt_index = self
+                .alt_settings()
+                .iter()
+                .position(|&a| a == alt_setting)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::NotFound,
+                        format!("alternate setting {alt_setting} not found"),
+                    )
+                })?;
+            debug!(
+                "Set interface {} alt setting to {alt_setting}",
+                self.interface_number
+            );
+            ugen::set_alt_interface(&self.device.ctrl_fd, self.interface_index, alt_index as u8)?;
+            state.alt_setting = alt_setting;
+            Ok(())
+        })
+    }
+
+    pub fn clear_halt(
+        self: Arc<Self>,
+        endpoint: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            debug!("Clear halt, endpoint {endpoint:02x}");
+
+            // ugen also resets the host-side data toggle when it sees this
+            // request.
+            self.device.control_out_blocking(
+                Control {
+                    control_type: ControlType::Standard,
+                    recipient: Recipient::Endpoint,
+                    request: 0x01,
+                    value: 0,
+                    index: endpoint.into(),
+                },
+                &[],
+                Duration::from_secs(1),
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn reset_pipe(
+        self: Arc<Self>,
+        endpoint: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            debug!("Reset pipe, endpoint {endpoint:02x}");
+            Err(Error::new(
+                ErrorKind::Unsupported,
+                "ugen can't reset the host side of an endpoint alone",
+            ))
+        })
+    }
+
+    pub fn set_send_zlp(&self, endpoint: u8, enable: bool) -> Result<(), Error> {
+        // Applied as `USB_FS_FLAG_FORCE_SHORT` to each transfer when submitted
+        let bit = 1 << (endpoint & 0x0f);
+        if enable {
+            self.send_zlp.fetch_or(bit, Ordering::Relaxed);
+        } else {
+            self.send_zlp.fetch_and(!bit, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn abort_pipe(&self, endpoint: u8) -> Result<(), Error> {
+        // ugen only stops individual slots, so stop each one with a transfer
+        // pending on the endpoint.
+        self.device.stop_endpoint_transfers(endpoint);
+        Ok(())
+    }
+
+    pub fn frame_number(&self) -> Result<u64, Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "ugen does not provide the bus frame number",
+        ))
+    }
+
+    pub(crate) fn send_zlp(&self, endpoint: u8) -> bool {
+        endpoint & 0x80 == 0
+            && self.send_zlp.load(Ordering::Relaxed) & (1 << (endpoint & 0x0f)) != 0
+    }
+}
@@ -0,0 +1,202 @@
+use std::ffi::CString;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use log::{debug, warn};
+use rustix::{
+    fd::OwnedFd,
+    fs::{Mode, OFlags},
+};
+
+use super::ugen;
+use crate::descriptors::{ConfigurationDescriptor, DeviceDescriptor};
+use crate::enumeration::InterfaceInfo;
+use crate::hotplug::Filter;
+use crate::maybe_future::{MaybeFuture, Ready};
+use crate::{BusInfo, DeviceInfo, Error, Speed, UsbControllerType};
+
+/// Path of the ugen device node for a device.
+pub(crate) fn device_path(busnum: u8, addr: u8) -> PathBuf {
+    PathBuf::from(format!("/dev/ugen{busnum}.{addr}"))
+}
+
+/// Parse the bus and address from a ugen device name, like `ugen0.2`.
+pub(crate) fn parse_ugen_name(name: &str) -> Option<(u8, u8)> {
+    let (bus, addr) = name.strip_prefix("ugen")?.split_once('.')?;
+    Some((bus.parse().ok()?, addr.parse().ok()?))
+}
+
+#[test]
+fn test_parse_ugen_name() {
+    assert_eq!(parse_ugen_name("ugen0.2"), Some((0, 2)));
+    assert_eq!(parse_ugen_name("ugen12.104"), Some((12, 104)));
+    assert_eq!(parse_ugen_name("ugen0.2.0"), None);
+    assert_eq!(parse_ugen_name("usb"), None);
+}
+
+fn bus_id(busnum: u8) -> String {
+    format!("usbus{busnum}")
+}
+
+pub(crate) fn open_device(busnum: u8, addr: u8) -> Result<OwnedFd, Error> {
+    let path = device_path(busnum, addr);
+    rustix::fs::open(&path, OFlags::RDWR | OFlags::CLOEXEC, Mode::empty())
+        .inspect_err(|e| debug!("Failed to open device {path:?}: {e}"))
+        .map_err(Error::from)
+}
+
+pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>>
+{
+    list_devices_filtered(Filter::new())
+}
+
+pub fn list_devices_filtered(
+    filter: Filter,
+) -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>> {
+    Ready((move || {
+        Ok(list_ugen()?.filter_map(move |(busnum, addr)| {
+            if !filter.matches_bus(&bus_id(busnum)) {
+                return None;
+            }
+
+            probe_device(busnum, addr)
+                .inspect_err(|e| warn!("Failed to probe ugen{busnum}.{addr}: {e}; ignoring device"))
+                .ok()
+                .filter(|d| filter.includes_root_hubs() || !d.port_chain.is_empty())
+                .filter(|d| filter.matches(d))
+        }))
+    })())
+}
+
+/// List the bus and address of the `ugen` device nodes.
+fn list_ugen() -> Result<impl Iterator<Item = (u8, u8)>, Error> {
+    let mut devices: Vec<_> = fs::read_dir("/dev")?
+        .filter_map(|entry| parse_ugen_name(entry.ok()?.file_name().to_str()?))
+        .collect();
+    devices.sort_unstable();
+    Ok(devices.into_iter())
+}
+
+pub fn list_buses() -> impl MaybeFuture<Output = Result<impl Iterator<Item = BusInfo>, Error>> {
+    Ready((|| {
+        Ok(list_ugen()?
+            .filter_map(|(busnum, addr)| {
+                let rh = probe_device(busnum, addr)
+                    .inspect_err(|e| warn!("Failed to probe ugen{busnum}.{addr}: {e}"))
+                    .ok()?;
+                rh.port_chain.is_empty().then_some(rh)
+            })
+            .map(|rh| {
+                // The controller the `usbus` is attached to, like `xhci0`
+                let driver = sysctl_string(&format!("dev.usbus.{}.%parent", rh.busnum));
+                debug!("Bus {} is on controller {driver:?}", rh.busnum);
+
+                BusInfo {
+                    bus_id: rh.bus_id.clone(),
+                    busnum: rh.busnum,
+                    controller_type: driver.as_deref().and_then(UsbControllerType::from_str),
+                    driver,
+                    root_hub: rh,
+                }
+            }))
+    })())
+}
+
+fn sysctl_string(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut buf = [0u8; 64];
+    let mut len = buf.len();
+    let r = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            &mut len,
+            std::ptr::null(),
+            0,
+        )
+    };
+    (r == 0).then(|| ugen::c_string(&buf[..len.min(buf.len())]))
+}
+
+pub(crate) fn probe_device(busnum: u8, addr: u8) -> Result<DeviceInfo, Error> {
+    debug!("Probing device ugen{busnum}.{addr}");
+
+    // Reading the properties doesn't need write access, which may be denied.
+    let path = device_path(busnum, addr);
+    let fd = match open_device(busnum, addr) {
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            rustix::fs::open(&path, OFlags::RDONLY | OFlags::CLOEXEC, Mode::empty())?
+        }
+        r => r?,
+    };
+
+    let info = ugen::get_device_info(&fd)?;
+    let desc = ugen::get_device_desc(&fd)?;
+    let desc = DeviceDescriptor::new(&desc)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid device descriptor"))?;
+
+    let port_chain = ugen::get_port_path(&fd)
+        .inspect_err(|e| debug!("USB_GET_DEV_PORT_PATH failed: {e}"))
+        .unwrap_or_default();
+
+    let interfaces = if info.config_index != ugen::UNCONFIG_INDEX {
+        let config = ugen::get_full_config_desc(&fd, info.config_index)?;
+        ConfigurationDescriptor::new(&config)
+            .map(|config| {
+                config
+                    .interfaces()
+                    .enumerate()
+                    .map(|(index, interface)| {
+                        let alt = interface.first_alt_setting();
+                        InterfaceInfo {
+                            interface_number: interface.interface_number(),
+                            class: alt.class(),
+                            subclass: alt.subclass(),
+                            protocol: alt.protocol(),
+                            interface_string: None,
+                            driver: ugen::get_iface_driver(&fd, index as u8)
+                                .ok()
+                                .filter(|d| !d.is_empty()),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    // The kernel substitutes placeholders for missing strings
+    let string = |index: Option<_>, buf: &[u8]| index.map(|_| ugen::c_string(buf));
+
+    Ok(DeviceInfo {
+        busnum,
+        bus_id: bus_id(busnum),
+        device_address: addr,
+        port_chain,
+        vendor_id: desc.vendor_id(),
+        product_id: desc.product_id(),
+        usb_version: desc.usb_version(),
+        device_version: desc.device_version(),
+        class: desc.class(),
+        subclass: desc.subclass(),
+        protocol: desc.protocol(),
+        max_packet_size_0: desc.max_packet_size_0(),
+        speed: speed_from_raw(info.speed),
+        manufacturer_string: string(desc.manufacturer_string_index(), &info.vendor),
+        product_string: string(desc.product_string_index(), &info.product),
+        serial_number: string(desc.serial_number_string_index(), &info.serial),
+        interfaces,
+    })
+}
+
+pub(crate) fn speed_from_raw(speed: u8) -> Option<Speed> {
+    match speed {
+        ugen::speed::LOW => Some(Speed::Low),
+        ugen::speed::FULL => Some(Speed::Full),
+        ugen::speed::HIGH => Some(Speed::High),
+        ugen::speed::SUPER => Some(Speed::Super),
+        _ => None,
+    }
+}
@@ -0,0 +1,196 @@
+//! Poll based event loop for FreeBSD.
+//!
+//! Launches a thread when opening the first device that polls for completed
+//! transfers on ugen devices and for readiness of arbitrary file descriptors
+//! (used for the devd hotplug socket).
+//!
+//! The set of watched file descriptors changes as devices are opened and
+//! closed, so registrations are kept in a table, and a byte written to a pipe
+//! wakes the thread to poll the new set. A registration shares ownership of
+//! its file descriptor, so that it stays open until the thread has stopped
+//! polling it.
+
+use crate::{event_thread, Error};
+use once_cell::sync::OnceCell;
+use rustix::{
+    event::{poll, PollFd, PollFlags},
+    fd::OwnedFd,
+    io::{read, retry_on_intr, write, Errno},
+    pipe::{pipe_with, PipeFlags},
+};
+use slab::Slab;
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    task::Waker,
+};
+
+use atomic_waker::AtomicWaker;
+
+use super::Device;
+
+#[derive(Clone)]
+pub(crate) enum Tag {
+    Device(usize),
+    Waker(Arc<AtomicWaker>),
+}
+
+struct Registration {
+    fd: Arc<OwnedFd>,
+    tag: Tag,
+
+    /// Events to poll for, or empty while a waker registration is not armed.
+    flags: PollFlags,
+}
+
+struct EventLoop {
+    registrations: Mutex<Slab<Registration>>,
+
+    /// Pipe written to when `registrations` changes.
+    wake_read: OwnedFd,
+    wake_write: OwnedFd,
+}
+
+static EVENT_LOOP: OnceCell<EventLoop> = OnceCell::new();
+
+fn event_loop_handle() -> Result<&'static EventLoop, Error> {
+    let mut start_thread = false;
+    let handle = EVENT_LOOP.get_or_try_init(|| {
+        start_thread = true;
+        let (wake_read, wake_write) = pipe_with(PipeFlags::CLOEXEC | PipeFlags::NONBLOCK)
+            .inspect_err(|e| log::error!("Failed to create event loop pipe: {e}"))?;
+        Ok::<_, Error>(EventLoop {
+            registrations: Mutex::new(Slab::new()),
+            wake_read,
+            wake_write,
+        })
+    })?;
+
+    if start_thread {
+        event_thread::spawn(event_loop);
+    }
+    event_thread::check()?;
+    Ok(handle)
+}
+
+impl EventLoop {
+    fn notify(&self) {
+        // If the pipe is full, the thread already has a wakeup pending.
+        match write(&self.wake_write, &[0]) {
+            Ok(_) | Err(Errno::AGAIN) => {}
+            Err(e) => log::error!("Failed to wake event thread: {e}"),
+        }
+    }
+}
+
+/// Start polling `fd` for `flags`, returning an ID to unregister it with.
+pub(super) fn register_fd(fd: Arc<OwnedFd>, tag: Tag, flags: PollFlags) -> Result<usize, Error> {
+    let event_loop = event_loop_handle()?;
+    let id = event_loop
+        .registrations
+        .lock()
+        .unwrap()
+        .insert(Registration { fd, tag, flags });
+    event_loop.notify();
+    Ok(id)
+}
+
+/// Change the events polled for by a registration.
+fn modify(id: usize, flags: PollFlags) {
+    let Some(event_loop) = EVENT_LOOP.get() else {
+        return;
+    };
+    if let Some(r) = event_loop.registrations.lock().unwrap().get_mut(id) {
+        r.flags = flags;
+    }
+    event_loop.notify();
+}
+
+pub(super) fn unregister_fd(id: usize) {
+    let Some(event_loop) = EVENT_LOOP.get() else {
+        return;
+    };
+    event_loop.registrations.lock().unwrap().try_remove(id);
+    event_loop.notify();
+}
+
+fn event_loop() {
+    let event_loop = EVENT_LOOP.get().unwrap();
+    let mut watched: Vec<(usize, Arc<OwnedFd>, Tag, PollFlags)> = Vec::new();
+    loop {
+        watched.clear();
+        watched.extend(
+            event_loop
+                .registrations
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, r)| !r.flags.is_empty())
+                .map(|(id, r)| (id, r.fd.clone(), r.tag.clone(), r.flags)),
+        );
+
+        let mut fds: Vec<PollFd> = Vec::with_capacity(watched.len() + 1);
+        fds.push(PollFd::new(&event_loop.wake_read, PollFlags::IN));
+        fds.extend(
+            watched
+                .iter()
+                .map(|(_, fd, _, flags)| PollFd::new(&**fd, *flags)),
+        );
+
+        retry_on_intr(|| poll(&mut fds, -1)).unwrap();
+
+        if !fds[0].revents().is_empty() {
+            let mut buf = [0; 64];
+            while read(&event_loop.wake_read, &mut buf).is_ok_and(|n| n == buf.len()) {}
+        }
+
+        let ready: Vec<bool> = fds[1..].iter().map(|fd| !fd.revents().is_empty()).collect();
+        drop(fds);
+
+        for ((id, _, tag, _), ready) in watched.iter().zip(ready) {
+            if !ready {
+                continue;
+            }
+            match tag {
+                Tag::Device(device_id) => Device::handle_usb_events(*device_id),
+                Tag::Waker(waker) => {
+                    // Like `EPOLLONESHOT`: stop polling until re-armed. The
+                    // ID may have been reused if the registration was removed.
+                    if let Some(r) = event_loop.registrations.lock().unwrap().get_mut(*id) {
+                        if matches!(&r.tag, Tag::Waker(w) if Arc::ptr_eq(w, waker)) {
+                            r.flags = PollFlags::empty();
+                        }
+                    }
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+/// A file descriptor whose readiness wakes a task.
+pub(crate) struct Async {
+    pub(crate) inner: Arc<OwnedFd>,
+    waker: Arc<AtomicWaker>,
+    id: usize,
+}
+
+impl Async {
+    pub fn new(inner: OwnedFd) -> Result<Self, io::Error> {
+        let inner = Arc::new(inner);
+        let waker = Arc::new(AtomicWaker::new());
+        let id = register_fd(inner.clone(), Tag::Waker(waker.clone()), PollFlags::empty())?;
+        Ok(Async { inner, waker, id })
+    }
+
+    pub fn register(&self, waker: &Waker) {
+        self.waker.register(waker);
+        modify(self.id, PollFlags::IN);
+    }
+}
+
+impl Drop for Async {
+    fn drop(&mut self) {
+        unregister_fd(self.id);
+    }
+}
@@ -0,0 +1,149 @@
+use log::{debug, error, trace, warn};
+use rustix::{
+    io::Errno,
+    net::{
+        connect_unix, recv, socket_with, AddressFamily, RecvFlags, SocketAddrUnix, SocketFlags,
+        SocketType,
+    },
+};
+use std::task::Poll;
+
+use crate::{hotplug::HotplugEvent, Error};
+
+use super::{
+    enumeration::{parse_ugen_name, probe_device},
+    events::Async,
+};
+
+/// devd(8) sends each device event as a packet to the clients of this socket.
+const DEVD_SEQPACKET_PATH: &str = "/var/run/devd.seqpacket.pipe";
+
+pub(crate) struct FreeBsdHotplugWatch {
+    fd: Async,
+
+    /// Set once devd closed the socket or it failed. A hung-up socket stays
+    /// readable, so it is no longer polled.
+    closed: bool,
+}
+
+impl FreeBsdHotplugWatch {
+    pub(crate) fn new() -> Result<Self, Error> {
+        let fd = socket_with(
+            AddressFamily::UNIX,
+            SocketType::SEQPACKET,
+            SocketFlags::CLOEXEC,
+            None,
+        )?;
+        connect_unix(&fd, &SocketAddrUnix::new(DEVD_SEQPACKET_PATH)?)
+            .inspect_err(|e| warn!("Failed to connect to devd at {DEVD_SEQPACKET_PATH}: {e}"))?;
+
+        Ok(FreeBsdHotplugWatch {
+            fd: Async::new(fd)?,
+            closed: false,
+        })
+    }
+
+    pub(crate) fn poll_next(&mut self, cx: &mut std::task::Context<'_>) -> Poll<HotplugEvent> {
+        while let Some(packet) = self.try_receive() {
+            if let Some(event) = parse_event(&packet) {
+                return Poll::Ready(event);
+            }
+        }
+
+        if !self.closed {
+            self.fd.register(cx.waker());
+        }
+        Poll::Pending
+    }
+
+    fn try_receive(&mut self) -> Option<Vec<u8>> {
+        if self.closed {
+            return None;
+        }
+
+        let mut buf = vec![0; 8192];
+        match recv(&*self.fd.inner, &mut buf, RecvFlags::DONTWAIT) {
+            Ok(0) => {
+                error!("devd closed the event socket, no further hotplug events will be received");
+                self.closed = true;
+                None
+            }
+            Ok(size) => {
+                buf.truncate(size);
+                Some(buf)
+            }
+            Err(Errno::AGAIN | Errno::INTR) => None,
+            Err(e) => {
+                error!(
+                    "devd socket recv failed with {e}, no further hotplug events will be received"
+                );
+                self.closed = true;
+                None
+            }
+        }
+    }
+}
+
+/// Parse a devd notification like
+/// `!system=USB subsystem=DEVICE type=ATTACH ugen=ugen0.2 ...`.
+fn parse_event(buf: &[u8]) -> Option<HotplugEvent> {
+    let message = std::str::from_utf8(buf).ok()?.strip_prefix('!')?;
+
+    let mut is_attach = None;
+    let mut device = None;
+
+    for (k, v) in message
+        .split_ascii_whitespace()
+        .filter_map(|entry| entry.split_once('='))
+    {
+        trace!("devd event property {k} = {v}");
+        match k {
+            "system" if v != "USB" => return None,
+            "subsystem" if v != "DEVICE" => return None,
+            "type" => {
+                is_attach = Some(match v {
+                    "ATTACH" => true,
+                    "DETACH" => false,
+                    _ => return None,
+                });
+            }
+            "ugen" => {
+                device = parse_ugen_name(v);
+            }
+            _ => {}
+        }
+    }
+
+    let is_attach = is_attach?;
+    let (bus, addr) = device?;
+
+    if is_attach {
+        match probe_device(bus, addr) {
+            Ok(d) => Some(HotplugEvent::Connected(d)),
+            Err(e) => {
+                warn!("Failed to probe device ugen{bus}.{addr}: {e}");
+                None
+            }
+        }
+    } else {
+        debug!("Device ugen{bus}.{addr} detached");
+        Some(HotplugEvent::Disconnected(crate::DeviceId(
+            super::DeviceId { bus, addr },
+        )))
+    }
+}
+
+#[test]
+fn test_parse_devd_event() {
+    let event = b"!system=USB subsystem=DEVICE type=DETACH ugen=ugen0.5 cdev=ugen0.5 \
+        vendor=0x1234 product=0x5678 devclass=0x00 devsubclass=0x00 sernum=\"\" \
+        release=0x0100 mode=host port=2 parent=uhub1\n";
+    let Some(HotplugEvent::Disconnected(id)) = parse_event(event) else {
+        panic!("expected a disconnect event");
+    };
+    assert_eq!(id.0, super::DeviceId { bus: 0, addr: 5 });
+
+    let interface = b"!system=USB subsystem=INTERFACE type=ATTACH ugen=ugen0.5 interface=0\n";
+    assert!(parse_event(interface).is_none());
+    assert!(parse_event(b"+umass0 at bus=0 on uhub1").is_none());
+}
@@ -0,0 +1,33 @@
+mod transfer;
+use rustix::io::Errno;
+pub(crate) use transfer::TransferData;
+mod ugen;
+
+mod enumeration;
+mod events;
+pub use enumeration::{list_buses, list_devices, list_devices_filtered};
+
+mod device;
+pub(crate) use device::FreeBsdDevice as Device;
+pub(crate) use device::FreeBsdInterface as Interface;
+
+mod hotplug;
+pub(crate) use hotplug::FreeBsdHotplugWatch as HotplugWatch;
+
+use crate::transfer::TransferError;
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceId {
+    pub(crate) bus: u8,
+    pub(crate) addr: u8,
+}
+
+fn errno_to_transfer_error(e: Errno) -> TransferError {
+    match e {
+        Errno::NXIO | Errno::NODEV => TransferError::Disconnected,
+        Errno::PIPE => TransferError::Stall,
+        Errno::TIMEDOUT => TransferError::TimedOut,
+        _ => TransferError::Unknown(e.raw_os_error() as u32),
+    }
+}
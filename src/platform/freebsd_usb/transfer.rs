@@ -0,0 +1,487 @@
+use std::{
+    any::Any,
+    ffi::{c_int, c_void},
+    mem::{self, ManuallyDrop},
+    ptr::null_mut,
+    sync::Arc,
+};
+
+use log::debug;
+use rustix::io::Errno;
+
+use crate::transfer::{
+    notify_completion, Chunk, ChunkPosition, Completion, ControlIn, ControlInBuffer,
+    ControlInResponse, ControlOut, ControlOutSetup, OutBuffer, OutResponse, PlatformSubmit,
+    PlatformTransfer, RequestBuffer, ResponseBuffer, TransferError, TransferObserver,
+    TransferRequest, TransferType, SETUP_PACKET_SIZE,
+};
+
+use super::{
+    errno_to_transfer_error,
+    ugen::{usb_err, FS_FLAG_FORCE_SHORT, FS_FLAG_MULTI_SHORT_OK, FS_FLAG_SINGLE_SHORT_OK},
+};
+
+/// Smallest kernel buffer a slot is opened with, so that it isn't reopened
+/// for every small change in transfer size.
+const MIN_BUFSIZE: u32 = 4096;
+
+/// Buffer pointers and lengths of the frames of a transfer, referenced by
+/// its slot.
+///
+/// The kernel writes the actual lengths on completion, so this is stored
+/// out-of-line to avoid violating noalias while the transfer is pending.
+struct Frames {
+    buffers: [*mut c_void; 2],
+    lengths: [u32; 2],
+}
+
+/// FreeBSD-specific transfer state.
+///
+/// Each transfer uses a `usb_fs` slot of the device, taken when it is first
+/// submitted and bound to the endpoint with a kernel buffer large enough
+/// for the transfer. The data is copied to and from the kernel buffer when
+/// the transfer is started and completed.
+///
+/// This logically contains a `Vec` with `buf` and `capacity`. For an
+/// `OutBuffer` transfer, `buf` instead points into `owner`.
+pub struct TransferData {
+    device: Arc<super::Device>,
+    endpoint: u8,
+    ep_type: TransferType,
+
+    slot: Option<u8>,
+
+    /// `max_bufsize` the slot was opened with, if it is open.
+    bufsize: Option<u32>,
+
+    frames: *mut Frames,
+    buf: *mut u8,
+    capacity: usize,
+    owner: Option<Box<dyn Any + Send>>,
+
+    /// Set if the transfer failed before it could be started.
+    error: Option<TransferError>,
+
+    /// Position of the transfer being submitted, if it is a chunk of a larger
+    /// transfer. Taken when its flags are set.
+    chunk: Option<ChunkPosition>,
+
+    /// Keeps the interface from being released while the transfer is active,
+    /// and holds the endpoint's zero-length packet setting.
+    interface: Option<Arc<super::Interface>>,
+}
+
+unsafe impl Send for TransferData {}
+
+impl TransferData {
+    pub(super) fn new(
+        device: Arc<super::Device>,
+        interface: Option<Arc<super::Interface>>,
+        endpoint: u8,
+        ep_type: TransferType,
+    ) -> TransferData {
+        let slot = device.alloc_slot();
+        TransferData {
+            device,
+            endpoint,
+            ep_type,
+            slot,
+            bufsize: None,
+            frames: Box::into_raw(Box::new(Frames {
+                buffers: [null_mut(); 2],
+                lengths: [0; 2],
+            })),
+            buf: null_mut(),
+            capacity: 0,
+            owner: None,
+            error: None,
+            chunk: None,
+            interface,
+        }
+    }
+
+    fn frames_mut(&mut self) -> &mut Frames {
+        // SAFETY: if we have `&mut`, the transfer is not pending
+        unsafe { &mut *self.frames }
+    }
+
+    /// Flags for an `OUT` transfer.
+    fn out_flags(&mut self) -> u16 {
+        let chunk = self.chunk.take();
+
+        // Only the end of a transfer split into chunks may need a ZLP.
+        let send_zlp = chunk.map_or(true, |c| c.last)
+            && self
+                .interface
+                .as_ref()
+                .is_some_and(|i| i.send_zlp(self.endpoint));
+
+        if send_zlp {
+            FS_FLAG_FORCE_SHORT
+        } else {
+            0
+        }
+    }
+
+    /// Take a slot if needed, and open it with a kernel buffer of at least
+    /// `len` bytes.
+    fn prepare_slot(&mut self, len: usize) -> Result<u8, TransferError> {
+        let slot = match self.slot {
+            Some(slot) => slot,
+            None => {
+                // All slots were in use when the transfer was created
+                let slot = self
+                    .device
+                    .alloc_slot()
+                    .ok_or_else(|| errno_to_transfer_error(Errno::NOBUFS))?;
+                self.slot = Some(slot);
+                slot
+            }
+        };
+
+        let len = u32::try_from(len).map_err(|_| errno_to_transfer_error(Errno::INVAL))?;
+        if self.bufsize.is_some_and(|b| b >= len) {
+            return Ok(slot);
+        }
+
+        if self.bufsize.take().is_some() {
+            // Reopened below with a larger buffer
+            self.device.close_slot(slot);
+        }
+
+        let (bufsize, max_frames) = match self.ep_type {
+            TransferType::Control => (len, 2),
+            _ => (len.max(MIN_BUFSIZE), 1),
+        };
+        self.device
+            .open_slot(slot, self.endpoint, bufsize, max_frames)
+            .map_err(errno_to_transfer_error)?;
+        self.bufsize = Some(bufsize);
+        Ok(slot)
+    }
+
+    /// Fill in the slot for the frames set in `self.frames` and start the
+    /// transfer.
+    ///
+    /// SAFETY: the frame buffers must remain valid until the transfer
+    /// completes.
+    unsafe fn start(&mut self, bufsize: usize, n_frames: u32, flags: u16, user_data: *mut c_void) {
+        self.error = None;
+        let slot = match self.prepare_slot(bufsize) {
+            Ok(slot) => slot,
+            Err(e) => {
+                self.error = Some(e);
+                // SAFETY: the transfer was not started, so we can complete it
+                unsafe { notify_completion::<super::TransferData>(user_data) };
+                return;
+            }
+        };
+
+        let frames = self.frames;
+        unsafe {
+            let ep = self.device.slot_mut(slot);
+            ep.pp_buffer = (*frames).buffers.as_mut_ptr();
+            ep.p_length = (*frames).lengths.as_mut_ptr();
+            ep.n_frames = n_frames;
+            ep.a_frames = 0;
+            ep.flags = flags;
+            ep.timeout = 0;
+            ep.status = 0;
+
+            self.device.start_transfer(slot, user_data);
+        }
+    }
+
+    /// Start a transfer of a single frame of `len` bytes.
+    ///
+    /// SAFETY: `buf` must be valid for `len` bytes until the transfer completes.
+    unsafe fn start_single(
+        &mut self,
+        buf: *mut u8,
+        len: usize,
+        flags: u16,
+        user_data: *mut c_void,
+    ) {
+        let frames = self.frames_mut();
+        frames.buffers[0] = buf.cast();
+        frames.lengths[0] = len.try_into().expect("buffer size should fit in u32");
+        unsafe { self.start(len, 1, flags, user_data) }
+    }
+
+    /// Start a control transfer of `buf`, which begins with the SETUP packet.
+    ///
+    /// SAFETY: `buf` must be valid for `len` bytes until the transfer completes.
+    unsafe fn start_control(
+        &mut self,
+        buf: *mut u8,
+        len: usize,
+        flags: u16,
+        user_data: *mut c_void,
+    ) {
+        let data_len = len - SETUP_PACKET_SIZE;
+        let frames = self.frames_mut();
+        frames.buffers = [buf.cast(), unsafe { buf.add(SETUP_PACKET_SIZE).cast() }];
+        frames.lengths = [SETUP_PACKET_SIZE as u32, data_len as u32];
+        let n_frames = if data_len > 0 { 2 } else { 1 };
+        unsafe { self.start(len, n_frames, flags, user_data) }
+    }
+
+    fn set_vec(&mut self, v: Vec<u8>) -> *mut u8 {
+        let mut v = ManuallyDrop::new(v);
+        self.buf = v.as_mut_ptr();
+        self.capacity = v.capacity();
+        self.buf
+    }
+
+    /// SAFETY: requires that the transfer has completed and `length` bytes are initialized
+    unsafe fn take_buf(&mut self, length: usize) -> Vec<u8> {
+        assert!(!self.buf.is_null());
+        let ptr = mem::replace(&mut self.buf, null_mut());
+        let capacity = mem::replace(&mut self.capacity, 0);
+        assert!(length <= capacity);
+        Vec::from_raw_parts(ptr, length, capacity)
+    }
+
+    /// Get the status and the number of data bytes transferred.
+    ///
+    /// SAFETY: requires that the transfer has completed
+    unsafe fn take_status(&mut self) -> (Result<(), TransferError>, usize) {
+        if let Some(e) = self.error.take() {
+            return (Err(e), 0);
+        }
+        let slot = self.slot.expect("started transfer should have a slot");
+
+        // SAFETY: the transfer has completed, so we own the slot again
+        let ep = unsafe { self.device.slot_mut(slot) };
+        let (status, a_frames) = (ep.status, ep.a_frames as usize);
+
+        // The SETUP packet is not part of the data
+        let data_frames = match self.ep_type {
+            TransferType::Control => 1..a_frames.min(2),
+            _ => 0..a_frames.min(1),
+        };
+        let len = self.frames_mut().lengths[data_frames].iter().sum::<u32>();
+        (status_to_result(status), len as usize)
+    }
+}
+
+impl Drop for TransferData {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.device.release_slot(slot, self.bufsize.is_some());
+        }
+        unsafe {
+            if self.owner.is_none() && !self.buf.is_null() {
+                drop(Vec::from_raw_parts(self.buf, 0, self.capacity));
+            }
+            drop(Box::from_raw(self.frames));
+        }
+    }
+}
+
+impl PlatformTransfer for TransferData {
+    fn cancel(&self) {
+        if let Some(slot) = self.slot {
+            self.device.stop_transfer(slot);
+        }
+    }
+
+    fn endpoint(&self) -> u8 {
+        self.endpoint
+    }
+
+    fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+        self.device.observer.get()
+    }
+}
+
+impl PlatformSubmit<Vec<u8>> for TransferData {
+    unsafe fn submit(&mut self, data: Vec<u8>, user_data: *mut c_void) {
+        assert!(self.endpoint & 0x80 == 0);
+        let flags = self.out_flags();
+        let len = data.len();
+        let buf = self.set_vec(data);
+
+        // SAFETY: the buffer is owned by `self` until completion
+        unsafe { self.start_single(buf, len, flags, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        let (status, len) = self.take_status();
+
+        // SAFETY: self is completed (precondition)
+        let data = ResponseBuffer::from_vec(self.take_buf(0), len);
+        Completion { data, status }
+    }
+}
+
+impl<B: AsRef<[u8]> + Send + 'static> PlatformSubmit<OutBuffer<B>> for TransferData {
+    unsafe fn submit(&mut self, data: OutBuffer<B>, user_data: *mut c_void) {
+        assert!(self.endpoint & 0x80 == 0);
+        let flags = self.out_flags();
+        let (owner, ptr, len) = data.into_raw();
+        self.owner = Some(owner);
+
+        // SAFETY: the kernel only reads from the buffer of an OUT transfer,
+        // and it is owned by `self` until completion
+        unsafe { self.start_single(ptr as *mut u8, len, flags, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<OutResponse<B>> {
+        let (status, len) = self.take_status();
+        let owner = self.owner.take().expect("transfer should have a buffer");
+        let data = OutResponse::from_raw(owner, len);
+        Completion { data, status }
+    }
+}
+
+impl<R: TransferRequest> PlatformSubmit<Chunk<R>> for TransferData
+where
+    TransferData: PlatformSubmit<R>,
+{
+    unsafe fn submit(&mut self, data: Chunk<R>, user_data: *mut c_void) {
+        self.chunk = Some(data.position);
+        PlatformSubmit::<R>::submit(self, data.data, user_data);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<R::Response> {
+        PlatformSubmit::<R>::take_completed(self)
+    }
+}
+
+impl PlatformSubmit<RequestBuffer> for TransferData {
+    unsafe fn submit(&mut self, data: RequestBuffer, user_data: *mut c_void) {
+        assert!(self.endpoint & 0x80 == 0x80);
+        assert!(matches!(
+            self.ep_type,
+            TransferType::Bulk | TransferType::Interrupt
+        ));
+
+        // Without the short-OK flags, a short transfer completes with
+        // `USB_ERR_SHORT_XFER`.
+        let flags = if data.short_not_ok {
+            0
+        } else {
+            FS_FLAG_SINGLE_SHORT_OK | FS_FLAG_MULTI_SHORT_OK
+        };
+        self.chunk = None;
+        let (data, len) = data.into_vec();
+        let buf = self.set_vec(data);
+
+        // SAFETY: the buffer is owned by `self` until completion
+        unsafe { self.start_single(buf, len, flags, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+        let (status, len) = self.take_status();
+
+        // SAFETY: self is completed (precondition) and `len` bytes were
+        // written by the kernel.
+        let data = unsafe { self.take_buf(len) };
+        Completion { data, status }
+    }
+}
+
+impl PlatformSubmit<ControlIn> for TransferData {
+    unsafe fn submit(&mut self, data: ControlIn, user_data: *mut c_void) {
+        unsafe {
+            PlatformSubmit::<ControlInBuffer>::submit(self, ControlInBuffer::new(data), user_data)
+        }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+        let c = unsafe { PlatformSubmit::<ControlInBuffer>::take_completed(self) };
+        Completion {
+            data: c.data.into_vec(),
+            status: c.status,
+        }
+    }
+}
+
+impl PlatformSubmit<ControlInBuffer> for TransferData {
+    unsafe fn submit(&mut self, data: ControlInBuffer, user_data: *mut c_void) {
+        // Keep the SETUP packet at the start of the buffer, like on Linux, so
+        // the data can be returned in place.
+        let buf_len = SETUP_PACKET_SIZE + data.setup.length as usize;
+        let mut buf = data.buf;
+        buf.reserve_exact(buf_len);
+        buf.extend_from_slice(&data.setup.to_bytes());
+        let ptr = self.set_vec(buf);
+
+        // SAFETY: the buffer is owned by `self` until completion, and has
+        // capacity for the data stage
+        let flags = FS_FLAG_SINGLE_SHORT_OK | FS_FLAG_MULTI_SHORT_OK;
+        unsafe { self.start_control(ptr, buf_len, flags, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ControlInResponse> {
+        let (status, len) = self.take_status();
+
+        // SAFETY: transfer is completed (precondition) and `len` bytes were
+        // written by the kernel after the SETUP packet
+        let data = unsafe { self.take_buf(SETUP_PACKET_SIZE + len) };
+        Completion {
+            data: ControlInResponse::new(data, SETUP_PACKET_SIZE),
+            status,
+        }
+    }
+}
+
+impl PlatformSubmit<ControlOut<'_>> for TransferData {
+    unsafe fn submit(&mut self, data: ControlOut, user_data: *mut c_void) {
+        let data = ControlOutSetup {
+            setup: data.setup(),
+            data: data.data,
+        };
+        unsafe { PlatformSubmit::<ControlOutSetup>::submit(self, data, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        unsafe { PlatformSubmit::<ControlOutSetup>::take_completed(self) }
+    }
+}
+
+impl PlatformSubmit<ControlOutSetup<'_>> for TransferData {
+    unsafe fn submit(&mut self, data: ControlOutSetup, user_data: *mut c_void) {
+        let buf_len = SETUP_PACKET_SIZE + data.data.len();
+        let mut buf = Vec::with_capacity(buf_len);
+        buf.extend_from_slice(&data.setup.to_bytes());
+        buf.extend_from_slice(data.data);
+        let ptr = self.set_vec(buf);
+
+        // SAFETY: the buffer is owned by `self` until completion
+        unsafe { self.start_control(ptr, buf_len, 0, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        let (status, len) = self.take_status();
+        let data = ResponseBuffer::from_vec(self.take_buf(0), len);
+        Completion { data, status }
+    }
+}
+
+/// Convert the `status` of a completed slot, which is a `usb_error_t`, or a
+/// negative errno if the transfer failed to start.
+///
+/// Only errno values are reported as `TransferError::Unknown`, so every
+/// `usb_error_t` is mapped to the closest other variant.
+fn status_to_result(status: c_int) -> Result<(), TransferError> {
+    let err = match status {
+        usb_err::NORMAL_COMPLETION => return Ok(()),
+        s if s < 0 => return Err(errno_to_transfer_error(Errno::from_raw_os_error(-s))),
+        usb_err::CANCELLED | usb_err::INTERRUPTED => TransferError::Cancelled,
+        usb_err::STALLED => TransferError::Stall,
+        usb_err::TIMEOUT => TransferError::TimedOut,
+        usb_err::SHORT_XFER => TransferError::ShortPacket,
+        usb_err::NO_ADDR
+        | usb_err::NO_PIPE
+        | usb_err::NO_POWER
+        | usb_err::NOT_CONFIGURED
+        | usb_err::NO_ROOT_HUB => TransferError::Disconnected,
+        _ => TransferError::Fault,
+    };
+    if err == TransferError::Fault || err == TransferError::Disconnected {
+        debug!("Transfer failed with usb_error_t {status}");
+    }
+    Err(err)
+}
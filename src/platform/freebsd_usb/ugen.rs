@@ -0,0 +1,427 @@
+//! Wrappers for the [ugen(4)] character device ioctls, translated from the
+//! [C structures and ioctl definitions][usb_ioctl].
+//!
+//! [ugen(4)]: https://man.freebsd.org/cgi/man.cgi?query=ugen&sektion=4
+//! [usb_ioctl]: https://github.com/freebsd/freebsd-src/blob/main/sys/dev/usb/usb_ioctl.h
+#![allow(dead_code)]
+use std::{
+    ffi::{c_int, c_void},
+    mem,
+};
+
+use rustix::{
+    fd::AsFd,
+    io,
+    ioctl::{self, Getter, ReadOpcode, ReadWriteOpcode, Setter, Updater, WriteOpcode},
+};
+
+/// `USB_MAX_STRING_LEN`
+const MAX_STRING_LEN: usize = 128;
+
+/// Configuration index that leaves the device unconfigured.
+pub const UNCONFIG_INDEX: u8 = 0xFF;
+
+/// `usb_fs_open.max_bufsize` limit.
+pub const FS_MAX_BUFSIZE: u32 = 1 << 25;
+
+/// `usb_fs_open.max_frames` limit.
+pub const FS_MAX_FRAMES: u32 = 1 << 12;
+
+/// Number of transfer slots allocated with `USB_FS_INIT`, limited by the
+/// width of `usb_fs_start.ep_index`.
+pub const FS_MAX_SLOTS: usize = 255;
+
+pub const FS_FLAG_SINGLE_SHORT_OK: u16 = 0x0001;
+pub const FS_FLAG_MULTI_SHORT_OK: u16 = 0x0002;
+pub const FS_FLAG_FORCE_SHORT: u16 = 0x0004;
+pub const FS_FLAG_CLEAR_STALL: u16 = 0x0008;
+
+/// `ucr_flags` bit allowing a control `IN` transfer to return less data than
+/// requested.
+pub const SHORT_XFER_OK: u16 = 0x0004;
+
+/// `usb_error_t` values reported in `usb_fs_endpoint.status`.
+pub mod usb_err {
+    pub const NORMAL_COMPLETION: i32 = 0;
+    pub const PENDING_REQUESTS: i32 = 1;
+    pub const NOT_STARTED: i32 = 2;
+    pub const INVAL: i32 = 3;
+    pub const NOMEM: i32 = 4;
+    pub const CANCELLED: i32 = 5;
+    pub const BAD_ADDRESS: i32 = 6;
+    pub const BAD_BUFSIZE: i32 = 7;
+    pub const BAD_FLAG: i32 = 8;
+    pub const NO_CALLBACK: i32 = 9;
+    pub const IN_USE: i32 = 10;
+    pub const NO_ADDR: i32 = 11;
+    pub const NO_PIPE: i32 = 12;
+    pub const ZERO_NFRAMES: i32 = 13;
+    pub const ZERO_MAXP: i32 = 14;
+    pub const SET_ADDR_FAILED: i32 = 15;
+    pub const NO_POWER: i32 = 16;
+    pub const TOO_DEEP: i32 = 17;
+    pub const IOERROR: i32 = 18;
+    pub const NOT_CONFIGURED: i32 = 19;
+    pub const TIMEOUT: i32 = 20;
+    pub const SHORT_XFER: i32 = 21;
+    pub const STALLED: i32 = 22;
+    pub const INTERRUPTED: i32 = 23;
+    pub const DMA_LOAD_FAILED: i32 = 24;
+    pub const BAD_CONTEXT: i32 = 25;
+    pub const NO_ROOT_HUB: i32 = 26;
+    pub const NO_INTR_THREAD: i32 = 27;
+    pub const NOT_LOCKED: i32 = 28;
+}
+
+/// `usb_dev_speed` values reported in `usb_device_info.udi_speed`.
+pub mod speed {
+    pub const LOW: u8 = 1;
+    pub const FULL: u8 = 2;
+    pub const HIGH: u8 = 3;
+    pub const SUPER: u8 = 4;
+}
+
+#[repr(C)]
+pub struct DeviceInfo {
+    pub bus: u8,
+    pub addr: u8,
+    pub index: u8,
+    pub product: [u8; MAX_STRING_LEN],
+    pub vendor: [u8; MAX_STRING_LEN],
+    pub serial: [u8; MAX_STRING_LEN],
+    pub release: [u8; 8],
+    pub product_no: u16,
+    pub vendor_no: u16,
+    pub release_no: u16,
+    pub class: u8,
+    pub subclass: u8,
+    pub protocol: u8,
+    pub config_no: u8,
+    pub config_index: u8,
+    pub speed: u8,
+    pub mode: u8,
+    pub nports: u8,
+    pub hubaddr: u8,
+    pub hubindex: u8,
+    pub hubport: u8,
+    pub power_mode: u8,
+    pub suspended: u8,
+    pub reserved: [u8; 16],
+}
+
+/// Get a NUL-terminated string field as a `String`.
+pub fn c_string(buf: &[u8]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+pub fn get_device_info<Fd: AsFd>(fd: Fd) -> io::Result<DeviceInfo> {
+    unsafe {
+        let ctl = Getter::<ReadOpcode<b'U', 112, DeviceInfo>, DeviceInfo>::new();
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+pub fn get_device_desc<Fd: AsFd>(fd: Fd) -> io::Result<[u8; 18]> {
+    unsafe {
+        let ctl = Getter::<ReadOpcode<b'U', 105, [u8; 18]>, [u8; 18]>::new();
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+#[repr(C)]
+struct DevicePortPath {
+    bus: u8,
+    index: u8,
+    port_level: u8,
+    port_no: [u8; 32],
+}
+
+/// Get the port numbers of the hubs between the root hub and the device.
+pub fn get_port_path<Fd: AsFd>(fd: Fd) -> io::Result<Vec<u8>> {
+    let path = unsafe {
+        let ctl = Getter::<ReadOpcode<b'U', 134, DevicePortPath>, DevicePortPath>::new();
+        ioctl::ioctl(fd, ctl)?
+    };
+    let len = usize::from(path.port_level).min(path.port_no.len());
+    Ok(path.port_no[..len].to_vec())
+}
+
+#[repr(C)]
+struct GenDescriptor {
+    data: *mut c_void,
+    lang_id: u16,
+    maxlen: u16,
+    actlen: u16,
+    offset: u16,
+    config_index: u8,
+    string_index: u8,
+    iface_index: u8,
+    altif_index: u8,
+    endpt_index: u8,
+    report_type: u8,
+    reserved: [u8; 8],
+}
+
+impl GenDescriptor {
+    fn new(buf: &mut [u8]) -> GenDescriptor {
+        GenDescriptor {
+            data: buf.as_mut_ptr().cast(),
+            maxlen: buf.len().try_into().unwrap_or(u16::MAX),
+            // SAFETY: all-zero is valid for the remaining integer fields
+            ..unsafe { mem::zeroed() }
+        }
+    }
+}
+
+/// Read the full configuration descriptor at `index`, including its
+/// interface, endpoint, and class-specific descriptors.
+pub fn get_full_config_desc<Fd: AsFd>(fd: Fd, index: u8) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; u16::MAX as usize];
+    let mut gd = GenDescriptor::new(&mut buf);
+    gd.config_index = index;
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 109, GenDescriptor>, GenDescriptor>::new(&mut gd);
+        ioctl::ioctl(&fd, ctl)?;
+    }
+    buf.truncate(gd.actlen.into());
+    Ok(buf)
+}
+
+/// Get the name and unit of the kernel driver attached to the interface at
+/// `iface_index`, like `umass0`.
+pub fn get_iface_driver<Fd: AsFd>(fd: Fd, iface_index: u8) -> io::Result<String> {
+    let mut buf = [0; 128];
+    let mut gd = GenDescriptor::new(&mut buf);
+    gd.iface_index = iface_index;
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 121, GenDescriptor>, GenDescriptor>::new(&mut gd);
+        ioctl::ioctl(&fd, ctl)?;
+    }
+    Ok(c_string(&buf))
+}
+
+/// Check whether a kernel driver is attached to the interface at
+/// `iface_index`.
+///
+/// Fails with `ENOENT` if none is.
+pub fn iface_driver_active<Fd: AsFd>(fd: Fd, iface_index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 124, c_int>, c_int>::new(iface_index.into());
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+pub fn iface_driver_detach<Fd: AsFd>(fd: Fd, iface_index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 125, c_int>, c_int>::new(iface_index.into());
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+/// Get the index of the active configuration, or [`UNCONFIG_INDEX`].
+pub fn get_config<Fd: AsFd>(fd: Fd) -> io::Result<u8> {
+    let index = unsafe {
+        let ctl = Getter::<ReadOpcode<b'U', 100, c_int>, c_int>::new();
+        ioctl::ioctl(fd, ctl)?
+    };
+    Ok(index as u8)
+}
+
+/// Select the configuration at `index`, or [`UNCONFIG_INDEX`].
+pub fn set_config<Fd: AsFd>(fd: Fd, index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 101, c_int>, c_int>::new(index.into());
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+#[repr(C)]
+struct AltInterface {
+    interface_index: u8,
+    alt_index: u8,
+}
+
+pub fn get_alt_interface<Fd: AsFd>(fd: Fd, iface_index: u8) -> io::Result<u8> {
+    let mut alt = AltInterface {
+        interface_index: iface_index,
+        alt_index: 0,
+    };
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 102, AltInterface>, AltInterface>::new(&mut alt);
+        ioctl::ioctl(&fd, ctl)?;
+    }
+    Ok(alt.alt_index)
+}
+
+pub fn set_alt_interface<Fd: AsFd>(fd: Fd, iface_index: u8, alt_index: u8) -> io::Result<()> {
+    let mut alt = AltInterface {
+        interface_index: iface_index,
+        alt_index,
+    };
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 103, AltInterface>, AltInterface>::new(&mut alt);
+        ioctl::ioctl(&fd, ctl)
+    }
+}
+
+/// Reset the device and read its descriptors again.
+pub fn re_enumerate<Fd: AsFd>(fd: Fd) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 6, c_int>, c_int>::new(0);
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+#[repr(C)]
+pub struct CtlRequest {
+    pub data: *mut c_void,
+    pub flags: u16,
+    pub actlen: u16,
+    pub addr: u8,
+    pub request: [u8; 8],
+}
+
+/// Perform a control transfer, returning the number of bytes transferred.
+///
+/// The kernel applies its own timeout.
+pub fn do_request<Fd: AsFd>(fd: Fd, mut req: CtlRequest) -> io::Result<usize> {
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 111, CtlRequest>, CtlRequest>::new(&mut req);
+        ioctl::ioctl(&fd, ctl)?;
+    }
+    Ok(req.actlen.into())
+}
+
+/// `usb_fs_endpoint`: a transfer slot, shared with the kernel.
+///
+/// The kernel reads it on `USB_FS_START`, and writes back `aFrames`,
+/// `status`, and the actual frame lengths on `USB_FS_COMPLETE`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FsEndpoint {
+    pub pp_buffer: *mut *mut c_void,
+    pub p_length: *mut u32,
+    pub n_frames: u32,
+    pub a_frames: u32,
+    pub flags: u16,
+    pub timeout: u16,
+    pub isoc_time_complete: u16,
+    pub status: c_int,
+}
+
+impl Default for FsEndpoint {
+    fn default() -> Self {
+        FsEndpoint {
+            pp_buffer: std::ptr::null_mut(),
+            p_length: std::ptr::null_mut(),
+            n_frames: 0,
+            a_frames: 0,
+            flags: 0,
+            timeout: 0,
+            isoc_time_complete: 0,
+            status: 0,
+        }
+    }
+}
+
+#[repr(C)]
+struct FsInit {
+    p_endpoints: *mut FsEndpoint,
+    ep_index_max: u8,
+}
+
+/// Set up `ep_index_max` transfer slots at `endpoints` for this file
+/// descriptor.
+///
+/// SAFETY: `endpoints` must remain valid for `ep_index_max` elements until
+/// `fs_uninit` or the file descriptor is closed.
+pub unsafe fn fs_init<Fd: AsFd>(
+    fd: Fd,
+    endpoints: *mut FsEndpoint,
+    ep_index_max: u8,
+) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 195, FsInit>, FsInit>::new(FsInit {
+            p_endpoints: endpoints,
+            ep_index_max,
+        });
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+pub fn fs_uninit<Fd: AsFd>(fd: Fd) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 196, u8>, u8>::new(0);
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+#[repr(C)]
+struct FsOpen {
+    max_bufsize: u32,
+    max_frames: u32,
+    max_packet_length: u16,
+    dev_index: u8,
+    ep_index: u8,
+    ep_no: u8,
+}
+
+/// Bind slot `ep_index` to the endpoint with address `ep_no`, allocating a
+/// kernel buffer of `max_bufsize` bytes.
+pub fn fs_open<Fd: AsFd>(
+    fd: Fd,
+    ep_index: u8,
+    ep_no: u8,
+    max_bufsize: u32,
+    max_frames: u32,
+) -> io::Result<()> {
+    let mut open = FsOpen {
+        max_bufsize,
+        max_frames,
+        max_packet_length: 0,
+        dev_index: 0,
+        ep_index,
+        ep_no,
+    };
+    unsafe {
+        let ctl = Updater::<ReadWriteOpcode<b'U', 197, FsOpen>, FsOpen>::new(&mut open);
+        ioctl::ioctl(&fd, ctl)
+    }
+}
+
+pub fn fs_close<Fd: AsFd>(fd: Fd, ep_index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 198, u8>, u8>::new(ep_index);
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+/// Start the transfer described by slot `ep_index`.
+///
+/// SAFETY: the buffers referenced by the slot must remain valid until its
+/// completion is returned by `fs_complete`.
+pub unsafe fn fs_start<Fd: AsFd>(fd: Fd, ep_index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 192, u8>, u8>::new(ep_index);
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+pub fn fs_stop<Fd: AsFd>(fd: Fd, ep_index: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = Setter::<WriteOpcode<b'U', 193, u8>, u8>::new(ep_index);
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
+/// Get the index of a slot whose transfer has completed, copying its
+/// results and received data back to the slot.
+///
+/// Fails with `EBUSY` if no transfer has completed.
+pub fn fs_complete<Fd: AsFd>(fd: Fd) -> io::Result<u8> {
+    unsafe {
+        let ctl = Getter::<ReadOpcode<b'U', 194, u8>, u8>::new();
+        ioctl::ioctl(fd, ctl)
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::io::{ErrorKind, Seek};
 use std::sync::{Mutex, Weak};
 use std::{ffi::c_void, time::Duration};
@@ -7,7 +8,7 @@ use std::{
     mem::ManuallyDrop,
     path::PathBuf,
     sync::{
-        atomic::{AtomicU8, Ordering},
+        atomic::{AtomicBool, AtomicU16, AtomicU8, Ordering},
         Arc,
     },
 };
@@ -16,7 +17,7 @@ use log::{debug, error, warn};
 use rustix::event::epoll;
 use rustix::fd::AsFd;
 use rustix::{
-    fd::{AsRawFd, FromRawFd, OwnedFd},
+    fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
     fs::{Mode, OFlags},
     io::Errno,
 };
@@ -25,15 +26,18 @@ use slab::Slab;
 use super::{
     errno_to_transfer_error, events,
     usbfs::{self, Urb},
-    SysfsPath,
+    MmapBuffer, SysfsPath,
 };
 use crate::descriptors::{ConfigurationDescriptor, DeviceDescriptor};
 use crate::maybe_future::{blocking::Blocking, MaybeFuture};
 use crate::transfer::{ControlType, Recipient};
 use crate::{
     descriptors::{parse_concatenated_config_descriptors, DESCRIPTOR_LEN_DEVICE},
+    device::{DetachPolicy, DetachRequest, PowerState},
+    disconnect::DisconnectSignal,
     transfer::{
-        notify_completion, Control, Direction, TransferError, TransferHandle, TransferType,
+        notify_completion, Control, Direction, ObserverSlot, TransferError, TransferHandle,
+        TransferType,
     },
     DeviceInfo, Error, Speed,
 };
@@ -49,11 +53,22 @@ pub(crate) struct LinuxDevice {
 
     sysfs: Option<SysfsPath>,
     active_config: AtomicU8,
+
+    /// Value of `power/control` to restore on close, if it was changed.
+    restore_power_control: Mutex<Option<String>>,
+
+    /// Addresses of the URBs submitted and not yet reaped, so that those of
+    /// an endpoint can be discarded by `abort_pipe`.
+    pending_urbs: Mutex<HashSet<usize>>,
+
+    pub(crate) observer: ObserverSlot,
+    pub(crate) disconnect: DisconnectSignal,
 }
 
 impl LinuxDevice {
     pub(crate) fn from_device_info(
         d: &DeviceInfo,
+        _shared: bool,
     ) -> impl MaybeFuture<Output = Result<Arc<LinuxDevice>, Error>> {
         let busnum = d.busnum();
         let devnum = d.device_address();
@@ -112,6 +127,10 @@ impl LinuxDevice {
                 descriptors,
                 sysfs,
                 active_config: AtomicU8::new(active_config),
+                restore_power_control: Mutex::new(None),
+                pending_urbs: Mutex::new(HashSet::new()),
+                observer: ObserverSlot::default(),
+                disconnect: DisconnectSignal::default(),
             }
         });
 
@@ -141,6 +160,11 @@ impl LinuxDevice {
         debug!("Handling events for device {}", self.events_id);
         match usbfs::reap_urb_ndelay(&self.fd) {
             Ok(urb_ptr) => {
+                // Removed before completing, which may free the URB.
+                self.pending_urbs
+                    .lock()
+                    .unwrap()
+                    .remove(&(urb_ptr as usize));
                 let user_data = {
                     let urb = unsafe { &*urb_ptr };
                     debug!(
@@ -162,6 +186,7 @@ impl LinuxDevice {
                 // keep the event thread from spinning because we won't receive further events.
                 // The drop impl will try to unregister again, but that's ok.
                 events::unregister_fd(self.fd.as_fd());
+                self.disconnect.notify();
             }
             Err(e) => {
                 error!("Unexpected error {e} from REAPURBNDELAY");
@@ -169,6 +194,10 @@ impl LinuxDevice {
         }
     }
 
+    pub(crate) fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+
     pub(crate) fn device_descriptor(&self) -> DeviceDescriptor {
         DeviceDescriptor::new(&self.descriptors).unwrap()
     }
@@ -179,6 +208,10 @@ impl LinuxDevice {
         parse_concatenated_config_descriptors(&self.descriptors[DESCRIPTOR_LEN_DEVICE as usize..])
     }
 
+    pub(crate) fn update_active_configuration(&self, configuration: u8) {
+        self.active_config.store(configuration, Ordering::SeqCst);
+    }
+
     pub(crate) fn active_configuration_value(&self) -> u8 {
         if let Some(sysfs) = self.sysfs.as_ref() {
             match sysfs.read_attr("bConfigurationValue") {
@@ -288,11 +321,12 @@ impl LinuxDevice {
         interface_number: u8,
     ) -> impl MaybeFuture<Output = Result<Arc<LinuxInterface>, Error>> {
         Blocking::new(move || {
-            usbfs::claim_interface(&self.fd, interface_number).inspect_err(|e| {
+            usbfs::claim_interface(&self.fd, interface_number).map_err(|e| {
                 warn!(
                     "Failed to claim interface {interface_number} on device id {dev}: {e}",
                     dev = self.events_id
-                )
+                );
+                self.claim_error(interface_number, e)
             })?;
             debug!(
                 "Claimed interface {interface_number} on device id {dev}",
@@ -301,18 +335,44 @@ impl LinuxDevice {
             Ok(Arc::new(LinuxInterface {
                 device: self,
                 interface_number,
-                reattach: false,
+                reattach: AtomicBool::new(false),
                 state: Mutex::new(Default::default()),
+                send_zlp: AtomicU16::new(0),
             }))
         })
     }
 
+    /// Name the kernel driver in the error if the interface is claimed by one.
+    fn claim_error(&self, interface_number: u8, e: Errno) -> Error {
+        if e == Errno::BUSY {
+            if let Ok(driver) = usbfs::get_driver(&self.fd, interface_number) {
+                return Error::new(
+                    Error::from(e).kind(),
+                    format!("interface {interface_number} is in use by kernel driver `{driver}`"),
+                );
+            }
+        }
+        e.into()
+    }
+
     pub(crate) fn detach_and_claim_interface(
         self: Arc<Self>,
         interface_number: u8,
+        policy: Option<DetachPolicy>,
     ) -> impl MaybeFuture<Output = Result<Arc<LinuxInterface>, Error>> {
         Blocking::new(move || {
-            usbfs::detach_and_claim_interface(&self.fd, interface_number)?;
+            let approved = self.check_detach_policy(interface_number, policy.as_ref())?;
+            match usbfs::detach_and_claim_interface(&self.fd, interface_number, approved.as_deref())
+            {
+                // `USBDEVFS_DISCONNECT_CLAIM` was added in Linux 3.8. Older
+                // kernels need two steps, leaving a window for the driver to
+                // be rebound in between.
+                Err(Errno::NOTTY) => {
+                    debug!("USBDEVFS_DISCONNECT_CLAIM not supported, detaching and claiming separately");
+                    self.detach_then_claim(interface_number, approved.as_deref())?;
+                }
+                res => res.map_err(|e| self.claim_error(interface_number, e))?,
+            }
             debug!(
                 "Detached and claimed interface {interface_number} on device id {dev}",
                 dev = self.events_id
@@ -320,20 +380,92 @@ impl LinuxDevice {
             Ok(Arc::new(LinuxInterface {
                 device: self,
                 interface_number,
-                reattach: true,
+                reattach: AtomicBool::new(true),
                 state: Mutex::new(Default::default()),
+                send_zlp: AtomicU16::new(0),
             }))
         })
     }
 
+    fn detach_then_claim(
+        &self,
+        interface_number: u8,
+        if_driver: Option<&str>,
+    ) -> Result<(), Error> {
+        match usbfs::get_driver(&self.fd, interface_number) {
+            Ok(driver) if driver == "usbfs" => {}
+            Ok(driver) if if_driver.is_some_and(|d| d != driver) => {
+                return Err(self.claim_error(interface_number, Errno::BUSY));
+            }
+            Ok(_) => match usbfs::detach_kernel_driver(&self.fd, interface_number) {
+                Ok(()) | Err(Errno::NODATA) => {}
+                Err(e) => return Err(e.into()),
+            },
+            Err(Errno::NODATA) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        usbfs::claim_interface(&self.fd, interface_number)
+            .map_err(|e| self.claim_error(interface_number, e))
+    }
+
     #[cfg(target_os = "linux")]
     pub(crate) fn detach_kernel_driver(
         self: &Arc<Self>,
         interface_number: u8,
+        policy: Option<DetachPolicy>,
     ) -> Result<(), Error> {
+        self.check_detach_policy(interface_number, policy.as_ref())?;
         usbfs::detach_kernel_driver(&self.fd, interface_number).map_err(|e| e.into())
     }
 
+    /// Ask `policy` whether the kernel driver bound to the interface may be
+    /// detached. Returns the name of the driver that was approved, if one is
+    /// bound and a policy is set.
+    fn check_detach_policy(
+        &self,
+        interface_number: u8,
+        policy: Option<&DetachPolicy>,
+    ) -> Result<Option<String>, Error> {
+        let Some(policy) = policy else {
+            return Ok(None);
+        };
+
+        let driver = match usbfs::get_driver(&self.fd, interface_number) {
+            Ok(driver) if driver != "usbfs" => driver,
+            Ok(_) | Err(Errno::NODATA) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let active_config = self.active_configuration_value();
+        let interface = self
+            .configuration_descriptors()
+            .find(|c| c.configuration_value() == active_config)
+            .and_then(|c| {
+                c.interfaces()
+                    .find(|i| i.interface_number() == interface_number)
+            })
+            .map(|i| i.first_alt_setting());
+
+        let request = DetachRequest {
+            interface_number,
+            driver: &driver,
+            device_descriptor: self.device_descriptor(),
+            interface,
+        };
+
+        if policy(&request) {
+            debug!("Detach policy allowed detaching {driver} from interface {interface_number}");
+            Ok(Some(driver))
+        } else {
+            warn!("Detach policy refused detaching {driver} from interface {interface_number}");
+            Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("detaching kernel driver `{driver}` was refused by the detach policy"),
+            ))
+        }
+    }
+
     #[cfg(target_os = "linux")]
     pub(crate) fn attach_kernel_driver(
         self: &Arc<Self>,
@@ -342,9 +474,49 @@ impl LinuxDevice {
         usbfs::attach_kernel_driver(&self.fd, interface_number).map_err(|e| e.into())
     }
 
+    #[cfg(target_os = "linux")]
+    pub(crate) fn bind_kernel_driver(
+        &self,
+        interface_number: u8,
+        driver: &str,
+        policy: Option<DetachPolicy>,
+    ) -> Result<(), Error> {
+        if driver.is_empty() || driver.contains('/') || driver.starts_with('.') {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid driver name"));
+        }
+
+        let sysfs = self.sysfs.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                "binding a driver requires a device opened from sysfs",
+            )
+        })?;
+        let interface = sysfs.interface(self.active_configuration_value(), interface_number);
+        let name = interface.name();
+
+        match interface.readlink_attr_filename("driver") {
+            Ok(current) if current == driver => return Ok(()),
+            Ok(current) => {
+                self.check_detach_policy(interface_number, policy.as_ref())?;
+                interface.write_attr("driver/unbind", name)?;
+                debug!("Unbound {current} from interface {name}");
+            }
+            Err(_) => {}
+        }
+
+        SysfsPath(PathBuf::from("/sys/bus/usb/drivers").join(driver)).write_attr("bind", name)?;
+        debug!("Bound {driver} to interface {name}");
+        Ok(())
+    }
+
     pub(crate) unsafe fn submit_urb(&self, urb: *mut Urb) {
         let ep = unsafe { (*urb).endpoint };
+
+        // Added before submitting, since it may be reaped right away.
+        self.pending_urbs.lock().unwrap().insert(urb as usize);
         if let Err(e) = usbfs::submit_urb(&self.fd, urb) {
+            self.pending_urbs.lock().unwrap().remove(&(urb as usize));
+
             // SAFETY: Transfer was not submitted. We still own the transfer
             // and can write to the URB and complete it in place of the handler.
             unsafe {
@@ -362,6 +534,20 @@ impl LinuxDevice {
         }
     }
 
+    /// Discard all pending URBs on `endpoint`.
+    pub(crate) fn discard_endpoint_urbs(&self, endpoint: u8) {
+        // The lock is held while discarding, so that the URBs aren't reaped
+        // and freed in the meantime.
+        let pending = self.pending_urbs.lock().unwrap();
+        for &urb in pending.iter() {
+            let urb = urb as *mut Urb;
+            // SAFETY: the URB is pending, so it hasn't been freed
+            if unsafe { (*urb).endpoint } == endpoint {
+                unsafe { self.cancel_urb(urb) };
+            }
+        }
+    }
+
     pub(crate) unsafe fn cancel_urb(&self, urb: *mut Urb) {
         unsafe {
             if let Err(e) = usbfs::discard_urb(&self.fd, urb) {
@@ -384,7 +570,7 @@ impl LinuxDevice {
         };
 
         let r = usbfs::control(
-            &fd,
+            fd,
             usbfs::CtrlTransfer {
                 bRequestType: control.request_type(Direction::In),
                 bRequest: control.request,
@@ -437,7 +623,79 @@ impl LinuxDevice {
             "No available configurations for device fd {}",
             fd.as_raw_fd()
         );
-        return Err(ErrorKind::Other.into());
+        Err(ErrorKind::Other.into())
+    }
+
+    pub(crate) fn alloc_mmap_buffer(&self, len: usize) -> Result<MmapBuffer, Error> {
+        let caps = usbfs::get_capabilities(&self.fd)
+            .inspect_err(|e| warn!("USBDEVFS_GET_CAPABILITIES failed: {e}"))?;
+        if caps & usbfs::USBDEVFS_CAP_MMAP == 0 {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "kernel does not support usbfs mmap buffers",
+            ));
+        }
+        MmapBuffer::new(&self.fd, len)
+    }
+
+    fn power_sysfs(&self) -> Result<&SysfsPath, Error> {
+        self.sysfs.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                "power management requires a device opened from sysfs",
+            )
+        })
+    }
+
+    pub(crate) fn set_autosuspend(&self, enable: bool) -> Result<(), Error> {
+        let sysfs = self.power_sysfs()?;
+        let mut restore = self.restore_power_control.lock().unwrap();
+        let previous: String = sysfs.read_attr("power/control")?;
+        sysfs.write_attr("power/control", if enable { "auto" } else { "on" })?;
+        restore.get_or_insert(previous);
+        Ok(())
+    }
+
+    pub(crate) fn set_autosuspend_delay(&self, delay: Duration) -> Result<(), Error> {
+        let ms = delay.as_millis().to_string();
+        self.power_sysfs()?
+            .write_attr("power/autosuspend_delay_ms", &ms)?;
+        Ok(())
+    }
+
+    pub(crate) fn set_remote_wakeup(&self, enable: bool) -> Result<(), Error> {
+        // Without sysfs, e.g. for a device from `from_fd` on Android, only
+        // the request to the device is sent.
+        let Some(sysfs) = &self.sysfs else {
+            debug!("No sysfs path, not setting power/wakeup");
+            return Ok(());
+        };
+        sysfs.write_attr("power/wakeup", if enable { "enabled" } else { "disabled" })?;
+        Ok(())
+    }
+
+    pub(crate) fn set_usb3_lpm_permit(&self, u1: bool, u2: bool) -> Result<(), Error> {
+        let permit = match (u1, u2) {
+            (false, false) => "0",
+            (true, false) => "u1",
+            (false, true) => "u2",
+            (true, true) => "u1_u2",
+        };
+        self.power_sysfs()?
+            .write_attr("port/usb3_lpm_permit", permit)?;
+        Ok(())
+    }
+
+    pub(crate) fn power_state(&self) -> Result<PowerState, Error> {
+        let status: String = self.power_sysfs()?.read_attr("power/runtime_status")?;
+        Ok(match status.as_str() {
+            "active" => PowerState::Active,
+            "suspending" => PowerState::Suspending,
+            "suspended" => PowerState::Suspended,
+            "resuming" => PowerState::Resuming,
+            "unsupported" => PowerState::Unsupported,
+            _ => PowerState::Error,
+        })
     }
 
     pub(crate) fn speed(&self) -> Option<Speed> {
@@ -459,6 +717,14 @@ impl LinuxDevice {
 impl Drop for LinuxDevice {
     fn drop(&mut self) {
         debug!("Closing device {}", self.events_id);
+
+        if let Some(control) = self.restore_power_control.get_mut().unwrap().take() {
+            if let Some(sysfs) = &self.sysfs {
+                let res = sysfs.write_attr("power/control", &control);
+                debug!("Restored power/control to {control:?}: {res:?}");
+            }
+        }
+
         events::unregister_fd(self.fd.as_fd());
         DEVICES.lock().unwrap().remove(self.events_id);
     }
@@ -467,8 +733,13 @@ impl Drop for LinuxDevice {
 pub(crate) struct LinuxInterface {
     pub(crate) interface_number: u8,
     pub(crate) device: Arc<LinuxDevice>,
-    reattach: bool,
+    /// Whether to reconnect the kernel driver when the interface is released.
+    reattach: AtomicBool,
     state: Mutex<InterfaceState>,
+
+    /// Bit `n` is set if `OUT` transfers on endpoint `n` are terminated with
+    /// a zero-length packet.
+    send_zlp: AtomicU16,
 }
 
 #[derive(Default)]
@@ -512,6 +783,42 @@ impl LinuxInterface {
         self.state.lock().unwrap().alt_setting
     }
 
+    pub fn refresh_alt_setting(&self) -> Result<u8, Error> {
+        let mut state = self.state.lock().unwrap();
+        let alt_setting = match self.device.sysfs.as_ref() {
+            Some(sysfs) => {
+                let config = self.device.active_configuration_value();
+                sysfs
+                    .interface(config, self.interface_number)
+                    .read_attr("bAlternateSetting")?
+            }
+            None => {
+                // Without sysfs, ask the device with GET_INTERFACE
+                let mut buf = [0];
+                let len = self.device.control_in_blocking(
+                    Control {
+                        control_type: ControlType::Standard,
+                        recipient: Recipient::Interface,
+                        request: 0x0A,
+                        value: 0,
+                        index: self.interface_number.into(),
+                    },
+                    &mut buf,
+                    Duration::from_secs(1),
+                )?;
+                if len != 1 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "GET_INTERFACE returned no data",
+                    ));
+                }
+                buf[0]
+            }
+        };
+        state.alt_setting = alt_setting;
+        Ok(alt_setting)
+    }
+
     pub fn set_alt_setting(
         self: Arc<Self>,
         alt_setting: u8,
@@ -537,6 +844,54 @@ impl LinuxInterface {
             Ok(usbfs::clear_halt(&self.device.fd, endpoint)?)
         })
     }
+
+    pub fn reset_pipe(
+        self: Arc<Self>,
+        endpoint: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            debug!("Reset pipe, endpoint {endpoint:02x}");
+            Ok(usbfs::reset_ep(&self.device.fd, endpoint)?)
+        })
+    }
+
+    pub fn set_send_zlp(&self, endpoint: u8, enable: bool) -> Result<(), Error> {
+        // Applied as `USBDEVFS_URB_ZERO_PACKET` to each URB when submitted
+        let bit = 1 << (endpoint & 0x0f);
+        if enable {
+            self.send_zlp.fetch_or(bit, Ordering::Relaxed);
+        } else {
+            self.send_zlp.fetch_and(!bit, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn abort_pipe(&self, endpoint: u8) -> Result<(), Error> {
+        // usbfs only cancels individual URBs, so discard each one pending on
+        // the endpoint.
+        self.device.discard_endpoint_urbs(endpoint);
+        Ok(())
+    }
+
+    pub fn frame_number(&self) -> Result<u64, Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "usbfs does not provide the bus frame number",
+        ))
+    }
+
+    pub(crate) fn set_reattach_on_drop(&self, reattach: bool) {
+        self.reattach.store(reattach, Ordering::Relaxed);
+    }
+
+    pub(crate) fn reattach_on_drop(&self) -> bool {
+        self.reattach.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn send_zlp(&self, endpoint: u8) -> bool {
+        endpoint & 0x80 == 0
+            && self.send_zlp.load(Ordering::Relaxed) & (1 << (endpoint & 0x0f)) != 0
+    }
 }
 
 impl Drop for LinuxInterface {
@@ -547,7 +902,7 @@ impl Drop for LinuxInterface {
             self.interface_number, self.device.events_id
         );
 
-        if res.is_ok() && self.reattach {
+        if res.is_ok() && *self.reattach.get_mut() {
             let res = usbfs::attach_kernel_driver(&self.device.fd, self.interface_number);
             debug!(
                 "Reattached kernel drivers for interface {} on device {}: {res:?}",
@@ -8,10 +8,13 @@ use log::debug;
 use log::warn;
 
 use crate::enumeration::InterfaceInfo;
+use crate::hotplug::Filter;
 use crate::maybe_future::{MaybeFuture, Ready};
 use crate::{BusInfo, DeviceInfo, Error, Speed, UsbControllerType};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct SysfsPath(pub(crate) PathBuf);
 
 #[derive(Debug)]
@@ -44,7 +47,11 @@ impl std::error::Error for SysfsError {
 
 impl From<SysfsError> for io::Error {
     fn from(value: SysfsError) -> Self {
-        io::Error::other(Box::new(value))
+        let kind = match &value.1 {
+            SysfsErrorKind::Io(e) => e.kind(),
+            SysfsErrorKind::Parse(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, Box::new(value))
     }
 }
 
@@ -66,6 +73,28 @@ impl SysfsPath {
         fs::read_link(&attr_path).map_err(|e| SysfsError(attr_path, SysfsErrorKind::Io(e)))
     }
 
+    /// Get the sysfs path of one of the device's interfaces.
+    pub(crate) fn interface(&self, configuration: u8, interface: u8) -> SysfsPath {
+        let name = self.name();
+
+        // Root hubs are named `usbN`, while their interfaces are `N-0:C.I`
+        let port = match name.strip_prefix("usb") {
+            Some(bus) => format!("{bus}-0"),
+            None => name.to_owned(),
+        };
+        SysfsPath(self.0.join(format!("{port}:{configuration}.{interface}")))
+    }
+
+    /// Get the name of the sysfs directory, e.g. `1-2:1.0` for an interface.
+    pub(crate) fn name(&self) -> &str {
+        self.0.file_name().and_then(|n| n.to_str()).unwrap_or("")
+    }
+
+    pub(crate) fn write_attr(&self, attr: &str, value: &str) -> Result<(), SysfsError> {
+        let attr_path = self.0.join(attr);
+        fs::write(&attr_path, value).map_err(|e| SysfsError(attr_path, SysfsErrorKind::Io(e)))
+    }
+
     pub(crate) fn read_attr<T: FromStr>(&self, attr: &str) -> Result<T, SysfsError> {
         self.parse_attr(attr, |s| s.parse())
     }
@@ -95,7 +124,7 @@ impl SysfsPath {
         fs::read_dir(&self.0)
             .ok()
             .into_iter()
-            .flat_map(|x| x)
+            .flatten()
             .filter_map(|f| f.ok())
             .filter(|f| f.file_type().ok().is_some_and(|t| t.is_dir()))
             .map(|f| SysfsPath(f.path()))
@@ -118,34 +147,61 @@ impl FromHexStr for u16 {
     }
 }
 
-const SYSFS_USB_PREFIX: &'static str = "/sys/bus/usb/devices/";
+const SYSFS_USB_PREFIX: &str = "/sys/bus/usb/devices/";
 
 pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>>
 {
-    Ready((|| {
-        Ok(fs::read_dir(SYSFS_USB_PREFIX)?.flat_map(|entry| {
+    list_devices_filtered(Filter::new())
+}
+
+pub fn list_devices_filtered(
+    filter: Filter,
+) -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>> {
+    Ready((move || {
+        Ok(fs::read_dir(SYSFS_USB_PREFIX)?.flat_map(move |entry| {
             let path = entry.ok()?.path();
             let name = path.file_name()?;
 
             // Device names look like `1-6` or `1-6.4.2`
             // We'll ignore:
-            //  * root hubs (`usb1`) -- they're not useful to talk to and are not exposed on other platforms
+            //  * root hubs (`usb1`) -- they're not useful to talk to and are not exposed on other
+            //    platforms, unless requested by the filter
             //  * interfaces (`1-6:1.0`)
-            if !name
+            let is_device = name
                 .as_encoded_bytes()
                 .iter()
-                .all(|c| matches!(c, b'0'..=b'9' | b'-' | b'.'))
-            {
+                .all(|c| matches!(c, b'0'..=b'9' | b'-' | b'.'));
+            let is_root_hub = name.as_encoded_bytes().starts_with(b"usb");
+            if !(is_device || (is_root_hub && filter.includes_root_hubs())) {
+                return None;
+            }
+
+            let path = SysfsPath(path);
+            if !prefilter(&path, &filter) {
                 return None;
             }
 
-            probe_device(SysfsPath(path))
+            probe_device(path)
                 .inspect_err(|e| warn!("{e}; ignoring device"))
                 .ok()
+                .filter(|d| filter.matches(d))
         }))
     })())
 }
 
+/// Check the attributes `filter` can reject a device on, without reading the
+/// rest of the device's attributes or its interfaces.
+fn prefilter(path: &SysfsPath, filter: &Filter) -> bool {
+    let ids = path
+        .read_attr_hex("idVendor")
+        .and_then(|v| Ok((v, path.read_attr_hex("idProduct")?)));
+    let bus = path.read_attr::<u8>("busnum");
+
+    // Leave unreadable devices to `probe_device`, which logs the error
+    ids.map_or(true, |(v, p)| filter.matches_ids(v, p))
+        && bus.map_or(true, |b| filter.matches_bus(&format!("{b:03}")))
+}
+
 pub fn list_root_hubs() -> Result<impl Iterator<Item = DeviceInfo>, Error> {
     Ok(fs::read_dir(SYSFS_USB_PREFIX)?.filter_map(|entry| {
         let path = entry.ok()?.path();
@@ -198,12 +254,7 @@ pub fn probe_device(path: SysfsPath) -> Result<DeviceInfo, SysfsError> {
     let port_chain = path
         .read_attr::<String>("devpath")
         .ok()
-        .filter(|p| p != "0") // root hub should be empty but devpath is 0
-        .and_then(|p| {
-            p.split('.')
-                .map(|v| v.parse::<u8>().ok())
-                .collect::<Option<Vec<u8>>>()
-        })
+        .and_then(|p| parse_devpath(&p))
         .unwrap_or_default();
 
     Ok(DeviceInfo {
@@ -213,6 +264,7 @@ pub fn probe_device(path: SysfsPath) -> Result<DeviceInfo, SysfsError> {
         port_chain,
         vendor_id: path.read_attr_hex("idVendor")?,
         product_id: path.read_attr_hex("idProduct")?,
+        usb_version: path.parse_attr("version", parse_version)?,
         device_version: path.read_attr_hex("bcdDevice")?,
         class: path.read_attr_hex("bDeviceClass")?,
         subclass: path.read_attr_hex("bDeviceSubClass")?,
@@ -245,6 +297,7 @@ pub fn probe_device(path: SysfsPath) -> Result<DeviceInfo, SysfsError> {
                         subclass: i.read_attr_hex("bInterfaceSubClass").ok()?,
                         protocol: i.read_attr_hex("bInterfaceProtocol").ok()?,
                         interface_string: i.read_attr("interface").ok(),
+                        driver: i.readlink_attr_filename("driver").ok(),
                     })
                 })
                 .collect();
@@ -254,3 +307,40 @@ pub fn probe_device(path: SysfsPath) -> Result<DeviceInfo, SysfsError> {
         path,
     })
 }
+
+/// Parse the sysfs `devpath` attribute, the dot-separated port numbers from
+/// the root hub, e.g. `2.4.1`.
+fn parse_devpath(devpath: &str) -> Option<Vec<u8>> {
+    // The root hub's devpath is `0`, but its port chain is empty.
+    if devpath == "0" {
+        return Some(Vec::new());
+    }
+
+    devpath.split('.').map(|v| v.parse::<u8>().ok()).collect()
+}
+
+#[test]
+fn test_parse_devpath() {
+    assert_eq!(parse_devpath("2.4.1"), Some(vec![2, 4, 1]));
+    assert_eq!(parse_devpath("3"), Some(vec![3]));
+    assert_eq!(parse_devpath("0"), Some(vec![]));
+    assert_eq!(parse_devpath("1.x"), None);
+}
+
+/// Parse the sysfs `version` attribute back into `bcdUSB`. The kernel prints
+/// the two bytes as hex separated by a dot, e.g. `2.10` for `0x0210`.
+fn parse_version(version: &str) -> Result<u16, ParseIntError> {
+    let (major, minor) = version.split_once('.').unwrap_or((version, ""));
+    Ok(u16::from_be_bytes([
+        u8::from_str_radix(major, 16)?,
+        u8::from_str_radix(minor, 16)?,
+    ]))
+}
+
+#[test]
+fn test_parse_version() {
+    assert_eq!(parse_version("2.10").unwrap(), 0x0210);
+    assert_eq!(parse_version("3.20").unwrap(), 0x0320);
+    assert_eq!(parse_version("1.00").unwrap(), 0x0100);
+    assert!(parse_version("2").is_err());
+}
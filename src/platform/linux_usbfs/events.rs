@@ -11,7 +11,7 @@
 //! on a device use the same file descriptor, putting USB-specific
 //! dispatch in the event loop avoids additonal synchronization.
 
-use crate::Error;
+use crate::{event_thread, Error};
 use once_cell::sync::OnceCell;
 use rustix::{
     event::epoll::{self, EventData, EventFlags},
@@ -23,7 +23,6 @@ use std::{
     io,
     sync::{Arc, Mutex},
     task::Waker,
-    thread,
 };
 
 use atomic_waker::AtomicWaker;
@@ -52,7 +51,7 @@ impl Tag {
     fn from_event_data(data: EventData) -> Self {
         let id = (data.u64() >> 3) as usize;
         let tag = data.u64() & 0b111;
-        match (tag, id as usize) {
+        match (tag, id) {
             (Self::DEVICE, id) => Tag::Device(id),
             (Self::WAKER, id) => Tag::Waker(id),
             _ => panic!("Invalid event data"),
@@ -70,8 +69,9 @@ pub(super) fn register_fd(fd: BorrowedFd, tag: Tag, flags: EventFlags) -> Result
     })?;
 
     if start_thread {
-        thread::spawn(event_loop);
+        event_thread::spawn(event_loop);
     }
+    event_thread::check()?;
 
     epoll::add(epoll_fd, fd, tag.as_event_data(), flags).inspect_err(|e| {
         log::error!("Failed to add epoll watch: {e}");
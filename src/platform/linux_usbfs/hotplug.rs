@@ -1,5 +1,5 @@
 use libc::{sockaddr, sockaddr_nl, socklen_t, AF_NETLINK, MSG_DONTWAIT};
-use log::{error, trace, warn};
+use log::{debug, error, trace, warn};
 use rustix::{
     fd::{AsFd, AsRawFd, OwnedFd},
     net::{netlink, socket_with, AddressFamily, SocketFlags, SocketType},
@@ -18,13 +18,30 @@ use super::{enumeration::probe_device, events::Async, SysfsPath};
 
 const UDEV_MAGIC: &[u8; 12] = b"libudev\0\xfe\xed\xca\xfe";
 const UDEV_MULTICAST_GROUP: u32 = 1 << 1;
+const KERNEL_MULTICAST_GROUP: u32 = 1 << 0;
+
+/// Exists while udevd is running.
+const UDEV_CONTROL_PATH: &str = "/run/udev/control";
 
 pub(crate) struct LinuxHotplugWatch {
     fd: Async<OwnedFd>,
+    group: u32,
 }
 
 impl LinuxHotplugWatch {
     pub(crate) fn new() -> Result<Self, Error> {
+        // udevd rebroadcasts the kernel's uevents once it has processed
+        // them, so listen to it if it is running: by then the device node
+        // has been created and its permissions set by udev rules. Without
+        // udev (e.g. in containers and minimal systems), listen to the
+        // kernel's uevents directly.
+        let group = if Path::new(UDEV_CONTROL_PATH).exists() {
+            UDEV_MULTICAST_GROUP
+        } else {
+            debug!("udev is not running, listening to kernel uevents");
+            KERNEL_MULTICAST_GROUP
+        };
+
         let fd = socket_with(
             AddressFamily::NETLINK,
             SocketType::RAW,
@@ -37,7 +54,7 @@ impl LinuxHotplugWatch {
             // so use libc for now.
             let mut addr: sockaddr_nl = mem::zeroed();
             addr.nl_family = AF_NETLINK as u16;
-            addr.nl_groups = UDEV_MULTICAST_GROUP;
+            addr.nl_groups = group;
             let r = libc::bind(
                 fd.as_raw_fd(),
                 &addr as *const sockaddr_nl as *const sockaddr,
@@ -50,11 +67,12 @@ impl LinuxHotplugWatch {
 
         Ok(LinuxHotplugWatch {
             fd: Async::new(fd)?,
+            group,
         })
     }
 
     pub(crate) fn poll_next(&mut self, cx: &mut std::task::Context<'_>) -> Poll<HotplugEvent> {
-        if let Some(event) = try_receive_event(self.fd.inner.as_fd()) {
+        if let Some(event) = try_receive_event(self.fd.inner.as_fd(), self.group) {
             return Poll::Ready(event);
         }
 
@@ -66,7 +84,7 @@ impl LinuxHotplugWatch {
     }
 }
 
-fn try_receive_event(fd: BorrowedFd) -> Option<HotplugEvent> {
+fn try_receive_event(fd: BorrowedFd, group: u32) -> Option<HotplugEvent> {
     let mut buf = [0; 8192];
 
     let received = unsafe {
@@ -81,7 +99,7 @@ fn try_receive_event(fd: BorrowedFd) -> Option<HotplugEvent> {
             &mut addrlen,
         );
         if r >= 0 {
-            Ok((r as usize, addr.nl_groups))
+            Ok((r as usize, addr.nl_groups, addr.nl_pid))
         } else {
             Err(Error::last_os_error())
         }
@@ -90,9 +108,14 @@ fn try_receive_event(fd: BorrowedFd) -> Option<HotplugEvent> {
     match received {
         // udev messages will normally be sent to a multicast group, which only
         // root can send to. Reject unicast messages that may be from anywhere.
-        Ok((size, groups)) if groups == UDEV_MULTICAST_GROUP => parse_packet(&buf[..size]),
-        Ok((_, src)) => {
-            warn!("udev netlink socket received message from {src:?}");
+        // Kernel messages also come from port ID 0.
+        Ok((size, groups, pid))
+            if groups == group && (group == UDEV_MULTICAST_GROUP || pid == 0) =>
+        {
+            parse_packet(&buf[..size])
+        }
+        Ok((_, groups, pid)) => {
+            warn!("uevent netlink socket received message from {pid} to {groups:?}");
             None
         }
         Err(e) if e.kind() == ErrorKind::WouldBlock => None,
@@ -104,21 +127,10 @@ fn try_receive_event(fd: BorrowedFd) -> Option<HotplugEvent> {
 }
 
 fn parse_packet(buf: &[u8]) -> Option<HotplugEvent> {
-    if buf.len() < 24 {
-        error!("packet too short: {buf:x?}");
-        return None;
-    }
-
-    if !buf.starts_with(UDEV_MAGIC) {
-        error!("packet does not start with expected header: {buf:x?}");
-        return None;
-    }
-
-    let properties_off = u32::from_ne_bytes(buf[16..20].try_into().unwrap()) as usize;
-    let properties_len = u32::from_ne_bytes(buf[20..24].try_into().unwrap()) as usize;
-    let Some(properties_buf) = buf.get(properties_off..properties_off + properties_len) else {
-        error!("properties offset={properties_off} length={properties_len} exceeds buffer length {len}", len = buf.len());
-        return None;
+    let properties_buf = if buf.starts_with(UDEV_MAGIC) {
+        udev_properties(buf)?
+    } else {
+        kernel_properties(buf)?
     };
 
     let mut is_add = None;
@@ -175,8 +187,54 @@ fn parse_packet(buf: &[u8]) -> Option<HotplugEvent> {
     }
 }
 
+/// Get the properties of a message from udev, which starts with a header
+/// giving their offset and length.
+fn udev_properties(buf: &[u8]) -> Option<&[u8]> {
+    if buf.len() < 24 {
+        error!("packet too short: {buf:x?}");
+        return None;
+    }
+
+    let properties_off = u32::from_ne_bytes(buf[16..20].try_into().unwrap()) as usize;
+    let properties_len = u32::from_ne_bytes(buf[20..24].try_into().unwrap()) as usize;
+    let properties_buf = buf.get(properties_off..properties_off + properties_len);
+    if properties_buf.is_none() {
+        error!("properties offset={properties_off} length={properties_len} exceeds buffer length {len}", len = buf.len());
+    }
+    properties_buf
+}
+
+/// Get the properties of a message from the kernel, which follow an
+/// `ACTION@DEVPATH` summary line.
+fn kernel_properties(buf: &[u8]) -> Option<&[u8]> {
+    let header_end = buf.iter().position(|&b| b == 0);
+    match header_end {
+        Some(end) if buf[..end].contains(&b'@') => Some(&buf[end + 1..]),
+        _ => {
+            error!("packet does not start with expected header: {buf:x?}");
+            None
+        }
+    }
+}
+
 /// Split nul-separated key=value pairs
 fn parse_properties(buf: &[u8]) -> impl Iterator<Item = (&str, &str)> + '_ {
     buf.split(|b| b == &0)
         .filter_map(|entry| std::str::from_utf8(entry).ok()?.split_once('='))
 }
+
+#[test]
+fn test_parse_kernel_packet() {
+    let packet = b"remove@/devices/pci0000:00/0000:00:14.0/usb1/1-2\0\
+        ACTION=remove\0DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-2\0\
+        SUBSYSTEM=usb\0DEVTYPE=usb_device\0BUSNUM=001\0DEVNUM=005\0SEQNUM=4242\0";
+    let Some(HotplugEvent::Disconnected(id)) = parse_packet(packet) else {
+        panic!("expected a disconnect event");
+    };
+    assert_eq!(id.0, super::DeviceId { bus: 1, addr: 5 });
+
+    let interface = b"remove@/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0\0\
+        ACTION=remove\0SUBSYSTEM=usb\0DEVTYPE=usb_interface\0";
+    assert!(parse_packet(interface).is_none());
+    assert!(parse_packet(b"not a uevent").is_none());
+}
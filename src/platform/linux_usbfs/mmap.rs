@@ -0,0 +1,154 @@
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    ptr::{null_mut, NonNull},
+    slice,
+};
+
+use log::error;
+use rustix::{
+    fd::AsFd,
+    mm::{mmap, munmap, MapFlags, ProtFlags},
+};
+
+use crate::{transfer::TransferRequest, Error};
+
+/// A transfer buffer allocated by the kernel and mapped into this process.
+///
+/// Transfers on Linux normally copy data between the buffer and kernel memory.
+/// An `MmapBuffer` is allocated from the device's `usbfs` file with `mmap`, so
+/// the host controller can access it directly and the copy is skipped. This
+/// matters for sustained high-throughput transfers, such as SuperSpeed capture.
+///
+/// Allocate one with
+/// [`Device::alloc_mmap_buffer`][crate::Device::alloc_mmap_buffer], and submit
+/// it with [`Interface::bulk_mmap`][crate::Interface::bulk_mmap] or
+/// [`Interface::bulk_mmap_queue`][crate::Interface::bulk_mmap_queue].
+///
+/// Like a `Vec<u8>`, the buffer has a fixed [`capacity`][Self::capacity] and a
+/// `len` that can be changed with [`set_len`][Self::set_len].
+/// It dereferences to the first `len` bytes. The whole mapping is
+/// zero-initialized by the kernel, so any length up to the capacity is valid.
+///
+/// * For an `IN` transfer, up to `capacity` bytes are requested, and `len` is
+///   set to the number of bytes received when the transfer completes.
+/// * For an `OUT` transfer, the first `len` bytes are sent, and `len` is set to
+///   the number of bytes sent when the transfer completes.
+///
+/// The memory is unmapped when the `MmapBuffer` is dropped.
+///
+/// This type is only available on Linux and Android.
+pub struct MmapBuffer {
+    ptr: NonNull<u8>,
+    capacity: usize,
+    len: usize,
+}
+
+// SAFETY: `MmapBuffer` uniquely owns its mapping, like a `Vec<u8>`.
+unsafe impl Send for MmapBuffer {}
+unsafe impl Sync for MmapBuffer {}
+
+impl MmapBuffer {
+    pub(crate) fn new<Fd: AsFd>(fd: Fd, capacity: usize) -> Result<MmapBuffer, Error> {
+        if capacity == 0 {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "buffer size must be non-zero",
+            ));
+        }
+
+        // SAFETY: mapping new memory at an address chosen by the kernel
+        let ptr = unsafe {
+            mmap(
+                null_mut(),
+                capacity,
+                ProtFlags::READ | ProtFlags::WRITE,
+                MapFlags::SHARED,
+                fd,
+                0,
+            )?
+        };
+
+        Ok(MmapBuffer {
+            ptr: NonNull::new(ptr as *mut u8).expect("mmap should not return null"),
+            capacity,
+            len: capacity,
+        })
+    }
+
+    /// Get the size of the mapping, which is the maximum length of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Set the length of the data in the buffer.
+    ///
+    /// Panics if `len` is greater than the buffer's capacity.
+    pub fn set_len(&mut self, len: usize) {
+        assert!(len <= self.capacity, "length exceeds buffer capacity");
+        self.len = len;
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+}
+
+impl Deref for MmapBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: the mapping is valid and initialized for `capacity` bytes
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for MmapBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: the mapping is valid and initialized for `capacity` bytes
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for MmapBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for MmapBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl Debug for MmapBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MmapBuffer")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MmapBuffer {
+    fn drop(&mut self) {
+        // SAFETY: we own the mapping, and no transfer is using it because it
+        // is only accessible to the transfer while the transfer owns `self`.
+        if let Err(e) = unsafe { munmap(self.ptr.as_ptr() as *mut _, self.capacity) } {
+            error!("Failed to unmap transfer buffer: {e}");
+        }
+    }
+}
+
+impl TransferRequest for MmapBuffer {
+    type Response = MmapBuffer;
+
+    fn request_len(&self) -> usize {
+        self.len
+    }
+
+    fn response_len(response: &MmapBuffer) -> usize {
+        response.len
+    }
+}
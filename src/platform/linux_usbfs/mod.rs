@@ -3,9 +3,12 @@ use rustix::io::Errno;
 pub(crate) use transfer::TransferData;
 mod usbfs;
 
+mod mmap;
+pub use mmap::MmapBuffer;
+
 mod enumeration;
 mod events;
-pub use enumeration::{list_buses, list_devices, SysfsPath};
+pub use enumeration::{list_buses, list_devices, list_devices_filtered, SysfsPath};
 
 mod device;
 pub(crate) use device::LinuxDevice as Device;
@@ -17,6 +20,7 @@ pub(crate) use hotplug::LinuxHotplugWatch as HotplugWatch;
 use crate::transfer::TransferError;
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceId {
     pub(crate) bus: u8,
     pub(crate) addr: u8,
@@ -26,10 +30,13 @@ fn errno_to_transfer_error(e: Errno) -> TransferError {
     match e {
         Errno::NODEV | Errno::SHUTDOWN => TransferError::Disconnected,
         Errno::PIPE => TransferError::Stall,
-        Errno::NOENT | Errno::CONNRESET | Errno::TIMEDOUT => TransferError::Cancelled,
-        Errno::PROTO | Errno::ILSEQ | Errno::OVERFLOW | Errno::COMM | Errno::TIME => {
-            TransferError::Fault
-        }
-        _ => TransferError::Unknown,
+        Errno::REMOTEIO => TransferError::ShortPacket,
+        Errno::NOENT | Errno::CONNRESET => TransferError::Cancelled,
+        Errno::TIMEDOUT => TransferError::TimedOut,
+        Errno::OVERFLOW => TransferError::Overflow,
+        Errno::PROTO | Errno::ILSEQ => TransferError::CrcOrBitstuff,
+        Errno::TIME => TransferError::NoResponse,
+        Errno::COMM | Errno::NOSR => TransferError::Fault,
+        _ => TransferError::Unknown(e.raw_os_error() as u32),
     }
 }
@@ -1,5 +1,6 @@
 use std::{
-    ffi::c_void,
+    any::Any,
+    ffi::{c_uint, c_void},
     mem::{self, ManuallyDrop},
     ptr::null_mut,
     slice,
@@ -10,16 +11,20 @@ use libc::realloc;
 use rustix::io::Errno;
 
 use crate::transfer::{
-    Completion, ControlIn, ControlOut, PlatformSubmit, PlatformTransfer, RequestBuffer,
-    RequestIsochronousBuffer, ResponseBuffer, TransferError, TransferType, SETUP_PACKET_SIZE,
+    Chunk, ChunkPosition, Completion, ControlIn, ControlInBuffer, ControlInResponse, ControlOut,
+    ControlOutSetup, IsochronousPacket, IsochronousPackets, OutBuffer, OutResponse, PlatformSubmit,
+    PlatformTransfer, RequestBuffer, RequestIsochronousBuffer, ResponseBuffer, TransferError,
+    TransferObserver, TransferRequest, TransferType, SETUP_PACKET_SIZE,
 };
 
 use super::{
     errno_to_transfer_error,
     usbfs::{
-        IsoPacketDesc, Urb, USBDEVFS_URB_TYPE_BULK, USBDEVFS_URB_TYPE_CONTROL,
-        USBDEVFS_URB_TYPE_INTERRUPT, USBDEVFS_URB_TYPE_ISO,
+        IsoPacketDesc, Urb, USBDEVFS_URB_BULK_CONTINUATION, USBDEVFS_URB_SHORT_NOT_OK,
+        USBDEVFS_URB_TYPE_BULK, USBDEVFS_URB_TYPE_CONTROL, USBDEVFS_URB_TYPE_INTERRUPT,
+        USBDEVFS_URB_TYPE_ISO, USBDEVFS_URB_ZERO_PACKET,
     },
+    MmapBuffer,
 };
 
 /// Linux-specific transfer state.
@@ -28,14 +33,22 @@ use super::{
 /// It also owns the `urb` allocation itself, which is stored out-of-line
 /// to avoid violating noalias when submitting the transfer while holding
 /// `&mut TransferData`.
+///
+/// For an `OutBuffer` or `MmapBuffer` transfer, urb.buffer instead points
+/// into `owner`.
 pub struct TransferData {
     urb: *mut Urb,
     capacity: usize,
+    owner: Option<Box<dyn Any + Send>>,
     device: Arc<super::Device>,
 
-    /// Not directly used, exists just to keep the interface from being released
-    /// while active.
-    _interface: Option<Arc<super::Interface>>,
+    /// Position of the transfer being submitted, if it is a chunk of a larger
+    /// transfer. Taken when its flags are set.
+    chunk: Option<ChunkPosition>,
+
+    /// Keeps the interface from being released while the transfer is active,
+    /// and holds the endpoint's zero-length packet setting.
+    interface: Option<Arc<super::Interface>>,
 }
 
 unsafe impl Send for TransferData {}
@@ -71,8 +84,10 @@ impl TransferData {
                 iso_frame_desc: [],
             })),
             capacity: 0,
+            owner: None,
             device,
-            _interface: interface,
+            chunk: None,
+            interface,
         }
     }
 
@@ -103,6 +118,35 @@ impl TransferData {
         }
     }
 
+    /// Flag for a chunk after the first, so that the kernel cancels it if a
+    /// previous chunk of the same transfer fails or ends with a short packet.
+    fn continuation_flag(chunk: Option<ChunkPosition>) -> c_uint {
+        match chunk {
+            Some(c) if !c.first => USBDEVFS_URB_BULK_CONTINUATION,
+            _ => 0,
+        }
+    }
+
+    /// Set the URB flags for an `OUT` transfer.
+    fn set_out_flags(&mut self) {
+        let chunk = self.chunk.take();
+        let endpoint = self.urb_mut().endpoint;
+
+        // Only the end of a transfer split into chunks may need a ZLP.
+        let send_zlp = chunk.map_or(true, |c| c.last)
+            && self
+                .interface
+                .as_ref()
+                .is_some_and(|i| i.send_zlp(endpoint));
+
+        let zlp_flag = if send_zlp {
+            USBDEVFS_URB_ZERO_PACKET
+        } else {
+            0
+        };
+        self.urb_mut().flags = zlp_flag | Self::continuation_flag(chunk);
+    }
+
     fn fill(&mut self, v: Vec<u8>, len: usize, user_data: *mut c_void) {
         let mut v = ManuallyDrop::new(v);
         let urb = self.urb_mut();
@@ -127,7 +171,7 @@ impl TransferData {
 impl Drop for TransferData {
     fn drop(&mut self) {
         unsafe {
-            if !self.urb_mut().buffer.is_null() {
+            if self.owner.is_none() && !self.urb_mut().buffer.is_null() {
                 drop(Vec::from_raw_parts(self.urb_mut().buffer, 0, self.capacity));
             }
             drop(Box::from_raw(self.urb));
@@ -141,12 +185,22 @@ impl PlatformTransfer for TransferData {
             self.device.cancel_urb(self.urb);
         }
     }
+
+    fn endpoint(&self) -> u8 {
+        // SAFETY: the endpoint is not modified by the kernel
+        unsafe { (*self.urb).endpoint }
+    }
+
+    fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+        self.device.observer.get()
+    }
 }
 
 impl PlatformSubmit<Vec<u8>> for TransferData {
     unsafe fn submit(&mut self, data: Vec<u8>, user_data: *mut c_void) {
         let ep = self.urb_mut().endpoint;
         assert!(ep & 0x80 == 0);
+        self.set_out_flags();
         let len = data.len();
         self.fill(data, len, user_data);
 
@@ -164,6 +218,84 @@ impl PlatformSubmit<Vec<u8>> for TransferData {
     }
 }
 
+impl<B: AsRef<[u8]> + Send + 'static> PlatformSubmit<OutBuffer<B>> for TransferData {
+    unsafe fn submit(&mut self, data: OutBuffer<B>, user_data: *mut c_void) {
+        let ep = self.urb_mut().endpoint;
+        assert!(ep & 0x80 == 0);
+        self.set_out_flags();
+        let (owner, ptr, len) = data.into_raw();
+        let urb = self.urb_mut();
+        // The kernel only reads from the buffer of an OUT transfer
+        urb.buffer = ptr as *mut u8;
+        urb.buffer_length = len.try_into().expect("buffer size should fit in i32");
+        urb.usercontext = user_data;
+        urb.actual_length = 0;
+        self.owner = Some(owner);
+
+        // SAFETY: we just properly filled the buffer and it is not already pending
+        unsafe { self.device.submit_urb(self.urb) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<OutResponse<B>> {
+        let status = urb_status(self.urb_mut());
+        let len = self.urb_mut().actual_length as usize;
+        self.urb_mut().buffer = null_mut();
+        let owner = self.owner.take().expect("transfer should have a buffer");
+        let data = OutResponse::from_raw(owner, len);
+        Completion { data, status }
+    }
+}
+
+impl PlatformSubmit<MmapBuffer> for TransferData {
+    unsafe fn submit(&mut self, mut data: MmapBuffer, user_data: *mut c_void) {
+        let ep = self.urb_mut().endpoint;
+        let len = if ep & 0x80 == 0x80 {
+            self.urb_mut().flags = 0;
+            data.capacity()
+        } else {
+            self.set_out_flags();
+            data.len()
+        };
+
+        let urb = self.urb_mut();
+        urb.buffer = data.as_mut_ptr();
+        urb.buffer_length = len.try_into().expect("buffer size should fit in i32");
+        urb.usercontext = user_data;
+        urb.actual_length = 0;
+        self.owner = Some(Box::new(data));
+
+        // SAFETY: we just properly filled the buffer and it is not already pending
+        unsafe { self.device.submit_urb(self.urb) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<MmapBuffer> {
+        let status = urb_status(self.urb_mut());
+        let len = self.urb_mut().actual_length as usize;
+        self.urb_mut().buffer = null_mut();
+        let mut data = *self
+            .owner
+            .take()
+            .and_then(|owner| owner.downcast::<MmapBuffer>().ok())
+            .expect("transfer should have an MmapBuffer");
+        data.set_len(len);
+        Completion { data, status }
+    }
+}
+
+impl<R: TransferRequest> PlatformSubmit<Chunk<R>> for TransferData
+where
+    TransferData: PlatformSubmit<R>,
+{
+    unsafe fn submit(&mut self, data: Chunk<R>, user_data: *mut c_void) {
+        self.chunk = Some(data.position);
+        PlatformSubmit::<R>::submit(self, data.data, user_data);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<R::Response> {
+        PlatformSubmit::<R>::take_completed(self)
+    }
+}
+
 impl PlatformSubmit<RequestBuffer> for TransferData {
     unsafe fn submit(&mut self, data: RequestBuffer, user_data: *mut c_void) {
         let ep = self.urb_mut().endpoint;
@@ -171,6 +303,13 @@ impl PlatformSubmit<RequestBuffer> for TransferData {
         assert!(ep & 0x80 == 0x80);
         assert!(ty == USBDEVFS_URB_TYPE_BULK || ty == USBDEVFS_URB_TYPE_INTERRUPT);
 
+        let short_not_ok_flag = if data.short_not_ok {
+            USBDEVFS_URB_SHORT_NOT_OK
+        } else {
+            0
+        };
+        let chunk = self.chunk.take();
+        self.urb_mut().flags = short_not_ok_flag | Self::continuation_flag(chunk);
         let (data, len) = data.into_vec();
         self.fill(data, len, user_data);
 
@@ -197,68 +336,104 @@ impl PlatformSubmit<RequestIsochronousBuffer> for TransferData {
 
         self.urb_setup_iso_packet_descriptors(data.number_of_packets, data.requested);
 
-        let (data, len) = data.into_vec();
+        // The kernel only copies back the received part of each packet, so
+        // initialize the rest of the buffer.
+        let (mut data, len) = data.into_vec();
+        data.resize(len, 0);
         self.fill(data, len, user_data);
 
         // SAFETY: we just properly filled the buffer and it is not already pending
         unsafe { self.device.submit_urb(self.urb) }
     }
 
-    unsafe fn take_completed(&mut self) -> Completion<Vec<Vec<u8>>> {
+    unsafe fn take_completed(&mut self) -> Completion<IsochronousPackets> {
         let status = urb_status(self.urb_mut());
         let len = self.urb_mut().buffer_length as usize;
 
-        // SAFETY: self is completed (precondition) and `actual_length` bytes were initialized.
-        let buffer = unsafe { self.take_buf(len) };
-        let mut data_start = 0;
-        let mut data = Vec::new();
+        // SAFETY: self is completed (precondition) and the whole buffer was
+        // initialized on submit.
+        let buf = unsafe { self.take_buf(len) };
+        let mut offset = 0;
+        let mut packets = Vec::new();
 
         for iso_packet_descriptor in unsafe { self.urb_mut().iso_packet_descriptors() } {
-            if iso_packet_descriptor.status == 0 {
-                let range = data_start..data_start + iso_packet_descriptor.actual_length as usize;
-
-                data.push(buffer[range].to_vec());
-            }
-
-            data_start += iso_packet_descriptor.length as usize;
+            packets.push(IsochronousPacket {
+                offset,
+                actual_length: iso_packet_descriptor.actual_length as usize,
+                status: status_to_result(iso_packet_descriptor.status as i32),
+            });
+            offset += iso_packet_descriptor.length as usize;
         }
 
-        Completion { data, status }
+        Completion {
+            data: IsochronousPackets { buf, packets },
+            status,
+        }
     }
 }
 
 impl PlatformSubmit<ControlIn> for TransferData {
     unsafe fn submit(&mut self, data: ControlIn, user_data: *mut c_void) {
-        let buf_len = SETUP_PACKET_SIZE + data.length as usize;
-        let mut buf = Vec::with_capacity(buf_len);
-        buf.extend_from_slice(&data.setup_packet());
+        unsafe {
+            PlatformSubmit::<ControlInBuffer>::submit(self, ControlInBuffer::new(data), user_data)
+        }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+        let c = unsafe { PlatformSubmit::<ControlInBuffer>::take_completed(self) };
+        Completion {
+            data: c.data.into_vec(),
+            status: c.status,
+        }
+    }
+}
+
+impl PlatformSubmit<ControlInBuffer> for TransferData {
+    unsafe fn submit(&mut self, data: ControlInBuffer, user_data: *mut c_void) {
+        // usbfs expects the SETUP packet at the start of the buffer.
+        let buf_len = SETUP_PACKET_SIZE + data.setup.length as usize;
+        let mut buf = data.buf;
+        buf.reserve_exact(buf_len);
+        buf.extend_from_slice(&data.setup.to_bytes());
         self.fill(buf, buf_len, user_data);
 
         // SAFETY: we just properly filled the buffer and it is not already pending
         unsafe { self.device.submit_urb(self.urb) }
     }
 
-    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+    unsafe fn take_completed(&mut self) -> Completion<ControlInResponse> {
         let status = urb_status(self.urb_mut());
         let len = self.urb_mut().actual_length as usize;
 
         // SAFETY: transfer is completed (precondition) and `actual_length`
         // bytes were initialized with setup buf in front
-        let mut data = unsafe { self.take_buf(SETUP_PACKET_SIZE + len) };
-        data.splice(0..SETUP_PACKET_SIZE, []);
-        Completion { data, status }
+        let data = unsafe { self.take_buf(SETUP_PACKET_SIZE + len) };
+        Completion {
+            data: ControlInResponse::new(data, SETUP_PACKET_SIZE),
+            status,
+        }
     }
 }
 
 impl PlatformSubmit<ControlOut<'_>> for TransferData {
     unsafe fn submit(&mut self, data: ControlOut, user_data: *mut c_void) {
+        let data = ControlOutSetup {
+            setup: data.setup(),
+            data: data.data,
+        };
+        unsafe { PlatformSubmit::<ControlOutSetup>::submit(self, data, user_data) }
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        unsafe { PlatformSubmit::<ControlOutSetup>::take_completed(self) }
+    }
+}
+
+impl PlatformSubmit<ControlOutSetup<'_>> for TransferData {
+    unsafe fn submit(&mut self, data: ControlOutSetup, user_data: *mut c_void) {
         let buf_len = SETUP_PACKET_SIZE + data.data.len();
         let mut buf = Vec::with_capacity(buf_len);
-        buf.extend_from_slice(
-            &data
-                .setup_packet()
-                .expect("data length should fit in setup packet's u16"),
-        );
+        buf.extend_from_slice(&data.setup.to_bytes());
         buf.extend_from_slice(data.data);
         self.fill(buf, buf_len, user_data);
 
@@ -275,12 +450,16 @@ impl PlatformSubmit<ControlOut<'_>> for TransferData {
 }
 
 fn urb_status(urb: &Urb) -> Result<(), TransferError> {
-    if urb.status == 0 {
+    status_to_result(urb.status)
+}
+
+fn status_to_result(status: i32) -> Result<(), TransferError> {
+    if status == 0 {
         return Ok(());
     }
 
     // It's sometimes positive, sometimes negative, but rustix panics if negative.
     Err(errno_to_transfer_error(Errno::from_raw_os_error(
-        urb.status.abs(),
+        status.abs(),
     )))
 }
@@ -47,16 +47,33 @@ struct DetachAndClaim {
     driver: [c_uchar; 255 + 1],
 }
 
-pub fn detach_and_claim_interface<Fd: AsFd>(fd: Fd, interface: u8) -> io::Result<()> {
+/// Detach the kernel driver, if any, and claim the interface.
+///
+/// If `if_driver` is `Some`, only a driver with that name is detached, and the
+/// claim fails with `EBUSY` if another driver is bound. Otherwise any driver
+/// other than `usbfs` is detached.
+pub fn detach_and_claim_interface<Fd: AsFd>(
+    fd: Fd,
+    interface: u8,
+    if_driver: Option<&str>,
+) -> io::Result<()> {
+    const USBDEVFS_DISCONNECT_CLAIM_IF_DRIVER: c_uint = 0x01;
     const USBDEVFS_DISCONNECT_CLAIM_EXCEPT_DRIVER: c_uint = 0x02;
     unsafe {
+        let (flags, driver) = match if_driver {
+            Some(driver) => (USBDEVFS_DISCONNECT_CLAIM_IF_DRIVER, driver.as_bytes()),
+            None => (USBDEVFS_DISCONNECT_CLAIM_EXCEPT_DRIVER, &b"usbfs"[..]),
+        };
+
         let mut dc = DetachAndClaim {
             interface: interface.into(),
-            flags: USBDEVFS_DISCONNECT_CLAIM_EXCEPT_DRIVER,
+            flags,
             driver: [0; 256],
         };
 
-        dc.driver[0..6].copy_from_slice(b"usbfs\0");
+        // Leave room for the NUL terminator
+        let len = driver.len().min(dc.driver.len() - 1);
+        dc.driver[..len].copy_from_slice(&driver[..len]);
 
         let ctl = ioctl::Setter::<opcodes::USBDEVFS_DISCONNECT_CLAIM, DetachAndClaim>::new(dc);
 
@@ -64,6 +81,32 @@ pub fn detach_and_claim_interface<Fd: AsFd>(fd: Fd, interface: u8) -> io::Result
     }
 }
 
+#[repr(C)]
+struct GetDriver {
+    interface: c_uint,
+    driver: [c_uchar; 255 + 1],
+}
+
+/// Get the name of the kernel driver bound to an interface.
+///
+/// Fails with `ENODATA` if no driver is bound.
+pub fn get_driver<Fd: AsFd>(fd: Fd, interface: u8) -> io::Result<String> {
+    let mut gd = GetDriver {
+        interface: interface.into(),
+        driver: [0; 256],
+    };
+    unsafe {
+        let ctl = ioctl::Updater::<opcodes::USBDEVFS_GETDRIVER, GetDriver>::new(&mut gd);
+        ioctl::ioctl(&fd, ctl)?;
+    }
+    let len = gd
+        .driver
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(gd.driver.len());
+    Ok(String::from_utf8_lossy(&gd.driver[..len]).into_owned())
+}
+
 #[repr(C)]
 struct UsbFsIoctl {
     interface: c_uint,
@@ -83,6 +126,7 @@ mod opcodes {
 
     pub type USBDEVFS_IOCTL = ioctl::ReadWriteOpcode<b'U', 18, UsbFsIoctl>;
     pub type USBDEVFS_DISCONNECT_CLAIM = ioctl::ReadOpcode<b'U', 27, DetachAndClaim>;
+    pub type USBDEVFS_GETDRIVER = ioctl::WriteOpcode<b'U', 8, GetDriver>;
 
     /// These opcodes are nested inside a [`USBDEVFS_IOCTL`] operation.
     pub mod nested {
@@ -201,10 +245,10 @@ pub fn reset<Fd: AsFd>(fd: Fd) -> io::Result<()> {
     }
 }
 
-const USBDEVFS_URB_SHORT_NOT_OK: c_uint = 0x01;
+pub const USBDEVFS_URB_SHORT_NOT_OK: c_uint = 0x01;
 const USBDEVFS_URB_ISO_ASAP: c_uint = 0x02;
-const USBDEVFS_URB_BULK_CONTINUATION: c_uint = 0x04;
-const USBDEVFS_URB_ZERO_PACKET: c_uint = 0x40;
+pub const USBDEVFS_URB_BULK_CONTINUATION: c_uint = 0x04;
+pub const USBDEVFS_URB_ZERO_PACKET: c_uint = 0x40;
 const USBDEVFS_URB_NO_INTERRUPT: c_uint = 0x80;
 
 pub const USBDEVFS_URB_TYPE_ISO: c_uchar = 0;
@@ -297,6 +341,13 @@ pub fn control<Fd: AsFd>(fd: Fd, transfer: CtrlTransfer) -> io::Result<usize> {
     }
 }
 
+pub fn reset_ep<Fd: AsFd>(fd: Fd, endpoint: u8) -> io::Result<()> {
+    unsafe {
+        let ctl = ioctl::Setter::<ioctl::ReadOpcode<b'U', 3, c_uint>, c_uint>::new(endpoint.into());
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
 pub fn clear_halt<Fd: AsFd>(fd: Fd, endpoint: u8) -> io::Result<()> {
     unsafe {
         let ctl =
@@ -305,6 +356,16 @@ pub fn clear_halt<Fd: AsFd>(fd: Fd, endpoint: u8) -> io::Result<()> {
     }
 }
 
+/// `USBDEVFS_GET_CAPABILITIES` bit for support of `mmap` transfer buffers.
+pub const USBDEVFS_CAP_MMAP: u32 = 0x20;
+
+pub fn get_capabilities<Fd: AsFd>(fd: Fd) -> io::Result<u32> {
+    unsafe {
+        let ctl = ioctl::Getter::<ioctl::ReadOpcode<b'U', 26, u32>, u32>::new();
+        ioctl::ioctl(fd, ctl)
+    }
+}
+
 pub fn get_speed<Fd: AsFd>(fd: Fd) -> io::Result<usize> {
     unsafe {
         let ctl = Transfer::<ioctl::NoneOpcode<b'U', 31, ()>, ()>::new(());
@@ -1,6 +1,6 @@
 use std::{
     collections::BTreeMap,
-    ffi::c_void,
+    ffi::{c_char, c_void},
     io::ErrorKind,
     sync::{
         atomic::{AtomicU8, AtomicUsize, Ordering},
@@ -9,32 +9,132 @@ use std::{
     time::Duration,
 };
 
+use core_foundation::{base::TCFType, runloop::CFRunLoopSource};
+use io_kit_sys::{
+    keys::kIOGeneralInterest, ret::kIOReturnSuccess, types::io_service_t,
+    IONotificationPortGetRunLoopSource, IOServiceAddInterestNotification,
+};
 use log::{debug, error};
 
 use crate::{
     descriptors::{ConfigurationDescriptor, DeviceDescriptor},
+    disconnect::DisconnectSignal,
     maybe_future::blocking::Blocking,
-    transfer::{Control, Direction, TransferError, TransferHandle, TransferType},
+    transfer::{Control, Direction, ObserverSlot, TransferError, TransferHandle, TransferType},
     DeviceInfo, Error, MaybeFuture, Speed,
 };
 
 use super::{
     enumeration::{device_descriptor_from_fields, service_by_registry_id},
     events::{add_event_source, EventRegistration},
-    iokit::{call_iokit_function, check_iokit_return},
-    iokit_c::IOUSBDevRequestTO,
+    hotplug::NotificationPort,
+    iokit::{call_iokit_function, check_iokit_return, IoObject, IoService},
+    iokit_c::{kIOMessageServiceIsTerminated, AbsoluteTime, IOUSBDevRequestTO},
     iokit_usb::{EndpointInfo, IoKitDevice, IoKitInterface},
     status_to_transfer_result,
 };
 
 pub(crate) struct MacDevice {
     _event_registration: EventRegistration,
+    _disconnect_notification: DisconnectNotification,
     pub(super) device: IoKitDevice,
     device_descriptor: DeviceDescriptor,
     speed: Option<Speed>,
     active_config: AtomicU8,
     is_open_exclusive: Mutex<bool>,
     claimed_interfaces: AtomicUsize,
+    pub(crate) observer: ObserverSlot,
+    pub(crate) disconnect: Arc<DisconnectSignal>,
+}
+
+// Signals are owned by a global map so that a notification callback can't
+// race with freeing them. Keys are never reused, so a late callback for a
+// closed device can't signal another one.
+static DISCONNECT_SIGNALS: Mutex<BTreeMap<usize, Arc<DisconnectSignal>>> =
+    Mutex::new(BTreeMap::new());
+
+static NEXT_SIGNAL_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A `DisconnectSignal` registered with `DISCONNECT_SIGNALS`
+struct RegisteredSignal(usize);
+
+impl RegisteredSignal {
+    fn new(signal: Arc<DisconnectSignal>) -> Self {
+        let id = NEXT_SIGNAL_ID.fetch_add(1, Ordering::Relaxed);
+        DISCONNECT_SIGNALS.lock().unwrap().insert(id, signal);
+        RegisteredSignal(id)
+    }
+}
+
+impl Drop for RegisteredSignal {
+    fn drop(&mut self) {
+        DISCONNECT_SIGNALS.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// IOKit interest notification that sets a `DisconnectSignal` when the
+/// device's service is terminated.
+struct DisconnectNotification {
+    // Fields are dropped in order: the notification is removed before the
+    // signal is unregistered.
+    _registration: EventRegistration,
+    _notification: IoObject,
+    _port: NotificationPort,
+    _signal: RegisteredSignal,
+}
+
+// Safety: the port is only used for registration and in `Drop`.
+unsafe impl Send for DisconnectNotification {}
+unsafe impl Sync for DisconnectNotification {}
+
+impl DisconnectNotification {
+    fn new(service: &IoService, signal: Arc<DisconnectSignal>) -> Result<Self, Error> {
+        let signal = RegisteredSignal::new(signal);
+        let port = NotificationPort::new();
+
+        let notification = unsafe {
+            let mut notification = 0;
+            let r = IOServiceAddInterestNotification(
+                port.0,
+                service.get(),
+                kIOGeneralInterest as *mut c_char,
+                disconnect_callback,
+                signal.0 as *mut c_void,
+                &mut notification,
+            );
+            if r != kIOReturnSuccess {
+                return Err(Error::other("Failed to register interest notification"));
+            }
+            IoObject::new(notification)
+        };
+
+        let source = unsafe {
+            CFRunLoopSource::wrap_under_get_rule(IONotificationPortGetRunLoopSource(port.0))
+        };
+        let registration = add_event_source(source)?;
+
+        Ok(DisconnectNotification {
+            _registration: registration,
+            _notification: notification,
+            _port: port,
+            _signal: signal,
+        })
+    }
+}
+
+unsafe extern "C" fn disconnect_callback(
+    refcon: *mut c_void,
+    _service: io_service_t,
+    message_type: u32,
+    _message_argument: *mut c_void,
+) {
+    if message_type == kIOMessageServiceIsTerminated {
+        debug!("device service terminated");
+        let id = refcon as usize;
+        if let Some(signal) = DISCONNECT_SIGNALS.lock().unwrap().get(&id) {
+            signal.notify();
+        }
+    }
 }
 
 // `get_configuration` does IO, so avoid it in the common case that:
@@ -53,6 +153,7 @@ fn guess_active_config(dev: &IoKitDevice) -> Option<u8> {
 impl MacDevice {
     pub(crate) fn from_device_info(
         d: &DeviceInfo,
+        shared: bool,
     ) -> impl MaybeFuture<Output = Result<Arc<MacDevice>, Error>> {
         let registry_id = d.registry_id;
         let speed = d.speed;
@@ -60,15 +161,20 @@ impl MacDevice {
             log::info!("Opening device from registry id {}", registry_id);
             let service = service_by_registry_id(registry_id)?;
             let device = IoKitDevice::new(&service)?;
-            let _event_registration = add_event_source(device.create_async_event_source()?);
-
-            let opened = match unsafe { call_iokit_function!(device.raw, USBDeviceOpen()) } {
-                io_kit_sys::ret::kIOReturnSuccess => true,
-                err => {
-                    // Most methods don't require USBDeviceOpen() so this can be ignored
-                    // to allow different processes to open different interfaces.
-                    log::debug!("Could not open device for exclusive access: {err:x}");
-                    false
+            let _event_registration = add_event_source(device.create_async_event_source()?)?;
+
+            let opened = if shared {
+                // Left to `require_open_exclusive` for the methods that need it
+                false
+            } else {
+                match unsafe { call_iokit_function!(device.raw, USBDeviceOpen()) } {
+                    io_kit_sys::ret::kIOReturnSuccess => true,
+                    err => {
+                        // Most methods don't require USBDeviceOpen() so this can be ignored
+                        // to allow different processes to open different interfaces.
+                        log::debug!("Could not open device for exclusive access: {err:x}");
+                        false
+                    }
                 }
             };
 
@@ -88,18 +194,31 @@ impl MacDevice {
                 res.unwrap_or(0)
             };
 
+            let disconnect = Arc::new(DisconnectSignal::default());
+            let _disconnect_notification =
+                DisconnectNotification::new(&service, disconnect.clone()).inspect_err(|e| {
+                    log::error!("Failed to register for removal of device: {e}")
+                })?;
+
             Ok(Arc::new(MacDevice {
                 _event_registration,
+                _disconnect_notification,
                 device,
                 device_descriptor,
                 speed,
                 active_config: AtomicU8::new(active_config),
                 is_open_exclusive: Mutex::new(opened),
                 claimed_interfaces: AtomicUsize::new(0),
+                observer: ObserverSlot::default(),
+                disconnect,
             }))
         })
     }
 
+    pub(crate) fn iokit_device_interface(&self) -> *mut *mut c_void {
+        self.device.raw.cast()
+    }
+
     pub(crate) fn device_descriptor(&self) -> DeviceDescriptor {
         self.device_descriptor.clone()
     }
@@ -108,13 +227,17 @@ impl MacDevice {
         self.speed
     }
 
+    pub(crate) fn update_active_configuration(&self, configuration: u8) {
+        self.active_config.store(configuration, Ordering::SeqCst);
+    }
+
     pub(crate) fn active_configuration_value(&self) -> u8 {
         self.active_config.load(Ordering::SeqCst)
     }
 
     pub(crate) fn configuration_descriptors(
         &self,
-    ) -> impl Iterator<Item = ConfigurationDescriptor> {
+    ) -> impl Iterator<Item = ConfigurationDescriptor<'_>> {
         let num_configs = self.device.get_number_of_configurations().unwrap_or(0);
         (0..num_configs)
             .flat_map(|i| self.device.get_configuration_descriptor(i).ok())
@@ -245,7 +368,7 @@ impl MacDevice {
                 .ok_or(Error::new(ErrorKind::NotFound, "interface not found"))?;
 
             let mut interface = IoKitInterface::new(intf_service)?;
-            let _event_registration = add_event_source(interface.create_async_event_source()?);
+            let _event_registration = add_event_source(interface.create_async_event_source()?)?;
 
             interface.open()?;
 
@@ -374,21 +497,41 @@ impl MacInterface {
         self.state.lock().unwrap().alt_setting
     }
 
+    pub fn refresh_alt_setting(&self) -> Result<u8, Error> {
+        let mut state = self.state.lock().unwrap();
+        let mut alt_setting = 0;
+        unsafe {
+            check_iokit_return(call_iokit_function!(
+                self.interface.raw,
+                GetAlternateSetting(&mut alt_setting)
+            ))?;
+        }
+        if alt_setting != state.alt_setting {
+            // The endpoints depend on the alternate setting
+            *self.endpoints.lock().unwrap() = self.interface.endpoints()?;
+        }
+        state.alt_setting = alt_setting;
+        Ok(alt_setting)
+    }
+
+    fn pipe_ref(&self, endpoint: u8) -> Result<u8, Error> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let ep = endpoints
+            .get(&endpoint)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "Endpoint not found"))?;
+        Ok(ep.pipe_ref)
+    }
+
     pub fn clear_halt(
         self: Arc<Self>,
         endpoint: u8,
     ) -> impl MaybeFuture<Output = Result<(), Error>> {
         Blocking::new(move || {
             debug!("Clear halt, endpoint {endpoint:02x}");
+            let pipe_ref = self.pipe_ref(endpoint)?;
 
-            let pipe_ref = {
-                let endpoints = self.endpoints.lock().unwrap();
-                let ep = endpoints
-                    .get(&endpoint)
-                    .ok_or_else(|| Error::new(ErrorKind::NotFound, "Endpoint not found"))?;
-                ep.pipe_ref
-            };
-
+            // Clears the host-side stall and data toggle, and sends
+            // CLEAR_FEATURE(ENDPOINT_HALT) to the device.
             unsafe {
                 check_iokit_return(call_iokit_function!(
                     self.interface.raw,
@@ -397,6 +540,54 @@ impl MacInterface {
             }
         })
     }
+
+    pub fn reset_pipe(
+        self: Arc<Self>,
+        endpoint: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            debug!("Reset pipe, endpoint {endpoint:02x}");
+            let pipe_ref = self.pipe_ref(endpoint)?;
+
+            // Only clears the host-side stall and data toggle.
+            unsafe {
+                check_iokit_return(call_iokit_function!(
+                    self.interface.raw,
+                    ClearPipeStall(pipe_ref)
+                ))
+            }
+        })
+    }
+
+    pub fn abort_pipe(&self, _endpoint: u8) -> Result<(), Error> {
+        // `ClearPipeStallBothEnds` in `clear_halt` aborts pending transfers
+        Ok(())
+    }
+
+    pub fn frame_number(&self) -> Result<u64, Error> {
+        let mut frame = 0;
+        let mut time = AbsoluteTime { lo: 0, hi: 0 };
+        unsafe {
+            check_iokit_return(call_iokit_function!(
+                self.interface.raw,
+                GetBusFrameNumber(&mut frame, &mut time)
+            ))?;
+        }
+        Ok(frame)
+    }
+
+    pub fn set_send_zlp(&self, _endpoint: u8, enable: bool) -> Result<(), Error> {
+        // IOUSBInterfaceInterface has no option to terminate a write with a
+        // zero-length packet.
+        if enable {
+            Err(Error::new(
+                ErrorKind::Unsupported,
+                "zero-length packet termination is not supported on macOS",
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl Drop for MacInterface {
@@ -1,24 +1,33 @@
 use std::io::ErrorKind;
 
+use std::collections::BTreeMap;
+
 use core_foundation::{
+    array::CFArray,
     base::{CFType, TCFType},
+    boolean::CFBoolean,
     data::CFData,
+    dictionary::CFDictionary,
     number::CFNumber,
     string::CFString,
     ConcreteCFType,
 };
+use core_foundation_sys::number::CFNumberIsFloatType;
 use io_kit_sys::{
     kIOMasterPortDefault, kIORegistryIterateParents, kIORegistryIterateRecursively,
     keys::kIOServicePlane, ret::kIOReturnSuccess, usb::lib::kIOUSBDeviceClassName,
-    IORegistryEntryGetChildIterator, IORegistryEntryGetRegistryEntryID,
-    IORegistryEntrySearchCFProperty, IOServiceGetMatchingServices, IOServiceMatching,
+    IOObjectCopyClass, IORegistryEntryCopyPath, IORegistryEntryCreateCFProperty,
+    IORegistryEntryGetChildIterator, IORegistryEntryGetRegistryEntryID, IORegistryEntryIDMatching,
+    IORegistryEntrySearchCFProperty, IOServiceGetMatchingService, IOServiceGetMatchingServices,
+    IOServiceMatching,
 };
 use log::debug;
 
 use crate::{
     descriptors::DeviceDescriptor,
+    hotplug::Filter,
     maybe_future::{MaybeFuture, Ready},
-    BusInfo, DeviceInfo, Error, InterfaceInfo, Speed, UsbControllerType,
+    BusInfo, DeviceInfo, Error, InterfaceInfo, PlistValue, Speed, UsbControllerType,
 };
 
 use super::iokit::{IoService, IoServiceIterator};
@@ -85,6 +94,37 @@ pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = D
     Ready(usb_service_iter().map(|i| i.filter_map(probe_device)))
 }
 
+pub fn list_devices_filtered(
+    filter: Filter,
+) -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>> {
+    Ready(usb_service_iter().map(|i| {
+        i.filter_map(move |dev| {
+            if !prefilter(&dev, &filter) {
+                return None;
+            }
+            probe_device(dev).filter(|d| filter.matches(d))
+        })
+    }))
+}
+
+/// Check the properties `filter` can reject a device on, without reading the
+/// rest of the device's properties or its interfaces.
+fn prefilter(device: &IoService, filter: &Filter) -> bool {
+    let vendor_id = get_integer_property(device, "idVendor");
+    let product_id = get_integer_property(device, "idProduct");
+    let location_id = get_integer_property(device, "locationID");
+
+    // Leave devices missing properties to `probe_device`
+    let ids_match = match (vendor_id, product_id) {
+        (Some(v), Some(p)) => filter.matches_ids(v as u16, p as u16),
+        _ => true,
+    };
+    ids_match
+        && location_id.map_or(true, |l| {
+            filter.matches_bus(&format!("{:02x}", (l as u32 >> 24) as u8))
+        })
+}
+
 pub fn list_buses() -> impl MaybeFuture<Output = Result<impl Iterator<Item = BusInfo>, Error>> {
     // Chain all the HCI types into one iterator
     // A bit of a hack, could maybe probe IOPCIDevice and filter on children with IOClass.starts_with("AppleUSB")
@@ -117,12 +157,14 @@ pub(crate) fn probe_device(device: IoService) -> Option<DeviceInfo> {
     // Can run `ioreg -p IOUSB -l` to see all properties
     Some(DeviceInfo {
         registry_id,
+        registry_path: get_registry_path(&device).unwrap_or_default(),
         location_id,
         bus_id: format!("{:02x}", (location_id >> 24) as u8),
         device_address: get_integer_property(&device, "USB Address")? as u8,
         port_chain: parse_location_id(location_id),
         vendor_id: get_integer_property(&device, "idVendor")? as u16,
         product_id: get_integer_property(&device, "idProduct")? as u16,
+        usb_version: get_integer_property(&device, "bcdUSB")? as u16,
         device_version: get_integer_property(&device, "bcdDevice")? as u16,
         class: get_integer_property(&device, "bDeviceClass")? as u8,
         subclass: get_integer_property(&device, "bDeviceSubClass")? as u8,
@@ -144,6 +186,7 @@ pub(crate) fn probe_device(device: IoService) -> Option<DeviceInfo> {
                     protocol: get_integer_property(&child, "bInterfaceProtocol")? as u8,
                     interface_string: get_string_property(&child, "kUSBString")
                         .or_else(|| get_string_property(&child, "USB Interface Name")),
+                    driver: get_driver_name(&child),
                 })
             })
             .collect()
@@ -187,6 +230,83 @@ pub(crate) fn get_registry_id(device: &IoService) -> Option<u64> {
     }
 }
 
+fn get_registry_path(device: &IoService) -> Option<String> {
+    unsafe {
+        let path = IORegistryEntryCopyPath(device.get(), kIOServicePlane as *mut _);
+        if path.is_null() {
+            debug!("IORegistryEntryCopyPath failed");
+            return None;
+        }
+        Some(CFString::wrap_under_create_rule(path).to_string())
+    }
+}
+
+/// Read a property of the registry entry with the specified ID, without
+/// searching its parents.
+pub(crate) fn get_registry_property(registry_id: u64, property: &str) -> Option<PlistValue> {
+    unsafe {
+        // The matching dictionary is consumed by `IOServiceGetMatchingService`
+        let service = IOServiceGetMatchingService(
+            kIOMasterPortDefault,
+            IORegistryEntryIDMatching(registry_id),
+        );
+        if service == 0 {
+            debug!("Registry entry {registry_id:08x} not found");
+            return None;
+        }
+        let service = IoService::new(service);
+
+        let key = CFString::new(property);
+        let raw = IORegistryEntryCreateCFProperty(
+            service.get(),
+            key.as_concrete_TypeRef(),
+            std::ptr::null(),
+            0,
+        );
+        if raw.is_null() {
+            return None;
+        }
+        plist_value(&CFType::wrap_under_create_rule(raw))
+    }
+}
+
+/// Convert a property list value, skipping any type that isn't supported.
+fn plist_value(value: &CFType) -> Option<PlistValue> {
+    if let Some(s) = value.downcast::<CFString>() {
+        Some(PlistValue::String(s.to_string()))
+    } else if let Some(b) = value.downcast::<CFBoolean>() {
+        Some(PlistValue::Boolean(b.into()))
+    } else if let Some(n) = value.downcast::<CFNumber>() {
+        if unsafe { CFNumberIsFloatType(n.as_concrete_TypeRef()) } != 0 {
+            n.to_f64().map(PlistValue::Float)
+        } else {
+            n.to_i64().map(PlistValue::Integer)
+        }
+    } else if let Some(d) = value.downcast::<CFData>() {
+        Some(PlistValue::Data(d.bytes().to_vec()))
+    } else if let Some(a) = value.downcast::<CFArray>() {
+        Some(PlistValue::Array(
+            a.iter()
+                .flat_map(|v| plist_value(&unsafe { CFType::wrap_under_get_rule(*v) }))
+                .collect(),
+        ))
+    } else if let Some(d) = value.downcast::<CFDictionary>() {
+        let (keys, values) = d.get_keys_and_values();
+        let dict: BTreeMap<String, PlistValue> = keys
+            .into_iter()
+            .zip(values)
+            .flat_map(|(k, v)| unsafe {
+                let k = CFType::wrap_under_get_rule(k).downcast::<CFString>()?;
+                Some((k.to_string(), plist_value(&CFType::wrap_under_get_rule(v))?))
+            })
+            .collect();
+        Some(PlistValue::Dictionary(dict))
+    } else {
+        debug!("Unsupported property list type {}", value.type_of());
+        None
+    }
+}
+
 fn get_property<T: ConcreteCFType>(device: &IoService, property: &'static str) -> Option<T> {
     unsafe {
         let cf_property = CFString::from_static_string(property);
@@ -251,6 +371,32 @@ fn get_children(device: &IoService) -> Result<IoServiceIterator, Error> {
     }
 }
 
+/// Get the name of the driver matched to an interface, its first child in
+/// the service plane.
+fn get_driver_name(interface: &IoService) -> Option<String> {
+    let driver = get_children(interface).ok()?.next()?;
+    unsafe {
+        // DriverKit extensions are represented by a generic kernel class, but
+        // name their own class in the `IOUserClass` property
+        let key = CFString::from_static_string("IOUserClass");
+        let user_class = IORegistryEntryCreateCFProperty(
+            driver.get(),
+            key.as_concrete_TypeRef(),
+            std::ptr::null(),
+            0,
+        );
+        if !user_class.is_null() {
+            if let Some(s) = CFType::wrap_under_create_rule(user_class).downcast_into::<CFString>()
+            {
+                return Some(s.to_string());
+            }
+        }
+
+        let class = IOObjectCopyClass(driver.get());
+        (!class.is_null()).then(|| CFString::wrap_under_create_rule(class).to_string())
+    }
+}
+
 fn map_speed(speed: i64) -> Option<Speed> {
     // https://developer.apple.com/documentation/iokit/1425357-usbdevicespeed
     match speed {
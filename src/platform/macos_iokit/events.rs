@@ -1,13 +1,14 @@
 use std::{
     ops::Deref,
     sync::{mpsc, Mutex},
-    thread,
 };
 
 use core_foundation::runloop::{CFRunLoop, CFRunLoopSource};
 use core_foundation_sys::runloop::kCFRunLoopCommonModes;
 use log::info;
 
+use crate::{event_thread, Error};
+
 // Pending release of https://github.com/servo/core-foundation-rs/pull/610
 struct SendCFRunLoop(CFRunLoop);
 unsafe impl Send for SendCFRunLoop {}
@@ -26,14 +27,33 @@ unsafe impl Sync for SendCFRunLoopSource {}
 struct EventLoop {
     runloop: Option<SendCFRunLoop>,
     count: usize,
+
+    /// Whether `runloop` was provided by the application with
+    /// `use_current_run_loop` rather than run on a thread spawned here.
+    external: bool,
 }
 
 static EVENT_LOOP: Mutex<EventLoop> = Mutex::new(EventLoop {
     runloop: None,
     count: 0,
+    external: false,
 });
 
-pub(crate) fn add_event_source(source: CFRunLoopSource) -> EventRegistration {
+pub fn use_current_run_loop() -> Result<(), Error> {
+    let mut event_loop = EVENT_LOOP.lock().unwrap();
+    if event_loop.count > 0 {
+        return Err(Error::other(
+            "event sources are already registered on another run loop",
+        ));
+    }
+    info!("using application-provided run loop");
+    event_loop.runloop = Some(SendCFRunLoop(CFRunLoop::get_current()));
+    event_loop.external = true;
+    Ok(())
+}
+
+pub(crate) fn add_event_source(source: CFRunLoopSource) -> Result<EventRegistration, Error> {
+    event_thread::check()?;
     let mut event_loop = EVENT_LOOP.lock().unwrap();
     if let Some(runloop) = event_loop.runloop.as_ref() {
         if runloop.contains_source(&source, unsafe { kCFRunLoopCommonModes }) {
@@ -45,7 +65,7 @@ pub(crate) fn add_event_source(source: CFRunLoopSource) -> EventRegistration {
         let (tx, rx) = mpsc::channel();
         let source = SendCFRunLoopSource(source.clone());
         info!("starting event loop thread");
-        thread::spawn(move || {
+        event_thread::spawn(move || {
             let runloop = CFRunLoop::get_current();
             let source = source;
             runloop.add_source(&source.0, unsafe { kCFRunLoopCommonModes });
@@ -56,7 +76,7 @@ pub(crate) fn add_event_source(source: CFRunLoopSource) -> EventRegistration {
         event_loop.runloop = Some(rx.recv().expect("failed to start run loop thread"));
         event_loop.count = 1;
     }
-    EventRegistration(SendCFRunLoopSource(source))
+    Ok(EventRegistration(SendCFRunLoopSource(source)))
 }
 pub(crate) struct EventRegistration(SendCFRunLoopSource);
 
@@ -71,7 +91,7 @@ impl Drop for EventRegistration {
             .expect("runloop should exist while events are registered");
         runloop.remove_source(&self.0 .0, unsafe { kCFRunLoopCommonModes });
 
-        if event_loop.count == 0 {
+        if event_loop.count == 0 && !event_loop.external {
             runloop.stop();
             event_loop.runloop.take();
         }
@@ -58,10 +58,10 @@ pub(crate) struct MacHotplugWatch {
     _notification_port: NotificationPort,
 }
 
-struct NotificationPort(*mut IONotificationPort);
+pub(super) struct NotificationPort(pub(super) *mut IONotificationPort);
 
 impl NotificationPort {
-    fn new() -> NotificationPort {
+    pub(super) fn new() -> NotificationPort {
         unsafe { NotificationPort(IONotificationPortCreate(kIOMasterPortDefault)) }
     }
 }
@@ -105,7 +105,7 @@ impl MacHotplugWatch {
                 notification_port.0,
             ))
         };
-        let registration = add_event_source(source);
+        let registration = add_event_source(source)?;
 
         Ok(MacHotplugWatch {
             waker_id,
@@ -116,10 +116,14 @@ impl MacHotplugWatch {
         })
     }
 
+    /// Return the next event from the notification iterators.
+    ///
+    /// IOKit only re-arms a notification once its iterator has been drained,
+    /// so both are read until they are empty before returning `Pending`.
     pub fn poll_next(&mut self, cx: &mut Context) -> Poll<HotplugEvent> {
         self.waker_id.register(cx.waker());
 
-        while let Some(s) = self.matched_iter.next() {
+        for s in self.matched_iter.by_ref() {
             if let Some(dev) = probe_device(s) {
                 return Poll::Ready(HotplugEvent::Connected(dev));
             } else {
@@ -127,7 +131,7 @@ impl MacHotplugWatch {
             }
         }
 
-        if let Some(s) = self.terminated_iter.next() {
+        for s in self.terminated_iter.by_ref() {
             if let Some(registry_id) = get_registry_id(&s) {
                 debug!("device {registry_id} disconnected");
                 let id = DeviceId(registry_id);
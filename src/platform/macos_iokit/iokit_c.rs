@@ -58,9 +58,15 @@ pub(crate) const kIOUSBInvalidSSEndpoint: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x
 pub(crate) const kIOUSBTooManyTransactionsPending: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x42; // 0xe0004042  The transaction cannot be submitted because it would exceed the allowed number of pending transactions
 pub(crate) const kIOUSBTransactionReturned: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x50;
 pub(crate) const kIOUSBTransactionTimeout: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x51;
+pub(crate) const kIOUSBBufferOverrunErr: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x0c; // 0xe000400c  Buffer overrun
+pub(crate) const kIOUSBBitstufErr: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x02; // 0xe0004002  Bit-stuffing error
+pub(crate) const kIOUSBCRCErr: c_int = SYS_IOKIT | SUB_IOKIT_USB | 0x01; // 0xe0004001  CRC error
 
 pub(crate) const kIOUSBFindInterfaceDontCare: UInt16 = 0xFFFF;
 
+const SUB_IOKIT_COMMON: c_int = 0;
+pub(crate) const kIOMessageServiceIsTerminated: u32 = (SYS_IOKIT | SUB_IOKIT_COMMON | 0x010) as u32; // 0xe0000010
+
 //
 
 //
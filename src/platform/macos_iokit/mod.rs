@@ -4,7 +4,9 @@ pub(crate) use transfer::TransferData;
 
 mod enumeration;
 mod events;
-pub use enumeration::{list_buses, list_devices};
+pub(crate) use enumeration::get_registry_property;
+pub use enumeration::{list_buses, list_devices, list_devices_filtered};
+pub use events::use_current_run_loop;
 
 mod device;
 pub(crate) use device::MacDevice as Device;
@@ -30,6 +32,14 @@ fn status_to_transfer_result(status: IOReturn) -> Result<(), TransferError> {
         io_kit_sys::ret::kIOReturnNoDevice => Err(TransferError::Disconnected),
         io_kit_sys::ret::kIOReturnAborted => Err(TransferError::Cancelled),
         iokit_c::kIOUSBPipeStalled => Err(TransferError::Stall),
-        _ => Err(TransferError::Unknown),
+        io_kit_sys::ret::kIOReturnOverrun | iokit_c::kIOUSBBufferOverrunErr => {
+            Err(TransferError::Overflow)
+        }
+        iokit_c::kIOUSBCRCErr | iokit_c::kIOUSBBitstufErr => Err(TransferError::CrcOrBitstuff),
+        io_kit_sys::ret::kIOReturnNotResponding => Err(TransferError::NoResponse),
+        io_kit_sys::ret::kIOReturnTimeout | iokit_c::kIOUSBTransactionTimeout => {
+            Err(TransferError::TimedOut)
+        }
+        _ => Err(TransferError::Unknown(status as u32)),
     }
 }
@@ -1,4 +1,5 @@
 use std::{
+    any::Any,
     ffi::c_void,
     mem::{self, ManuallyDrop},
     ptr::null_mut,
@@ -11,8 +12,10 @@ use log::{error, info};
 use crate::{
     platform::macos_iokit::iokit_c::IOUSBDevRequest,
     transfer::{
-        notify_completion, Completion, ControlIn, ControlOut, PlatformSubmit, PlatformTransfer,
-        RequestBuffer, ResponseBuffer, TransferError,
+        check_short_packet, notify_completion, Chunk, Completion, ControlIn, ControlInBuffer,
+        ControlInResponse, ControlOut, ControlOutSetup, OutBuffer, OutResponse, PlatformSubmit,
+        PlatformTransfer, RequestBuffer, ResponseBuffer, TransferError, TransferObserver,
+        TransferRequest,
     },
 };
 
@@ -40,6 +43,14 @@ pub struct TransferData {
     pipe_ref: u8,
     buf: *mut u8,
     capacity: usize,
+
+    /// Owner of the data for an `OutBuffer` transfer, in which case `buf`
+    /// points into it rather than to a `Vec` allocation.
+    owner: Option<Box<dyn Any + Send>>,
+
+    /// Requested length of an `IN` transfer submitted with `short_not_ok`,
+    /// checked on completion since the OS has no equivalent option.
+    short_not_ok_len: Option<usize>,
     inner: *mut TransferDataInner,
     device: Arc<super::Device>,
     interface: Option<Arc<super::Interface>>,
@@ -47,7 +58,7 @@ pub struct TransferData {
 
 impl Drop for TransferData {
     fn drop(&mut self) {
-        if !self.buf.is_null() {
+        if self.owner.is_none() && !self.buf.is_null() {
             unsafe { drop(Vec::from_raw_parts(self.buf, 0, self.capacity)) }
         }
         unsafe { drop(Box::from_raw(self.inner)) }
@@ -76,6 +87,8 @@ impl TransferData {
             pipe_ref,
             buf: null_mut(),
             capacity: 0,
+            owner: None,
+            short_not_ok_len: None,
             inner: Box::into_raw(Box::new(TransferDataInner {
                 actual_len: 0,
                 callback_data: null_mut(),
@@ -92,6 +105,8 @@ impl TransferData {
             pipe_ref: 0,
             buf: null_mut(),
             capacity: 0,
+            owner: None,
+            short_not_ok_len: None,
             inner: Box::into_raw(Box::new(TransferDataInner {
                 actual_len: 0,
                 callback_data: null_mut(),
@@ -152,6 +167,14 @@ impl TransferData {
 unsafe impl Send for TransferData {}
 
 impl PlatformTransfer for TransferData {
+    fn endpoint(&self) -> u8 {
+        self.endpoint_addr
+    }
+
+    fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+        self.device.observer.get()
+    }
+
     fn cancel(&self) {
         if let Some(intf) = self.interface.as_ref() {
             let r = unsafe { call_iokit_function!(intf.interface.raw, AbortPipe(self.pipe_ref)) };
@@ -202,10 +225,67 @@ impl PlatformSubmit<Vec<u8>> for TransferData {
     }
 }
 
+impl<B: AsRef<[u8]> + Send + 'static> PlatformSubmit<OutBuffer<B>> for TransferData {
+    unsafe fn submit(&mut self, data: OutBuffer<B>, callback_data: *mut std::ffi::c_void) {
+        assert!(self.endpoint_addr & 0x80 == 0);
+        let (owner, ptr, len) = data.into_raw();
+        self.buf = ptr as *mut u8;
+        self.capacity = 0;
+        self.owner = Some(owner);
+
+        let inner = &mut *self.inner;
+        inner.actual_len = 0;
+        inner.status = kIOReturnSuccess;
+        inner.callback_data = callback_data;
+
+        // SAFETY: we just properly filled the buffer and it is not already pending
+        let res = call_iokit_function!(
+            self.interface.as_ref().unwrap().interface.raw,
+            WritePipeAsync(
+                self.pipe_ref,
+                self.buf as *mut c_void,
+                u32::try_from(len).expect("request too large"),
+                transfer_callback,
+                self.inner as *mut c_void
+            )
+        );
+        info!(
+            "Submitted OUT transfer {inner:?} on endpoint {ep:02x}",
+            inner = self.inner,
+            ep = self.endpoint_addr
+        );
+        self.check_submit_result(res);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<OutResponse<B>> {
+        let (status, actual_len) = self.take_status();
+        self.buf = null_mut();
+        let owner = self.owner.take().expect("transfer should have a buffer");
+        let data = OutResponse::from_raw(owner, actual_len);
+        Completion { data, status }
+    }
+}
+
+impl<R: TransferRequest> PlatformSubmit<Chunk<R>> for TransferData
+where
+    TransferData: PlatformSubmit<R>,
+{
+    // The OS splits large transfers itself, so transfers aren't split into
+    // chunks on this platform.
+    unsafe fn submit(&mut self, data: Chunk<R>, user_data: *mut c_void) {
+        PlatformSubmit::<R>::submit(self, data.data, user_data);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<R::Response> {
+        PlatformSubmit::<R>::take_completed(self)
+    }
+}
+
 impl PlatformSubmit<RequestBuffer> for TransferData {
     unsafe fn submit(&mut self, data: RequestBuffer, callback_data: *mut std::ffi::c_void) {
         assert!(self.endpoint_addr & 0x80 == 0x80);
 
+        self.short_not_ok_len = data.short_not_ok.then_some(data.requested);
         let (data, len) = data.into_vec();
         self.fill(data, callback_data);
 
@@ -231,6 +311,7 @@ impl PlatformSubmit<RequestBuffer> for TransferData {
 
     unsafe fn take_completed(&mut self) -> crate::transfer::Completion<Vec<u8>> {
         let (status, actual_len) = self.take_status();
+        let status = check_short_packet(status, actual_len, self.short_not_ok_len.take());
 
         // SAFETY: self is completed (precondition) and `actual_length` bytes were initialized.
         let data = unsafe { self.take_buf(actual_len) };
@@ -240,13 +321,31 @@ impl PlatformSubmit<RequestBuffer> for TransferData {
 
 impl PlatformSubmit<ControlIn> for TransferData {
     unsafe fn submit(&mut self, data: ControlIn, callback_data: *mut std::ffi::c_void) {
+        PlatformSubmit::<ControlInBuffer>::submit(self, ControlInBuffer::new(data), callback_data)
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+        let c = PlatformSubmit::<ControlInBuffer>::take_completed(self);
+        Completion {
+            data: c.data.into_vec(),
+            status: c.status,
+        }
+    }
+}
+
+impl PlatformSubmit<ControlInBuffer> for TransferData {
+    unsafe fn submit(&mut self, request: ControlInBuffer, callback_data: *mut std::ffi::c_void) {
         assert!(self.pipe_ref == 0);
 
-        let buf = Vec::with_capacity(data.length as usize);
+        let ControlInBuffer {
+            setup: data,
+            mut buf,
+        } = request;
+        buf.reserve_exact(data.length as usize);
         self.fill(buf, callback_data);
 
         let mut req = IOUSBDevRequest {
-            bmRequestType: data.request_type(),
+            bmRequestType: data.request_type,
             bRequest: data.request,
             wValue: data.value,
             wIndex: data.index,
@@ -267,29 +366,52 @@ impl PlatformSubmit<ControlIn> for TransferData {
         self.check_submit_result(res);
     }
 
-    unsafe fn take_completed(&mut self) -> crate::transfer::Completion<Vec<u8>> {
+    unsafe fn take_completed(&mut self) -> Completion<ControlInResponse> {
         let (status, actual_len) = self.take_status();
 
         // SAFETY: self is completed (precondition) and `actual_length` bytes were initialized.
         let data = unsafe { self.take_buf(actual_len) };
-        Completion { data, status }
+        Completion {
+            data: ControlInResponse::new(data, 0),
+            status,
+        }
     }
 }
 
 impl PlatformSubmit<ControlOut<'_>> for TransferData {
     unsafe fn submit(&mut self, data: ControlOut<'_>, callback_data: *mut std::ffi::c_void) {
+        let data = ControlOutSetup {
+            setup: data.setup(),
+            data: data.data,
+        };
+        PlatformSubmit::<ControlOutSetup>::submit(self, data, callback_data)
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        PlatformSubmit::<ControlOutSetup>::take_completed(self)
+    }
+}
+
+impl PlatformSubmit<ControlOutSetup<'_>> for TransferData {
+    unsafe fn submit(
+        &mut self,
+        request: ControlOutSetup<'_>,
+        callback_data: *mut std::ffi::c_void,
+    ) {
         assert!(self.pipe_ref == 0);
 
-        let buf = data.data.to_vec();
-        let len = buf.len();
-        self.fill(buf, callback_data);
+        let ControlOutSetup {
+            setup: data,
+            data: buf,
+        } = request;
+        self.fill(buf.to_vec(), callback_data);
 
         let mut req = IOUSBDevRequest {
-            bmRequestType: data.request_type(),
+            bmRequestType: data.request_type,
             bRequest: data.request,
             wValue: data.value,
             wIndex: data.index,
-            wLength: u16::try_from(len).expect("request too long"),
+            wLength: data.length,
             pData: self.buf as *mut c_void,
             wLenDone: 0,
         };
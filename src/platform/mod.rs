@@ -15,3 +15,9 @@ mod macos_iokit;
 
 #[cfg(target_os = "macos")]
 pub use macos_iokit::*;
+
+#[cfg(target_os = "freebsd")]
+mod freebsd_usb;
+
+#[cfg(target_os = "freebsd")]
+pub use freebsd_usb::*;
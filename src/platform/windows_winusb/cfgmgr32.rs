@@ -13,8 +13,8 @@ use windows_sys::{
                 CM_LOCATE_DEVNODE_PHANTOM, CM_REGISTRY_HARDWARE, CR_BUFFER_SMALL, CR_SUCCESS,
             },
             Properties::{
-                DEVPKEY_Device_InstanceId, DEVPROPKEY, DEVPROPTYPE, DEVPROP_TYPE_STRING,
-                DEVPROP_TYPE_STRING_LIST, DEVPROP_TYPE_UINT32,
+                DEVPKEY_Device_InstanceId, DEVPROPKEY, DEVPROPTYPE, DEVPROP_TYPE_GUID,
+                DEVPROP_TYPE_STRING, DEVPROP_TYPE_STRING_LIST, DEVPROP_TYPE_UINT32,
             },
         },
         Foundation::INVALID_HANDLE_VALUE,
@@ -28,6 +28,8 @@ use super::{
 };
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct DevInst(u32);
 
 impl DevInst {
@@ -160,6 +162,17 @@ impl PropertyType for u32 {
     }
 }
 
+impl PropertyType for GUID {
+    const PROPTYPE: DEVPROPTYPE = DEVPROP_TYPE_GUID;
+    type Buffer = GUID;
+    fn empty_buffer() -> GUID {
+        GUID::from_u128(0)
+    }
+    fn from_buffer(b: &Self::Buffer) -> Self {
+        *b
+    }
+}
+
 impl PropertyType for WCString {
     const PROPTYPE: DEVPROPTYPE = DEVPROP_TYPE_STRING;
     type Buffer = [u16; 1024];
@@ -1,25 +1,40 @@
 use std::{
     collections::{btree_map::Entry, BTreeMap},
-    ffi::c_void,
+    ffi::{c_void, OsStr},
     io::{self, ErrorKind},
-    mem::{size_of_val, transmute},
+    mem::{size_of, size_of_val, transmute},
     os::windows::{
+        ffi::OsStrExt,
         io::{AsRawHandle, RawHandle},
         prelude::OwnedHandle,
     },
     ptr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
 use log::{debug, error, info, warn};
 use windows_sys::Win32::{
+    Devices::DeviceAndDriverInstallation::{
+        CM_Register_Notification, CM_Unregister_Notification, CM_NOTIFY_ACTION,
+        CM_NOTIFY_ACTION_DEVICEINSTANCEREMOVED, CM_NOTIFY_EVENT_DATA, CM_NOTIFY_FILTER,
+        CM_NOTIFY_FILTER_0, CM_NOTIFY_FILTER_0_1, CM_NOTIFY_FILTER_TYPE_DEVICEINSTANCE, CR_SUCCESS,
+        HCMNOTIFICATION,
+    },
     Devices::Usb::{
-        WinUsb_ControlTransfer, WinUsb_Free, WinUsb_GetAssociatedInterface, WinUsb_Initialize,
+        WinUsb_AbortPipe, WinUsb_ControlTransfer, WinUsb_Free, WinUsb_GetAssociatedInterface,
+        WinUsb_GetCurrentAlternateSetting, WinUsb_GetCurrentFrameNumber, WinUsb_Initialize,
         WinUsb_ResetPipe, WinUsb_SetCurrentAlternateSetting, WinUsb_SetPipePolicy,
-        PIPE_TRANSFER_TIMEOUT, WINUSB_INTERFACE_HANDLE, WINUSB_SETUP_PACKET,
+        PIPE_TRANSFER_TIMEOUT, SHORT_PACKET_TERMINATE, USB_DEVICE_DESCRIPTOR,
+        WINUSB_INTERFACE_HANDLE, WINUSB_SETUP_PACKET,
+    },
+    Foundation::{
+        GetLastError, ERROR_INVALID_PARAMETER, ERROR_NOT_SUPPORTED, ERROR_SUCCESS, FALSE, TRUE,
+        WIN32_ERROR,
     },
-    Foundation::{GetLastError, FALSE, TRUE},
 };
 
 use crate::{
@@ -27,8 +42,11 @@ use crate::{
         ConfigurationDescriptor, DeviceDescriptor, DESCRIPTOR_LEN_DEVICE,
         DESCRIPTOR_TYPE_CONFIGURATION,
     },
+    disconnect::DisconnectSignal,
     maybe_future::{blocking::Blocking, Ready},
-    transfer::{Control, Direction, Recipient, TransferError, TransferHandle, TransferType},
+    transfer::{
+        Control, Direction, ObserverSlot, Recipient, TransferError, TransferHandle, TransferType,
+    },
     DeviceInfo, Error, MaybeFuture, Speed,
 };
 
@@ -44,15 +62,19 @@ use super::{
 pub(crate) struct WindowsDevice {
     device_descriptor: DeviceDescriptor,
     config_descriptors: Vec<Vec<u8>>,
-    active_config: u8,
+    active_config: AtomicU8,
     speed: Option<Speed>,
     devinst: DevInst,
     handles: Mutex<BTreeMap<u8, WinusbFileHandle>>,
+    pub(crate) observer: ObserverSlot,
+    pub(crate) disconnect: Arc<DisconnectSignal>,
+    _disconnect_notification: Option<DisconnectNotification>,
 }
 
 impl WindowsDevice {
     pub(crate) fn from_device_info(
         d: &DeviceInfo,
+        _shared: bool,
     ) -> impl MaybeFuture<Output = Result<Arc<WindowsDevice>, Error>> {
         let instance_id = d.instance_id.clone();
         let devinst = d.devinst;
@@ -68,7 +90,9 @@ impl WindowsDevice {
 
             // Safety: Windows API struct is repr(C), packed, and we're assuming Windows is little-endian
             let device_descriptor = unsafe {
-                &transmute::<_, [u8; DESCRIPTOR_LEN_DEVICE as usize]>(connection_info.device_desc)
+                &transmute::<USB_DEVICE_DESCRIPTOR, [u8; DESCRIPTOR_LEN_DEVICE as usize]>(
+                    connection_info.device_desc,
+                )
             };
             let device_descriptor = DeviceDescriptor::new(device_descriptor)
                 .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid device descriptor"))?;
@@ -85,13 +109,22 @@ impl WindowsDevice {
                 })
                 .collect();
 
+            let disconnect = Arc::new(DisconnectSignal::default());
+            let disconnect_notification =
+                DisconnectNotification::new(&instance_id, disconnect.clone())
+                    .inspect_err(|e| warn!("Failed to register for removal of device: {e}"))
+                    .ok();
+
             Ok(Arc::new(WindowsDevice {
                 device_descriptor,
                 config_descriptors,
                 speed: connection_info.speed,
-                active_config: connection_info.active_config,
-                devinst: devinst,
+                active_config: AtomicU8::new(connection_info.active_config),
+                devinst,
                 handles: Mutex::new(BTreeMap::new()),
+                observer: ObserverSlot::default(),
+                disconnect,
+                _disconnect_notification: disconnect_notification,
             }))
         })
     }
@@ -105,12 +138,21 @@ impl WindowsDevice {
     }
 
     pub(crate) fn active_configuration_value(&self) -> u8 {
+        self.active_config.load(Ordering::SeqCst)
+    }
+
+    /// Query the hub for the active configuration, so a change made by
+    /// another process or a driver is picked up, and update the cached value.
+    pub(crate) fn refresh_active_configuration(&self) -> Result<u8, Error> {
+        let info = HubPort::by_child_devinst(self.devinst)?.get_info()?;
         self.active_config
+            .store(info.active_config, Ordering::SeqCst);
+        Ok(info.active_config)
     }
 
     pub(crate) fn configuration_descriptors(
         &self,
-    ) -> impl Iterator<Item = ConfigurationDescriptor> {
+    ) -> impl Iterator<Item = ConfigurationDescriptor<'_>> {
         self.config_descriptors
             .iter()
             .map(|d| ConfigurationDescriptor::new_unchecked(&d[..]))
@@ -118,11 +160,16 @@ impl WindowsDevice {
 
     pub(crate) fn set_configuration(
         &self,
-        _configuration: u8,
+        configuration: u8,
     ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        // The configuration is selected by the USB stack when the device is
+        // enumerated, and WinUSB rejects `SET_CONFIGURATION`.
         Ready(Err(io::Error::new(
             ErrorKind::Unsupported,
-            "set_configuration not supported by WinUSB",
+            format!(
+                "can't set configuration {configuration}: Windows selects the configuration \
+                 when the device is enumerated, and WinUSB doesn't allow changing it"
+            ),
         )))
     }
 
@@ -136,10 +183,53 @@ impl WindowsDevice {
     }
 
     pub(crate) fn reset(&self) -> impl MaybeFuture<Output = Result<(), Error>> {
-        Ready(Err(io::Error::new(
-            ErrorKind::Unsupported,
-            "reset not supported by WinUSB",
-        )))
+        // WinUSB has no device reset, so cycle the hub port instead.
+        let devinst = self.devinst;
+        Blocking::new(move || {
+            HubPort::by_child_devinst(devinst)?.cycle()?;
+            debug!("Cycled port of device {devinst:?}");
+            Ok(())
+        })
+    }
+
+    /// Get the handle that provides `interface_number`, opening it if it is
+    /// not already open.
+    fn open_handle<'h>(
+        &self,
+        handles: &'h mut BTreeMap<u8, WinusbFileHandle>,
+        interface_number: u8,
+    ) -> Result<&'h mut WinusbFileHandle, Error> {
+        let driver = get_driver_name(self.devinst);
+
+        let (first_interface, child_dev) = if driver.eq_ignore_ascii_case("winusb") {
+            (0, None)
+        } else if driver.eq_ignore_ascii_case("usbccgp") {
+            let (first_interface, child_dev) =
+                find_usbccgp_child(self.devinst, interface_number)
+                    .ok_or_else(|| Error::new(ErrorKind::NotFound, "Interface not found"))?;
+
+            if first_interface != interface_number {
+                debug!("Guessing that interface {interface_number} is an associated interface of {first_interface}");
+            }
+
+            (first_interface, Some(child_dev))
+        } else {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Device driver is {driver:?}, not WinUSB or USBCCGP"),
+            ));
+        };
+
+        match handles.entry(first_interface) {
+            Entry::Occupied(e) => Ok(e.into_mut()),
+            Entry::Vacant(e) => {
+                let path = match child_dev {
+                    Some(child_dev) => get_usbccgp_winusb_device_path(child_dev)?,
+                    None => get_winusb_device_path(self.devinst)?,
+                };
+                Ok(e.insert(WinusbFileHandle::new(&path, first_interface)?))
+            }
+        }
     }
 
     pub(crate) fn claim_interface(
@@ -147,47 +237,98 @@ impl WindowsDevice {
         interface_number: u8,
     ) -> impl MaybeFuture<Output = Result<Arc<WindowsInterface>, Error>> {
         Blocking::new(move || {
-            let driver = get_driver_name(self.devinst);
-
             let mut handles = self.handles.lock().unwrap();
+            let handle = self.open_handle(&mut handles, interface_number)?;
+            let first_interface = handle.first_interface;
+            let intf = handle.claim_interface(&self, interface_number);
 
-            if driver.eq_ignore_ascii_case("winusb") {
-                match handles.entry(0) {
-                    Entry::Occupied(mut e) => e.get_mut().claim_interface(&self, interface_number),
-                    Entry::Vacant(e) => {
-                        let path = get_winusb_device_path(self.devinst)?;
-                        let mut handle = WinusbFileHandle::new(&path, 0)?;
-                        let intf = handle.claim_interface(&self, interface_number)?;
-                        e.insert(handle);
-                        Ok(intf)
-                    }
-                }
-            } else if driver.eq_ignore_ascii_case("usbccgp") {
-                let (first_interface, child_dev) =
-                    find_usbccgp_child(self.devinst, interface_number)
-                        .ok_or_else(|| Error::new(ErrorKind::NotFound, "Interface not found"))?;
+            // Don't keep a handle opened for this claim if it failed.
+            if intf.is_err() && handle.is_unused() {
+                handles.remove(&first_interface);
+            }
 
-                if first_interface != interface_number {
-                    debug!("Guessing that interface {interface_number} is an associated interface of {first_interface}");
-                }
+            intf
+        })
+    }
+
+    /// Get an interface for control transfers to the device.
+    ///
+    /// WinUSB only allows control transfers on an interface handle, so like
+    /// libusb, this uses a handle that is already open, or opens the first
+    /// interface of the active configuration that has the WinUSB driver. The
+    /// interface is not claimed, and remains available to `claim_interface`.
+    fn control_interface(self: &Arc<Self>) -> Result<Arc<WindowsInterface>, Error> {
+        let mut handles = self.handles.lock().unwrap();
+
+        if let Some(handle) = handles.values_mut().next() {
+            return Ok(handle.control_interface(self));
+        }
 
-                match handles.entry(first_interface) {
-                    Entry::Occupied(mut e) => e.get_mut().claim_interface(&self, interface_number),
-                    Entry::Vacant(e) => {
-                        let path = get_usbccgp_winusb_device_path(child_dev)?;
-                        let mut handle = WinusbFileHandle::new(&path, first_interface)?;
-                        let intf = handle.claim_interface(&self, interface_number)?;
-                        e.insert(handle);
-                        Ok(intf)
-                    }
+        let active = self.active_config.load(Ordering::SeqCst);
+        let interfaces: Vec<u8> = self
+            .configuration_descriptors()
+            .find(|c| c.configuration_value() == active)
+            .map(|c| c.interfaces().map(|i| i.interface_number()).collect())
+            .unwrap_or_default();
+
+        let mut error = Error::new(
+            ErrorKind::NotFound,
+            "no interface of the active configuration is available",
+        );
+        for interface_number in interfaces {
+            match self.open_handle(&mut handles, interface_number) {
+                Ok(handle) => return Ok(handle.control_interface(self)),
+                Err(e) => {
+                    debug!("Can't use interface {interface_number} for control transfers: {e}");
+                    error = e;
                 }
-            } else {
-                Err(Error::new(
-                    ErrorKind::Unsupported,
-                    format!("Device driver is {driver:?}, not WinUSB or USBCCGP"),
-                ))
             }
-        })
+        }
+
+        Err(error)
+    }
+
+    pub(crate) fn make_control_transfer(self: &Arc<Self>) -> TransferHandle<super::TransferData> {
+        let interface = self.control_interface().unwrap_or_else(|e| {
+            // The transfer fails on submit with an invalid handle.
+            error!("No WinUSB handle available for control transfer: {e}");
+            Arc::new(WindowsInterface {
+                handle: ptr::null_mut(),
+                device: self.clone(),
+                first_interface_number: 0,
+                interface_number: 0,
+                winusb_handle: ptr::null_mut(),
+                device_control: true,
+                state: Mutex::new(InterfaceState::default()),
+            })
+        });
+        TransferHandle::new(super::TransferData::new(
+            interface,
+            0,
+            TransferType::Control,
+        ))
+    }
+
+    pub(crate) fn control_in_blocking(
+        self: &Arc<Self>,
+        control: Control,
+        data: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.control_interface()
+            .map_err(open_error)?
+            .control_in_blocking(control, data, timeout)
+    }
+
+    pub(crate) fn control_out_blocking(
+        self: &Arc<Self>,
+        control: Control,
+        data: &[u8],
+        timeout: Duration,
+    ) -> Result<usize, TransferError> {
+        self.control_interface()
+            .map_err(open_error)?
+            .control_out_blocking(control, data, timeout)
     }
 
     pub(crate) fn detach_and_claim_interface(
@@ -198,6 +339,98 @@ impl WindowsDevice {
     }
 }
 
+fn open_error(e: Error) -> TransferError {
+    error!("No WinUSB handle available for control transfer: {e}");
+    super::transfer::map_error(
+        e.raw_os_error()
+            .map_or(ERROR_NOT_SUPPORTED, |code| code as WIN32_ERROR),
+    )
+}
+
+/// Registration with `CM_Register_Notification` that sets a
+/// `DisconnectSignal` when the device instance is removed.
+struct DisconnectNotification {
+    registration: HCMNOTIFICATION,
+    signal: *const DisconnectSignal,
+}
+
+// SAFETY: `signal` is an `Arc` that is only dropped after unregistering, and
+// the registration is only used in `Drop`.
+unsafe impl Send for DisconnectNotification {}
+unsafe impl Sync for DisconnectNotification {}
+
+impl DisconnectNotification {
+    fn new(instance_id: &OsStr, signal: Arc<DisconnectSignal>) -> Result<Self, Error> {
+        let mut id = [0; 200];
+        let encoded: Vec<u16> = instance_id.encode_wide().collect();
+        // Leave room for the null terminator.
+        if encoded.len() >= id.len() {
+            return Err(Error::new(ErrorKind::InvalidInput, "instance ID too long"));
+        }
+        id[..encoded.len()].copy_from_slice(&encoded);
+
+        let filter = CM_NOTIFY_FILTER {
+            cbSize: size_of::<CM_NOTIFY_FILTER>() as u32,
+            Flags: 0,
+            FilterType: CM_NOTIFY_FILTER_TYPE_DEVICEINSTANCE,
+            Reserved: 0,
+            u: CM_NOTIFY_FILTER_0 {
+                DeviceInstance: CM_NOTIFY_FILTER_0_1 { InstanceId: id },
+            },
+        };
+
+        let signal = Arc::into_raw(signal);
+        let mut registration = ptr::null_mut();
+        let cr = unsafe {
+            CM_Register_Notification(
+                &filter,
+                signal as *const c_void,
+                Some(disconnect_callback),
+                &mut registration,
+            )
+        };
+
+        if cr != CR_SUCCESS {
+            error!("CM_Register_Notification failed: {cr}");
+            // SAFETY: not registered, so the callback can't use it
+            drop(unsafe { Arc::from_raw(signal) });
+            return Err(Error::other(
+                "Failed to register for device removal notification",
+            ));
+        }
+
+        Ok(DisconnectNotification {
+            registration,
+            signal,
+        })
+    }
+}
+
+impl Drop for DisconnectNotification {
+    fn drop(&mut self) {
+        unsafe {
+            // Waits for running callbacks, so `signal` can be freed afterward.
+            CM_Unregister_Notification(self.registration);
+            drop(Arc::from_raw(self.signal));
+        }
+    }
+}
+
+unsafe extern "system" fn disconnect_callback(
+    _hnotify: HCMNOTIFICATION,
+    context: *const c_void,
+    action: CM_NOTIFY_ACTION,
+    _eventdata: *const CM_NOTIFY_EVENT_DATA,
+    _eventdatasize: u32,
+) -> u32 {
+    if action == CM_NOTIFY_ACTION_DEVICEINSTANCEREMOVED {
+        debug!("Device instance removed");
+        let signal = unsafe { &*(context as *const DisconnectSignal) };
+        signal.notify();
+    }
+    ERROR_SUCCESS
+}
+
 struct BitSet256([u64; 4]);
 
 impl BitSet256 {
@@ -236,6 +469,10 @@ pub(crate) struct WinusbFileHandle {
     handle: OwnedHandle,
     winusb_handle: WINUSB_INTERFACE_HANDLE,
     claimed_interfaces: BitSet256,
+
+    /// Number of `WindowsInterface`s using this handle for device control
+    /// transfers without claiming an interface.
+    control_users: usize,
 }
 
 // SAFETY: WinUSB methods on the interface handle are thread-safe
@@ -244,7 +481,7 @@ unsafe impl Sync for WinusbFileHandle {}
 
 impl WinusbFileHandle {
     fn new(path: &WCStr, first_interface: u8) -> Result<Self, Error> {
-        let handle = create_file(&path)?;
+        let handle = create_file(path)?;
         super::events::register(&handle)?;
 
         let winusb_handle = unsafe {
@@ -263,6 +500,25 @@ impl WinusbFileHandle {
             handle,
             winusb_handle,
             claimed_interfaces: BitSet256::new(),
+            control_users: 0,
+        })
+    }
+
+    fn is_unused(&self) -> bool {
+        self.claimed_interfaces.is_empty() && self.control_users == 0
+    }
+
+    fn control_interface(&mut self, device: &Arc<WindowsDevice>) -> Arc<WindowsInterface> {
+        self.control_users += 1;
+
+        Arc::new(WindowsInterface {
+            handle: self.handle.as_raw_handle(),
+            device: device.clone(),
+            interface_number: self.first_interface,
+            first_interface_number: self.first_interface,
+            winusb_handle: self.winusb_handle,
+            device_control: true,
+            state: Mutex::new(InterfaceState::default()),
         })
     }
 
@@ -313,6 +569,7 @@ impl WinusbFileHandle {
             interface_number,
             first_interface_number: self.first_interface,
             winusb_handle,
+            device_control: false,
             state: Mutex::new(InterfaceState::default()),
         }))
     }
@@ -336,6 +593,10 @@ pub(crate) struct WindowsInterface {
     pub(crate) first_interface_number: u8,
     pub(crate) interface_number: u8,
     pub(crate) winusb_handle: WINUSB_INTERFACE_HANDLE,
+
+    /// Used only for control transfers on behalf of the `WindowsDevice`,
+    /// without claiming the interface.
+    device_control: bool,
     state: Mutex<InterfaceState>,
 }
 
@@ -349,6 +610,11 @@ unsafe impl Sync for WindowsInterface {}
 
 impl Drop for WindowsInterface {
     fn drop(&mut self) {
+        if self.device_control {
+            self.release_control();
+            return;
+        }
+
         // The WinUSB handle for the first interface is owned by WinusbFileHandle
         // because it is used to open subsequent interfaces.
         let is_first_interface = self.interface_number == self.first_interface_number;
@@ -372,7 +638,7 @@ impl Drop for WindowsInterface {
             .claimed_interfaces
             .clear(self.interface_number);
 
-        if entry.get().claimed_interfaces.is_empty() {
+        if entry.get().is_unused() {
             entry.remove();
         } else if is_first_interface {
             log::debug!(
@@ -384,6 +650,23 @@ impl Drop for WindowsInterface {
 }
 
 impl WindowsInterface {
+    fn release_control(&self) {
+        if self.winusb_handle.is_null() {
+            return;
+        }
+
+        let mut handles = self.device.handles.lock().unwrap();
+        let Entry::Occupied(mut entry) = handles.entry(self.first_interface_number) else {
+            panic!("missing handle that should be open")
+        };
+
+        entry.get_mut().control_users -= 1;
+
+        if entry.get().is_unused() {
+            entry.remove();
+        }
+    }
+
     pub(crate) fn make_transfer(
         self: &Arc<Self>,
         endpoint: u8,
@@ -488,7 +771,7 @@ impl WindowsInterface {
                 "Control transfer length {} exceeds limit of 4096",
                 data.len()
             );
-            return Err(TransferError::Unknown);
+            return Err(TransferError::Unknown(ERROR_INVALID_PARAMETER));
         };
         buf.copy_from_slice(data);
 
@@ -509,7 +792,7 @@ impl WindowsInterface {
     ) -> impl MaybeFuture<Output = Result<(), Error>> {
         Blocking::new(move || unsafe {
             let mut state = self.state.lock().unwrap();
-            let r = WinUsb_SetCurrentAlternateSetting(self.winusb_handle, alt_setting.into());
+            let r = WinUsb_SetCurrentAlternateSetting(self.winusb_handle, alt_setting);
             if r == TRUE {
                 debug!(
                     "Set interface {} alt setting to {alt_setting}",
@@ -527,20 +810,87 @@ impl WindowsInterface {
         self.state.lock().unwrap().alt_setting
     }
 
+    pub fn refresh_alt_setting(&self) -> Result<u8, Error> {
+        let mut state = self.state.lock().unwrap();
+        let mut alt_setting = 0;
+        let r = unsafe { WinUsb_GetCurrentAlternateSetting(self.winusb_handle, &mut alt_setting) };
+        if r == TRUE {
+            state.alt_setting = alt_setting;
+            Ok(alt_setting)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
     pub fn clear_halt(
         self: Arc<Self>,
         endpoint: u8,
     ) -> impl MaybeFuture<Output = Result<(), Error>> {
         Blocking::new(move || {
             debug!("Clear halt, endpoint {endpoint:02x}");
-            unsafe {
-                let r = WinUsb_ResetPipe(self.winusb_handle, endpoint);
-                if r == TRUE {
-                    Ok(())
-                } else {
-                    Err(io::Error::last_os_error())
-                }
-            }
+            self.reset_pipe_blocking(endpoint)
+        })
+    }
+
+    pub fn reset_pipe(
+        self: Arc<Self>,
+        endpoint: u8,
+    ) -> impl MaybeFuture<Output = Result<(), Error>> {
+        Blocking::new(move || {
+            // WinUSB has no host-only reset: WinUsb_ResetPipe also sends
+            // CLEAR_FEATURE(ENDPOINT_HALT) to the device.
+            debug!("Reset pipe, endpoint {endpoint:02x}");
+            self.reset_pipe_blocking(endpoint)
         })
     }
+
+    pub fn set_send_zlp(&self, endpoint: u8, enable: bool) -> Result<(), Error> {
+        let value = u8::from(enable);
+        let r = unsafe {
+            WinUsb_SetPipePolicy(
+                self.winusb_handle,
+                endpoint,
+                SHORT_PACKET_TERMINATE,
+                size_of_val(&value) as u32,
+                &value as *const u8 as *const c_void,
+            )
+        };
+        if r == TRUE {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn abort_pipe(&self, endpoint: u8) -> Result<(), Error> {
+        let r = unsafe { WinUsb_AbortPipe(self.winusb_handle, endpoint) };
+        if r == TRUE {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn frame_number(&self) -> Result<u64, Error> {
+        let mut frame = 0;
+        let mut timestamp = 0;
+        let r =
+            unsafe { WinUsb_GetCurrentFrameNumber(self.winusb_handle, &mut frame, &mut timestamp) };
+        if r == TRUE {
+            Ok(frame.into())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn reset_pipe_blocking(&self, endpoint: u8) -> Result<(), Error> {
+        // WinUsb_ResetPipe clears the stall on both ends and resets the
+        // host-side data toggle.
+        let r = unsafe { WinUsb_ResetPipe(self.winusb_handle, endpoint) };
+        if r == TRUE {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
 }
@@ -4,20 +4,25 @@ use std::{
 };
 
 use log::debug;
-use windows_sys::Win32::Devices::{
-    Properties::{
-        DEVPKEY_Device_Address, DEVPKEY_Device_BusReportedDeviceDesc, DEVPKEY_Device_CompatibleIds,
-        DEVPKEY_Device_DeviceDesc, DEVPKEY_Device_HardwareIds, DEVPKEY_Device_InstanceId,
-        DEVPKEY_Device_LocationPaths, DEVPKEY_Device_Parent, DEVPKEY_Device_Service,
+use windows_sys::{
+    core::GUID,
+    Win32::Devices::{
+        Properties::{
+            DEVPKEY_Device_Address, DEVPKEY_Device_BusReportedDeviceDesc,
+            DEVPKEY_Device_CompatibleIds, DEVPKEY_Device_ContainerId, DEVPKEY_Device_DeviceDesc,
+            DEVPKEY_Device_HardwareIds, DEVPKEY_Device_InstanceId, DEVPKEY_Device_LocationPaths,
+            DEVPKEY_Device_Parent, DEVPKEY_Device_Service,
+        },
+        Usb::{GUID_DEVINTERFACE_USB_DEVICE, GUID_DEVINTERFACE_USB_HUB},
     },
-    Usb::{GUID_DEVINTERFACE_USB_DEVICE, GUID_DEVINTERFACE_USB_HUB},
 };
 
 use crate::{
     descriptors::{
-        decode_string_descriptor, language_id::US_ENGLISH, ConfigurationDescriptor,
-        DESCRIPTOR_TYPE_CONFIGURATION, DESCRIPTOR_TYPE_STRING,
+        decode_string_descriptor, language_id::US_ENGLISH, validate_string_descriptor,
+        ConfigurationDescriptor, DESCRIPTOR_TYPE_CONFIGURATION, DESCRIPTOR_TYPE_STRING,
     },
+    hotplug::Filter,
     maybe_future::{blocking::Blocking, MaybeFuture},
     BusInfo, DeviceInfo, Error, InterfaceInfo, UsbControllerType,
 };
@@ -30,14 +35,33 @@ use super::{
 
 pub fn list_devices() -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>>
 {
-    Blocking::new(|| {
+    list_devices_filtered(Filter::new())
+}
+
+pub fn list_devices_filtered(
+    filter: Filter,
+) -> impl MaybeFuture<Output = Result<impl Iterator<Item = DeviceInfo>, Error>> {
+    Blocking::new(move || {
         let devs: Vec<DeviceInfo> = cfgmgr32::list_interfaces(GUID_DEVINTERFACE_USB_DEVICE, None)
             // get USB_HUB devices as well, like other platforms. ROOT_HUBs will be dropped by probe_device
             .iter()
             .chain(cfgmgr32::list_interfaces(GUID_DEVINTERFACE_USB_HUB, None).iter())
             .flat_map(|i| get_device_interface_property::<WCString>(i, DEVPKEY_Device_InstanceId))
+            .filter(|d| {
+                // The instance ID is already known, so check the IDs in it
+                // before querying the hub for the rest of the device info.
+                parse_instance_id(&d.to_string()).map_or(true, |(v, p)| filter.matches_ids(v, p))
+            })
             .flat_map(|d| DevInst::from_instance_id(&d))
+            .filter(|devinst| prefilter_bus(*devinst, &filter))
             .flat_map(probe_device)
+            .filter(|d| filter.matches(d))
+            .map(|mut d| {
+                if filter.fetches_strings() {
+                    fetch_missing_strings(&mut d);
+                }
+                d
+            })
             .collect();
         Ok(devs.into_iter())
     })
@@ -111,11 +135,17 @@ pub fn probe_device(devinst: DevInst) -> Option<DeviceInfo> {
                     subclass,
                     protocol,
                     interface_string,
+                    driver: Some(get_driver_name(intf)).filter(|s| !s.is_empty()),
                 })
             })
             .collect()
     } else {
-        list_interfaces_from_desc(&hub_port, info.active_config).unwrap_or(Vec::new())
+        let mut interfaces =
+            list_interfaces_from_desc(&hub_port, info.active_config).unwrap_or_default();
+        for interface in &mut interfaces {
+            interface.driver = Some(driver.clone()).filter(|s| !s.is_empty());
+        }
+        interfaces
     };
 
     interfaces.sort_unstable_by_key(|i| i.interface_number);
@@ -129,8 +159,17 @@ pub fn probe_device(devinst: DevInst) -> Option<DeviceInfo> {
         .find_map(|p| parse_location_path(p))
         .unwrap_or_default();
 
+    let container_id = devinst
+        .get_property::<GUID>(DEVPKEY_Device_ContainerId)
+        .map(format_guid);
+    let hardware_ids = devinst
+        .get_property::<Vec<OsString>>(DEVPKEY_Device_HardwareIds)
+        .unwrap_or_default();
+
     Some(DeviceInfo {
         instance_id,
+        container_id,
+        hardware_ids,
         location_paths,
         parent_instance_id,
         devinst,
@@ -141,6 +180,7 @@ pub fn probe_device(devinst: DevInst) -> Option<DeviceInfo> {
         device_address: info.address,
         vendor_id: info.device_desc.idVendor,
         product_id: info.device_desc.idProduct,
+        usb_version: info.device_desc.bcdUSB,
         device_version: info.device_desc.bcdDevice,
         class: info.device_desc.bDeviceClass,
         subclass: info.device_desc.bDeviceSubClass,
@@ -154,6 +194,59 @@ pub fn probe_device(devinst: DevInst) -> Option<DeviceInfo> {
     })
 }
 
+/// Request the strings Windows didn't cache from the device through its hub.
+pub(crate) fn fetch_missing_strings(device: &mut DeviceInfo) {
+    if device.manufacturer_string.is_some()
+        && device.product_string.is_some()
+        && device.serial_number.is_some()
+    {
+        return;
+    }
+
+    let Ok(hub_port) = HubPort::by_child_devinst(device.devinst) else {
+        return;
+    };
+    let Ok(info) = hub_port.get_info() else {
+        return;
+    };
+
+    let language_id = hub_port
+        .get_descriptor(DESCRIPTOR_TYPE_STRING, 0, 0)
+        .ok()
+        .filter(|data| validate_string_descriptor(data))
+        .and_then(|data| Some(u16::from_le_bytes([*data.get(2)?, *data.get(3)?])))
+        .unwrap_or(US_ENGLISH);
+
+    let get_string = |index: u8| {
+        if index == 0 {
+            return None;
+        }
+        debug!("Fetching string {index} of {:?}", device.instance_id);
+        hub_port
+            .get_descriptor(DESCRIPTOR_TYPE_STRING, index, language_id)
+            .ok()
+            .and_then(|data| decode_string_descriptor(&data).ok())
+    };
+
+    let desc = &info.device_desc;
+    let manufacturer_string = device
+        .manufacturer_string
+        .clone()
+        .or_else(|| get_string(desc.iManufacturer));
+    let product_string = device
+        .product_string
+        .clone()
+        .or_else(|| get_string(desc.iProduct));
+    let serial_number = device
+        .serial_number
+        .clone()
+        .or_else(|| get_string(desc.iSerialNumber));
+
+    device.manufacturer_string = manufacturer_string;
+    device.product_string = product_string;
+    device.serial_number = serial_number;
+}
+
 pub fn probe_bus(devinst: DevInst) -> Option<BusInfo> {
     let instance_id = devinst.get_property::<OsString>(DEVPKEY_Device_InstanceId)?;
     // Skip non-root hubs; buses which have instance IDs starting with "USB\\ROOT_HUB"
@@ -223,6 +316,7 @@ fn list_interfaces_from_desc(hub_port: &HubPort, active_config: u8) -> Option<Ve
                     subclass: i_desc.subclass(),
                     protocol: i_desc.protocol(),
                     interface_string: None,
+                    driver: None,
                 }
             })
             .collect(),
@@ -240,10 +334,7 @@ pub(crate) fn get_winusb_device_path(dev: DevInst) -> Result<WCString, Error> {
     let paths = dev.interfaces(GUID_DEVINTERFACE_USB_DEVICE);
 
     let Some(path) = paths.iter().next() else {
-        return Err(Error::new(
-            ErrorKind::Other,
-            "Failed to find device path for WinUSB device",
-        ));
+        return Err(Error::other("Failed to find device path for WinUSB device"));
     };
 
     Ok(path.to_owned())
@@ -300,8 +391,7 @@ pub(crate) fn get_usbccgp_winusb_device_path(child: DevInst) -> Result<WCString,
 
     let paths = child.interfaces(guid);
     let Some(path) = paths.iter().next() else {
-        return Err(Error::new(
-            ErrorKind::Other,
+        return Err(Error::other(
             "Failed to find device path for WinUSB interface",
         ));
     };
@@ -322,6 +412,55 @@ fn get_interface_number(intf_dev: DevInst) -> Option<u8> {
         })
 }
 
+/// Check the bus of a device against `filter`, without probing the device.
+fn prefilter_bus(devinst: DevInst, filter: &Filter) -> bool {
+    devinst
+        .get_property::<Vec<OsString>>(DEVPKEY_Device_LocationPaths)
+        .and_then(|paths| paths.iter().find_map(|p| parse_location_path(p)))
+        .map_or(true, |(bus_id, _)| filter.matches_bus(&bus_id))
+}
+
+/// Format a GUID the way Windows displays it, e.g. in Device Manager.
+fn format_guid(guid: GUID) -> String {
+    let d = guid.data4;
+    format!(
+        "{{{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}}}",
+        guid.data1, guid.data2, guid.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+    )
+}
+
+#[test]
+fn test_format_guid() {
+    assert_eq!(
+        format_guid(GUID::from_u128(0xa5dcbf10_6530_11d2_901f_00c04fb951ed)),
+        "{a5dcbf10-6530-11d2-901f-00c04fb951ed}"
+    );
+}
+
+/// Parse vendor and product ID from a device Instance ID value
+fn parse_instance_id(s: &str) -> Option<(u16, u16)> {
+    let s = s.get(..21)?.to_ascii_uppercase();
+    let s = s.strip_prefix("USB\\VID_")?;
+    let vendor_id = u16::from_str_radix(s.get(0..4)?, 16).ok()?;
+    let s = s.get(4..)?.strip_prefix("&PID_")?;
+    let product_id = u16::from_str_radix(s.get(0..4)?, 16).ok()?;
+    Some((vendor_id, product_id))
+}
+
+#[test]
+fn test_parse_instance_id() {
+    assert_eq!(parse_instance_id(""), None);
+    assert_eq!(parse_instance_id("USB\\ROOT_HUB30\\4&1A2B3C4D&0&0"), None);
+    assert_eq!(
+        parse_instance_id("USB\\VID_1234&PID_ABCD\\0123456789"),
+        Some((0x1234, 0xABCD))
+    );
+    assert_eq!(
+        parse_instance_id("USB\\vid_1234&pid_abcd&mi_00\\6&1234&0&0000"),
+        Some((0x1234, 0xABCD))
+    );
+}
+
 /// Parse interface number from a Hardware ID value
 fn parse_hardware_id(s: &OsStr) -> Option<u8> {
     let s = s.to_str()?;
@@ -1,21 +1,33 @@
-use log::error;
+use log::{error, warn};
 use once_cell::sync::OnceCell;
 use std::{
     os::windows::{
         io::HandleOrNull,
         prelude::{OwnedHandle, RawHandle},
     },
-    ptr, thread,
+    ptr,
+    task::Waker,
 };
 use windows_sys::Win32::{
     Foundation::{GetLastError, FALSE, INVALID_HANDLE_VALUE},
-    System::IO::{CreateIoCompletionPort, GetQueuedCompletionStatusEx, OVERLAPPED_ENTRY},
+    Storage::FileSystem::SetFileCompletionNotificationModes,
+    System::{
+        WindowsProgramming::FILE_SKIP_SET_EVENT_ON_HANDLE,
+        IO::{CreateIoCompletionPort, GetQueuedCompletionStatusEx, OVERLAPPED_ENTRY},
+    },
 };
 
-use crate::Error;
+use crate::{event_thread, Error};
 
 use super::util::raw_handle;
 
+/// Completion key for WinUSB device and interface handles, whose completions
+/// carry an `EventNotify` as their `OVERLAPPED`.
+const KEY_TRANSFER: usize = 1;
+
+/// Maximum number of completions dequeued per wakeup of the event thread.
+const BATCH_SIZE: usize = 64;
+
 struct IoCompletionPort(OwnedHandle);
 
 impl IoCompletionPort {
@@ -33,16 +45,24 @@ impl IoCompletionPort {
         }
     }
 
-    fn register(&self, handle: &OwnedHandle) -> Result<(), Error> {
+    fn register(&self, handle: &OwnedHandle, key: usize) -> Result<(), Error> {
         unsafe {
-            let r = CreateIoCompletionPort(raw_handle(handle), raw_handle(&self.0), 0, 0);
-            if r == ptr::null_mut() {
+            let r = CreateIoCompletionPort(raw_handle(handle), raw_handle(&self.0), key, 0);
+            if r.is_null() {
                 let err = std::io::Error::last_os_error();
                 error!("CreateIoCompletionPort (register) failed: {err:?}");
-                Err(err)
-            } else {
-                Ok(())
+                return Err(err);
             }
+
+            // Completions are only consumed through the port, so don't
+            // bother signaling the file handle's event.
+            let flags = FILE_SKIP_SET_EVENT_ON_HANDLE as u8;
+            if SetFileCompletionNotificationModes(raw_handle(handle), flags) == FALSE {
+                let err = std::io::Error::last_os_error();
+                warn!("SetFileCompletionNotificationModes failed: {err:?}");
+            }
+
+            Ok(())
         }
     }
 
@@ -83,21 +103,40 @@ pub(super) fn register(usb_fd: &OwnedHandle) -> Result<(), Error> {
     })?;
 
     if start_thread {
-        thread::spawn(event_loop);
+        event_thread::spawn(event_loop);
     }
+    event_thread::check()?;
 
-    iocp.register(usb_fd)
+    iocp.register(usb_fd, KEY_TRANSFER)
 }
 
 fn event_loop() {
     let iocp = IOCP_HANDLE.get().unwrap();
-    let mut event_list = Vec::with_capacity(8);
+    let mut event_list = Vec::with_capacity(BATCH_SIZE);
+    let mut wakers: Vec<Waker> = Vec::with_capacity(BATCH_SIZE);
     loop {
         event_list.clear();
         iocp.wait(&mut event_list).unwrap();
 
         for event in &event_list {
-            super::transfer::handle_event(event.lpOverlapped);
+            match event.lpCompletionKey {
+                KEY_TRANSFER => {
+                    if let Some(waker) = super::transfer::handle_event(event.lpOverlapped) {
+                        // Transfers on a `Queue` share its task's waker, so
+                        // only wake each task once per batch.
+                        if !wakers.iter().any(|w| w.will_wake(&waker)) {
+                            wakers.push(waker);
+                        }
+                    }
+                }
+                key => {
+                    warn!("Ignoring completion with unknown key {key}");
+                }
+            }
+        }
+
+        for waker in wakers.drain(..) {
+            waker.wake();
         }
     }
 }
@@ -1,7 +1,6 @@
 use std::{
     collections::VecDeque,
     ffi::c_void,
-    io::ErrorKind,
     mem::size_of,
     ptr::{self, addr_of},
     sync::Mutex,
@@ -81,10 +80,7 @@ impl WindowsHotplugWatch {
 
         if cr != CR_SUCCESS {
             error!("CM_Register_Notification failed: {cr}");
-            return Err(Error::new(
-                ErrorKind::Other,
-                "Failed to initialize hotplug notifications",
-            ));
+            return Err(Error::other("Failed to initialize hotplug notifications"));
         }
 
         Ok(WindowsHotplugWatch {
@@ -99,19 +95,23 @@ impl WindowsHotplugWatch {
 
     pub fn poll_next(&mut self, cx: &mut Context) -> Poll<HotplugEvent> {
         self.inner().waker.register(cx.waker());
-        let event = self.inner().events.lock().unwrap().pop_front();
-        match event {
-            Some((Action::Connect, devinst)) => {
-                if let Some(dev) = probe_device(devinst) {
-                    return Poll::Ready(HotplugEvent::Connected(dev));
-                };
+        loop {
+            let event = self.inner().events.lock().unwrap().pop_front();
+            match event {
+                Some((Action::Connect, devinst)) => {
+                    // Keep going with the next event, which won't wake the task
+                    // again if it was queued already.
+                    match probe_device(devinst) {
+                        Some(dev) => return Poll::Ready(HotplugEvent::Connected(dev)),
+                        None => debug!("Failed to probe connected device {devinst:?}"),
+                    }
+                }
+                Some((Action::Disconnect, devinst)) => {
+                    return Poll::Ready(HotplugEvent::Disconnected(DeviceId(devinst)));
+                }
+                None => return Poll::Pending,
             }
-            Some((Action::Disconnect, devinst)) => {
-                return Poll::Ready(HotplugEvent::Disconnected(DeviceId(devinst)));
-            }
-            None => {}
         }
-        Poll::Pending
     }
 }
 
@@ -155,13 +155,20 @@ unsafe extern "system" fn hotplug_callback(
     let device_interface =
         unsafe { WCStr::from_ptr(addr_of!((*eventdata).u.DeviceInterface.SymbolicLink[0])) };
 
-    let device_instance =
+    // Panicking here would unwind into cfgmgr32, so failures are only logged.
+    let Some(device_instance) =
         get_device_interface_property::<WCString>(device_interface, DEVPKEY_Device_InstanceId)
-            .unwrap();
-    let devinst = DevInst::from_instance_id(&device_instance).unwrap();
+    else {
+        error!("Hotplug callback: failed to get instance ID for {action:?} of {device_interface}");
+        return ERROR_SUCCESS;
+    };
+    let Some(devinst) = DevInst::from_instance_id(&device_instance) else {
+        error!("Hotplug callback: failed to locate device instance {device_instance}");
+        return ERROR_SUCCESS;
+    };
 
     debug!("Hotplug callback: action={action:?}, instance={device_instance}");
     inner.events.lock().unwrap().push_back((action, devinst));
     inner.waker.wake();
-    return ERROR_SUCCESS;
+    ERROR_SUCCESS
 }
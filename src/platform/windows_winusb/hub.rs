@@ -16,9 +16,10 @@ use windows_sys::Win32::{
             UsbFullSpeed, UsbHighSpeed, UsbLowSpeed, GUID_DEVINTERFACE_USB_HUB,
             IOCTL_USB_GET_DESCRIPTOR_FROM_NODE_CONNECTION,
             IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX,
-            IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX_V2, USB_DESCRIPTOR_REQUEST,
-            USB_DESCRIPTOR_REQUEST_0, USB_DEVICE_DESCRIPTOR, USB_DEVICE_SPEED,
-            USB_NODE_CONNECTION_INFORMATION_EX, USB_NODE_CONNECTION_INFORMATION_EX_V2,
+            IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX_V2, IOCTL_USB_HUB_CYCLE_PORT,
+            USB_CYCLE_PORT_PARAMS, USB_DESCRIPTOR_REQUEST, USB_DESCRIPTOR_REQUEST_0,
+            USB_DEVICE_DESCRIPTOR, USB_DEVICE_SPEED, USB_NODE_CONNECTION_INFORMATION_EX,
+            USB_NODE_CONNECTION_INFORMATION_EX_V2,
         },
     },
     Foundation::{GetLastError, ERROR_GEN_FAILURE, TRUE},
@@ -86,9 +87,8 @@ impl HubHandle {
                 if info.DeviceDescriptor.bDescriptorType != DESCRIPTOR_TYPE_DEVICE {
                     // When the device is disconnected during this call, Windows is observed to
                     // sometimes return an all-zero device descriptor.
-                    return Err(Error::new(
-                       std::io::ErrorKind::Other,
-                       "IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX returned an invalid device descriptor",
+                    return Err(Error::other(
+                        "IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX returned an invalid device descriptor",
                     ));
                 }
 
@@ -132,6 +132,36 @@ impl HubHandle {
         }
     }
 
+    /// Simulate unplugging and replugging the device on a port, which makes
+    /// it re-enumerate.
+    pub fn cycle_port(&self, port_number: u32) -> Result<(), Error> {
+        unsafe {
+            let mut params = USB_CYCLE_PORT_PARAMS {
+                ConnectionIndex: port_number,
+                StatusReturned: 0,
+            };
+            let mut bytes_returned: u32 = 0;
+            let r = DeviceIoControl(
+                raw_handle(&self.0),
+                IOCTL_USB_HUB_CYCLE_PORT,
+                &params as *const _ as *const c_void,
+                mem::size_of_val(&params) as u32,
+                &mut params as *mut _ as *mut c_void,
+                mem::size_of_val(&params) as u32,
+                &mut bytes_returned,
+                null_mut(),
+            );
+
+            if r == TRUE {
+                Ok(())
+            } else {
+                let err = Error::last_os_error();
+                debug!("IOCTL_USB_HUB_CYCLE_PORT failed on port {port_number}: {err:?}");
+                Err(err)
+            }
+        }
+    }
+
     pub fn get_descriptor(
         &self,
         port_number: u32,
@@ -187,10 +217,9 @@ impl HubHandle {
                 let err = GetLastError();
                 debug!("IOCTL_USB_GET_DESCRIPTOR_FROM_NODE_CONNECTION failed: type={descriptor_type} index={descriptor_index} error={err:?}");
                 Err(match err {
-                    ERROR_GEN_FAILURE => Error::new(
-                        ErrorKind::Other,
-                        "Descriptor request failed. Device might be suspended.",
-                    ),
+                    ERROR_GEN_FAILURE => {
+                        Error::other("Descriptor request failed. Device might be suspended.")
+                    }
                     _ => Error::from_raw_os_error(err as i32),
                 })
             };
@@ -217,9 +246,9 @@ impl HubPort {
     pub fn by_child_devinst(devinst: DevInst) -> Result<HubPort, Error> {
         let parent_hub = devinst
             .parent()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "failed to find parent hub"))?;
+            .ok_or_else(|| Error::other("failed to find parent hub"))?;
         let hub_handle = HubHandle::by_devinst(parent_hub)
-            .ok_or_else(|| Error::new(ErrorKind::Other, "failed to open parent hub"))?;
+            .ok_or_else(|| Error::other("failed to open parent hub"))?;
         let Some(port_number) = devinst.get_property::<u32>(DEVPKEY_Device_Address) else {
             return Err(Error::new(
                 ErrorKind::NotConnected,
@@ -278,4 +307,8 @@ impl HubPort {
             language_id,
         )
     }
+
+    pub fn cycle(&self) -> Result<(), Error> {
+        self.hub_handle.cycle_port(self.port_number)
+    }
 }
@@ -1,5 +1,6 @@
 mod enumeration;
-pub use enumeration::{list_buses, list_devices};
+pub(crate) use enumeration::fetch_missing_strings;
+pub use enumeration::{list_buses, list_devices, list_devices_filtered};
 
 mod events;
 
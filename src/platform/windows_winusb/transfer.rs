@@ -1,9 +1,11 @@
 use std::{
+    any::Any,
     ffi::c_void,
     io,
     mem::{self, ManuallyDrop},
     ptr::{addr_of_mut, null_mut},
     sync::Arc,
+    task::Waker,
 };
 
 use log::{debug, error, warn};
@@ -21,8 +23,10 @@ use windows_sys::Win32::{
 };
 
 use crate::transfer::{
-    notify_completion, Completion, ControlIn, ControlOut, PlatformSubmit, PlatformTransfer,
-    Recipient, RequestBuffer, ResponseBuffer, TransferError, TransferType,
+    check_short_packet, complete_deferred, notify_completion, Chunk, Completion, ControlIn,
+    ControlInBuffer, ControlInResponse, ControlOut, ControlOutSetup, OutBuffer, OutResponse,
+    PlatformSubmit, PlatformTransfer, Recipient, RequestBuffer, ResponseBuffer, SetupPacket,
+    TransferError, TransferObserver, TransferRequest, TransferType,
 };
 
 #[repr(C)]
@@ -37,6 +41,14 @@ pub struct TransferData {
     event: *mut EventNotify,
     buf: *mut u8,
     capacity: usize,
+
+    /// Owner of the data for an `OutBuffer` transfer, in which case `buf`
+    /// points into it rather than to a `Vec` allocation.
+    owner: Option<Box<dyn Any + Send>>,
+
+    /// Requested length of an `IN` transfer submitted with `short_not_ok`,
+    /// checked on completion since the OS has no equivalent option.
+    short_not_ok_len: Option<usize>,
     endpoint: u8,
     ep_type: TransferType,
     submit_error: Option<WIN32_ERROR>,
@@ -55,6 +67,8 @@ impl TransferData {
             event: Box::into_raw(Box::new(unsafe { mem::zeroed() })),
             buf: null_mut(),
             capacity: 0,
+            owner: None,
+            short_not_ok_len: None,
             endpoint,
             ep_type,
             submit_error: None,
@@ -89,6 +103,14 @@ impl TransferData {
         }
     }
 
+    fn check_interface_index(&self, setup: &SetupPacket) {
+        if setup.recipient() == Some(Recipient::Interface)
+            && setup.index as u8 != self.interface.interface_number
+        {
+            warn!("WinUSB sends interface number instead of passed `index` when performing a control transfer with `Recipient::Interface`");
+        }
+    }
+
     /// SAFETY: transfer must be completed
     unsafe fn get_status(&mut self) -> (usize, Result<(), TransferError>) {
         if let Some(err) = self.submit_error {
@@ -128,7 +150,7 @@ impl TransferData {
 
 impl Drop for TransferData {
     fn drop(&mut self) {
-        if !self.buf.is_null() {
+        if self.owner.is_none() && !self.buf.is_null() {
             unsafe { drop(Vec::from_raw_parts(self.buf, 0, self.capacity)) }
         }
         unsafe { drop(Box::from_raw(self.event)) }
@@ -136,6 +158,14 @@ impl Drop for TransferData {
 }
 
 impl PlatformTransfer for TransferData {
+    fn endpoint(&self) -> u8 {
+        self.endpoint
+    }
+
+    fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+        self.interface.device.observer.get()
+    }
+
     fn cancel(&self) {
         debug!("Cancelling transfer {:?}", self.event);
         unsafe {
@@ -188,10 +218,60 @@ impl PlatformSubmit<Vec<u8>> for TransferData {
     }
 }
 
+impl<B: AsRef<[u8]> + Send + 'static> PlatformSubmit<OutBuffer<B>> for TransferData {
+    unsafe fn submit(&mut self, data: OutBuffer<B>, user_data: *mut c_void) {
+        addr_of_mut!((*self.event).ptr).write(user_data);
+
+        let (owner, ptr, len) = data.into_raw();
+        self.buf = ptr as *mut u8;
+        self.capacity = 0;
+        self.owner = Some(owner);
+
+        debug!(
+            "Submit transfer {:?} on endpoint {:02X} for {} bytes OUT",
+            self.event, self.endpoint, len
+        );
+
+        let r = WinUsb_WritePipe(
+            self.interface.winusb_handle,
+            self.endpoint,
+            self.buf,
+            len.try_into().expect("transfer size should fit in u32"),
+            null_mut(),
+            self.event as *mut OVERLAPPED,
+        );
+        self.post_submit(r, "WinUsb_WritePipe", user_data);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<OutResponse<B>> {
+        let (actual_len, status) = self.get_status();
+        self.buf = null_mut();
+        let owner = self.owner.take().expect("transfer should have a buffer");
+        let data = OutResponse::from_raw(owner, actual_len);
+        Completion { data, status }
+    }
+}
+
+impl<R: TransferRequest> PlatformSubmit<Chunk<R>> for TransferData
+where
+    TransferData: PlatformSubmit<R>,
+{
+    // The OS splits large transfers itself, so transfers aren't split into
+    // chunks on this platform.
+    unsafe fn submit(&mut self, data: Chunk<R>, user_data: *mut c_void) {
+        PlatformSubmit::<R>::submit(self, data.data, user_data);
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<R::Response> {
+        PlatformSubmit::<R>::take_completed(self)
+    }
+}
+
 impl PlatformSubmit<RequestBuffer> for TransferData {
     unsafe fn submit(&mut self, data: RequestBuffer, user_data: *mut c_void) {
         addr_of_mut!((*self.event).ptr).write(user_data);
 
+        self.short_not_ok_len = data.short_not_ok.then_some(data.requested);
         let (buf, request_len) = data.into_vec();
         let mut buf = ManuallyDrop::new(buf);
         self.buf = buf.as_mut_ptr();
@@ -217,6 +297,7 @@ impl PlatformSubmit<RequestBuffer> for TransferData {
 
     unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
         let (actual_len, status) = self.get_status();
+        let status = check_short_packet(status, actual_len, self.short_not_ok_len.take());
         let data = self.take_buf(actual_len);
         Completion { data, status }
     }
@@ -224,18 +305,29 @@ impl PlatformSubmit<RequestBuffer> for TransferData {
 
 impl PlatformSubmit<ControlIn> for TransferData {
     unsafe fn submit(&mut self, data: ControlIn, user_data: *mut c_void) {
-        assert_eq!(self.endpoint, 0);
-        assert_eq!(self.ep_type, TransferType::Control);
+        PlatformSubmit::<ControlInBuffer>::submit(self, ControlInBuffer::new(data), user_data)
+    }
 
-        if data.recipient == Recipient::Interface
-            && data.index as u8 != self.interface.interface_number
-        {
-            warn!("WinUSB sends interface number instead of passed `index` when performing a control transfer with `Recipient::Interface`");
+    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+        let c = PlatformSubmit::<ControlInBuffer>::take_completed(self);
+        Completion {
+            data: c.data.into_vec(),
+            status: c.status,
         }
+    }
+}
+
+impl PlatformSubmit<ControlInBuffer> for TransferData {
+    unsafe fn submit(&mut self, request: ControlInBuffer, user_data: *mut c_void) {
+        let ControlInBuffer { setup: data, buf } = request;
+        assert_eq!(self.endpoint, 0);
+        assert_eq!(self.ep_type, TransferType::Control);
+        self.check_interface_index(&data);
 
         addr_of_mut!((*self.event).ptr).write(user_data);
 
-        let mut buf = ManuallyDrop::new(Vec::with_capacity(data.length as usize));
+        let mut buf = ManuallyDrop::new(buf);
+        buf.reserve_exact(data.length as usize);
         self.buf = buf.as_mut_ptr();
         self.capacity = buf.capacity();
 
@@ -245,7 +337,7 @@ impl PlatformSubmit<ControlIn> for TransferData {
         );
 
         let pkt = WINUSB_SETUP_PACKET {
-            RequestType: data.request_type(),
+            RequestType: data.request_type,
             Request: data.request,
             Value: data.value,
             Index: data.index,
@@ -264,33 +356,43 @@ impl PlatformSubmit<ControlIn> for TransferData {
         self.post_submit(r, "WinUsb_ControlTransfer", user_data);
     }
 
-    unsafe fn take_completed(&mut self) -> Completion<Vec<u8>> {
+    unsafe fn take_completed(&mut self) -> Completion<ControlInResponse> {
         let (actual_len, status) = self.get_status();
-        let data = self.take_buf(actual_len);
+        let data = ControlInResponse::new(self.take_buf(actual_len), 0);
         Completion { data, status }
     }
 }
 
 impl PlatformSubmit<ControlOut<'_>> for TransferData {
     unsafe fn submit(&mut self, data: ControlOut, user_data: *mut c_void) {
+        let data = ControlOutSetup {
+            setup: data.setup(),
+            data: data.data,
+        };
+        PlatformSubmit::<ControlOutSetup>::submit(self, data, user_data)
+    }
+
+    unsafe fn take_completed(&mut self) -> Completion<ResponseBuffer> {
+        PlatformSubmit::<ControlOutSetup>::take_completed(self)
+    }
+}
+
+impl PlatformSubmit<ControlOutSetup<'_>> for TransferData {
+    unsafe fn submit(&mut self, request: ControlOutSetup, user_data: *mut c_void) {
+        let ControlOutSetup {
+            setup: data,
+            data: buf,
+        } = request;
         assert_eq!(self.endpoint, 0);
         assert_eq!(self.ep_type, TransferType::Control);
-
-        if data.recipient == Recipient::Interface
-            && data.index as u8 != self.interface.interface_number
-        {
-            warn!("WinUSB sends interface number instead of passed `index` when performing a control transfer with `Recipient::Interface`");
-        }
+        self.check_interface_index(&data);
 
         addr_of_mut!((*self.event).ptr).write(user_data);
 
-        let mut buf = ManuallyDrop::new(data.data.to_vec());
+        let mut buf = ManuallyDrop::new(buf.to_vec());
         self.buf = buf.as_mut_ptr();
         self.capacity = buf.capacity();
-        let len: u16 = buf
-            .len()
-            .try_into()
-            .expect("transfer size should fit in u16");
+        let len = data.length;
 
         debug!(
             "Submit transfer {:?} on endpoint {:02X} for {} bytes ControlOUT",
@@ -298,11 +400,11 @@ impl PlatformSubmit<ControlOut<'_>> for TransferData {
         );
 
         let pkt = WINUSB_SETUP_PACKET {
-            RequestType: data.request_type(),
+            RequestType: data.request_type,
             Request: data.request,
             Value: data.value,
             Index: data.index,
-            Length: len as u16,
+            Length: len,
         };
 
         let r = WinUsb_ControlTransfer(
@@ -324,24 +426,25 @@ impl PlatformSubmit<ControlOut<'_>> for TransferData {
     }
 }
 
-pub(super) fn handle_event(completion: *mut OVERLAPPED) {
+/// Mark the transfer owning `completion` as completed, returning the waker of
+/// the task waiting on it. The caller is responsible for waking it.
+pub(super) fn handle_event(completion: *mut OVERLAPPED) -> Option<Waker> {
     let completion = completion as *mut EventNotify;
     debug!("Handling completion for transfer {completion:?}");
     unsafe {
         let p = addr_of_mut!((*completion).ptr).read();
-        notify_completion::<TransferData>(p)
+        complete_deferred::<TransferData>(p)
     }
 }
 
 pub(crate) fn map_error(err: WIN32_ERROR) -> TransferError {
     match err {
         ERROR_GEN_FAILURE => TransferError::Stall,
-        ERROR_REQUEST_ABORTED | ERROR_TIMEOUT | ERROR_SEM_TIMEOUT | ERROR_OPERATION_ABORTED => {
-            TransferError::Cancelled
-        }
+        ERROR_REQUEST_ABORTED | ERROR_OPERATION_ABORTED => TransferError::Cancelled,
+        ERROR_TIMEOUT | ERROR_SEM_TIMEOUT => TransferError::TimedOut,
         ERROR_FILE_NOT_FOUND | ERROR_DEVICE_NOT_CONNECTED | ERROR_NO_SUCH_DEVICE => {
             TransferError::Disconnected
         }
-        _ => TransferError::Unknown,
+        _ => TransferError::Unknown(err),
     }
 }
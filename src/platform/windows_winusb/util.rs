@@ -52,7 +52,7 @@ impl From<&OsStr> for WCString {
 
 impl Borrow<WCStr> for WCString {
     fn borrow(&self) -> &WCStr {
-        &self
+        self
     }
 }
 
@@ -153,7 +153,7 @@ impl Display for WCStr {
 pub struct NulSepList(pub Vec<u16>);
 
 impl NulSepList {
-    pub fn iter(&self) -> NulSepListIter {
+    pub fn iter(&self) -> NulSepListIter<'_> {
         NulSepListIter(&self.0)
     }
 }
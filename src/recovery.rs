@@ -0,0 +1,137 @@
+use std::{fmt::Debug, time::Duration};
+
+use crate::{Error, Interface};
+
+/// A step of the escalation ladder run by
+/// [`Interface::recover`][crate::Interface::recover].
+///
+/// Steps are ordered from least to most disruptive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecoveryStep {
+    /// Clear the halt condition and reset the data toggle of the policy's
+    /// endpoints with [`Interface::clear_halt`][crate::Interface::clear_halt].
+    ///
+    /// Skipped if the policy has no endpoints.
+    ClearHalt,
+
+    /// Re-select the current alternate setting of the interface, which resets
+    /// the state of all of its endpoints on the device.
+    SetAltSetting,
+
+    /// Reset the device with
+    /// [`Device::reset_and_reopen`][crate::Device::reset_and_reopen], and
+    /// claim the interface again on the reopened device.
+    ///
+    /// The new interface is returned in [`Recovered::interface`]. The old
+    /// `Device` and its `Interface`s can no longer be used and should be
+    /// dropped.
+    ResetDevice,
+}
+
+/// Result of a successful [`Interface::recover`][crate::Interface::recover].
+pub struct Recovered {
+    /// The step that recovered the device.
+    pub step: RecoveryStep,
+
+    /// The interface claimed again on the reopened device, if the device was
+    /// recovered by [`RecoveryStep::ResetDevice`].
+    pub interface: Option<Interface>,
+}
+
+type StepHook = Box<dyn FnMut(RecoveryStep, &Result<(), Error>) -> bool + Send>;
+
+/// Configuration for [`Interface::recover`][crate::Interface::recover].
+///
+/// ### Example
+///
+/// ```no_run
+/// use nusb::{MaybeFuture, RecoveryPolicy, RecoveryStep};
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// # let device = di.open().wait().unwrap();
+/// # let interface = device.claim_interface(0).wait().unwrap();
+/// # fn probe() -> bool { true }
+/// let policy = RecoveryPolicy::new([0x01, 0x81])
+///     .max_step(RecoveryStep::SetAltSetting)
+///     .on_step(|step, result| {
+///         println!("{step:?}: {result:?}");
+///         result.is_ok() && probe() // your function
+///     });
+///
+/// match interface.recover(policy).wait() {
+///     Ok(recovered) => println!("recovered by {:?}", recovered.step),
+///     Err(e) => println!("failed to recover: {e}"),
+/// }
+/// ```
+pub struct RecoveryPolicy {
+    pub(crate) endpoints: Vec<u8>,
+    pub(crate) max_step: RecoveryStep,
+    pub(crate) reopen_timeout: Duration,
+    pub(crate) on_step: Option<StepHook>,
+}
+
+impl RecoveryPolicy {
+    /// Create a policy that recovers the specified endpoints of the interface,
+    /// escalating up to [`RecoveryStep::ResetDevice`].
+    ///
+    /// With no endpoints, [`RecoveryStep::ClearHalt`] is skipped.
+    pub fn new(endpoints: impl IntoIterator<Item = u8>) -> RecoveryPolicy {
+        RecoveryPolicy {
+            endpoints: endpoints.into_iter().collect(),
+            max_step: RecoveryStep::ResetDevice,
+            reopen_timeout: Duration::from_secs(5),
+            on_step: None,
+        }
+    }
+
+    /// Stop escalating after `step`.
+    pub fn max_step(mut self, step: RecoveryStep) -> RecoveryPolicy {
+        self.max_step = step;
+        self
+    }
+
+    /// Set how long [`RecoveryStep::ResetDevice`] waits for the device to
+    /// reappear after the reset. The default is 5 seconds.
+    pub fn reopen_timeout(mut self, timeout: Duration) -> RecoveryPolicy {
+        self.reopen_timeout = timeout;
+        self
+    }
+
+    /// Set a function to call after each step with the result of the step.
+    ///
+    /// It returns `true` if the device has recovered, which ends the ladder.
+    /// Use it to log progress, or to check that the device responds again,
+    /// for example by performing a transfer.
+    ///
+    /// By default, the ladder ends at the first step that succeeds.
+    pub fn on_step<F>(mut self, f: F) -> RecoveryPolicy
+    where
+        F: FnMut(RecoveryStep, &Result<(), Error>) -> bool + Send + 'static,
+    {
+        self.on_step = Some(Box::new(f));
+        self
+    }
+
+    /// Report the result of a step, and return whether the device recovered.
+    pub(crate) fn step_done(&mut self, step: RecoveryStep, result: &Result<(), Error>) -> bool {
+        match &mut self.on_step {
+            Some(f) => f(step, result),
+            None => result.is_ok(),
+        }
+    }
+}
+
+impl Debug for RecoveryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecoveryPolicy")
+            .field("endpoints", &self.endpoints)
+            .field("max_step", &self.max_step)
+            .field("reopen_timeout", &self.reopen_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[test]
+fn assert_send() {
+    fn require_send<T: Send>() {}
+    require_send::<RecoveryPolicy>();
+}
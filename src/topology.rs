@@ -0,0 +1,164 @@
+use crate::{BusInfo, DeviceInfo};
+
+/// A USB bus and the tree of devices connected to it.
+///
+/// Returned by [`topology`][crate::topology].
+#[derive(Debug)]
+pub struct BusTree {
+    bus: BusInfo,
+    children: Vec<DeviceNode>,
+}
+
+impl BusTree {
+    /// Information about the bus.
+    pub fn bus(&self) -> &BusInfo {
+        &self.bus
+    }
+
+    /// The ports of the root hub that have a device connected, ordered by
+    /// port number.
+    pub fn children(&self) -> &[DeviceNode] {
+        &self.children
+    }
+
+    /// Find the node at the specified [port chain][DeviceInfo::port_chain].
+    ///
+    /// The hub a device is connected to is the node at its port chain
+    /// without the last element. Devices connected directly to the root hub
+    /// have a single element port chain.
+    pub fn node(&self, port_chain: &[u8]) -> Option<&DeviceNode> {
+        let (first, rest) = port_chain.split_first()?;
+        let mut node = self.children.iter().find(|n| n.port() == *first)?;
+        for port in rest {
+            node = node.children.iter().find(|n| n.port() == *port)?;
+        }
+        Some(node)
+    }
+}
+
+/// A port in the tree returned by [`topology`][crate::topology], and the
+/// device connected to it.
+#[derive(Debug, Clone)]
+pub struct DeviceNode {
+    port_chain: Vec<u8>,
+    device: Option<DeviceInfo>,
+    children: Vec<DeviceNode>,
+}
+
+impl DeviceNode {
+    /// The port number on the parent hub.
+    pub fn port(&self) -> u8 {
+        *self.port_chain.last().expect("port chain is not empty")
+    }
+
+    /// The path of port numbers from the root hub to this port.
+    pub fn port_chain(&self) -> &[u8] {
+        &self.port_chain
+    }
+
+    /// The device connected to the port.
+    ///
+    /// This is `None` for a hub that was not listed by
+    /// [`list_devices`][crate::list_devices], but has listed devices
+    /// connected to it.
+    pub fn device(&self) -> Option<&DeviceInfo> {
+        self.device.as_ref()
+    }
+
+    /// The ports of this hub that have a device connected, ordered by port
+    /// number. Empty if the device is not a hub.
+    pub fn children(&self) -> &[DeviceNode] {
+        &self.children
+    }
+}
+
+/// Get the node at `port_chain` among `children`, the nodes at `depth`,
+/// creating it and any missing hubs on the way.
+fn node_mut<'a>(
+    children: &'a mut Vec<DeviceNode>,
+    port_chain: &[u8],
+    depth: usize,
+) -> &'a mut DeviceNode {
+    let chain = &port_chain[..=depth];
+    let i = match children.iter().position(|n| n.port_chain == chain) {
+        Some(i) => i,
+        None => {
+            children.push(DeviceNode {
+                port_chain: chain.to_vec(),
+                device: None,
+                children: Vec::new(),
+            });
+            children.len() - 1
+        }
+    };
+
+    let node = &mut children[i];
+    if depth + 1 == port_chain.len() {
+        node
+    } else {
+        node_mut(&mut node.children, port_chain, depth + 1)
+    }
+}
+
+fn sort(children: &mut [DeviceNode]) {
+    children.sort_unstable_by_key(|n| n.port());
+    for n in children {
+        sort(&mut n.children);
+    }
+}
+
+/// Build the tree of each bus from the listed devices.
+///
+/// Devices without a port chain, or on a bus that is not listed, can't be
+/// placed in the tree and are left out.
+pub(crate) fn build(
+    buses: impl Iterator<Item = BusInfo>,
+    devices: impl Iterator<Item = DeviceInfo>,
+) -> Vec<BusTree> {
+    let mut trees: Vec<BusTree> = buses
+        .map(|bus| BusTree {
+            bus,
+            children: Vec::new(),
+        })
+        .collect();
+
+    for device in devices {
+        if device.port_chain().is_empty() {
+            continue;
+        }
+
+        if let Some(tree) = trees.iter_mut().find(|t| t.bus.bus_id() == device.bus_id()) {
+            let port_chain = device.port_chain().to_vec();
+            node_mut(&mut tree.children, &port_chain, 0).device = Some(device);
+        }
+    }
+
+    for tree in &mut trees {
+        sort(&mut tree.children);
+    }
+    trees
+}
+
+#[test]
+fn test_node_mut() {
+    let mut children = Vec::new();
+    node_mut(&mut children, &[2, 4, 1], 0);
+    node_mut(&mut children, &[2, 3], 0);
+    node_mut(&mut children, &[1], 0);
+    node_mut(&mut children, &[2, 4], 0);
+    sort(&mut children);
+
+    assert_eq!(
+        children.iter().map(|n| n.port()).collect::<Vec<_>>(),
+        [1, 2]
+    );
+    let hub = &children[1];
+    assert_eq!(
+        hub.children
+            .iter()
+            .map(|n| n.port_chain())
+            .collect::<Vec<_>>(),
+        [&[2, 3][..], &[2, 4]]
+    );
+    assert_eq!(hub.children[1].children[0].port_chain(), [2, 4, 1]);
+}
@@ -0,0 +1,100 @@
+use std::{
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::platform;
+
+use super::{Completion, PlatformSubmit, TransferError, TransferHandle, TransferRequest};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Block the current thread calling `poll` until it returns `Ready`, or until
+/// `deadline` passes.
+///
+/// Returns `None` on timeout. Since `poll` doesn't consume anything, the
+/// operation can be resumed by calling this again.
+pub(crate) fn poll_blocking<T>(
+    mut poll: impl FnMut(&mut Context) -> Poll<T>,
+    deadline: Option<Instant>,
+) -> Option<T> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(v) = poll(&mut cx) {
+            return Some(v);
+        }
+
+        match deadline {
+            None => thread::park(),
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+        }
+    }
+}
+
+/// Block waiting for a submitted transfer to complete. If it doesn't
+/// complete within `timeout`, cancel it and wait for the cancellation, which
+/// is reported as `TransferError::TimedOut`.
+pub(crate) fn wait_transfer<D>(
+    mut transfer: TransferHandle<platform::TransferData>,
+    timeout: Duration,
+) -> Completion<D::Response>
+where
+    D: TransferRequest,
+    platform::TransferData: PlatformSubmit<D>,
+{
+    let deadline = Instant::now().checked_add(timeout);
+    if let Some(c) = poll_blocking(|cx| transfer.poll_completion::<D>(cx), deadline) {
+        return c;
+    }
+
+    transfer.cancel();
+    let mut c = poll_blocking(|cx| transfer.poll_completion::<D>(cx), None)
+        .expect("wait without deadline can't time out");
+    if c.status == Err(TransferError::Cancelled) {
+        c.status = Err(TransferError::TimedOut);
+    }
+    c
+}
+
+#[test]
+fn poll_blocking_timeout() {
+    use std::time::Duration;
+
+    let deadline = Instant::now() + Duration::from_millis(10);
+    assert_eq!(poll_blocking(|_| Poll::<()>::Pending, Some(deadline)), None);
+    assert!(Instant::now() >= deadline);
+
+    let mut polls = 0;
+    let r = poll_blocking(
+        |cx| {
+            polls += 1;
+            if polls < 3 {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(polls)
+            }
+        },
+        None,
+    );
+    assert_eq!(r, Some(3));
+}
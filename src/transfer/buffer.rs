@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::fmt::Debug;
 use std::mem::ManuallyDrop;
 
@@ -16,6 +17,7 @@ pub struct RequestBuffer {
     pub(crate) buf: *mut u8,
     pub(crate) capacity: usize,
     pub(crate) requested: usize,
+    pub(crate) short_not_ok: bool,
 }
 
 impl RequestBuffer {
@@ -26,9 +28,39 @@ impl RequestBuffer {
             buf: v.as_mut_ptr(),
             capacity: v.capacity(),
             requested: len,
+            short_not_ok: false,
         }
     }
 
+    /// Treat a short packet that ends the transfer before `len` bytes are
+    /// received as an error.
+    ///
+    /// Normally, a packet shorter than the endpoint's maximum packet size
+    /// ends an `IN` transfer successfully with less data than requested. With
+    /// this option set, such a transfer instead completes with
+    /// [`TransferError::ShortPacket`][super::TransferError::ShortPacket], which
+    /// is useful when a protocol splits a fixed-size read across several
+    /// transfers and a short packet in the middle means data was lost. The
+    /// data received before the short packet is still returned in the
+    /// completion.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, this sets `USBDEVFS_URB_SHORT_NOT_OK` on the URB, and the
+    ///   kernel also cancels the continuation URBs of a transfer it had to
+    ///   split.
+    /// * WinUSB and IOKit have no equivalent, so on Windows and macOS the
+    ///   transfer is checked for a short length when it completes.
+    pub fn set_short_not_ok(&mut self, short_not_ok: bool) {
+        self.short_not_ok = short_not_ok;
+    }
+
+    /// Get whether a short packet is treated as an error, as set by
+    /// [`set_short_not_ok`][Self::set_short_not_ok].
+    pub fn short_not_ok(&self) -> bool {
+        self.short_not_ok
+    }
+
     pub(crate) fn into_vec(self) -> (Vec<u8>, usize) {
         let s = ManuallyDrop::new(self);
         let v = unsafe { Vec::from_raw_parts(s.buf, 0, s.capacity) };
@@ -44,10 +76,26 @@ impl RequestBuffer {
             buf: v.as_mut_ptr(),
             capacity: v.capacity(),
             requested: len,
+            short_not_ok: false,
         }
     }
 }
 
+#[cfg(feature = "bytes")]
+impl RequestBuffer {
+    /// Create a `RequestBuffer` by re-using the allocation of a
+    /// [`BytesMut`][bytes::BytesMut].
+    ///
+    /// The allocation is re-used without copying if `buf` is the only handle
+    /// to it, e.g. a `BytesMut` obtained from a previous completion that has
+    /// not been split or frozen into shared `Bytes`.
+    ///
+    /// Requires the `bytes` feature.
+    pub fn reuse_bytes(buf: bytes::BytesMut, len: usize) -> RequestBuffer {
+        RequestBuffer::reuse(buf.into(), len)
+    }
+}
+
 unsafe impl Send for RequestBuffer {}
 unsafe impl Sync for RequestBuffer {}
 
@@ -61,12 +109,21 @@ impl Debug for RequestBuffer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RequestBuffer")
             .field("requested", &self.requested)
+            .field("short_not_ok", &self.short_not_ok)
             .finish_non_exhaustive()
     }
 }
 
 impl TransferRequest for RequestBuffer {
     type Response = Vec<u8>;
+
+    fn request_len(&self) -> usize {
+        self.requested
+    }
+
+    fn response_len(response: &Vec<u8>) -> usize {
+        response.len()
+    }
 }
 
 /// Returned buffer and actual length for a completed OUT transfer.
@@ -125,4 +182,86 @@ impl Drop for ResponseBuffer {
 
 impl TransferRequest for Vec<u8> {
     type Response = ResponseBuffer;
+
+    fn request_len(&self) -> usize {
+        self.len()
+    }
+
+    fn response_len(response: &ResponseBuffer) -> usize {
+        response.actual_length()
+    }
+}
+
+/// Data for an OUT transfer, sent directly from a buffer of any type.
+///
+/// Unlike a `Vec<u8>`, the data doesn't need to be copied into an owned
+/// allocation: the transfer takes ownership of `B` (for example an
+/// `Arc<[u8]>`, a `Bytes`, or a memory-mapped file) and sends the slice
+/// returned by its `AsRef<[u8]>` implementation. The buffer is returned in the
+/// [`Completion`][`crate::transfer::Completion`] as an [`OutResponse`].
+pub struct OutBuffer<B>(pub B);
+
+impl<B: AsRef<[u8]> + Send + 'static> OutBuffer<B> {
+    /// Move the buffer into a stable allocation, and get a pointer and length
+    /// for its data that remain valid as long as the returned owner is alive.
+    pub(crate) fn into_raw(self) -> (Box<dyn Any + Send>, *const u8, usize) {
+        let owner = Box::new(self.0);
+        let data = (*owner).as_ref();
+        let (ptr, len) = (data.as_ptr(), data.len());
+        (owner, ptr, len)
+    }
+}
+
+impl<B: AsRef<[u8]> + Send + 'static> TransferRequest for OutBuffer<B> {
+    type Response = OutResponse<B>;
+
+    fn request_len(&self) -> usize {
+        self.0.as_ref().len()
+    }
+
+    fn response_len(response: &OutResponse<B>) -> usize {
+        response.actual_length()
+    }
+}
+
+impl<B> Debug for OutBuffer<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutBuffer").finish_non_exhaustive()
+    }
+}
+
+/// Returned buffer and actual length for a completed [`OutBuffer`] transfer.
+pub struct OutResponse<B> {
+    buf: B,
+    transferred: usize,
+}
+
+impl<B: 'static> OutResponse<B> {
+    /// Panics if `owner` was not created by `OutBuffer::<B>::into_raw`.
+    pub(crate) fn from_raw(owner: Box<dyn Any + Send>, transferred: usize) -> OutResponse<B> {
+        let buf = *owner
+            .downcast::<B>()
+            .expect("transfer buffer should have the submitted type");
+        OutResponse { buf, transferred }
+    }
+}
+
+impl<B> OutResponse<B> {
+    /// Get the number of bytes successfully transferred.
+    pub fn actual_length(&self) -> usize {
+        self.transferred
+    }
+
+    /// Get back the buffer passed to the transfer.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B> Debug for OutResponse<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutResponse")
+            .field("transferred", &self.transferred)
+            .finish_non_exhaustive()
+    }
 }
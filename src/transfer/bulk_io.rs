@@ -0,0 +1,265 @@
+use std::{
+    io::{self, BufRead, IoSlice, Read, Write},
+    mem,
+    time::{Duration, Instant},
+};
+
+use super::{blocking::poll_blocking, Queue, RequestBuffer};
+
+const DEFAULT_TRANSFER_SIZE: usize = 16 * 1024;
+const DEFAULT_NUM_TRANSFERS: usize = 4;
+
+/// Longest a `BulkWriter` without a timeout waits to flush on drop.
+const DROP_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn deadline(timeout: Option<Duration>) -> Option<Instant> {
+    timeout.map(|t| Instant::now() + t)
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "USB transfer timed out")
+}
+
+/// Blocking [`Read`] wrapper for a bulk IN endpoint.
+///
+/// Use [`Interface::bulk_reader`][crate::Interface::bulk_reader] to obtain a
+/// `BulkReader`.
+///
+/// The reader keeps several transfers pending so that the endpoint is read
+/// continuously, and buffers data received beyond what the caller has read
+/// so far. Data in transfers that are still pending when the reader is
+/// dropped is lost.
+///
+/// If a read times out, the pending transfers are left in place and the
+/// next read continues waiting for them, so no data is lost.
+pub struct BulkReader {
+    queue: Queue<RequestBuffer>,
+    transfer_size: usize,
+    num_transfers: usize,
+    timeout: Option<Duration>,
+
+    /// Data of the last completed transfer, of which `pos..` is unread.
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl BulkReader {
+    pub(crate) fn new(queue: Queue<RequestBuffer>) -> BulkReader {
+        BulkReader {
+            queue,
+            transfer_size: DEFAULT_TRANSFER_SIZE,
+            num_transfers: DEFAULT_NUM_TRANSFERS,
+            timeout: None,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Set the maximum time a read waits for data, or `None` to wait
+    /// indefinitely (the default).
+    ///
+    /// A read that times out returns an error of kind
+    /// [`TimedOut`][io::ErrorKind::TimedOut].
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the timeout set with [`set_timeout`][Self::set_timeout].
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Set the size of each transfer and the number of transfers to keep
+    /// pending. The defaults are 4 transfers of 16 KiB.
+    ///
+    /// The transfer size must be a multiple of the endpoint's maximum packet
+    /// size. Takes effect for transfers submitted after this call.
+    ///
+    /// Panics if either is 0.
+    pub fn set_transfers(&mut self, transfer_size: usize, num_transfers: usize) {
+        assert!(transfer_size > 0, "transfer size must be at least 1");
+        assert!(num_transfers > 0, "number of transfers must be at least 1");
+        self.transfer_size = transfer_size;
+        self.num_transfers = num_transfers;
+    }
+
+    /// Get back the underlying `Queue`, along with any data that has been
+    /// received but not yet read.
+    pub fn into_inner(mut self) -> (Queue<RequestBuffer>, Vec<u8>) {
+        self.buf.drain(..self.pos);
+        (self.queue, self.buf)
+    }
+}
+
+impl BufRead for BulkReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let deadline = deadline(self.timeout);
+        while self.pos >= self.buf.len() {
+            while self.queue.pending() < self.num_transfers {
+                let buf = self.queue.buffer(self.transfer_size);
+                self.queue
+                    .submit(RequestBuffer::reuse(buf, self.transfer_size));
+            }
+
+            let queue = &mut self.queue;
+            let completion =
+                poll_blocking(|cx| queue.poll_next(cx), deadline).ok_or_else(timed_out)?;
+
+            // Resubmit the allocation of the consumed buffer.
+            let old = mem::replace(&mut self.buf, completion.data);
+            self.pos = 0;
+            self.queue
+                .submit(RequestBuffer::reuse(old, self.transfer_size));
+
+            completion.status?;
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+impl Read for BulkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+/// Blocking [`Write`] wrapper for a bulk OUT endpoint.
+///
+/// Use [`Interface::bulk_writer`][crate::Interface::bulk_writer] to obtain a
+/// `BulkWriter`.
+///
+/// Written data is collected into transfers of up to the configured transfer
+/// size, which are submitted when full or when the writer is flushed. Several
+/// transfers may be pending at once; [`flush`][Write::flush] waits for all of
+/// them to complete.
+///
+/// The writer is flushed on drop, ignoring errors, for at most its
+/// [timeout][Self::set_timeout], or 1 second if it has none. Transfers still
+/// pending after that are cancelled, so data may be lost. Call `flush` before
+/// dropping the writer to make sure all data was sent.
+pub struct BulkWriter {
+    queue: Queue<Vec<u8>>,
+    transfer_size: usize,
+    num_transfers: usize,
+    timeout: Option<Duration>,
+
+    /// Data not yet submitted.
+    buf: Vec<u8>,
+}
+
+impl BulkWriter {
+    pub(crate) fn new(queue: Queue<Vec<u8>>) -> BulkWriter {
+        BulkWriter {
+            queue,
+            transfer_size: DEFAULT_TRANSFER_SIZE,
+            num_transfers: DEFAULT_NUM_TRANSFERS,
+            timeout: None,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Set the maximum time a write or flush waits for pending transfers to
+    /// complete, or `None` to wait indefinitely (the default).
+    ///
+    /// An operation that times out returns an error of kind
+    /// [`TimedOut`][io::ErrorKind::TimedOut]. Its transfers remain pending
+    /// and can be waited for by calling `flush` again.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the timeout set with [`set_timeout`][Self::set_timeout].
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Set the maximum size of each transfer and the number of transfers that
+    /// may be pending at once. The defaults are 4 transfers of 16 KiB.
+    ///
+    /// Panics if either is 0.
+    pub fn set_transfers(&mut self, transfer_size: usize, num_transfers: usize) {
+        assert!(transfer_size > 0, "transfer size must be at least 1");
+        assert!(num_transfers > 0, "number of transfers must be at least 1");
+        self.transfer_size = transfer_size;
+        self.num_transfers = num_transfers;
+    }
+
+    /// Wait until at most `max_pending` transfers are pending.
+    fn wait_pending(&mut self, max_pending: usize, deadline: Option<Instant>) -> io::Result<()> {
+        while self.queue.pending() > max_pending {
+            let queue = &mut self.queue;
+            let completion =
+                poll_blocking(|cx| queue.poll_next(cx), deadline).ok_or_else(timed_out)?;
+            completion.status?;
+        }
+        Ok(())
+    }
+
+    /// Submit the buffered data as a transfer, waiting for room if needed.
+    fn submit_buf(&mut self, deadline: Option<Instant>) -> io::Result<()> {
+        self.wait_pending(self.num_transfers - 1, deadline)?;
+        let next = self.queue.buffer(self.transfer_size);
+        self.queue.submit(mem::replace(&mut self.buf, next));
+        Ok(())
+    }
+}
+
+impl Write for BulkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(data)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        if self.buf.len() >= self.transfer_size {
+            self.submit_buf(deadline(self.timeout))?;
+        }
+
+        let mut n = 0;
+        for data in bufs {
+            let len = data.len().min(self.transfer_size - self.buf.len());
+            self.buf.extend_from_slice(&data[..len]);
+            n += len;
+            if len < data.len() {
+                break;
+            }
+        }
+
+        // Start sending a full buffer right away if that doesn't require waiting.
+        if self.buf.len() >= self.transfer_size && self.queue.pending() < self.num_transfers {
+            self.submit_buf(None)?;
+        }
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let deadline = deadline(self.timeout);
+        if !self.buf.is_empty() {
+            self.submit_buf(deadline)?;
+        }
+        self.wait_pending(0, deadline)
+    }
+}
+
+impl Drop for BulkWriter {
+    fn drop(&mut self) {
+        // Don't block forever on a device that stopped accepting data.
+        self.timeout.get_or_insert(DROP_FLUSH_TIMEOUT);
+        let _ = self.flush();
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<BulkReader>();
+    require_send_sync::<BulkWriter>();
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    mem,
+    ops::Range,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use crate::MaybeFuture;
+
+use super::{
+    blocking::poll_blocking, Completion, OutBuffer, Queue, RequestBuffer, ResponseBuffer,
+    TransferError, TransferRequest,
+};
+
+/// Where a chunk falls within a transfer split into several.
+///
+/// On Linux, this selects the `USBDEVFS_URB_BULK_CONTINUATION` and
+/// `USBDEVFS_URB_ZERO_PACKET` flags of the chunk's URB. On FreeBSD, it
+/// selects whether the chunk may end with a zero-length packet. Other
+/// platforms ignore it.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "android", target_os = "freebsd")),
+    allow(dead_code)
+)]
+pub(crate) struct ChunkPosition {
+    #[cfg_attr(target_os = "freebsd", allow(dead_code))]
+    pub(crate) first: bool,
+    pub(crate) last: bool,
+}
+
+/// A chunk of a transfer split by [`ChunkedIn`] or [`ChunkedOut`].
+pub(crate) struct Chunk<R> {
+    pub(crate) data: R,
+    #[cfg_attr(
+        not(any(target_os = "linux", target_os = "android", target_os = "freebsd")),
+        allow(dead_code)
+    )]
+    pub(crate) position: ChunkPosition,
+}
+
+impl<R: TransferRequest> TransferRequest for Chunk<R> {
+    type Response = R::Response;
+
+    fn request_len(&self) -> usize {
+        self.data.request_len()
+    }
+
+    fn response_len(response: &R::Response) -> usize {
+        R::response_len(response)
+    }
+}
+
+/// Split `len` bytes into chunks of at most `chunk_size` bytes.
+///
+/// A transfer is only split on Linux and FreeBSD, where the size of a single
+/// URB or `usb_fs` transfer is limited. WinUSB and IOKit split large
+/// transfers themselves.
+fn chunk_ranges(
+    len: usize,
+    chunk_size: usize,
+) -> impl Iterator<Item = (Range<usize>, ChunkPosition)> {
+    assert!(chunk_size > 0, "chunk size must be non-zero");
+    let chunk_size = if cfg!(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd"
+    )) {
+        chunk_size
+    } else {
+        len.max(1)
+    };
+
+    // An empty transfer is still submitted as one chunk.
+    let count = len.div_ceil(chunk_size).max(1);
+    (0..count).map(move |i| {
+        let start = i * chunk_size;
+        let end = (start + chunk_size).min(len);
+        let position = ChunkPosition {
+            first: i == 0,
+            last: i == count - 1,
+        };
+        (start..end, position)
+    })
+}
+
+/// Returned by [`Interface::bulk_in_chunked`][crate::Interface::bulk_in_chunked].
+pub(crate) struct ChunkedIn {
+    queue: Queue<Chunk<RequestBuffer>>,
+    requested: VecDeque<usize>,
+    data: Vec<u8>,
+    status: Result<(), TransferError>,
+    ended: bool,
+}
+
+impl ChunkedIn {
+    pub(crate) fn new(
+        mut queue: Queue<Chunk<RequestBuffer>>,
+        len: usize,
+        chunk_size: usize,
+    ) -> ChunkedIn {
+        let mut requested = VecDeque::new();
+        for (range, position) in chunk_ranges(len, chunk_size) {
+            let mut data = RequestBuffer::new(range.len());
+            // A short packet ends the whole transfer, so the remaining
+            // chunks must not receive the data that follows it.
+            data.set_short_not_ok(!position.last);
+            requested.push_back(range.len());
+            queue.submit(Chunk { data, position });
+        }
+
+        ChunkedIn {
+            queue,
+            requested,
+            data: Vec::with_capacity(len),
+            status: Ok(()),
+            ended: false,
+        }
+    }
+}
+
+impl Future for ChunkedIn {
+    type Output = Completion<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.queue.pending() > 0 {
+            let c = ready!(this.queue.poll_next(cx));
+            let requested = this.requested.pop_front().unwrap_or(0);
+
+            // Chunks after the end were cancelled, and carry no data.
+            if this.ended {
+                continue;
+            }
+
+            this.data.extend_from_slice(&c.data);
+            match c.status {
+                Ok(()) if c.data.len() == requested => continue,
+                Ok(()) | Err(TransferError::ShortPacket) => {}
+                Err(e) => this.status = Err(e),
+            }
+
+            this.ended = true;
+            this.queue.cancel_all();
+        }
+
+        Poll::Ready(Completion {
+            data: mem::take(&mut this.data),
+            status: mem::replace(&mut this.status, Ok(())),
+        })
+    }
+}
+
+impl MaybeFuture for ChunkedIn {
+    fn wait(mut self) -> Self::Output {
+        poll_blocking(|cx| Pin::new(&mut self).poll(cx), None)
+            .expect("wait without deadline can't time out")
+    }
+}
+
+/// A range of a buffer shared by the chunks of a [`ChunkedOut`].
+pub(crate) struct SharedSlice {
+    buf: Arc<Vec<u8>>,
+    range: Range<usize>,
+}
+
+impl AsRef<[u8]> for SharedSlice {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[self.range.clone()]
+    }
+}
+
+/// Returned by [`Interface::bulk_out_chunked`][crate::Interface::bulk_out_chunked].
+pub(crate) struct ChunkedOut {
+    queue: Queue<Chunk<OutBuffer<SharedSlice>>>,
+    buf: Arc<Vec<u8>>,
+    actual_length: usize,
+    status: Result<(), TransferError>,
+    ended: bool,
+}
+
+impl ChunkedOut {
+    pub(crate) fn new(
+        mut queue: Queue<Chunk<OutBuffer<SharedSlice>>>,
+        buf: Vec<u8>,
+        chunk_size: usize,
+    ) -> ChunkedOut {
+        let buf = Arc::new(buf);
+        for (range, position) in chunk_ranges(buf.len(), chunk_size) {
+            let data = OutBuffer(SharedSlice {
+                buf: buf.clone(),
+                range,
+            });
+            queue.submit(Chunk { data, position });
+        }
+
+        ChunkedOut {
+            queue,
+            buf,
+            actual_length: 0,
+            status: Ok(()),
+            ended: false,
+        }
+    }
+}
+
+impl Future for ChunkedOut {
+    type Output = Completion<ResponseBuffer>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.queue.pending() > 0 {
+            let c = ready!(this.queue.poll_next(cx));
+            if this.ended {
+                continue;
+            }
+
+            this.actual_length += c.data.actual_length();
+            if let Err(e) = c.status {
+                this.status = Err(e);
+                this.ended = true;
+                this.queue.cancel_all();
+            }
+        }
+
+        // Every chunk has been returned, so this is the only reference left.
+        let buf = Arc::try_unwrap(mem::take(&mut this.buf)).unwrap_or_else(|buf| Vec::clone(&buf));
+
+        Poll::Ready(Completion {
+            data: ResponseBuffer::from_vec(buf, this.actual_length),
+            status: mem::replace(&mut this.status, Ok(())),
+        })
+    }
+}
+
+impl MaybeFuture for ChunkedOut {
+    fn wait(mut self) -> Self::Output {
+        poll_blocking(|cx| Pin::new(&mut self).poll(cx), None)
+            .expect("wait without deadline can't time out")
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn chunk_ranges_cover_transfer() {
+    let chunks: Vec<_> = chunk_ranges(10, 4)
+        .map(|(r, p)| (r, p.first, p.last))
+        .collect();
+    assert_eq!(
+        chunks,
+        [
+            (0..4, true, false),
+            (4..8, false, false),
+            (8..10, false, true)
+        ]
+    );
+
+    let chunks: Vec<_> = chunk_ranges(0, 4)
+        .map(|(r, p)| (r, p.first, p.last))
+        .collect();
+    assert_eq!(chunks, [(0..0, true, true)]);
+}
@@ -0,0 +1,170 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use bytes::BytesMut;
+use futures_core::Stream;
+use futures_sink::Sink;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{QueueSink, QueueStream};
+
+/// A [`Stream`] and [`Sink`] of messages framed by a
+/// [`tokio_util::codec`] [`Decoder`] and [`Encoder`] over a pair of bulk
+/// endpoints.
+///
+/// Data received from the [`QueueStream`] is appended to a read buffer, which
+/// is passed to the codec's [`Decoder`] to produce the items of the stream.
+/// Messages may span several transfers, and a transfer may contain several
+/// messages. When the stream ends, the remaining data is passed to
+/// [`Decoder::decode_eof`].
+///
+/// Each item sent into the sink is encoded by the codec's [`Encoder`] and
+/// submitted to the [`QueueSink`] as one transfer. Combine this with
+/// [`Interface::set_send_zlp`][crate::Interface::set_send_zlp] for protocols
+/// that delimit messages with short packets.
+///
+/// Transfer errors are converted to the codec's error type through
+/// [`io::Error`]. After an error or the end of the data, the stream ends.
+///
+/// This type is only available with the `tokio-util` feature.
+///
+/// ### Example
+///
+/// ```no_run
+/// use futures_lite::{future::block_on, StreamExt};
+/// use nusb::transfer::FramedQueue;
+/// use tokio_util::codec::LinesCodec;
+/// # use nusb::MaybeFuture;
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// # let device = di.open().wait().unwrap();
+/// # let interface = device.claim_interface(0).wait().unwrap();
+/// let reader = interface.bulk_in_queue(0x81).into_stream(512, 4);
+/// let writer = interface.bulk_out_queue(0x01).into_sink(4);
+/// let mut lines = FramedQueue::new(reader, writer, LinesCodec::new());
+///
+/// while let Some(line) = block_on(lines.next()) {
+///     println!("{}", line.unwrap());
+/// }
+/// ```
+pub struct FramedQueue<C> {
+    reader: QueueStream,
+    writer: QueueSink,
+    codec: C,
+    read_buf: BytesMut,
+    eof: bool,
+    done: bool,
+}
+
+impl<C> FramedQueue<C> {
+    /// Frame the data of `reader` and `writer` with `codec`.
+    pub fn new(reader: QueueStream, writer: QueueSink, codec: C) -> FramedQueue<C> {
+        FramedQueue {
+            reader,
+            writer,
+            codec,
+            read_buf: BytesMut::new(),
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// Get a reference to the codec.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Get a mutable reference to the codec.
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Get the received data that has not yet been decoded.
+    pub fn read_buffer(&self) -> &BytesMut {
+        &self.read_buf
+    }
+
+    /// Get back the underlying stream, sink, and codec.
+    ///
+    /// Received data that has not yet been decoded is discarded; check
+    /// [`read_buffer`][Self::read_buffer] first if it is needed.
+    pub fn into_parts(self) -> (QueueStream, QueueSink, C) {
+        (self.reader, self.writer, self.codec)
+    }
+}
+
+impl<C: Decoder + Unpin> Stream for FramedQueue<C> {
+    type Item = Result<C::Item, C::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            if this.eof {
+                this.done = true;
+                return Poll::Ready(this.codec.decode_eof(&mut this.read_buf).transpose());
+            }
+
+            match this.codec.decode(&mut this.read_buf) {
+                Ok(Some(item)) => return Poll::Ready(Some(Ok(item))),
+                Ok(None) => {}
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+
+            match ready!(Pin::new(&mut this.reader).poll_next(cx)) {
+                Some(Ok(data)) => this.read_buf.extend_from_slice(&data),
+                Some(Err(e)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(io::Error::from(e).into())));
+                }
+                None => this.eof = true,
+            }
+        }
+    }
+}
+
+impl<C: Encoder<I> + Unpin, I> Sink<I> for FramedQueue<C> {
+    type Error = C::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().writer)
+            .poll_ready(cx)
+            .map_err(|e| io::Error::from(e).into())
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: I) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let mut buf = BytesMut::new();
+        this.codec.encode(item, &mut buf)?;
+        Pin::new(&mut this.writer)
+            .start_send(Vec::from(buf))
+            .map_err(|e| io::Error::from(e).into())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().writer)
+            .poll_flush(cx)
+            .map_err(|e| io::Error::from(e).into())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().writer)
+            .poll_close(cx)
+            .map_err(|e| io::Error::from(e).into())
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<FramedQueue<tokio_util::codec::BytesCodec>>();
+}
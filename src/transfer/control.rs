@@ -1,25 +1,6 @@
-use super::{ResponseBuffer, TransferRequest};
+use std::{fmt::Debug, ops::Deref};
 
-/// Transfer direction
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[repr(u8)]
-pub enum Direction {
-    /// Host to device
-    Out = 0,
-
-    /// Device to host
-    In = 0x80,
-}
-
-impl Direction {
-    pub(crate) fn from_address(addr: u8) -> Direction {
-        match addr & Self::MASK {
-            0 => Self::Out,
-            _ => Self::In,
-        }
-    }
-    pub(crate) const MASK: u8 = 0x80;
-}
+use super::{Direction, ResponseBuffer, TransferRequest};
 
 /// Specification defining the request.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -52,6 +33,129 @@ pub enum Recipient {
     Other = 3,
 }
 
+/// A raw SETUP packet for a request on a control endpoint.
+///
+/// Unlike [`ControlIn`] and [`ControlOut`], the `bmRequestType` field is
+/// specified as a raw byte, so it can hold any combination of direction, type
+/// and recipient bits, including the reserved values that some devices
+/// require. Submit it with [`ControlInBuffer::from_setup`] or
+/// [`ControlOutSetup::new`].
+///
+/// ### Example
+///
+/// ```
+/// use nusb::transfer::{ControlType, Direction, Recipient, SetupPacket};
+///
+/// // A vendor request with the reserved type bits set.
+/// let setup = SetupPacket::new(0xE0, 0x01).with_value(0x1234);
+/// assert_eq!(setup.direction(), Direction::In);
+/// assert_eq!(setup.to_bytes(), [0xE0, 0x01, 0x34, 0x12, 0, 0, 0, 0]);
+///
+/// let setup = SetupPacket::from_parts(Direction::Out, ControlType::Class, Recipient::Interface, 0x0A);
+/// assert_eq!(setup.request_type, 0x21);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SetupPacket {
+    /// `bmRequestType` field. The top bit selects the direction of the data
+    /// stage.
+    #[doc(alias = "bmRequestType")]
+    pub request_type: u8,
+
+    /// `bRequest` field.
+    #[doc(alias = "bRequest")]
+    pub request: u8,
+
+    /// `wValue` field.
+    #[doc(alias = "wValue")]
+    pub value: u16,
+
+    /// `wIndex` field.
+    #[doc(alias = "wIndex")]
+    pub index: u16,
+
+    /// `wLength` field: the number of bytes in the data stage.
+    #[doc(alias = "wLength")]
+    pub length: u16,
+}
+
+impl SetupPacket {
+    /// Create a SETUP packet with a raw `bmRequestType`, and the other fields
+    /// set to zero.
+    pub fn new(request_type: u8, request: u8) -> SetupPacket {
+        SetupPacket {
+            request_type,
+            request,
+            value: 0,
+            index: 0,
+            length: 0,
+        }
+    }
+
+    /// Create a SETUP packet with a `bmRequestType` built from its parts, and
+    /// the other fields set to zero.
+    pub fn from_parts(
+        direction: Direction,
+        control_type: ControlType,
+        recipient: Recipient,
+        request: u8,
+    ) -> SetupPacket {
+        SetupPacket::new(request_type(direction, control_type, recipient), request)
+    }
+
+    /// Set the `wValue` field.
+    pub fn with_value(mut self, value: u16) -> SetupPacket {
+        self.value = value;
+        self
+    }
+
+    /// Set the `wIndex` field.
+    pub fn with_index(mut self, index: u16) -> SetupPacket {
+        self.index = index;
+        self
+    }
+
+    /// Set the `wLength` field.
+    pub fn with_length(mut self, length: u16) -> SetupPacket {
+        self.length = length;
+        self
+    }
+
+    /// Get the direction of the data stage from the top bit of
+    /// `bmRequestType`.
+    pub fn direction(&self) -> Direction {
+        Direction::from_address(self.request_type)
+    }
+
+    /// Get the recipient bits of `bmRequestType`, or `None` if they are a
+    /// reserved value.
+    pub fn recipient(&self) -> Option<Recipient> {
+        match self.request_type & 0x1f {
+            0 => Some(Recipient::Device),
+            1 => Some(Recipient::Interface),
+            2 => Some(Recipient::Endpoint),
+            3 => Some(Recipient::Other),
+            _ => None,
+        }
+    }
+
+    /// Serialize the packet as sent on the bus.
+    pub fn to_bytes(&self) -> [u8; SETUP_PACKET_SIZE] {
+        let [value_lo, value_hi] = self.value.to_le_bytes();
+        let [index_lo, index_hi] = self.index.to_le_bytes();
+        let [length_lo, length_hi] = self.length.to_le_bytes();
+        [
+            self.request_type,
+            self.request,
+            value_lo,
+            value_hi,
+            index_lo,
+            index_hi,
+            length_lo,
+            length_hi,
+        ]
+    }
+}
+
 /// SETUP packet without direction or buffers
 pub struct Control {
     /// Request type used for the `bmRequestType` field sent in the SETUP packet.
@@ -78,6 +182,7 @@ pub struct Control {
 }
 
 impl Control {
+    #[cfg_attr(target_os = "freebsd", allow(dead_code))]
     pub(crate) fn request_type(&self, direction: Direction) -> u8 {
         request_type(direction, self.control_type, self.recipient)
     }
@@ -113,27 +218,34 @@ pub struct ControlOut<'a> {
 }
 
 impl<'a> ControlOut<'a> {
-    #[allow(unused)]
-    pub(crate) fn setup_packet(&self) -> Result<[u8; SETUP_PACKET_SIZE], ()> {
-        Ok(pack_setup(
+    pub(crate) fn setup(&self) -> SetupPacket {
+        SetupPacket::from_parts(
             Direction::Out,
             self.control_type,
             self.recipient,
             self.request,
-            self.value,
-            self.index,
-            self.data.len().try_into().map_err(|_| ())?,
-        ))
-    }
-
-    #[allow(unused)]
-    pub(crate) fn request_type(&self) -> u8 {
-        request_type(Direction::Out, self.control_type, self.recipient)
+        )
+        .with_value(self.value)
+        .with_index(self.index)
+        .with_length(
+            self.data
+                .len()
+                .try_into()
+                .expect("data length should fit in setup packet's u16"),
+        )
     }
 }
 
 impl TransferRequest for ControlOut<'_> {
     type Response = ResponseBuffer;
+
+    fn request_len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn response_len(response: &ResponseBuffer) -> usize {
+        response.actual_length()
+    }
 }
 
 /// SETUP packet to make an **IN** request on a control endpoint.
@@ -166,52 +278,177 @@ pub struct ControlIn {
 }
 
 impl ControlIn {
-    #[allow(unused)]
-    pub(crate) fn setup_packet(&self) -> [u8; SETUP_PACKET_SIZE] {
-        pack_setup(
+    pub(crate) fn setup(&self) -> SetupPacket {
+        SetupPacket::from_parts(
             Direction::In,
             self.control_type,
             self.recipient,
             self.request,
-            self.value,
-            self.index,
-            self.length,
         )
+        .with_value(self.value)
+        .with_index(self.index)
+        .with_length(self.length)
+    }
+}
+
+/// A control **IN** request that receives its data into a reusable buffer.
+///
+/// Submit with [`Interface::control_in_buffer`][crate::Interface::control_in_buffer].
+/// Unlike [`ControlIn`], which allocates a new `Vec` for each transfer, the
+/// buffer is returned in the [`Completion`][super::Completion] as a
+/// [`ControlInResponse`], and its allocation can be passed to
+/// [`ControlInBuffer::reuse`] for the next request.
+pub struct ControlInBuffer {
+    pub(crate) setup: SetupPacket,
+    pub(crate) buf: Vec<u8>,
+}
+
+impl ControlInBuffer {
+    /// Create a request with a new buffer.
+    pub fn new(control: ControlIn) -> ControlInBuffer {
+        ControlInBuffer::reuse(control, Vec::new())
+    }
+
+    /// Create a request that receives its data into the allocation of `buf`.
+    ///
+    /// The contents of `buf` are discarded, and it is grown if needed.
+    pub fn reuse(control: ControlIn, buf: Vec<u8>) -> ControlInBuffer {
+        ControlInBuffer::from_setup(control.setup(), buf)
     }
 
-    #[allow(unused)]
-    pub(crate) fn request_type(&self) -> u8 {
-        request_type(Direction::In, self.control_type, self.recipient)
+    /// Create a request from a raw SETUP packet, receiving `setup.length`
+    /// bytes into the allocation of `buf`.
+    ///
+    /// Panics if the direction bit of `setup.request_type` is not set.
+    pub fn from_setup(setup: SetupPacket, mut buf: Vec<u8>) -> ControlInBuffer {
+        assert_eq!(
+            setup.direction(),
+            Direction::In,
+            "control IN request must have the direction bit set"
+        );
+        buf.clear();
+        ControlInBuffer { setup, buf }
     }
 }
 
-pub(crate) const SETUP_PACKET_SIZE: usize = 8;
+impl TransferRequest for ControlInBuffer {
+    type Response = ControlInResponse;
 
-fn pack_setup(
-    direction: Direction,
-    control_type: ControlType,
-    recipient: Recipient,
-    request: u8,
-    value: u16,
-    index: u16,
-    length: u16,
-) -> [u8; SETUP_PACKET_SIZE] {
-    let bmrequesttype = request_type(direction, control_type, recipient);
-
-    [
-        bmrequesttype,
-        request,
-        (value & 0xFF) as u8,
-        (value >> 8) as u8,
-        (index & 0xFF) as u8,
-        (index >> 8) as u8,
-        (length & 0xFF) as u8,
-        (length >> 8) as u8,
-    ]
+    fn request_len(&self) -> usize {
+        self.setup.length.into()
+    }
+
+    fn response_len(response: &ControlInResponse) -> usize {
+        response.len()
+    }
+}
+
+/// A control **OUT** request made from a raw [`SetupPacket`].
+///
+/// Submit with [`Interface::control_out_setup`][crate::Interface::control_out_setup].
+pub struct ControlOutSetup<'a> {
+    pub(crate) setup: SetupPacket,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> ControlOutSetup<'a> {
+    /// Create a request sending `data` in the data stage. The `wLength` field
+    /// of `setup` is replaced by the length of `data`.
+    ///
+    /// Panics if the direction bit of `setup.request_type` is set, or if
+    /// `data` is longer than 65535 bytes.
+    pub fn new(setup: SetupPacket, data: &'a [u8]) -> ControlOutSetup<'a> {
+        assert_eq!(
+            setup.direction(),
+            Direction::Out,
+            "control OUT request must have the direction bit clear"
+        );
+        let length = data
+            .len()
+            .try_into()
+            .expect("data length should fit in setup packet's u16");
+        ControlOutSetup {
+            setup: setup.with_length(length),
+            data,
+        }
+    }
+}
+
+impl TransferRequest for ControlOutSetup<'_> {
+    type Response = ResponseBuffer;
+
+    fn request_len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn response_len(response: &ResponseBuffer) -> usize {
+        response.actual_length()
+    }
+}
+
+/// Data received by a control transfer submitted with a [`ControlInBuffer`].
+///
+/// Dereferences to the received data.
+pub struct ControlInResponse {
+    buf: Vec<u8>,
+
+    /// Offset of the data in `buf`, which starts with the SETUP packet on
+    /// platforms that need it in the same buffer.
+    offset: usize,
 }
 
+impl ControlInResponse {
+    pub(crate) fn new(buf: Vec<u8>, offset: usize) -> ControlInResponse {
+        debug_assert!(offset <= buf.len());
+        ControlInResponse { buf, offset }
+    }
+
+    /// Extract the buffer as an empty `Vec` to re-use in another transfer.
+    pub fn reuse(self) -> Vec<u8> {
+        let mut buf = self.buf;
+        buf.clear();
+        buf
+    }
+
+    /// Get the received data as a `Vec`.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On Linux, the buffer also holds the SETUP packet, so this moves the
+    ///   data to the start of the buffer.
+    pub fn into_vec(self) -> Vec<u8> {
+        let mut buf = self.buf;
+        buf.drain(..self.offset);
+        buf
+    }
+}
+
+impl Deref for ControlInResponse {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.offset..]
+    }
+}
+
+impl Debug for ControlInResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ControlInResponse").field(&&**self).finish()
+    }
+}
+
+pub(crate) const SETUP_PACKET_SIZE: usize = 8;
+
 impl TransferRequest for ControlIn {
     type Response = Vec<u8>;
+
+    fn request_len(&self) -> usize {
+        self.length.into()
+    }
+
+    fn response_len(response: &Vec<u8>) -> usize {
+        response.len()
+    }
 }
 
 pub(crate) fn request_type(
@@ -221,3 +458,38 @@ pub(crate) fn request_type(
 ) -> u8 {
     (direction as u8) | ((control_type as u8) << 5) | (recipient as u8)
 }
+
+#[test]
+fn control_in_response_skips_setup() {
+    let buf = vec![0xC0, 0x30, 0, 0, 0, 0, 2, 0, 0xAA, 0xBB];
+    let capacity = buf.capacity();
+
+    let response = ControlInResponse::new(buf.clone(), SETUP_PACKET_SIZE);
+    assert_eq!(&*response, [0xAA, 0xBB]);
+    assert_eq!(response.into_vec(), [0xAA, 0xBB]);
+
+    let reused = ControlInResponse::new(buf, SETUP_PACKET_SIZE).reuse();
+    assert!(reused.is_empty());
+    assert_eq!(reused.capacity(), capacity);
+}
+
+#[test]
+fn setup_packet_from_requests() {
+    let control = ControlIn {
+        control_type: ControlType::Vendor,
+        recipient: Recipient::Interface,
+        request: 0x30,
+        value: 0x0102,
+        index: 3,
+        length: 64,
+    };
+    assert_eq!(
+        control.setup().to_bytes(),
+        [0xC1, 0x30, 0x02, 0x01, 3, 0, 64, 0]
+    );
+
+    let setup = SetupPacket::new(0x7F, 0x01).with_length(100);
+    assert_eq!(setup.recipient(), None);
+    let out = ControlOutSetup::new(setup, &[1, 2, 3]);
+    assert_eq!(out.setup.length, 3);
+}
@@ -0,0 +1,168 @@
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use crate::{platform, Error, MaybeFuture};
+
+use super::{Direction, Queue, RequestBuffer, TransferFuture, TransferType};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Type-level transfer type of an [`Endpoint`]: [`Bulk`] or [`Interrupt`].
+pub trait EndpointType: private::Sealed + Send + Sync + 'static {
+    /// The transfer type of the endpoint.
+    const TRANSFER_TYPE: TransferType;
+}
+
+/// Type-level direction of an [`Endpoint`]: [`In`] or [`Out`].
+pub trait EndpointDirection: private::Sealed + Send + Sync + 'static {
+    /// The direction of the endpoint.
+    const DIRECTION: Direction;
+}
+
+/// Marker for a **bulk** [`Endpoint`].
+#[derive(Debug)]
+pub enum Bulk {}
+
+impl private::Sealed for Bulk {}
+impl EndpointType for Bulk {
+    const TRANSFER_TYPE: TransferType = TransferType::Bulk;
+}
+
+/// Marker for an **interrupt** [`Endpoint`].
+#[derive(Debug)]
+pub enum Interrupt {}
+
+impl private::Sealed for Interrupt {}
+impl EndpointType for Interrupt {
+    const TRANSFER_TYPE: TransferType = TransferType::Interrupt;
+}
+
+/// Marker for an **IN (device-to-host)** [`Endpoint`].
+#[derive(Debug)]
+pub enum In {}
+
+impl private::Sealed for In {}
+impl EndpointDirection for In {
+    const DIRECTION: Direction = Direction::In;
+}
+
+/// Marker for an **OUT (host-to-device)** [`Endpoint`].
+#[derive(Debug)]
+pub enum Out {}
+
+impl private::Sealed for Out {}
+impl EndpointDirection for Out {
+    const DIRECTION: Direction = Direction::Out;
+}
+
+/// An endpoint of a claimed interface, with its transfer type and direction
+/// checked against the interface descriptor.
+///
+/// Obtain an `Endpoint` with [`Interface::endpoint`][crate::Interface::endpoint].
+/// Since the type and direction are part of the type, its methods can't be
+/// used with the wrong kind of buffer for the endpoint.
+///
+/// Like [`Queue`], an `Endpoint` keeps the interface claimed while it exists.
+/// If the alternate setting is changed, endpoints obtained for the previous
+/// alternate setting should no longer be used.
+///
+/// ### Example
+///
+/// ```no_run
+/// use futures_lite::future::block_on;
+/// use nusb::transfer::{Bulk, In, RequestBuffer};
+/// # use nusb::MaybeFuture;
+/// # fn main() -> Result<(), std::io::Error> {
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// # let device = di.open().wait().unwrap();
+/// # let interface = device.claim_interface(0).wait().unwrap();
+///
+/// let ep = interface.endpoint::<Bulk, In>(0x81)?;
+/// let data = block_on(ep.submit(RequestBuffer::new(ep.max_packet_size()))).into_result()?;
+/// # Ok(()) }
+/// ```
+pub struct Endpoint<T: EndpointType, D: EndpointDirection> {
+    interface: Arc<platform::Interface>,
+    address: u8,
+    max_packet_size: usize,
+    _marker: PhantomData<(T, D)>,
+}
+
+impl<T: EndpointType, D: EndpointDirection> Endpoint<T, D> {
+    pub(crate) fn new(
+        interface: Arc<platform::Interface>,
+        address: u8,
+        max_packet_size: usize,
+    ) -> Endpoint<T, D> {
+        Endpoint {
+            interface,
+            address,
+            max_packet_size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the endpoint address.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Get the maximum packet size of the endpoint, from its descriptor.
+    pub fn max_packet_size(&self) -> usize {
+        self.max_packet_size
+    }
+
+    /// Clear the endpoint's halt / stall condition.
+    ///
+    /// See [`Interface::clear_halt`][crate::Interface::clear_halt].
+    pub fn clear_halt(&self) -> impl MaybeFuture<Output = Result<(), Error>> {
+        self.interface.clone().clear_halt(self.address)
+    }
+}
+
+impl<T: EndpointType> Endpoint<T, In> {
+    /// Submit a single transfer receiving up to `buf.len()` bytes.
+    pub fn submit(&self, buf: RequestBuffer) -> TransferFuture<RequestBuffer> {
+        let mut t = self.interface.make_transfer(self.address, T::TRANSFER_TYPE);
+        t.submit(buf);
+        TransferFuture::new(t)
+    }
+
+    /// Create a queue for managing multiple transfers on the endpoint.
+    pub fn queue(&self) -> Queue<RequestBuffer> {
+        Queue::new(self.interface.clone(), self.address, T::TRANSFER_TYPE)
+    }
+}
+
+impl<T: EndpointType> Endpoint<T, Out> {
+    /// Submit a single transfer sending the contents of `buf`.
+    pub fn submit(&self, buf: Vec<u8>) -> TransferFuture<Vec<u8>> {
+        let mut t = self.interface.make_transfer(self.address, T::TRANSFER_TYPE);
+        t.submit(buf);
+        TransferFuture::new(t)
+    }
+
+    /// Create a queue for managing multiple transfers on the endpoint.
+    pub fn queue(&self) -> Queue<Vec<u8>> {
+        Queue::new(self.interface.clone(), self.address, T::TRANSFER_TYPE)
+    }
+}
+
+impl<T: EndpointType, D: EndpointDirection> Debug for Endpoint<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("address", &format_args!("0x{:02X}", self.address))
+            .field("transfer_type", &T::TRANSFER_TYPE)
+            .field("direction", &D::DIRECTION)
+            .field("max_packet_size", &self.max_packet_size)
+            .finish()
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<Endpoint<Bulk, In>>();
+    require_send_sync::<Endpoint<Interrupt, Out>>();
+}
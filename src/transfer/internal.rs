@@ -6,21 +6,38 @@ use std::{
         atomic::{AtomicU8, Ordering},
         Arc,
     },
-    task::{Context, Poll},
+    task::{ready, Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
 use atomic_waker::AtomicWaker;
 
-use super::Completion;
+use crate::event_thread;
+
+use super::{CompleteEvent, Completion, SubmitEvent, TransferObserver, TransferTiming};
 
 pub trait PlatformTransfer: Send {
     /// Request cancellation of a transfer that may or may not currently be
     /// pending.
     fn cancel(&self);
+
+    /// Endpoint address the transfer is submitted to.
+    fn endpoint(&self) -> u8;
+
+    /// Observer set on the device, if any.
+    fn observer(&self) -> Option<Arc<dyn TransferObserver>>;
 }
 
 pub trait TransferRequest {
     type Response;
+
+    /// Number of bytes requested or to be sent, reported to a
+    /// `TransferObserver` on submit.
+    fn request_len(&self) -> usize;
+
+    /// Number of bytes transferred, reported to a `TransferObserver` on
+    /// completion.
+    fn response_len(response: &Self::Response) -> usize;
 }
 
 pub trait PlatformSubmit<D: TransferRequest>: PlatformTransfer {
@@ -48,6 +65,18 @@ struct TransferInner<P: PlatformTransfer> {
 
     /// Waker that is notified when transfer completes.
     waker: Arc<AtomicWaker>,
+
+    /// When the transfer was submitted and completed.
+    ///
+    /// Written on submit, and by the event thread before it marks the
+    /// transfer completed, so the same rules as `platform_data` apply.
+    timestamps: UnsafeCell<Timestamps>,
+}
+
+#[derive(Default)]
+struct Timestamps {
+    submitted: Option<Instant>,
+    completed: Option<Instant>,
 }
 
 /// Handle to a transfer.
@@ -56,6 +85,10 @@ struct TransferInner<P: PlatformTransfer> {
 /// when dropped.
 pub(crate) struct TransferHandle<P: PlatformTransfer> {
     ptr: NonNull<TransferInner<P>>,
+
+    /// Observer to notify of the completion of the pending transfer, and its
+    /// endpoint.
+    observer: Option<(Arc<dyn TransferObserver>, u8)>,
 }
 
 unsafe impl<P: PlatformTransfer> Send for TransferHandle<P> {}
@@ -85,10 +118,12 @@ impl<P: PlatformTransfer> TransferHandle<P> {
             platform_data: UnsafeCell::new(inner),
             state: AtomicU8::new(STATE_IDLE),
             waker: Arc::new(AtomicWaker::new()),
+            timestamps: UnsafeCell::new(Timestamps::default()),
         });
 
         TransferHandle {
             ptr: Box::leak(b).into(),
+            observer: None,
         }
     }
 
@@ -109,30 +144,66 @@ impl<P: PlatformTransfer> TransferHandle<P> {
         D: TransferRequest,
         P: PlatformSubmit<D>,
     {
-        let inner = self.inner();
-
         // It's the syscall that submits the transfer that actually performs the
         // release ordering.
         let prev = self.inner().state.swap(STATE_PENDING, Ordering::Relaxed);
         assert_eq!(prev, STATE_IDLE, "Transfer should be idle when submitted");
 
+        self.observer = self.platform_data().observer().map(|observer| {
+            let endpoint = self.platform_data().endpoint();
+            observer.on_submit(&SubmitEvent {
+                endpoint,
+                length: data.request_len(),
+            });
+            (observer, endpoint)
+        });
+
+        let inner = self.inner();
+
         // SAFETY: while `TransferHandle` is alive, the only mutable access to `platform_data`
         // is via this `TransferHandle`. Verified that it is idle.
         unsafe {
+            *inner.timestamps.get() = Timestamps {
+                submitted: Some(Instant::now()),
+                completed: None,
+            };
             let p = &mut *inner.platform_data.get();
             p.submit(data, self.ptr.as_ptr() as *mut c_void);
         }
     }
 
+    /// Get the timing of the transfer, once its completion has been taken.
+    pub(crate) fn timing(&self) -> Option<TransferTiming> {
+        // SAFETY: the event thread only writes the timestamps while the
+        // transfer is pending, and the caller has already observed its
+        // completion.
+        let timestamps = unsafe { &*self.inner().timestamps.get() };
+        let submitted = timestamps.submitted?;
+        let completed = timestamps.completed?;
+        Some(TransferTiming {
+            latency: completed.saturating_duration_since(submitted),
+            delivery: completed.elapsed(),
+        })
+    }
+
     pub(crate) fn cancel(&mut self) {
         self.platform_data().cancel();
     }
 
+    /// Check whether the transfer has been submitted and its completion has
+    /// not yet been handled by the event thread.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.inner().state.load(Ordering::Acquire) == STATE_PENDING
+    }
+
     fn poll_completion_generic(&mut self, cx: &Context) -> Poll<&mut P> {
         let inner = self.inner();
         inner.waker.register(cx.waker());
         match inner.state.load(Ordering::Acquire) {
-            STATE_PENDING => Poll::Pending,
+            STATE_PENDING => {
+                event_thread::report_stranded(self.platform_data().endpoint());
+                Poll::Pending
+            }
             STATE_COMPLETED => {
                 // Relaxed because this doesn't synchronize with anything,
                 // just marks that we no longer need to drop the buffer
@@ -146,17 +217,39 @@ impl<P: PlatformTransfer> TransferHandle<P> {
         }
     }
 
-    pub fn poll_completion<D: TransferRequest>(
-        &mut self,
-        cx: &Context,
-    ) -> Poll<Completion<D::Response>>
+    /// Register for wakeup and check whether the transfer has completed,
+    /// without taking its completion.
+    pub(crate) fn poll_done(&self, cx: &Context) -> Poll<()> {
+        let inner = self.inner();
+        inner.waker.register(cx.waker());
+        match inner.state.load(Ordering::Acquire) {
+            STATE_PENDING => {
+                event_thread::report_stranded(self.platform_data().endpoint());
+                Poll::Pending
+            }
+            STATE_COMPLETED => Poll::Ready(()),
+            s => panic!("Polling transfer in unexpected state {s}"),
+        }
+    }
+
+    pub fn poll_completion<D>(&mut self, cx: &Context) -> Poll<Completion<D::Response>>
     where
         D: TransferRequest,
         P: PlatformSubmit<D>,
     {
         // SAFETY: `poll_completion_generic` checks that it is completed
-        self.poll_completion_generic(cx)
-            .map(|u| unsafe { u.take_completed() })
+        let c = ready!(self.poll_completion_generic(cx));
+        let c = unsafe { c.take_completed() };
+
+        if let Some((observer, endpoint)) = self.observer.take() {
+            observer.on_complete(&CompleteEvent {
+                endpoint,
+                length: D::response_len(&c.data),
+                duration: self.timing().map_or(Duration::ZERO, |t| t.latency),
+                status: c.status,
+            });
+        }
+        Poll::Ready(c)
     }
 }
 
@@ -181,15 +274,279 @@ impl<P: PlatformTransfer> Drop for TransferHandle<P> {
 /// SAFETY: `transfer` must be a pointer previously passed to `submit`, and
 /// the caller / kernel must no longer dereference it or its buffer.
 pub(crate) unsafe fn notify_completion<P: PlatformTransfer>(transfer: *mut c_void) {
+    if let Some(waker) = unsafe { complete_deferred::<P>(transfer) } {
+        waker.wake();
+    }
+}
+
+/// Mark a transfer as completed like [`notify_completion`], but return the
+/// waker to be notified rather than waking it, so that an event loop handling
+/// a batch of completions can wake each task only once.
+///
+/// SAFETY: same as `notify_completion`.
+pub(crate) unsafe fn complete_deferred<P: PlatformTransfer>(
+    transfer: *mut c_void,
+) -> Option<Waker> {
     unsafe {
         let transfer = transfer as *mut TransferInner<P>;
+        (*(*transfer).timestamps.get()).completed = Some(Instant::now());
         let waker = (*transfer).waker.clone();
         match (*transfer).state.swap(STATE_COMPLETED, Ordering::Release) {
-            STATE_PENDING => waker.wake(),
+            STATE_PENDING => waker.take(),
             STATE_ABANDONED => {
                 drop(Box::from_raw(transfer));
+                None
             }
             s => panic!("Completing transfer in unexpected state {s}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::c_void,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+    };
+
+    use super::{notify_completion, PlatformSubmit, PlatformTransfer, TransferHandle};
+    use crate::transfer::{
+        blocking::poll_blocking, CompleteEvent, Completion, SubmitEvent, TransferError,
+        TransferObserver,
+    };
+
+    /// Transfer that is completed by another thread right after submission,
+    /// standing in for the event thread.
+    struct MockTransfer {
+        value: u32,
+        dropped: Arc<AtomicUsize>,
+    }
+
+    /// Transfer that is only completed when the test reaps it, and records
+    /// cancellation requests.
+    struct ManualTransfer {
+        cancelled: Arc<AtomicUsize>,
+        dropped: Arc<AtomicUsize>,
+        observer: Option<Arc<dyn TransferObserver>>,
+    }
+
+    impl Drop for ManualTransfer {
+        fn drop(&mut self) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    impl PlatformTransfer for ManualTransfer {
+        fn cancel(&self) {
+            self.cancelled.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn endpoint(&self) -> u8 {
+            0x81
+        }
+
+        fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+            self.observer.clone()
+        }
+    }
+
+    impl PlatformSubmit<MockRequest> for ManualTransfer {
+        unsafe fn submit(&mut self, _data: MockRequest, _transfer: *mut c_void) {}
+
+        unsafe fn take_completed(&mut self) -> Completion<u32> {
+            Completion {
+                data: 0,
+                status: Err(TransferError::Cancelled),
+            }
+        }
+    }
+
+    impl Drop for MockTransfer {
+        fn drop(&mut self) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    impl PlatformTransfer for MockTransfer {
+        fn cancel(&self) {}
+
+        fn endpoint(&self) -> u8 {
+            0x81
+        }
+
+        fn observer(&self) -> Option<Arc<dyn TransferObserver>> {
+            None
+        }
+    }
+
+    struct MockRequest(u32);
+
+    impl super::TransferRequest for MockRequest {
+        type Response = u32;
+
+        fn request_len(&self) -> usize {
+            4
+        }
+
+        fn response_len(response: &u32) -> usize {
+            *response as usize
+        }
+    }
+
+    impl PlatformSubmit<MockRequest> for MockTransfer {
+        unsafe fn submit(&mut self, data: MockRequest, transfer: *mut c_void) {
+            self.value = data.0;
+            let transfer = transfer as usize;
+            thread::spawn(move || unsafe { notify_completion::<MockTransfer>(transfer as _) });
+        }
+
+        unsafe fn take_completed(&mut self) -> Completion<u32> {
+            Completion {
+                data: self.value,
+                status: Ok(()),
+            }
+        }
+    }
+
+    /// Race the completion against the owner polling for it, or dropping the
+    /// handle, from several threads, and check that every transfer is freed
+    /// exactly once.
+    #[test]
+    fn completion_races_poll_and_drop() {
+        const THREADS: usize = 8;
+        const ITERATIONS: u32 = 200;
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let dropped = dropped.clone();
+                thread::spawn(move || {
+                    for i in 0..ITERATIONS {
+                        let mut handle = TransferHandle::new(MockTransfer {
+                            value: 0,
+                            dropped: dropped.clone(),
+                        });
+                        handle.submit(MockRequest(i));
+
+                        if (t + i as usize) % 2 == 0 {
+                            // Abandoned while the completion may be in flight.
+                            drop(handle);
+                            continue;
+                        }
+
+                        // Resubmit the same transfer to cover reuse.
+                        for expected in [i, i + 1] {
+                            let c =
+                                poll_blocking(|cx| handle.poll_completion::<MockRequest>(cx), None)
+                                    .unwrap();
+                            assert_eq!(c.data, expected);
+                            if expected == i {
+                                handle.submit(MockRequest(i + 1));
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        // Abandoned transfers are freed by the completing thread, which may
+        // still be running.
+        let total = THREADS * ITERATIONS as usize;
+        for _ in 0..1000 {
+            if dropped.load(Ordering::Relaxed) == total {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(dropped.load(Ordering::Relaxed), total);
+    }
+
+    /// Dropping a pending transfer must cancel it, but leave its memory alive
+    /// until the completion is reaped.
+    #[test]
+    fn drop_pending_frees_after_reap() {
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let mut handle = TransferHandle::new(ManualTransfer {
+            cancelled: cancelled.clone(),
+            dropped: dropped.clone(),
+            observer: None,
+        });
+        handle.submit(MockRequest(0));
+        let ptr = handle.ptr.as_ptr() as usize;
+
+        drop(handle);
+        assert_eq!(cancelled.load(Ordering::Relaxed), 1);
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+
+        thread::spawn(move || unsafe { notify_completion::<ManualTransfer>(ptr as _) })
+            .join()
+            .unwrap();
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    /// A transfer that completed but was never polled is freed on drop without
+    /// being cancelled.
+    #[test]
+    fn drop_completed_frees_immediately() {
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let mut handle = TransferHandle::new(ManualTransfer {
+            cancelled: cancelled.clone(),
+            dropped: dropped.clone(),
+            observer: None,
+        });
+        handle.submit(MockRequest(0));
+        unsafe { notify_completion::<ManualTransfer>(handle.ptr.as_ptr() as _) };
+        assert!(!handle.is_pending());
+
+        drop(handle);
+        assert_eq!(cancelled.load(Ordering::Relaxed), 0);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn observer_events() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl TransferObserver for Recorder {
+            fn on_submit(&self, event: &SubmitEvent) {
+                let mut events = self.0.lock().unwrap();
+                events.push(format!("submit {:02x} {}", event.endpoint, event.length));
+            }
+
+            fn on_complete(&self, event: &CompleteEvent) {
+                let mut events = self.0.lock().unwrap();
+                events.push(format!(
+                    "complete {:02x} {} {:?}",
+                    event.endpoint, event.length, event.status
+                ));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut handle = TransferHandle::new(ManualTransfer {
+            cancelled: Default::default(),
+            dropped: Default::default(),
+            observer: Some(recorder.clone()),
+        });
+        handle.submit(MockRequest(0));
+        unsafe { notify_completion::<ManualTransfer>(handle.ptr.as_ptr() as _) };
+        let c = poll_blocking(|cx| handle.poll_completion::<MockRequest>(cx), None).unwrap();
+        assert_eq!(c.status, Err(TransferError::Cancelled));
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["submit 81 4", "complete 81 0 Err(Cancelled)"]
+        );
+    }
+}
@@ -1,15 +1,16 @@
 use std::fmt::Debug;
 use std::mem::ManuallyDrop;
 
-use super::TransferRequest;
+use super::{TransferError, TransferRequest};
 
 /// A buffer for requesting an IN transfer.
 ///
 /// A `RequestIsochronousBuffer` is passed when submitting an `IN` transfer to define the
 /// requested length and provide a buffer to receive data into. The buffer is
-/// returned in the [`Completion`][`crate::transfer::Completion`] as a `Vec<Vec<u8>>`
-/// with the data read from the endpoint. The `Vec`'s allocation can turned back
-/// into a `RequestIsochronousBuffer` to re-use it for another transfer.
+/// returned in the [`Completion`][`crate::transfer::Completion`] as an
+/// [`IsochronousPackets`] with the data read from the endpoint. Its allocation
+/// can turned back into a `RequestIsochronousBuffer` to re-use it for another
+/// transfer.
 ///
 /// You can think of a `RequestIsochronousBuffer` as a `Vec` of `Vec` with uninitialized contents.
 pub struct RequestIsochronousBuffer {
@@ -31,6 +32,7 @@ impl RequestIsochronousBuffer {
         }
     }
 
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(unused))]
     pub(crate) fn into_vec(self) -> (Vec<u8>, usize) {
         let s = ManuallyDrop::new(self);
         let v = unsafe { Vec::from_raw_parts(s.buf, 0, s.capacity) };
@@ -69,5 +71,86 @@ impl Debug for RequestIsochronousBuffer {
 }
 
 impl TransferRequest for RequestIsochronousBuffer {
-    type Response = Vec<Vec<u8>>;
+    type Response = IsochronousPackets;
+
+    fn request_len(&self) -> usize {
+        self.requested * self.number_of_packets
+    }
+
+    fn response_len(response: &IsochronousPackets) -> usize {
+        response.packets.iter().map(|p| p.actual_length).sum()
+    }
+}
+
+/// Data received by a completed isochronous IN transfer.
+///
+/// The packets are received into a single buffer, with the data for packet
+/// `i` starting at offset `i * len`, where `len` is the per-packet length
+/// passed to [`RequestIsochronousBuffer::new`]. A packet may contain fewer
+/// bytes than requested, or none if it failed; [`packets`][Self::packets]
+/// describes where each packet's data is, without copying it.
+pub struct IsochronousPackets {
+    pub(crate) buf: Vec<u8>,
+    pub(crate) packets: Vec<IsochronousPacket>,
+}
+
+/// Location and status of one packet of an [`IsochronousPackets`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IsochronousPacket {
+    /// Offset of the packet's data in [`IsochronousPackets::buffer`].
+    pub offset: usize,
+
+    /// Number of bytes received for the packet.
+    pub actual_length: usize,
+
+    /// Status of the packet.
+    pub status: Result<(), TransferError>,
+}
+
+impl IsochronousPackets {
+    /// Get the whole buffer the packets were received into.
+    ///
+    /// Bytes not covered by a packet's `actual_length` are zero.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Get the location and status of each packet.
+    pub fn packets(&self) -> &[IsochronousPacket] {
+        &self.packets
+    }
+
+    /// Get the data received for a packet.
+    pub fn data(&self, packet: &IsochronousPacket) -> &[u8] {
+        &self.buf[packet.offset..packet.offset + packet.actual_length]
+    }
+
+    /// Iterate over the data and status of each packet.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], Result<(), TransferError>)> {
+        self.packets.iter().map(|p| (self.data(p), p.status))
+    }
+
+    /// Copy the data of each successful packet into its own `Vec`.
+    pub fn to_vecs(&self) -> Vec<Vec<u8>> {
+        self.iter()
+            .filter(|(_, status)| status.is_ok())
+            .map(|(data, _)| data.to_vec())
+            .collect()
+    }
+
+    /// Extract the buffer as an empty `Vec` to re-use in another transfer with
+    /// [`RequestIsochronousBuffer::reuse`].
+    pub fn reuse(self) -> Vec<u8> {
+        let mut buf = self.buf;
+        buf.clear();
+        buf
+    }
+}
+
+impl Debug for IsochronousPackets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IsochronousPackets")
+            .field("packets", &self.packets)
+            .finish_non_exhaustive()
+    }
 }
@@ -16,41 +16,80 @@ use crate::platform;
 mod queue;
 pub use queue::Queue;
 
+mod stream;
+pub use stream::QueueStream;
+
+mod sink;
+pub use sink::QueueSink;
+
+#[cfg(feature = "tokio-util")]
+mod codec;
+#[cfg(feature = "tokio-util")]
+pub use codec::FramedQueue;
+
+mod blocking;
+pub(crate) use blocking::{poll_blocking, wait_transfer};
+
+mod bulk_io;
+pub use bulk_io::{BulkReader, BulkWriter};
+
+mod chunked;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub(crate) use chunked::ChunkPosition;
+pub(crate) use chunked::{Chunk, ChunkedIn, ChunkedOut};
+
 mod buffer;
-pub use buffer::{RequestBuffer, ResponseBuffer};
+pub use buffer::{OutBuffer, OutResponse, RequestBuffer, ResponseBuffer};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::platform::MmapBuffer;
+
+mod pool;
+pub use pool::BufferPool;
+
+mod observer;
+pub(crate) use observer::ObserverSlot;
+pub use observer::{CompleteEvent, SubmitEvent, TransferObserver};
+
+mod timing;
+pub use timing::{LatencyStats, TransferTiming};
 
 mod isochronous_buffer;
-pub use isochronous_buffer::RequestIsochronousBuffer;
+pub use isochronous_buffer::{IsochronousPacket, IsochronousPackets, RequestIsochronousBuffer};
 
 mod control;
 #[allow(unused)]
 pub(crate) use control::SETUP_PACKET_SIZE;
-pub use control::{Control, ControlIn, ControlOut, ControlType, Direction, Recipient};
-
-mod internal;
-pub(crate) use internal::{
-    notify_completion, PlatformSubmit, PlatformTransfer, TransferHandle, TransferRequest,
+pub use control::{
+    Control, ControlIn, ControlInBuffer, ControlInResponse, ControlOut, ControlOutSetup,
+    ControlType, Recipient, SetupPacket,
 };
 
-/// Endpoint type.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-#[allow(dead_code)]
-pub enum TransferType {
-    /// Control endpoint.
-    Control = 0,
+pub use crate::descriptors::{Direction, TransferType};
 
-    /// Isochronous endpoint.
-    Isochronous = 1,
+mod endpoint;
+pub use endpoint::{Bulk, Endpoint, EndpointDirection, EndpointType, In, Interrupt, Out};
 
-    /// Bulk endpoint.
-    Bulk = 2,
+mod standard;
+#[cfg_attr(target_os = "windows", allow(unused_imports))]
+pub(crate) use standard::get_configuration;
+pub(crate) use standard::{
+    get_device_status, get_endpoint_status, set_interface_control, synch_frame,
+};
+pub use standard::{DeviceStatus, EndpointStatus, Feature};
 
-    /// Interrupt endpoint.
-    Interrupt = 3,
-}
+mod internal;
+#[cfg(target_os = "windows")]
+pub(crate) use internal::complete_deferred;
+pub(crate) use internal::{
+    notify_completion, PlatformSubmit, PlatformTransfer, TransferHandle, TransferRequest,
+};
 
 /// Transfer error.
+///
+/// More variants may be added as platforms report more specific errors.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TransferError {
     /// Transfer was cancelled.
     Cancelled,
@@ -67,11 +106,37 @@ pub enum TransferError {
     /// Device disconnected.
     Disconnected,
 
-    /// Hardware issue or protocol violation.
+    /// The device sent more data than expected ("babble"), or more than fit
+    /// in the buffer.
+    Overflow,
+
+    /// A packet was received with a CRC or bit-stuffing error.
+    ///
+    /// This usually indicates signal integrity problems, such as a bad cable.
+    CrcOrBitstuff,
+
+    /// The device did not respond to a packet within the bus turnaround time.
+    NoResponse,
+
+    /// The transfer did not complete within its timeout.
+    ///
+    /// Returned by the blocking transfer methods when their `timeout`
+    /// expires, and when an OS-level transfer timeout expires.
+    TimedOut,
+
+    /// Other hardware issue or protocol violation.
     Fault,
 
+    /// An `IN` transfer ended with a short packet before receiving the
+    /// requested length, and was submitted with
+    /// [`RequestBuffer::set_short_not_ok`].
+    ShortPacket,
+
     /// Unknown or OS-specific error.
-    Unknown,
+    ///
+    /// Contains the raw OS error code: the `errno` value on Linux and FreeBSD,
+    /// the Win32 error code on Windows, and the `IOReturn` value on macOS.
+    Unknown(u32),
 }
 
 impl Display for TransferError {
@@ -80,8 +145,13 @@ impl Display for TransferError {
             TransferError::Cancelled => write!(f, "transfer was cancelled"),
             TransferError::Stall => write!(f, "endpoint STALL condition"),
             TransferError::Disconnected => write!(f, "device disconnected"),
+            TransferError::Overflow => write!(f, "data overflow (babble)"),
+            TransferError::CrcOrBitstuff => write!(f, "CRC or bit-stuffing error"),
+            TransferError::NoResponse => write!(f, "no response from device"),
+            TransferError::TimedOut => write!(f, "transfer timed out"),
             TransferError::Fault => write!(f, "hardware fault or protocol violation"),
-            TransferError::Unknown => write!(f, "unknown error"),
+            TransferError::ShortPacket => write!(f, "transfer ended with a short packet"),
+            TransferError::Unknown(code) => write!(f, "unknown error (OS error code {code:#x})"),
         }
     }
 }
@@ -94,12 +164,33 @@ impl From<TransferError> for io::Error {
             TransferError::Cancelled => io::Error::new(io::ErrorKind::Interrupted, value),
             TransferError::Stall => io::Error::new(io::ErrorKind::ConnectionReset, value),
             TransferError::Disconnected => io::Error::new(io::ErrorKind::ConnectionAborted, value),
-            TransferError::Fault => io::Error::new(io::ErrorKind::Other, value),
-            TransferError::Unknown => io::Error::new(io::ErrorKind::Other, value),
+            TransferError::Overflow => io::Error::other(value),
+            TransferError::CrcOrBitstuff => io::Error::new(io::ErrorKind::InvalidData, value),
+            TransferError::NoResponse => io::Error::new(io::ErrorKind::TimedOut, value),
+            TransferError::TimedOut => io::Error::new(io::ErrorKind::TimedOut, value),
+            TransferError::Fault => io::Error::other(value),
+            TransferError::ShortPacket => io::Error::new(io::ErrorKind::UnexpectedEof, value),
+            TransferError::Unknown(_) => io::Error::other(value),
         }
     }
 }
 
+/// Emulate `short_not_ok` on platforms where the OS doesn't support it: turn a
+/// successful `IN` transfer shorter than `short_not_ok_len` into an error.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn check_short_packet(
+    status: Result<(), TransferError>,
+    actual_len: usize,
+    short_not_ok_len: Option<usize>,
+) -> Result<(), TransferError> {
+    match short_not_ok_len {
+        Some(requested) if status.is_ok() && actual_len < requested => {
+            Err(TransferError::ShortPacket)
+        }
+        _ => status,
+    }
+}
+
 /// Status and data returned on transfer completion.
 ///
 /// A transfer can return partial data even in the case of failure or
@@ -133,6 +224,27 @@ impl TryFrom<Completion<Vec<u8>>> for Vec<u8> {
     }
 }
 
+/// Requires the `bytes` feature. Does not copy the received data.
+#[cfg(feature = "bytes")]
+impl TryFrom<Completion<Vec<u8>>> for bytes::Bytes {
+    type Error = TransferError;
+
+    fn try_from(c: Completion<Vec<u8>>) -> Result<Self, Self::Error> {
+        c.into_result().map(bytes::Bytes::from)
+    }
+}
+
+/// Requires the `bytes` feature. Does not copy the received data.
+#[cfg(feature = "bytes")]
+impl TryFrom<Completion<Vec<u8>>> for bytes::BytesMut {
+    type Error = TransferError;
+
+    fn try_from(c: Completion<Vec<u8>>) -> Result<Self, Self::Error> {
+        // A uniquely-owned `Bytes` converts to `BytesMut` without copying
+        c.into_result().map(|v| bytes::Bytes::from(v).into())
+    }
+}
+
 impl TryFrom<Completion<ResponseBuffer>> for ResponseBuffer {
     type Error = TransferError;
 
@@ -151,7 +263,15 @@ impl TryFrom<Completion<ResponseBuffer>> for ResponseBuffer {
 /// that `TransferFuture` is not [cancel-safe] and cannot be used
 /// in `select!{}`, When racing a `TransferFuture` with a timeout
 /// you cannot tell whether data may have been partially transferred on timeout.
-/// Use the [`Queue`] interface if these matter for your application.
+/// Use the [`Queue`] interface if these matter for your application, or
+/// call [`TransferFuture::cancel`] and keep polling the future to get the
+/// data transferred before the cancellation.
+///
+/// Dropping a pending `TransferFuture` requests cancellation from the OS and
+/// returns without waiting for it. The buffer stays owned by the transfer
+/// until the event thread has reaped the cancelled transfer, and is freed
+/// there, so the OS can never write into memory that has been freed or
+/// reused.
 ///
 /// [cancel-safe]: https://docs.rs/tokio/latest/tokio/macro.select.html#cancellation-safety
 pub struct TransferFuture<D: TransferRequest> {
@@ -166,6 +286,23 @@ impl<D: TransferRequest> TransferFuture<D> {
             ty: PhantomData,
         }
     }
+
+    /// Request cancellation of the transfer.
+    ///
+    /// The future still needs to be polled to completion. If the transfer was
+    /// cancelled before it completed, the completion has the status
+    /// [`TransferError::Cancelled`] and the data transferred before the
+    /// cancellation. This has no effect if the transfer has already
+    /// completed.
+    ///
+    /// ### Platform-specific notes
+    ///
+    /// * On macOS, this cancels all pending transfers on the endpoint.
+    pub fn cancel(&mut self) {
+        if self.transfer.is_pending() {
+            self.transfer.cancel();
+        }
+    }
 }
 
 impl<D: TransferRequest> Future for TransferFuture<D>
@@ -179,3 +316,15 @@ where
         self.as_mut().transfer.poll_completion::<D>(cx)
     }
 }
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<TransferFuture<RequestBuffer>>();
+    require_send_sync::<TransferFuture<Vec<u8>>>();
+    require_send_sync::<TransferFuture<ControlIn>>();
+    require_send_sync::<TransferFuture<ControlInBuffer>>();
+    require_send_sync::<TransferFuture<ControlOutSetup<'static>>>();
+    require_send_sync::<Completion<ResponseBuffer>>();
+    require_send_sync::<TransferError>();
+}
@@ -0,0 +1,100 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use super::TransferError;
+
+/// Receives events for the transfers of a device, for metrics or debugging.
+///
+/// Set with [`Device::set_transfer_observer`][crate::Device::set_transfer_observer].
+/// The methods are called on the thread that submits the transfer or
+/// receives its completion, so they should return quickly.
+///
+/// ### Example
+///
+/// ```no_run
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use nusb::transfer::{CompleteEvent, TransferObserver};
+/// # use nusb::MaybeFuture;
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// # let device = di.open().wait().unwrap();
+///
+/// #[derive(Default)]
+/// struct ByteCounter(AtomicUsize);
+///
+/// impl TransferObserver for ByteCounter {
+///     fn on_complete(&self, event: &CompleteEvent) {
+///         self.0.fetch_add(event.length, Ordering::Relaxed);
+///     }
+/// }
+///
+/// device.set_transfer_observer(ByteCounter::default());
+/// ```
+pub trait TransferObserver: Send + Sync {
+    /// Called when a transfer is submitted.
+    fn on_submit(&self, event: &SubmitEvent) {
+        let _ = event;
+    }
+
+    /// Called when the completion of a transfer is returned to the
+    /// application.
+    ///
+    /// Transfers that are cancelled by dropping their
+    /// [`TransferFuture`][super::TransferFuture] or [`Queue`][super::Queue]
+    /// are not reported.
+    fn on_complete(&self, event: &CompleteEvent) {
+        let _ = event;
+    }
+}
+
+/// A transfer submitted, passed to [`TransferObserver::on_submit`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SubmitEvent {
+    /// Endpoint address, with the top bit set for IN endpoints.
+    ///
+    /// Control transfers are reported on endpoint 0.
+    pub endpoint: u8,
+
+    /// Number of bytes requested for an IN transfer, or to send for an OUT
+    /// transfer.
+    pub length: usize,
+}
+
+/// A transfer completed, passed to [`TransferObserver::on_complete`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CompleteEvent {
+    /// Endpoint address, with the top bit set for IN endpoints.
+    ///
+    /// Control transfers are reported on endpoint 0.
+    pub endpoint: u8,
+
+    /// Number of bytes actually transferred.
+    pub length: usize,
+
+    /// Time from submission until the completion was reaped by the event
+    /// thread. See [`TransferTiming::latency`][super::TransferTiming::latency].
+    pub duration: Duration,
+
+    /// Status of the transfer.
+    pub status: Result<(), TransferError>,
+}
+
+/// Observer of a device, shared by its transfers.
+#[derive(Default)]
+pub(crate) struct ObserverSlot(Mutex<Option<Arc<dyn TransferObserver>>>);
+
+impl ObserverSlot {
+    pub(crate) fn set(&self, observer: Option<Arc<dyn TransferObserver>>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = observer;
+    }
+
+    pub(crate) fn get(&self) -> Option<Arc<dyn TransferObserver>> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
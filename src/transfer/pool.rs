@@ -0,0 +1,166 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use super::{RequestBuffer, ResponseBuffer};
+
+/// A pool of recycled transfer buffers.
+///
+/// A `BufferPool` holds up to `max_buffers` free buffers with capacity for
+/// at least `buffer_size` bytes each. Buffers taken from the pool are ordinary
+/// `Vec<u8>`s; return them with [`recycle`][Self::recycle] once their data has
+/// been consumed so that streaming at a high rate doesn't allocate for every
+/// transfer.
+///
+/// The pool is cheaply cloneable, and clones share the same buffers, so it
+/// can be shared between several [`Queue`][super::Queue]s (see
+/// [`Queue::set_buffer_pool`][super::Queue::set_buffer_pool]) or passed to the
+/// task or thread that consumes the received data.
+///
+/// ### Example
+///
+/// ```no_run
+/// use futures_lite::future::block_on;
+/// use nusb::transfer::BufferPool;
+/// # use nusb::MaybeFuture;
+/// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+/// # let device = di.open().wait().unwrap();
+/// # let interface = device.claim_interface(0).wait().unwrap();
+/// # fn handle_data(_: &[u8]) {}
+/// let pool = BufferPool::new(16384, 8);
+/// let mut queue = interface.bulk_in_queue(0x81);
+///
+/// loop {
+///     while queue.pending() < 8 {
+///         queue.submit(pool.request());
+///     }
+///     let data = block_on(queue.next_complete()).into_result().unwrap();
+///     handle_data(&data);
+///     pool.recycle(data);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    buffer_size: usize,
+    max_buffers: usize,
+    free: Mutex<Vec<Vec<u8>>>,
+}
+
+impl BufferPool {
+    /// Create an empty pool of buffers of `buffer_size` bytes that keeps at
+    /// most `max_buffers` free buffers for re-use.
+    pub fn new(buffer_size: usize, max_buffers: usize) -> BufferPool {
+        BufferPool {
+            inner: Arc::new(PoolInner {
+                buffer_size,
+                max_buffers,
+                free: Mutex::new(Vec::with_capacity(max_buffers)),
+            }),
+        }
+    }
+
+    /// Allocate free buffers until the pool holds `count` of them, or
+    /// `max_buffers` if that is lower.
+    pub fn prefill(&self, count: usize) {
+        let mut free = self.inner.free.lock().unwrap();
+        let target = count.min(self.inner.max_buffers);
+        while free.len() < target {
+            free.push(Vec::with_capacity(self.inner.buffer_size));
+        }
+    }
+
+    /// Get the size of the buffers in the pool.
+    pub fn buffer_size(&self) -> usize {
+        self.inner.buffer_size
+    }
+
+    /// Get the maximum number of free buffers kept in the pool.
+    pub fn max_buffers(&self) -> usize {
+        self.inner.max_buffers
+    }
+
+    /// Get the number of free buffers currently in the pool.
+    pub fn available(&self) -> usize {
+        self.inner.free.lock().unwrap().len()
+    }
+
+    /// Take an empty buffer with capacity for at least
+    /// [`buffer_size`][Self::buffer_size] bytes from the pool, or allocate a
+    /// new one if the pool is empty.
+    pub fn get(&self) -> Vec<u8> {
+        let buf = self.inner.free.lock().unwrap().pop();
+        buf.unwrap_or_else(|| Vec::with_capacity(self.inner.buffer_size))
+    }
+
+    /// Take a buffer from the pool as a [`RequestBuffer`] for an `IN` transfer
+    /// of [`buffer_size`][Self::buffer_size] bytes.
+    pub fn request(&self) -> RequestBuffer {
+        RequestBuffer::reuse(self.get(), self.inner.buffer_size)
+    }
+
+    /// Return a buffer to the pool.
+    ///
+    /// The buffer is cleared and kept for re-use if its capacity is at least
+    /// [`buffer_size`][Self::buffer_size] and the pool holds fewer than
+    /// [`max_buffers`][Self::max_buffers] free buffers. Otherwise it is
+    /// dropped.
+    pub fn recycle(&self, mut buf: Vec<u8>) {
+        if buf.capacity() < self.inner.buffer_size {
+            return;
+        }
+
+        let mut free = self.inner.free.lock().unwrap();
+        if free.len() < self.inner.max_buffers {
+            buf.clear();
+            free.push(buf);
+        }
+    }
+
+    /// Return the buffer of a completed `OUT` transfer to the pool.
+    pub fn recycle_response(&self, buf: ResponseBuffer) {
+        self.recycle(buf.reuse());
+    }
+}
+
+impl Debug for BufferPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("buffer_size", &self.inner.buffer_size)
+            .field("max_buffers", &self.inner.max_buffers)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+#[test]
+fn recycle_limits() {
+    let pool = BufferPool::new(64, 2);
+    pool.prefill(5);
+    assert_eq!(pool.available(), 2);
+
+    let a = pool.get();
+    let b = pool.get();
+    let c = pool.get();
+    assert!(a.capacity() >= 64 && c.capacity() >= 64);
+    assert_eq!(pool.available(), 0);
+
+    pool.recycle(Vec::with_capacity(16));
+    assert_eq!(pool.available(), 0);
+
+    pool.recycle(vec![1; 64]);
+    pool.recycle(a);
+    pool.recycle(b);
+    assert_eq!(pool.available(), 2);
+    assert!(pool.get().is_empty());
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<BufferPool>();
+}
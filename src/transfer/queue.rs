@@ -1,14 +1,29 @@
 use std::{
     collections::VecDeque,
-    future::{poll_fn, Future},
+    future::{poll_fn, Future, IntoFuture},
+    io::IoSlice,
     marker::PhantomData,
-    sync::Arc,
-    task::{Context, Poll},
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{ready, Context, Poll},
 };
 
-use crate::{platform, Error, MaybeFuture};
+use log::warn;
 
-use super::{Completion, PlatformSubmit, TransferHandle, TransferRequest, TransferType};
+use crate::{maybe_future::blocking::Blocking, platform, Error, MaybeFuture};
+
+use super::{
+    blocking::poll_blocking, timing::LatencyRecorder, BufferPool, Completion, LatencyStats,
+    PlatformSubmit, TransferError, TransferHandle, TransferRequest, TransferTiming, TransferType,
+};
+
+/// Type-erased handler set by `Queue::drain_on_drop`, which waits for a
+/// cancelled transfer and passes its completion to the user's callback.
+type DrainFn = Box<dyn FnMut(TransferHandle<platform::TransferData>) + Send>;
+
+/// `clear_halt` started by a queue in `auto_clear_halt` mode.
+type ClearHaltFuture = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
 
 /// Manages a stream of transfers on an endpoint.
 ///
@@ -20,7 +35,12 @@ use super::{Completion, PlatformSubmit, TransferHandle, TransferRequest, Transfe
 ///
 /// Use the methods on [`Interface`][`crate::Interface`] to obtain a `Queue`.
 ///
-/// When the `Queue` is dropped, all pending transfers are cancelled.
+/// When the `Queue` is dropped, all pending transfers are cancelled, in
+/// reverse order. By default, the drop doesn't wait for the cancellations to
+/// complete, and the buffers of the cancelled transfers are freed in the
+/// background once the OS releases them. To get the completions of the
+/// remaining transfers instead, either consume the queue with
+/// [`Queue::into_pending`], or set a callback with [`Queue::drain_on_drop`].
 ///
 /// ### Why use a `Queue` instead of submitting multiple transfers individually with the methods on [`Interface`][`crate::Interface`]?
 ///
@@ -42,11 +62,21 @@ use super::{Completion, PlatformSubmit, TransferHandle, TransferRequest, Transfe
 ///       Future returned from [`Queue::next_complete`] is cancel-safe because
 ///       it merely waits for completion, while the `Queue` owns the pending
 ///       transfers.
-///  * A queue caches the internal transfer data structures of the last
-///    completed transfer, meaning that if you re-use the data buffer there is
-///    no memory allocation involved in continued streaming.
+///  * A queue caches the internal transfer data structures of completed
+///    transfers, meaning that if you re-use the data buffer there is no memory
+///    allocation involved in continued streaming. [`Queue::prewarm`] can
+///    allocate them ahead of time.
 ///
 /// [cancel-safe]: https://docs.rs/tokio/latest/tokio/macro.select.html#cancellation-safety
+///
+/// ### Sharing a `Queue`
+///
+/// A `Queue` is `Send` and `Sync`, but submitting transfers and receiving
+/// completions require `&mut self`. To submit from several tasks, use the
+/// `&self` transfer methods of [`Interface`][`crate::Interface`], or send the
+/// data to the task that owns the queue. See the [crate-level
+/// documentation][crate#thread-safety] for the full thread-safety contract.
+///
 /// ### Example (read from an endpoint)
 ///
 /// ```no_run
@@ -104,7 +134,7 @@ use super::{Completion, PlatformSubmit, TransferHandle, TransferRequest, Transfe
 ///     let completion = block_on(queue.next_complete());
 ///     data_confirmed_sent(completion.data.actual_length()); // your function
 ///     next_buf = completion.data.reuse();
-
+///
 ///     if completion.status.is_err() {
 ///         break;
 ///     }
@@ -118,8 +148,51 @@ pub struct Queue<R: TransferRequest> {
     /// A queue of pending transfers, expected to complete in order
     pending: VecDeque<TransferHandle<platform::TransferData>>,
 
-    /// An idle transfer that recently completed for re-use.
-    cached: Option<TransferHandle<platform::TransferData>>,
+    /// Idle transfers that recently completed or were pre-allocated by
+    /// `prewarm`, for re-use.
+    idle: Vec<TransferHandle<platform::TransferData>>,
+
+    /// Buffers pre-allocated by `prewarm`, handed out by `buffer`.
+    spare_buffers: Vec<Vec<u8>>,
+
+    /// Pool that `buffer` draws from once `spare_buffers` is empty.
+    buffer_pool: Option<BufferPool>,
+
+    /// Limit on the number of transfers in flight, if in bounded mode.
+    max_in_flight: Option<usize>,
+
+    /// Timing of the transfer last returned by `poll_next`.
+    last_timing: Option<TransferTiming>,
+
+    /// Latencies of recently returned transfers.
+    latencies: LatencyRecorder,
+
+    /// Whether to clear the halt condition after a transfer stalls.
+    auto_clear_halt: bool,
+
+    /// Set after a stall in `auto_clear_halt` mode, until the transfers that
+    /// were pending have been cancelled and the halt is being cleared.
+    halted: bool,
+
+    /// Number of transfers at the front of `pending` that were cancelled
+    /// because an earlier transfer stalled.
+    cancelled_after_stall: usize,
+
+    /// Halt being cleared after a stall, waited for before the next
+    /// completion is returned. In a `Mutex` only so that `Queue` is `Sync`.
+    clearing_halt: Mutex<Option<ClearHaltFuture>>,
+
+    /// Requests submitted while recovering from a stall, submitted to the OS
+    /// once the halt has been cleared.
+    deferred: VecDeque<R>,
+
+    /// Whether to cancel the `deferred` requests as soon as they are
+    /// submitted, because `cancel_all` was called.
+    cancel_deferred: bool,
+
+    /// Handler for the transfers still pending on drop. In a `Mutex` only
+    /// so that `Queue` is `Sync`.
+    drain_on_drop: Mutex<Option<DrainFn>>,
 
     bufs: PhantomData<R>,
 }
@@ -139,17 +212,193 @@ where
             endpoint,
             endpoint_type,
             pending: VecDeque::new(),
-            cached: None,
+            idle: Vec::new(),
+            spare_buffers: Vec::new(),
+            buffer_pool: None,
+            max_in_flight: None,
+            last_timing: None,
+            latencies: LatencyRecorder::default(),
+            auto_clear_halt: false,
+            halted: false,
+            cancelled_after_stall: 0,
+            clearing_halt: Mutex::new(None),
+            deferred: VecDeque::new(),
+            cancel_deferred: false,
+            drain_on_drop: Mutex::new(None),
             bufs: PhantomData,
         }
     }
 
+    /// Limit the number of transfers that may be in flight at once.
+    ///
+    /// In bounded mode, [`ready`][Self::ready] and
+    /// [`poll_ready`][Self::poll_ready] wait until fewer than `limit`
+    /// submitted transfers are still pending with the OS before allowing
+    /// another submission. Transfers that have completed but not yet been
+    /// returned from [`next_complete`][Self::next_complete] don't count
+    /// against the limit.
+    ///
+    /// Pass `None` to disable the limit (the default).
+    ///
+    /// Panics if `limit` is `Some(0)`.
+    pub fn set_max_in_flight(&mut self, limit: Option<usize>) {
+        assert!(limit != Some(0), "limit must be at least 1");
+        self.max_in_flight = limit;
+    }
+
+    /// Get the limit on the number of transfers in flight set by
+    /// [`set_max_in_flight`][Self::set_max_in_flight].
+    pub fn max_in_flight(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
+    /// Return a `Future` that waits until another transfer may be submitted
+    /// without exceeding the [`max_in_flight`][Self::set_max_in_flight]
+    /// limit.
+    ///
+    /// Completes immediately if the queue is not in bounded mode.
+    ///
+    /// Completed transfers must still be collected with
+    /// [`next_complete`][Self::next_complete], which can be raced against this
+    /// future to handle completions while waiting for room to submit.
+    ///
+    /// This future is cancel-safe: it can be cancelled and re-created without
+    /// side effects, enabling its use in `select!{}` or similar.
+    ///
+    pub fn ready<'a>(&'a mut self) -> impl Future<Output = ()> + Unpin + Send + Sync + 'a {
+        poll_fn(|cx| self.poll_ready(cx))
+    }
+
+    /// Check whether another transfer may be submitted without exceeding the
+    /// [`max_in_flight`][Self::set_max_in_flight] limit, or register the
+    /// current task for wakeup when a pending transfer completes.
+    pub fn poll_ready(&mut self, cx: &mut Context) -> Poll<()> {
+        let Some(limit) = self.max_in_flight else {
+            return Poll::Ready(());
+        };
+
+        // Requests held back while recovering from a stall are all submitted
+        // once the halt is cleared, so they count against the limit.
+        if self.pending() >= limit && !self.deferred.is_empty() {
+            ready!(self.poll_recovery(cx));
+        }
+
+        let Some(idx) = self.pending.len().checked_sub(limit) else {
+            return Poll::Ready(());
+        };
+
+        // Transfers complete in order, so once this one is done, at most
+        // `limit - 1` transfers remain in flight.
+        self.pending[idx].poll_done(cx)
+    }
+
+    /// Pre-allocate `transfer_count` transfers and data buffers of
+    /// `transfer_size` bytes.
+    ///
+    /// Subsequent calls to [`submit`][Self::submit] re-use the pre-allocated
+    /// transfer structures instead of allocating them, and
+    /// [`buffer`][Self::buffer] hands out the pre-allocated buffers. Call this
+    /// before starting a stream to avoid allocation latency while the first
+    /// transfers are submitted.
+    ///
+    /// Idle transfers and spare buffers the queue already holds count towards
+    /// `transfer_count`, so calling this again doesn't allocate more.
+    pub fn prewarm(&mut self, transfer_count: usize, transfer_size: usize) {
+        let missing = transfer_count.saturating_sub(self.idle.len());
+        self.idle.reserve(missing);
+        for _ in 0..missing {
+            self.idle.push(
+                self.interface
+                    .make_transfer(self.endpoint, self.endpoint_type),
+            );
+        }
+
+        self.pending
+            .reserve(transfer_count.saturating_sub(self.pending.len()));
+
+        let missing = transfer_count.saturating_sub(self.spare_buffers.len());
+        self.spare_buffers.reserve(missing);
+        for _ in 0..missing {
+            self.spare_buffers.push(Vec::with_capacity(transfer_size));
+        }
+    }
+
+    /// Get an empty buffer with capacity for at least `len` bytes.
+    ///
+    /// Returns a buffer pre-allocated by [`prewarm`][Self::prewarm] if one is
+    /// left, then one from the [buffer pool][Self::set_buffer_pool] if one is
+    /// set, and otherwise allocates a new one. Use it to fill an `OUT`
+    /// transfer, or pass it to
+    /// [`RequestBuffer::reuse`][super::RequestBuffer::reuse] for an `IN`
+    /// transfer.
+    pub fn buffer(&mut self, len: usize) -> Vec<u8> {
+        let spare = self.spare_buffers.pop().or_else(|| {
+            self.buffer_pool
+                .as_ref()
+                .filter(|pool| len <= pool.buffer_size())
+                .map(|pool| pool.get())
+        });
+
+        match spare {
+            Some(mut buf) => {
+                buf.reserve_exact(len);
+                buf
+            }
+            None => Vec::with_capacity(len),
+        }
+    }
+
+    /// Set a [`BufferPool`] for [`buffer`][Self::buffer] to take buffers from.
+    ///
+    /// Return buffers to the queue with [`recycle`][Self::recycle], or to the
+    /// pool directly, once their data has been consumed. Pass `None` to stop
+    /// using the pool.
+    pub fn set_buffer_pool(&mut self, pool: Option<BufferPool>) {
+        self.buffer_pool = pool;
+    }
+
+    /// Get the [`BufferPool`] set by [`set_buffer_pool`][Self::set_buffer_pool].
+    pub fn buffer_pool(&self) -> Option<&BufferPool> {
+        self.buffer_pool.as_ref()
+    }
+
+    /// Return a buffer for re-use by a later call to [`buffer`][Self::buffer].
+    ///
+    /// The buffer goes to the [buffer pool][Self::set_buffer_pool] if one is
+    /// set. Otherwise it is cleared and kept by the queue, up to one spare
+    /// buffer for each transfer the queue has allocated, and dropped beyond
+    /// that.
+    pub fn recycle(&mut self, mut buf: Vec<u8>) {
+        match &self.buffer_pool {
+            Some(pool) => pool.recycle(buf),
+            None => {
+                if self.spare_buffers.len() < self.idle.len() + self.pending.len() {
+                    buf.clear();
+                    self.spare_buffers.push(buf);
+                }
+            }
+        }
+    }
+
     /// Submit a new transfer on the endpoint.
     ///
     /// For an `IN` endpoint, pass a [`RequestBuffer`][`super::RequestBuffer`].\
     /// For an `OUT` endpoint, pass a [`Vec<u8>`].
+    ///
+    /// This does not wait for the [`max_in_flight`][Self::set_max_in_flight]
+    /// limit. In bounded mode, await [`ready`][Self::ready] before calling
+    /// `submit`.
+    ///
+    /// While the queue is recovering from a stall in
+    /// [`auto_clear_halt`][Self::set_auto_clear_halt] mode, the transfer is
+    /// held back and submitted once the halt has been cleared.
     pub fn submit(&mut self, data: R) {
-        let mut transfer = self.cached.take().unwrap_or_else(|| {
+        if self.is_recovering() {
+            self.deferred.push_back(data);
+            return;
+        }
+
+        let mut transfer = self.idle.pop().unwrap_or_else(|| {
             self.interface
                 .make_transfer(self.endpoint, self.endpoint_type)
         });
@@ -182,21 +431,152 @@ where
     ///
     /// Panics if there are no transfers pending.
     pub fn poll_next(&mut self, cx: &mut Context) -> Poll<Completion<R::Response>> {
+        ready!(self.poll_recovery(cx));
+
         let res = self
             .pending
             .front_mut()
             .expect("queue should have pending transfers when calling next_complete")
             .poll_completion::<R>(cx);
         if res.is_ready() {
-            self.cached = self.pending.pop_front();
+            let transfer = self.pending.pop_front().unwrap();
+            self.last_timing = transfer.timing();
+            if let Some(timing) = self.last_timing {
+                self.latencies.record(timing.latency);
+            }
+            self.idle.push(transfer);
+        }
+
+        if let Poll::Ready(c) = &res {
+            // Transfers cancelled because of an earlier stall may report the
+            // same stall, which has already been handled.
+            let after_stall = self.cancelled_after_stall > 0;
+            self.cancelled_after_stall = self.cancelled_after_stall.saturating_sub(1);
+
+            if self.auto_clear_halt && !after_stall && c.status == Err(TransferError::Stall) {
+                self.halted = true;
+                self.cancelled_after_stall = self.pending.len();
+                for transfer in self.pending.iter_mut().rev() {
+                    transfer.cancel();
+                }
+            }
         }
         res
     }
 
+    fn is_recovering(&mut self) -> bool {
+        self.halted
+            || self
+                .clearing_halt
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some()
+    }
+
+    /// Drive the recovery from a stall in `auto_clear_halt` mode: wait for
+    /// the cancelled transfers, clear the halt, and then submit the requests
+    /// that were held back.
+    fn poll_recovery(&mut self, cx: &mut Context) -> Poll<()> {
+        if self.halted {
+            // The halt must not be cleared while transfers are pending on the
+            // endpoint. All of `pending` was cancelled after the stall.
+            for transfer in &self.pending {
+                ready!(transfer.poll_done(cx));
+            }
+            self.halted = false;
+
+            let interface = self.interface.clone();
+            let endpoint = self.endpoint;
+            let fut = Blocking::new(move || interface.clear_halt(endpoint).wait()).into_future();
+            *self
+                .clearing_halt
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner) = Some(Box::pin(fut));
+        }
+
+        let clearing_halt = self
+            .clearing_halt
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(fut) = clearing_halt {
+            let res = ready!(fut.as_mut().poll(cx));
+            *clearing_halt = None;
+            if let Err(e) = res {
+                warn!(
+                    "Failed to clear halt on endpoint {:02x}: {e}",
+                    self.endpoint
+                );
+            }
+
+            let first = self.pending.len();
+            while let Some(data) = self.deferred.pop_front() {
+                self.submit(data);
+            }
+            if mem::take(&mut self.cancel_deferred) {
+                for transfer in self.pending.range_mut(first..).rev() {
+                    transfer.cancel();
+                }
+            }
+        }
+
+        Poll::Ready(())
+    }
+
+    /// Automatically clear the endpoint's halt condition when a transfer
+    /// fails with [`TransferError::Stall`].
+    ///
+    /// When enabled, the stalled transfer is still returned with its error,
+    /// and the queue cancels the transfers that were pending behind it, which
+    /// are returned as usual. Once the OS has returned all of them, the queue
+    /// starts [`clear_halt`][Self::clear_halt] in the background, and the
+    /// next completion is returned once the halt has been cleared. Transfers
+    /// submitted in the meantime are held back and submitted after the halt
+    /// is cleared, so the stream continues with them. This is similar to
+    /// WinUSB's `AUTO_CLEAR_STALL` pipe policy, for all endpoints and
+    /// platforms. A failure to clear the halt is logged.
+    ///
+    /// Disabled by default.
+    pub fn set_auto_clear_halt(&mut self, enable: bool) {
+        self.auto_clear_halt = enable;
+    }
+
+    /// Get whether the queue clears the halt condition after a stall, as set
+    /// by [`set_auto_clear_halt`][Self::set_auto_clear_halt].
+    pub fn auto_clear_halt(&self) -> bool {
+        self.auto_clear_halt
+    }
+
+    /// Get the timing of the transfer most recently returned by
+    /// [`next_complete`][Self::next_complete] or [`poll_next`][Self::poll_next].
+    ///
+    /// The [`latency`][TransferTiming::latency] is measured from submitting
+    /// the transfer to the OS until its completion was reaped by the event
+    /// thread, and the [`delivery`][TransferTiming::delivery] from then until
+    /// it was returned from the queue. Comparing the two tells apart a device
+    /// that is slow to respond from an application that is slow to collect
+    /// completions.
+    ///
+    /// Returns `None` before the first completion.
+    pub fn last_timing(&self) -> Option<TransferTiming> {
+        self.last_timing
+    }
+
+    /// Get statistics of the latency of the last 1024 transfers returned by
+    /// the queue, such as percentiles.
+    pub fn latency_stats(&self) -> LatencyStats {
+        self.latencies.stats()
+    }
+
+    /// Discard the latencies recorded for
+    /// [`latency_stats`][Self::latency_stats].
+    pub fn reset_latency_stats(&mut self) {
+        self.latencies.clear();
+    }
+
     /// Get the number of transfers that have been submitted with `submit` that
     /// have not yet been returned from `next_complete`.
     pub fn pending(&self) -> usize {
-        self.pending.len()
+        self.pending.len() + self.deferred.len()
     }
 
     /// Request cancellation of all pending transfers.
@@ -210,6 +590,50 @@ where
         for transfer in self.pending.iter_mut().rev() {
             transfer.cancel();
         }
+        self.cancel_deferred |= !self.deferred.is_empty();
+    }
+
+    /// Cancel all pending transfers, and collect their completions.
+    ///
+    /// Consumes the queue, cancelling the remaining transfers like dropping
+    /// it would, but waits for each of them to be returned by the OS. The
+    /// completions are returned in submission order, so you can tell which
+    /// transfers completed, partially completed, or were cancelled, and
+    /// recover their buffers.
+    pub fn into_pending(mut self) -> impl MaybeFuture<Output = Vec<Completion<R::Response>>> {
+        self.cancel_all();
+        let completions = Vec::with_capacity(self.pending.len());
+        IntoPending {
+            queue: self,
+            completions,
+        }
+    }
+
+    /// Set a callback to receive the completions of transfers that are still
+    /// pending when the `Queue` is dropped.
+    ///
+    /// When a `Queue` with a callback is dropped, it cancels its pending
+    /// transfers, then blocks until the OS has returned each of them, and
+    /// calls `f` with their completions in submission order. This is useful
+    /// to return buffers to a [`BufferPool`] or account for data that was
+    /// transferred before the cancellation.
+    ///
+    /// Since the drop blocks the current thread, prefer
+    /// [`into_pending`][Self::into_pending] in async code when possible.
+    pub fn drain_on_drop<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Completion<R::Response>) + Send + 'static,
+        R: 'static,
+    {
+        let drain: DrainFn = Box::new(move |mut transfer| {
+            let completion = poll_blocking(|cx| transfer.poll_completion::<R>(cx), None)
+                .expect("wait without deadline can't time out");
+            f(completion)
+        });
+        *self
+            .drain_on_drop
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) = Some(drain);
     }
 
     /// Clear the endpoint's halt / stall condition.
@@ -226,12 +650,119 @@ where
     pub fn clear_halt(&mut self) -> impl MaybeFuture<Output = Result<(), Error>> {
         self.interface.clone().clear_halt(self.endpoint)
     }
+
+    /// Reset the host-side state of the endpoint without sending a request to
+    /// the device.
+    ///
+    /// See [`Interface::reset_pipe`][crate::Interface::reset_pipe] for details.
+    ///
+    /// This should not be called when transfers are pending on the endpoint.
+    pub fn reset_pipe(&mut self) -> impl MaybeFuture<Output = Result<(), Error>> {
+        self.interface.clone().reset_pipe(self.endpoint)
+    }
+
+    /// Enable or disable terminating `OUT` transfers on the endpoint with a
+    /// zero-length packet when their length is a multiple of the maximum
+    /// packet size.
+    ///
+    /// See [`Interface::set_send_zlp`][crate::Interface::set_send_zlp] for
+    /// details.
+    pub fn set_send_zlp(&mut self, enable: bool) -> Result<(), Error> {
+        if self.endpoint & 0x80 != 0 {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "zero-length packet termination only applies to OUT endpoints",
+            ));
+        }
+        self.interface.set_send_zlp(self.endpoint, enable)
+    }
+}
+
+impl Queue<Vec<u8>> {
+    /// Submit an `OUT` transfer of the concatenation of `bufs`.
+    ///
+    /// This avoids building an intermediate `Vec` when the data is split
+    /// across several slices, such as a header and a payload. None of the
+    /// supported OS APIs accept a scatter-gather list for a transfer from
+    /// userspace, so the slices are copied directly into a buffer from
+    /// [`buffer`][Self::buffer], which re-uses a
+    /// [pre-allocated][Self::prewarm] or [pooled][Self::set_buffer_pool]
+    /// allocation when one is available.
+    pub fn submit_vectored(&mut self, bufs: &[IoSlice<'_>]) {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        let mut buf = self.buffer(len);
+        for b in bufs {
+            buf.extend_from_slice(b);
+        }
+        self.submit(buf);
+    }
 }
 
 impl<R: TransferRequest> Drop for Queue<R> {
     fn drop(&mut self) {
-        // Cancel transfers in reverse order to ensure subsequent transfers
-        // can't complete out of order while we're going through them.
-        self.pending.drain(..).rev().for_each(drop)
+        let drain = self
+            .drain_on_drop
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        match drain {
+            Some(mut drain) => {
+                for transfer in self.pending.iter_mut().rev() {
+                    transfer.cancel();
+                }
+                self.pending.drain(..).for_each(&mut drain);
+            }
+            None => {
+                // Cancel transfers in reverse order to ensure subsequent transfers
+                // can't complete out of order while we're going through them.
+                self.pending.drain(..).rev().for_each(drop)
+            }
+        }
+    }
+}
+
+/// Returned by [`Queue::into_pending`].
+struct IntoPending<R: TransferRequest> {
+    queue: Queue<R>,
+    completions: Vec<Completion<R::Response>>,
+}
+
+// Nothing is structurally pinned.
+impl<R: TransferRequest> Unpin for IntoPending<R> {}
+
+impl<R> Future for IntoPending<R>
+where
+    R: TransferRequest + Send + Sync,
+    platform::TransferData: PlatformSubmit<R>,
+{
+    type Output = Vec<Completion<R::Response>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.queue.pending() > 0 {
+            let completion = ready!(this.queue.poll_next(cx));
+            this.completions.push(completion);
+        }
+        Poll::Ready(mem::take(&mut this.completions))
     }
 }
+
+impl<R> MaybeFuture for IntoPending<R>
+where
+    R: TransferRequest + Send + Sync,
+    platform::TransferData: PlatformSubmit<R>,
+{
+    fn wait(mut self) -> Self::Output {
+        poll_blocking(|cx| Pin::new(&mut self).poll(cx), None)
+            .expect("wait without deadline can't time out")
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<Queue<super::RequestBuffer>>();
+    require_send_sync::<Queue<Vec<u8>>>();
+    require_send_sync::<Queue<super::ControlIn>>();
+}
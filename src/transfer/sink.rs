@@ -0,0 +1,88 @@
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_sink::Sink;
+
+use super::{Queue, TransferError};
+
+impl Queue<Vec<u8>> {
+    /// Convert an `OUT` queue into a [`Sink`] of data to send.
+    ///
+    /// Each item sent into the sink is submitted as one transfer. At most
+    /// `depth` transfers are kept in flight: `poll_ready` waits for the oldest
+    /// transfer to complete before accepting another item, and `poll_flush`
+    /// waits for all of them.
+    ///
+    /// Transfer errors are returned from `poll_ready`, `poll_flush`, or
+    /// `poll_close`, whichever is waiting on the failed transfer. Use
+    /// [`QueueSink::into_inner`] to get the `Queue` back, e.g. to call
+    /// [`clear_halt`][Queue::clear_halt] after a stall.
+    ///
+    /// Panics if `depth` is 0.
+    pub fn into_sink(self, depth: usize) -> QueueSink {
+        assert!(depth > 0, "depth must be at least 1");
+        QueueSink { queue: self, depth }
+    }
+}
+
+/// [`Sink`] of data to send on an `OUT` endpoint.
+///
+/// Created by [`Queue::into_sink`].
+pub struct QueueSink {
+    queue: Queue<Vec<u8>>,
+    depth: usize,
+}
+
+impl QueueSink {
+    /// Get back the underlying `Queue`.
+    ///
+    /// Transfers that are still pending remain in the queue and can be
+    /// collected with [`Queue::next_complete`].
+    pub fn into_inner(self) -> Queue<Vec<u8>> {
+        self.queue
+    }
+
+    /// Wait until at most `max_pending` transfers are pending.
+    fn poll_pending_below(
+        &mut self,
+        cx: &mut Context<'_>,
+        max_pending: usize,
+    ) -> Poll<Result<(), TransferError>> {
+        while self.queue.pending() > max_pending {
+            let completion = ready!(self.queue.poll_next(cx));
+            completion.status?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Sink<Vec<u8>> for QueueSink {
+    type Error = TransferError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        let depth = this.depth;
+        this.poll_pending_below(cx, depth - 1)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Vec<u8>) -> Result<(), Self::Error> {
+        self.get_mut().queue.submit(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending_below(cx, 0)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<QueueSink>();
+}
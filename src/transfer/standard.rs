@@ -0,0 +1,265 @@
+use std::io::ErrorKind;
+
+use crate::Error;
+
+use super::{Control, ControlType, Recipient, TransferError};
+
+const REQUEST_GET_STATUS: u8 = 0x00;
+const REQUEST_CLEAR_FEATURE: u8 = 0x01;
+const REQUEST_SET_FEATURE: u8 = 0x03;
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const REQUEST_GET_CONFIGURATION: u8 = 0x08;
+const REQUEST_SET_INTERFACE: u8 = 0x0B;
+const REQUEST_SYNCH_FRAME: u8 = 0x0C;
+
+/// A feature selector for the standard `SET_FEATURE` and `CLEAR_FEATURE`
+/// requests.
+///
+/// Used with [`Interface::set_feature`][crate::Interface::set_feature] and
+/// [`Interface::clear_feature`][crate::Interface::clear_feature].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// `DEVICE_REMOTE_WAKEUP`: allow the device to wake the host from
+    /// suspend.
+    RemoteWakeup,
+
+    /// `ENDPOINT_HALT` of the endpoint with the specified address.
+    ///
+    /// Clearing it only resets the data toggle on the device side; use
+    /// [`Interface::clear_halt`][crate::Interface::clear_halt] to recover a
+    /// stalled endpoint.
+    EndpointHalt(u8),
+
+    /// `TEST_MODE` with the specified test selector. This can only be set,
+    /// and the device must be power cycled to leave test mode.
+    TestMode(u8),
+
+    /// `U1_ENABLE`: allow a SuperSpeed device to initiate U1 link power
+    /// management transitions.
+    U1Enable,
+
+    /// `U2_ENABLE`: allow a SuperSpeed device to initiate U2 link power
+    /// management transitions.
+    U2Enable,
+
+    /// `LTM_ENABLE`: allow a SuperSpeed device to send Latency Tolerance
+    /// Messages.
+    LtmEnable,
+}
+
+impl Feature {
+    fn control(self, request: u8) -> Control {
+        let (recipient, value, index) = match self {
+            Feature::RemoteWakeup => (Recipient::Device, 1, 0),
+            Feature::EndpointHalt(endpoint) => (Recipient::Endpoint, 0, endpoint.into()),
+            Feature::TestMode(selector) => (Recipient::Device, 2, u16::from(selector) << 8),
+            Feature::U1Enable => (Recipient::Device, 48, 0),
+            Feature::U2Enable => (Recipient::Device, 49, 0),
+            Feature::LtmEnable => (Recipient::Device, 50, 0),
+        };
+
+        Control {
+            control_type: ControlType::Standard,
+            recipient,
+            request,
+            value,
+            index,
+        }
+    }
+
+    pub(crate) fn set_control(self) -> Control {
+        self.control(REQUEST_SET_FEATURE)
+    }
+
+    pub(crate) fn clear_control(self) -> Control {
+        self.control(REQUEST_CLEAR_FEATURE)
+    }
+}
+
+/// Status of a device, returned by the standard `GET_STATUS` request.
+///
+/// Returned by [`Interface::device_status`][crate::Interface::device_status].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceStatus(u16);
+
+impl DeviceStatus {
+    /// Get the raw status bits.
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Whether the device is currently self-powered.
+    pub fn self_powered(&self) -> bool {
+        self.0 & (1 << 0) != 0
+    }
+
+    /// Whether remote wakeup is enabled, see [`Feature::RemoteWakeup`].
+    pub fn remote_wakeup(&self) -> bool {
+        self.0 & (1 << 1) != 0
+    }
+
+    /// Whether U1 transitions are enabled, see [`Feature::U1Enable`].
+    pub fn u1_enabled(&self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+
+    /// Whether U2 transitions are enabled, see [`Feature::U2Enable`].
+    pub fn u2_enabled(&self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+
+    /// Whether Latency Tolerance Messages are enabled, see
+    /// [`Feature::LtmEnable`].
+    pub fn ltm_enabled(&self) -> bool {
+        self.0 & (1 << 4) != 0
+    }
+}
+
+/// Status of an endpoint, returned by the standard `GET_STATUS` request.
+///
+/// Returned by [`Interface::endpoint_status`][crate::Interface::endpoint_status].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EndpointStatus(u16);
+
+impl EndpointStatus {
+    /// Get the raw status bits.
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Whether the endpoint is halted.
+    pub fn halted(&self) -> bool {
+        self.0 & 1 != 0
+    }
+}
+
+/// Perform a standard request returning a `u16` with `control_in_blocking`.
+fn get_u16(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+    control: Control,
+    name: &str,
+) -> Result<u16, Error> {
+    let mut buf = [0; 2];
+    match control_in_blocking(control, &mut buf)? {
+        2 => Ok(u16::from_le_bytes(buf)),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{name} returned a short response"),
+        )),
+    }
+}
+
+fn get_status(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+    recipient: Recipient,
+    index: u16,
+) -> Result<u16, Error> {
+    let control = Control {
+        control_type: ControlType::Standard,
+        recipient,
+        request: REQUEST_GET_STATUS,
+        value: 0,
+        index,
+    };
+    get_u16(control_in_blocking, control, "GET_STATUS")
+}
+
+pub(crate) fn get_device_status(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+) -> Result<DeviceStatus, Error> {
+    get_status(control_in_blocking, Recipient::Device, 0).map(DeviceStatus)
+}
+
+pub(crate) fn get_endpoint_status(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+    endpoint: u8,
+) -> Result<EndpointStatus, Error> {
+    get_status(control_in_blocking, Recipient::Endpoint, endpoint.into()).map(EndpointStatus)
+}
+
+/// Perform a `GET_CONFIGURATION` request, returning the configuration value.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn get_configuration(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+) -> Result<u8, Error> {
+    let control = Control {
+        control_type: ControlType::Standard,
+        recipient: Recipient::Device,
+        request: REQUEST_GET_CONFIGURATION,
+        value: 0,
+        index: 0,
+    };
+
+    let mut buf = [0; 1];
+    match control_in_blocking(control, &mut buf)? {
+        1 => Ok(buf[0]),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            "GET_CONFIGURATION returned a short response",
+        )),
+    }
+}
+
+/// Perform a `SYNCH_FRAME` request, returning the frame number.
+pub(crate) fn synch_frame(
+    control_in_blocking: impl FnOnce(Control, &mut [u8]) -> Result<usize, TransferError>,
+    endpoint: u8,
+) -> Result<u16, Error> {
+    let control = Control {
+        control_type: ControlType::Standard,
+        recipient: Recipient::Endpoint,
+        request: REQUEST_SYNCH_FRAME,
+        value: 0,
+        index: endpoint.into(),
+    };
+    get_u16(control_in_blocking, control, "SYNCH_FRAME")
+}
+
+pub(crate) fn set_interface_control(interface: u8, alt_setting: u8) -> Control {
+    Control {
+        control_type: ControlType::Standard,
+        recipient: Recipient::Interface,
+        request: REQUEST_SET_INTERFACE,
+        value: alt_setting.into(),
+        index: interface.into(),
+    }
+}
+
+#[test]
+fn feature_setup() {
+    let c = Feature::TestMode(4).set_control();
+    assert_eq!(
+        (c.recipient, c.request, c.value, c.index),
+        (Recipient::Device, 3, 2, 0x0400)
+    );
+
+    let c = Feature::EndpointHalt(0x81).clear_control();
+    assert_eq!(
+        (c.recipient, c.request, c.value, c.index),
+        (Recipient::Endpoint, 1, 0, 0x81)
+    );
+
+    let status = get_device_status(|c, buf| {
+        assert_eq!((c.recipient, c.request), (Recipient::Device, 0));
+        buf.copy_from_slice(&[0x03, 0x00]);
+        Ok(2)
+    })
+    .unwrap();
+    assert!(status.self_powered() && status.remote_wakeup() && !status.u1_enabled());
+}
+
+#[test]
+fn synch_frame_short_response() {
+    let frame = synch_frame(
+        |c, buf| {
+            assert_eq!(
+                (c.recipient, c.request, c.index),
+                (Recipient::Endpoint, 12, 0x83)
+            );
+            buf[0] = 0x10;
+            Ok(1)
+        },
+        0x83,
+    );
+    assert_eq!(frame.unwrap_err().kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,107 @@
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+
+use super::{Queue, RequestBuffer, TransferError};
+
+impl Queue<RequestBuffer> {
+    /// Convert an `IN` queue into a [`Stream`] of received data.
+    ///
+    /// The stream keeps `depth` transfers of `transfer_len` bytes in flight,
+    /// re-submitting a new transfer each time one completes, and yields the
+    /// data of each completed transfer in order. Call
+    /// [`prewarm`][Queue::prewarm] first to have it start with pre-allocated
+    /// transfers and buffers.
+    ///
+    /// When a transfer fails, the stream cancels the remaining pending
+    /// transfers, yields the error, and then ends. Use
+    /// [`QueueStream::into_inner`] to get the `Queue` back, e.g. to call
+    /// [`clear_halt`][Queue::clear_halt] after a stall.
+    ///
+    /// Panics if `depth` is 0.
+    ///
+    /// ### Example
+    ///
+    /// ```no_run
+    /// use futures_lite::{future::block_on, StreamExt};
+    /// # use nusb::MaybeFuture;
+    /// # let di = nusb::list_devices().wait().unwrap().next().unwrap();
+    /// # let device = di.open().wait().unwrap();
+    /// # let interface = device.claim_interface(0).wait().unwrap();
+    /// # fn handle_data(_: &[u8]) {}
+    /// let mut stream = interface.bulk_in_queue(0x81).into_stream(256, 8);
+    ///
+    /// while let Some(data) = block_on(stream.next()) {
+    ///     match data {
+    ///         Ok(data) => handle_data(&data), // your function
+    ///         Err(e) => eprintln!("transfer failed: {e}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn into_stream(self, transfer_len: usize, depth: usize) -> QueueStream {
+        assert!(depth > 0, "depth must be at least 1");
+        QueueStream {
+            queue: self,
+            transfer_len,
+            depth,
+            done: false,
+        }
+    }
+}
+
+/// [`Stream`] of data received on an `IN` endpoint.
+///
+/// Created by [`Queue::into_stream`].
+pub struct QueueStream {
+    queue: Queue<RequestBuffer>,
+    transfer_len: usize,
+    depth: usize,
+    done: bool,
+}
+
+impl QueueStream {
+    /// Get back the underlying `Queue`.
+    ///
+    /// Transfers that are still pending remain in the queue and can be
+    /// collected with [`Queue::next_complete`].
+    pub fn into_inner(self) -> Queue<RequestBuffer> {
+        self.queue
+    }
+}
+
+impl Stream for QueueStream {
+    type Item = Result<Vec<u8>, TransferError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        while this.queue.pending() < this.depth {
+            let buf = this.queue.buffer(this.transfer_len);
+            this.queue
+                .submit(RequestBuffer::reuse(buf, this.transfer_len));
+        }
+
+        let completion = ready!(this.queue.poll_next(cx));
+        match completion.status {
+            Ok(()) => Poll::Ready(Some(Ok(completion.data))),
+            Err(e) => {
+                this.done = true;
+                this.queue.cancel_all();
+                Poll::Ready(Some(Err(e)))
+            }
+        }
+    }
+}
+
+#[test]
+fn assert_send_sync() {
+    fn require_send_sync<T: Send + Sync>() {}
+    require_send_sync::<QueueStream>();
+}
@@ -0,0 +1,119 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// Timing of a completed transfer.
+///
+/// Returned by [`Queue::last_timing`][super::Queue::last_timing].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TransferTiming {
+    /// Time from submitting the transfer to the OS until its completion was
+    /// reaped by the event thread.
+    ///
+    /// This covers the time the transfer waited for the device, plus the
+    /// kernel and host controller overhead.
+    pub latency: Duration,
+
+    /// Time from the completion being reaped by the event thread until the
+    /// application received it from the `Queue`.
+    ///
+    /// A large value means that the application is slow to poll for
+    /// completions, rather than the device being slow to respond.
+    pub delivery: Duration,
+}
+
+/// Number of recent latencies kept by a `Queue` for [`LatencyStats`].
+pub(crate) const LATENCY_SAMPLES: usize = 1024;
+
+/// Recent latencies recorded by a `Queue`.
+#[derive(Default)]
+pub(crate) struct LatencyRecorder {
+    samples: VecDeque<Duration>,
+}
+
+impl LatencyRecorder {
+    pub(crate) fn record(&mut self, latency: Duration) {
+        if self.samples.len() == LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    pub(crate) fn stats(&self) -> LatencyStats {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        LatencyStats { sorted }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Statistics of the [`latency`][TransferTiming::latency] of recent transfers
+/// on a [`Queue`][super::Queue].
+///
+/// Returned by [`Queue::latency_stats`][super::Queue::latency_stats]. Covers
+/// up to the last 1024 transfers returned by the queue.
+#[derive(Debug, Clone)]
+pub struct LatencyStats {
+    sorted: Vec<Duration>,
+}
+
+impl LatencyStats {
+    /// Get the number of transfers included in the statistics.
+    pub fn count(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Get the lowest latency, or `None` if no transfers have completed.
+    pub fn min(&self) -> Option<Duration> {
+        self.sorted.first().copied()
+    }
+
+    /// Get the highest latency, or `None` if no transfers have completed.
+    pub fn max(&self) -> Option<Duration> {
+        self.sorted.last().copied()
+    }
+
+    /// Get the mean latency, or `None` if no transfers have completed.
+    pub fn mean(&self) -> Option<Duration> {
+        let total: Duration = self.sorted.iter().sum();
+        Some(total / u32::try_from(self.count()).ok().filter(|&n| n > 0)?)
+    }
+
+    /// Get the latency below which `p` percent of the transfers completed,
+    /// or `None` if no transfers have completed.
+    ///
+    /// For example, `percentile(50.0)` is the median and `percentile(99.0)` is
+    /// the tail latency.
+    ///
+    /// Panics if `p` is not between 0 and 100.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        assert!((0.0..=100.0).contains(&p), "percentile must be 0 to 100");
+        let last = self.sorted.len().checked_sub(1)?;
+        let idx = (p / 100.0 * last as f64).round() as usize;
+        self.sorted.get(idx).copied()
+    }
+}
+
+#[test]
+fn latency_percentiles() {
+    let mut recorder = LatencyRecorder::default();
+    assert_eq!(recorder.stats().percentile(50.0), None);
+    assert_eq!(recorder.stats().mean(), None);
+
+    for ms in (1..=100).rev() {
+        recorder.record(Duration::from_millis(ms));
+    }
+    let stats = recorder.stats();
+    assert_eq!(stats.count(), 100);
+    assert_eq!(stats.min(), Some(Duration::from_millis(1)));
+    assert_eq!(stats.max(), Some(Duration::from_millis(100)));
+    assert_eq!(stats.percentile(0.0), Some(Duration::from_millis(1)));
+    assert_eq!(stats.percentile(100.0), Some(Duration::from_millis(100)));
+    assert_eq!(stats.mean(), Some(Duration::from_micros(50500)));
+
+    for _ in 0..LATENCY_SAMPLES {
+        recorder.record(Duration::from_millis(5));
+    }
+    assert_eq!(recorder.stats().max(), Some(Duration::from_millis(5)));
+}